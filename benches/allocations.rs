@@ -0,0 +1,254 @@
+//! Allocations per call, counted with a wrapping global allocator. The
+//! transport answers in memory, so the numbers cover only the SDK's own work:
+//! serializing, signing and parsing. Covers a short `ChatCompletions` call,
+//! one with a large prompt, with and without retries, and `GetEmbedding` with
+//! a response of several megabytes.
+//!
+//! Run with `cargo bench --bench allocations`. Each call is measured three
+//! times by Criterion: in the `time`, `allocations` and `allocated bytes`
+//! groups, the latter two through the [`Allocated`] measurement.
+use bytes::Bytes;
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tencentcloud_hunyuan_sdk::models::{
+    ChatCompletionsRequest, GetEmbeddingRequest, Message, Role,
+};
+use tencentcloud_hunyuan_sdk::transport::BoxFuture;
+use tencentcloud_hunyuan_sdk::{
+    Client, Credential, PreparedRequest, RawResponse, RetryPolicy, SdkResult, Transport,
+};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Criterion measurement reading one of the allocator's counters, so a
+/// benchmark reports allocations or bytes allocated per iteration instead of
+/// time.
+struct Allocated {
+    counter: &'static AtomicUsize,
+    unit: &'static str,
+}
+
+const ALLOCATION_COUNT: Allocated = Allocated {
+    counter: &ALLOCATIONS,
+    unit: "allocations",
+};
+
+const ALLOCATED_BYTES: Allocated = Allocated {
+    counter: &BYTES,
+    unit: "bytes",
+};
+
+impl Measurement for Allocated {
+    type Intermediate = usize;
+    type Value = usize;
+
+    fn start(&self) -> usize {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    fn end(&self, started: usize) -> usize {
+        self.counter.load(Ordering::Relaxed) - started
+    }
+
+    fn add(&self, v1: &usize, v2: &usize) -> usize {
+        v1 + v2
+    }
+
+    fn zero(&self) -> usize {
+        0
+    }
+
+    fn to_f64(&self, value: &usize) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self
+    }
+}
+
+impl ValueFormatter for Allocated {
+    fn scale_values(&self, _typical: f64, _values: &mut [f64]) -> &'static str {
+        self.unit
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical: f64,
+        _throughput: &Throughput,
+        _values: &mut [f64],
+    ) -> &'static str {
+        self.unit
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        self.unit
+    }
+}
+
+const CHAT_RESPONSE: &str = include_str!("../tests/fixtures/chat_completions.json");
+
+/// Answers every request with the same in-memory response, failing the
+/// first `failures` attempts of each call with a retryable error.
+struct InMemory {
+    response: Bytes,
+    failures: usize,
+    attempt: AtomicUsize,
+}
+
+impl Transport for InMemory {
+    fn execute(&self, _req: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>> {
+        Box::pin(async move {
+            let attempt = self.attempt.fetch_add(1, Ordering::Relaxed) % (self.failures + 1);
+            let status = if attempt < self.failures { 503 } else { 200 };
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert("content-type", "application/json".parse().unwrap());
+            Ok(RawResponse::from_bytes(
+                status,
+                headers,
+                self.response.clone(),
+            ))
+        })
+    }
+}
+
+fn in_memory_client(response: impl Into<Bytes>, failures: usize) -> Client {
+    Client::builder()
+        .credential(Credential {
+            secret_id: "AKIDbench".to_string(),
+            secret_key: "benchsecret".to_string(),
+            token: None,
+        })
+        .transport(Arc::new(InMemory {
+            response: response.into(),
+            failures,
+            attempt: AtomicUsize::new(0),
+        }))
+        .retry_policy(RetryPolicy::new(2).initial_backoff(Duration::ZERO))
+        .debug(false)
+        .build()
+}
+
+/// Benchmarks the calls in a group named `group`, measured with `M`.
+fn calls<M: Measurement>(c: &mut Criterion<M>, group: &str) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    // A typical short request, as sent by high-QPS callers.
+    let short = ChatCompletionsRequest {
+        model: Some("hunyuan-lite".to_string()),
+        messages: vec![
+            Message::new(Role::System, "You are a helpful assistant."),
+            Message::new(Role::User, "What is the capital of France?"),
+        ],
+        temperature: Some(0.7),
+        top_p: None,
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    };
+    let mut group = c.benchmark_group(group);
+    let client = in_memory_client(CHAT_RESPONSE, 0);
+    group.bench_function("chat short", |b| {
+        b.iter(|| runtime.block_on(client.chat_completions(&short)).unwrap())
+    });
+
+    // A prompt of about 64 KiB makes body copies stand out.
+    let req = ChatCompletionsRequest {
+        model: Some("hunyuan-lite".to_string()),
+        messages: vec![Message::new(Role::User, "lorem ipsum ".repeat(5_000))],
+        temperature: None,
+        top_p: None,
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    };
+    for failures in [0, 2] {
+        let client = in_memory_client(CHAT_RESPONSE, failures);
+        let name = format!("chat attempts={}", failures + 1);
+        group.bench_function(name, |b| {
+            b.iter(|| runtime.block_on(client.chat_completions(&req)).unwrap())
+        });
+    }
+
+    // 256 embeddings of 1024 dimensions, about 5 MB of JSON.
+    let data: Vec<_> = (0..256)
+        .map(|index| {
+            let embedding: Vec<f64> = (0..1024)
+                .map(|i| ((index * 1024 + i) as f64).sin())
+                .collect();
+            serde_json::json!({ "Embedding": embedding, "Index": index, "Object": "embedding" })
+        })
+        .collect();
+    let response = serde_json::to_vec(&serde_json::json!({
+        "Response": {
+            "Data": data,
+            "Usage": { "PromptTokens": 6, "TotalTokens": 6 },
+            "RequestId": "bench",
+        }
+    }))
+    .unwrap();
+    let client = in_memory_client(response, 0);
+    let req = GetEmbeddingRequest {
+        input: Some("hello".to_string()),
+        ..Default::default()
+    };
+    group.sample_size(10);
+    group.bench_function("embedding 5 MB", |b| {
+        b.iter(|| runtime.block_on(client.get_embedding(&req)).unwrap())
+    });
+    group.finish();
+}
+
+fn time(c: &mut Criterion) {
+    calls(c, "time");
+}
+
+fn allocations(c: &mut Criterion<Allocated>) {
+    calls(c, "allocations");
+}
+
+fn allocated_bytes(c: &mut Criterion<Allocated>) {
+    calls(c, "allocated bytes");
+}
+
+criterion_group!(timed, time);
+// Counts barely vary between iterations, which the density plots cannot
+// draw.
+criterion_group! {
+    name = allocation_count;
+    config = Criterion::default().with_measurement(ALLOCATION_COUNT).without_plots();
+    targets = allocations
+}
+criterion_group! {
+    name = allocation_bytes;
+    config = Criterion::default().with_measurement(ALLOCATED_BYTES).without_plots();
+    targets = allocated_bytes
+}
+criterion_main!(timed, allocation_count, allocation_bytes);
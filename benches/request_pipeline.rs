@@ -0,0 +1,207 @@
+//! Timings for the per-request work of the client: TC3 key derivation, full
+//! signing, header construction, body serialization and SSE decoding.
+//!
+//! Runs offline without credentials: `cargo bench --bench request_pipeline`,
+//! optionally followed by `-- <filter>` to run only benchmarks whose name
+//! matches the `<filter>` regex. Criterion reports the time per iteration,
+//! plus throughput where a benchmark processes a body, and compares it with
+//! the previous run.
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::hint::black_box;
+use std::sync::Arc;
+use tencentcloud_hunyuan_sdk::models::{ChatCompletionsRequest, Message, Role};
+use tencentcloud_hunyuan_sdk::transport::BoxFuture;
+use tencentcloud_hunyuan_sdk::{
+    Client, Credential, PreparedRequest, RawResponse, SdkResult, Transport,
+};
+
+const TIMESTAMP: i64 = 1_704_164_645;
+
+fn credential() -> Credential {
+    Credential {
+        secret_id: "AKIDbench".to_string(),
+        secret_key: "benchsecretkeybenchsecretkey".to_string(),
+        token: None,
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The TC3 signing key for a date and service, derived the way the signer
+/// does it for every request. Its cost bounds what caching the key per day
+/// can save.
+fn derive_signing_key(secret_key: &str, date: &str, service: &str) -> Vec<u8> {
+    let date_key = hmac_sha256(format!("TC3{}", secret_key).as_bytes(), date.as_bytes());
+    let service_key = hmac_sha256(&date_key, service.as_bytes());
+    hmac_sha256(&service_key, b"tc3_request")
+}
+
+fn chat_request(messages: usize, content_len: usize) -> ChatCompletionsRequest {
+    ChatCompletionsRequest {
+        model: Some("hunyuan-lite".to_string()),
+        messages: (0..messages)
+            .map(|i| {
+                let role = if i % 2 == 0 {
+                    Role::User
+                } else {
+                    Role::Assistant
+                };
+                Message::new(role, "消息内容 message ".repeat(content_len / 20 + 1))
+            })
+            .collect(),
+        temperature: Some(0.7),
+        top_p: Some(0.9),
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    }
+}
+
+/// A streamed reply of `events` chunks, in the shape the service sends.
+fn sse_transcript(events: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    for i in 0..events {
+        let finish = if i + 1 == events { "stop" } else { "" };
+        let chunk = serde_json::json!({
+            "Note": "以上内容为AI生成，不代表开发者立场，请勿删除或修改本标记",
+            "Choices": [{
+                "Delta": { "Role": "assistant", "Content": format!("第{}段内容 chunk {} ", i, i) },
+                "FinishReason": finish,
+            }],
+            "Created": 1715150001,
+            "Id": "5d1c7b0e-8f4d-4f3a-a0b2-6c9e2d4f1a22",
+            "Usage": { "PromptTokens": 4, "CompletionTokens": i + 1, "TotalTokens": i + 5 },
+        });
+        out.extend_from_slice(format!("data: {}\n\n", chunk).as_bytes());
+    }
+    out
+}
+
+/// Streams a fixed SSE body in chunks of `chunk_size` bytes.
+struct SseReplay {
+    body: Bytes,
+    chunk_size: usize,
+}
+
+impl Transport for SseReplay {
+    fn execute(&self, _req: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>> {
+        Box::pin(async move {
+            let chunks: Vec<SdkResult<Bytes>> = (0..self.body.len())
+                .step_by(self.chunk_size)
+                .map(|start| {
+                    Ok(self
+                        .body
+                        .slice(start..(start + self.chunk_size).min(self.body.len())))
+                })
+                .collect();
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert("content-type", "text/event-stream".parse().unwrap());
+            Ok(RawResponse {
+                status: 200,
+                headers,
+                body: Box::pin(futures_util::stream::iter(chunks)),
+            })
+        })
+    }
+}
+
+fn signing(c: &mut Criterion) {
+    let client = Client::builder()
+        .credential(credential())
+        .debug(false)
+        .build();
+
+    c.bench_function("tc3 key derivation", |b| {
+        b.iter(|| derive_signing_key(&credential().secret_key, "2024-01-02", "hunyuan"))
+    });
+
+    let typical = serde_json::to_vec(&chat_request(4, 200)).unwrap();
+    let canonical_headers =
+        "content-type:application/json; charset=utf-8\nhost:hunyuan.tencentcloudapi.com\n";
+    let mut group = c.benchmark_group("tc3_sign");
+    group.throughput(Throughput::Bytes(typical.len() as u64));
+    group.bench_function("typical body", |b| {
+        b.iter(|| {
+            let hashed_payload = hex::encode(Sha256::digest(black_box(&typical)));
+            client.tc3_sign(
+                "POST",
+                "/",
+                "",
+                canonical_headers,
+                "content-type;host",
+                &hashed_payload,
+                TIMESTAMP,
+            )
+        })
+    });
+    group.finish();
+
+    c.bench_function("build_headers", |b| {
+        b.iter(|| {
+            client
+                .build_headers("ChatCompletions", "", TIMESTAMP)
+                .unwrap()
+        })
+    });
+}
+
+fn serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    for (name, messages) in [
+        ("2 messages", chat_request(2, 100)),
+        ("200 messages", chat_request(200, 500)),
+    ] {
+        let len = serde_json::to_vec(&messages).unwrap().len();
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| serde_json::to_vec(black_box(&messages)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn sse_decoding(c: &mut Criterion) {
+    let transcript = sse_transcript(1000);
+    let len = transcript.len();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let streaming = Client::builder()
+        .credential(credential())
+        .transport(Arc::new(SseReplay {
+            body: Bytes::from(transcript),
+            chunk_size: 4096,
+        }))
+        .debug(false)
+        .build();
+    let req = chat_request(2, 100);
+    let mut group = c.benchmark_group("sse decode");
+    group.throughput(Throughput::Bytes(len as u64));
+    group.bench_function("1000 events", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let mut stream = streaming.chat_completions_stream(&req).await.unwrap();
+                let mut chunks = 0;
+                while let Some(chunk) = stream.next().await {
+                    chunk.unwrap();
+                    chunks += 1;
+                }
+                assert_eq!(chunks, 1000);
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, signing, serialization, sse_decoding);
+criterion_main!(benches);
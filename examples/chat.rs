@@ -1,6 +1,5 @@
 use anyhow::Result;
-use tencentcloud_hunyuan_sdk::models::{ChatCompletionsRequest, Message};
-use tencentcloud_hunyuan_sdk::{Client, ClientBuilder, Credential, Region};
+use tencentcloud_hunyuan_sdk::prelude::*;
 
 // To run with default rustls-tls:
 // cargo run --example chat
@@ -25,13 +24,13 @@ async fn main() -> Result<()> {
 
     let req = ChatCompletionsRequest {
         model: Some("hunyuan-lite".to_string()),
-        messages: vec![Message {
-            role: "user".into(),
-            content: "Hello, Hunyuan!".into(),
-        }],
+        messages: vec![Message::new(Role::User, "Hello, Hunyuan!")],
         temperature: Some(0.7),
         top_p: Some(0.95),
         stream: Some(false),
+        tools: None,
+        n: None,
+        seed: None,
     };
 
     let resp = client.chat_completions(&req).await?;
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use tencentcloud_hunyuan_sdk::blocking;
+use tencentcloud_hunyuan_sdk::prelude::*;
+
+// No async runtime needed:
+// cargo run --example chat_blocking --features blocking
+
+fn main() -> Result<()> {
+    let secret_id = std::env::var("TENCENTCLOUD_SECRET_ID")?;
+    let secret_key = std::env::var("TENCENTCLOUD_SECRET_KEY")?;
+
+    let client: blocking::Client = blocking::Client::builder()
+        .credential(Credential {
+            secret_id,
+            secret_key,
+            token: None,
+        })
+        .region(Region::ApGuangzhou)
+        .build_blocking();
+
+    let req = ChatCompletionsRequest {
+        model: Some("hunyuan-lite".to_string()),
+        messages: vec![Message::new(Role::User, "Hello, Hunyuan!")],
+        temperature: Some(0.7),
+        top_p: Some(0.95),
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    };
+
+    let resp = client.chat_completions(&req)?;
+    println!("{:?}", resp);
+
+    // Streamed chunks arrive through a plain iterator.
+    for chunk in client.chat_completions_stream(&req)? {
+        let chunk = chunk?;
+        if let Some(delta) = chunk.choices.as_ref().and_then(|c| c[0].delta.as_ref()) {
+            print!("{}", delta.content.as_deref().unwrap_or(""));
+        }
+    }
+    println!();
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use tencentcloud_hunyuan_sdk::chat_model::{
+    ChatMessage, ChatModel, HunyuanChatModel, ModelError, ModelInfo, TextStream,
+};
+use tencentcloud_hunyuan_sdk::prelude::*;
+use tencentcloud_hunyuan_sdk::transport::BoxFuture;
+
+// The same application code against a fake provider and, when credentials
+// are exported, against Hunyuan:
+// cargo run --example chat_model --features model-trait
+
+/// Application code that only knows about `ChatModel`.
+async fn summarize(model: &dyn ChatModel, text: &str) -> Result<String, ModelError> {
+    let info = model.info();
+    let tokens = model.count_tokens(text).await?;
+    println!(
+        "[{}/{}] input is {} tokens",
+        info.provider, info.model, tokens
+    );
+    model
+        .generate(&[
+            ChatMessage::system("Summarize the user's text in one sentence."),
+            ChatMessage::user(text),
+        ])
+        .await
+}
+
+/// A provider that echoes the first sentence back.
+struct FakeModel;
+
+impl ChatModel for FakeModel {
+    fn info(&self) -> ModelInfo {
+        ModelInfo {
+            provider: "fake".to_string(),
+            model: "echo".to_string(),
+            supports_streaming: false,
+        }
+    }
+
+    fn generate<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+    ) -> BoxFuture<'a, Result<String, ModelError>> {
+        let last = messages.last().map(|m| m.content.as_str()).unwrap_or("");
+        let reply = last.split('.').next().unwrap_or("").to_string() + ".";
+        Box::pin(async move { Ok(reply) })
+    }
+
+    fn generate_stream<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+    ) -> BoxFuture<'a, Result<TextStream, ModelError>> {
+        Box::pin(async move {
+            let reply = self.generate(messages).await?;
+            let stream: TextStream = Box::pin(futures_util::stream::iter([Ok(reply)]));
+            Ok(stream)
+        })
+    }
+
+    fn count_tokens<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<u32, ModelError>> {
+        Box::pin(async move { Ok(text.split_whitespace().count() as u32) })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let text = "Rust is a systems programming language. It is fast and memory safe.";
+
+    println!("{}", summarize(&FakeModel, text).await?);
+
+    if let (Ok(secret_id), Ok(secret_key)) = (
+        std::env::var("TENCENTCLOUD_SECRET_ID"),
+        std::env::var("TENCENTCLOUD_SECRET_KEY"),
+    ) {
+        let client = ClientBuilder::new()
+            .credential(Credential {
+                secret_id,
+                secret_key,
+                token: None,
+            })
+            .build();
+        let hunyuan = HunyuanChatModel::new(client, "hunyuan-lite");
+        println!("{}", summarize(&hunyuan, text).await?);
+    }
+
+    Ok(())
+}
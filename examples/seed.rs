@@ -0,0 +1,55 @@
+use anyhow::Result;
+use tencentcloud_hunyuan_sdk::prelude::*;
+
+// Sends the same request with a fixed seed twice and compares the replies.
+//
+// cargo run --example seed
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let secret_id = std::env::var("TENCENTCLOUD_SECRET_ID")?;
+    let secret_key = std::env::var("TENCENTCLOUD_SECRET_KEY")?;
+
+    let client: Client = ClientBuilder::new()
+        .credential(Credential {
+            secret_id,
+            secret_key,
+            token: None,
+        })
+        .region(Region::ApGuangzhou)
+        .build();
+
+    let req = ChatCompletionsRequest {
+        model: Some("hunyuan-lite".to_string()),
+        messages: vec![Message::new(Role::User, "Write a haiku about the sea.")],
+        temperature: Some(1.0),
+        top_p: None,
+        stream: Some(false),
+        tools: None,
+        n: None,
+        seed: None,
+    }
+    .with_seed(42);
+
+    let mut replies = Vec::new();
+    for _ in 0..2 {
+        let (resp, meta) = client.chat_completions_with_meta(&req).await?;
+        let reply = resp
+            .messages()
+            .next()
+            .and_then(|m| m.content.clone())
+            .unwrap_or_default();
+        println!(
+            "seed={:?} request_id={:?}\n{}\n",
+            meta.seed, meta.request_id, reply
+        );
+        replies.push(reply);
+    }
+    if replies[0] == replies[1] {
+        println!("Both replies are identical.");
+    } else {
+        println!("The replies differ: the service did not reproduce the output.");
+    }
+
+    Ok(())
+}
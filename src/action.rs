@@ -0,0 +1,98 @@
+//! The Hunyuan API actions the SDK knows, with their per-action metadata.
+//!
+//! [`Client::call`](crate::Client::call) and
+//! [`Client::call_raw`](crate::Client::call_raw) accept an [`Action`] as well
+//! as an action name, so typed callers need not spell names out:
+//!
+//! ```no_run
+//! # use tencentcloud_hunyuan_sdk::{action::Action, Client, RequestOptions, SdkResult};
+//! # async fn run(client: Client) -> SdkResult<()> {
+//! let body = serde_json::json!({ "Model": "hunyuan-lite", "Prompt": "hello" });
+//! let count = client
+//!     .call_raw(Action::GetTokenCount, &body, &RequestOptions::new())
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Actions not listed here can still be called by name; they are sent as
+//! `POST` and never streamed. All actions share the client's endpoint.
+use std::fmt;
+use std::str::FromStr;
+
+/// A Hunyuan API action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Action {
+    ChatCompletions,
+    GetEmbedding,
+    GetTokenCount,
+    TextToImageLite,
+}
+
+impl Action {
+    /// Every known action.
+    pub const ALL: &'static [Action] = &[
+        Action::ChatCompletions,
+        Action::GetEmbedding,
+        Action::GetTokenCount,
+        Action::TextToImageLite,
+    ];
+
+    /// The name sent as `X-TC-Action`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Action::ChatCompletions => "ChatCompletions",
+            Action::GetEmbedding => "GetEmbedding",
+            Action::GetTokenCount => "GetTokenCount",
+            Action::TextToImageLite => "TextToImageLite",
+        }
+    }
+
+    /// The HTTP method the action is called with.
+    pub const fn method(self) -> &'static str {
+        "POST"
+    }
+
+    /// Whether the action can answer with a server-sent event stream.
+    pub const fn streams(self) -> bool {
+        matches!(self, Action::ChatCompletions)
+    }
+
+    /// The known action called `name`, if any.
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|a| a.name() == name)
+    }
+}
+
+impl AsRef<str> for Action {
+    fn as_ref(&self) -> &str {
+        self.name()
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Error returned when parsing the name of an action the SDK does not know.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownAction(pub String);
+
+impl fmt::Display for UnknownAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown Hunyuan action {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownAction {}
+
+impl FromStr for Action {
+    type Err = UnknownAction;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Action::from_name(s).ok_or_else(|| UnknownAction(s.to_string()))
+    }
+}
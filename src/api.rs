@@ -0,0 +1,85 @@
+//! Object-safe view of the Hunyuan API for dependency injection.
+//!
+//! Application code that only needs to talk to Hunyuan can depend on
+//! `Arc<dyn HunyuanApi>` instead of a concrete [`Client`], and swap in a fake
+//! (such as `test_utils::FakeHunyuan`) in its own unit tests:
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use tencentcloud_hunyuan_sdk::models::{ChatCompletionsRequest, Message};
+//! use tencentcloud_hunyuan_sdk::{Client, Credential, HunyuanApi, SdkResult};
+//!
+//! async fn greet(api: &dyn HunyuanApi) -> SdkResult<Option<String>> {
+//!     let req = ChatCompletionsRequest {
+//!         model: Some("hunyuan-lite".into()),
+//!         messages: vec![Message { role: "user".into(), content: "Hello".into(), ..Default::default() }],
+//!         temperature: None,
+//!         top_p: None,
+//!         stream: None,
+//!         tools: None,
+//!         n: None,
+//!         seed: None,
+//!     };
+//!     let resp = api.chat_completions(&req).await?;
+//!     Ok(resp.response.choices.and_then(|c| c.into_iter().next()?.message?.content))
+//! }
+//!
+//! # async fn run() -> SdkResult<()> {
+//! let client = Client::builder()
+//!     .credential(Credential { secret_id: "id".into(), secret_key: "key".into(), token: None })
+//!     .build();
+//! let api: Arc<dyn HunyuanApi> = Arc::new(client);
+//! greet(api.as_ref()).await?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::client::{Client, SdkResult};
+use crate::models::{
+    ChatCompletionsRequest, ChatCompletionsResponse, GetEmbeddingRequest, GetEmbeddingResponse,
+};
+use crate::stream::ChatCompletionsStream;
+use crate::transport::BoxFuture;
+
+/// Hunyuan operations as an object-safe trait, implemented by [`Client`].
+pub trait HunyuanApi: Send + Sync {
+    /// Calls the `ChatCompletions` action.
+    fn chat_completions<'a>(
+        &'a self,
+        req: &'a ChatCompletionsRequest,
+    ) -> BoxFuture<'a, SdkResult<ChatCompletionsResponse>>;
+
+    /// Calls the `ChatCompletions` action with `Stream` enabled.
+    fn chat_completions_stream<'a>(
+        &'a self,
+        req: &'a ChatCompletionsRequest,
+    ) -> BoxFuture<'a, SdkResult<ChatCompletionsStream>>;
+
+    /// Calls the `GetEmbedding` action.
+    fn get_embedding<'a>(
+        &'a self,
+        req: &'a GetEmbeddingRequest,
+    ) -> BoxFuture<'a, SdkResult<GetEmbeddingResponse>>;
+}
+
+impl HunyuanApi for Client {
+    fn chat_completions<'a>(
+        &'a self,
+        req: &'a ChatCompletionsRequest,
+    ) -> BoxFuture<'a, SdkResult<ChatCompletionsResponse>> {
+        Box::pin(Client::chat_completions(self, req))
+    }
+
+    fn chat_completions_stream<'a>(
+        &'a self,
+        req: &'a ChatCompletionsRequest,
+    ) -> BoxFuture<'a, SdkResult<ChatCompletionsStream>> {
+        Box::pin(Client::chat_completions_stream(self, req))
+    }
+
+    fn get_embedding<'a>(
+        &'a self,
+        req: &'a GetEmbeddingRequest,
+    ) -> BoxFuture<'a, SdkResult<GetEmbeddingResponse>> {
+        Box::pin(Client::get_embedding(self, req))
+    }
+}
@@ -0,0 +1,319 @@
+//! `proptest` strategies for the models.
+//!
+//! Enabled with the `proptest` feature. Every request and response model
+//! implements [`Arbitrary`], so property tests can use `any::<Message>()` and
+//! friends. Strings are printable Unicode of bounded length, floats are
+//! finite and within the ranges the API accepts, and `extra` maps of response
+//! models are left empty; use [`extra_fields`] to generate unknown fields.
+use crate::models::{
+    ChatChoice, ChatChoiceDelta, ChatChoiceMessage, ChatChunkChoice, ChatCompletionsChunk,
+    ChatCompletionsRequest, ChatCompletionsResponseInner, Citation, EmbeddingData, EmbeddingUsage,
+    FinishReason, GetEmbeddingRequest, GetEmbeddingResponseInner, Message, SearchInfo,
+    SearchResult, TextToImageLiteRequest, TextToImageLiteResponseInner, Usage,
+};
+use proptest::collection::vec;
+use proptest::option::of;
+use proptest::prelude::*;
+use serde_json::{Map, Value};
+
+/// Printable Unicode text of up to 32 characters.
+pub fn text() -> impl Strategy<Value = String> {
+    "\\PC{0,32}"
+}
+
+/// Objects of up to four fields whose names (`Ext...`) collide with no
+/// modeled field, holding strings, integers, booleans or nulls.
+pub fn extra_fields() -> impl Strategy<Value = Map<String, Value>> {
+    let value = prop_oneof![
+        text().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<bool>().prop_map(Value::from),
+        Just(Value::Null),
+    ];
+    proptest::collection::btree_map("Ext[A-Za-z]{1,8}", value, 0..4)
+        .prop_map(|fields| fields.into_iter().collect())
+}
+
+macro_rules! arbitrary {
+    ($ty:ty, $strategy:expr) => {
+        impl Arbitrary for $ty {
+            type Parameters = ();
+            type Strategy = BoxedStrategy<Self>;
+
+            fn arbitrary_with(_: ()) -> Self::Strategy {
+                $strategy.boxed()
+            }
+        }
+    };
+}
+
+arbitrary!(
+    FinishReason,
+    prop_oneof![
+        Just(FinishReason::Stop),
+        Just(FinishReason::Length),
+        Just(FinishReason::Sensitive),
+        Just(FinishReason::ToolCalls),
+    ]
+);
+
+arbitrary!(
+    Message,
+    (prop_oneof!["system", "user", "assistant"], text()).prop_map(|(role, content)| Message {
+        role,
+        content,
+        ..Default::default()
+    })
+);
+
+arbitrary!(
+    ChatCompletionsRequest,
+    (
+        of("hunyuan-[a-z]{1,10}"),
+        vec(any::<Message>(), 0..6),
+        of(0.0f32..=2.0),
+        of(0.0f32..=1.0),
+        of(any::<bool>()),
+        of(1u32..=4),
+        of(1u32..=10_000),
+    )
+        .prop_map(|(model, messages, temperature, top_p, stream, n, seed)| {
+            ChatCompletionsRequest {
+                model,
+                messages,
+                temperature,
+                top_p,
+                stream,
+                tools: None,
+                n,
+                seed,
+            }
+        })
+);
+
+arbitrary!(
+    ChatChoiceMessage,
+    (of(text()), of(text())).prop_map(|(role, content)| ChatChoiceMessage {
+        role,
+        content,
+        ..Default::default()
+    })
+);
+
+arbitrary!(
+    ChatChoiceDelta,
+    (of(text()), of(text()), of(text())).prop_map(|(role, content, reasoning_content)| {
+        ChatChoiceDelta {
+            role,
+            content,
+            reasoning_content,
+            ..Default::default()
+        }
+    })
+);
+
+arbitrary!(
+    ChatChoice,
+    (
+        of(any::<u32>()),
+        of(any::<ChatChoiceMessage>()),
+        of(any::<FinishReason>().prop_map(|r| r.as_str().to_string())),
+    )
+        .prop_map(|(index, message, finish_reason)| ChatChoice {
+            index,
+            message,
+            finish_reason,
+            ..Default::default()
+        })
+);
+
+arbitrary!(
+    Usage,
+    (of(any::<u32>()), of(any::<u32>()), of(any::<u32>())).prop_map(
+        |(prompt_tokens, completion_tokens, total_tokens)| Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens,
+            ..Default::default()
+        }
+    )
+);
+
+arbitrary!(
+    SearchResult,
+    (
+        of(any::<u32>()),
+        of(text()),
+        of("https://[a-z]{1,12}\\.com/[a-z0-9/]{0,16}")
+    )
+        .prop_map(|(index, title, url)| SearchResult { index, title, url })
+);
+
+arbitrary!(
+    Citation,
+    (any::<u32>(), any::<u32>()).prop_map(|(index, offset)| Citation { index, offset })
+);
+
+arbitrary!(
+    SearchInfo,
+    (
+        of(vec(any::<SearchResult>(), 0..4)),
+        of(vec(any::<Citation>(), 0..4)),
+    )
+        .prop_map(|(search_results, citations)| SearchInfo {
+            search_results,
+            citations,
+        })
+);
+
+arbitrary!(
+    ChatCompletionsResponseInner,
+    (
+        of(text()),
+        of(text()),
+        of(vec(any::<ChatChoice>(), 0..3)),
+        of(any::<Usage>()),
+        of(any::<SearchInfo>()),
+        of(text()),
+    )
+        .prop_map(|(request_id, id, choices, usage, search_info, note)| {
+            ChatCompletionsResponseInner {
+                request_id,
+                id,
+                choices,
+                usage,
+                search_info,
+                note,
+                ..Default::default()
+            }
+        })
+);
+
+arbitrary!(
+    ChatChunkChoice,
+    (
+        of(any::<u32>()),
+        of(any::<ChatChoiceDelta>()),
+        of(any::<FinishReason>().prop_map(|r| r.as_str().to_string())),
+    )
+        .prop_map(|(index, delta, finish_reason)| ChatChunkChoice {
+            index,
+            delta,
+            finish_reason,
+            ..Default::default()
+        })
+);
+
+arbitrary!(
+    ChatCompletionsChunk,
+    (
+        of(text()),
+        of(any::<i64>()),
+        of(vec(any::<ChatChunkChoice>(), 0..3)),
+        of(any::<Usage>()),
+        of(any::<SearchInfo>()),
+        of(text()),
+    )
+        .prop_map(
+            |(id, created, choices, usage, search_info, note)| ChatCompletionsChunk {
+                id,
+                created,
+                choices,
+                usage,
+                search_info,
+                note,
+                ..Default::default()
+            }
+        )
+);
+
+arbitrary!(
+    GetEmbeddingRequest,
+    (
+        of(text()),
+        of(vec(text(), 0..4)),
+        of(prop_oneof![
+            Just("query".to_string()),
+            Just("document".to_string())
+        ])
+    )
+        .prop_map(|(input, input_list, input_type)| GetEmbeddingRequest {
+            input,
+            input_list,
+            input_type,
+            ..Default::default()
+        })
+);
+
+arbitrary!(
+    EmbeddingData,
+    (
+        of(vec(-1.0f64..=1.0, 0..8)),
+        of(any::<u32>()),
+        of(Just("embedding".to_string())),
+    )
+        .prop_map(|(embedding, index, object)| EmbeddingData {
+            embedding,
+            index,
+            object,
+            ..Default::default()
+        })
+);
+
+arbitrary!(
+    EmbeddingUsage,
+    (of(any::<u32>()), of(any::<u32>())).prop_map(|(prompt_tokens, total_tokens)| {
+        EmbeddingUsage {
+            prompt_tokens,
+            total_tokens,
+        }
+    })
+);
+
+arbitrary!(
+    GetEmbeddingResponseInner,
+    (
+        of(text()),
+        of(vec(any::<EmbeddingData>(), 0..3)),
+        of(any::<EmbeddingUsage>()),
+    )
+        .prop_map(|(request_id, data, usage)| GetEmbeddingResponseInner {
+            request_id,
+            data,
+            usage,
+            ..Default::default()
+        })
+);
+
+arbitrary!(
+    TextToImageLiteRequest,
+    (
+        text(),
+        of(text()),
+        of("[0-9]{3}"),
+        of(prop_oneof!["768:768", "1024:1024"]),
+        of(prop_oneof!["base64", "url"]),
+    )
+        .prop_map(
+            |(prompt, negative_prompt, style, resolution, rsp_img_type)| {
+                TextToImageLiteRequest {
+                    prompt,
+                    negative_prompt,
+                    style,
+                    resolution,
+                    rsp_img_type,
+                }
+            }
+        )
+);
+
+arbitrary!(
+    TextToImageLiteResponseInner,
+    (of(text()), of("[A-Za-z0-9+/]{0,64}")).prop_map(|(request_id, result_image)| {
+        TextToImageLiteResponseInner {
+            request_id,
+            result_image,
+            ..Default::default()
+        }
+    })
+);
@@ -0,0 +1,153 @@
+//! Blocking client for callers without an async runtime.
+//!
+//! Enabled with the `blocking` feature. [`Client`] wraps the async
+//! [`crate::Client`] and drives it on a private current-thread Tokio runtime,
+//! so CLI tools and build scripts can make a call without setting up a
+//! runtime of their own. Configure it with the usual builder and finish with
+//! `build_blocking`:
+//!
+//! ```no_run
+//! use tencentcloud_hunyuan_sdk::blocking;
+//! use tencentcloud_hunyuan_sdk::models::{ChatCompletionsRequest, Message};
+//! use tencentcloud_hunyuan_sdk::Credential;
+//!
+//! let client = blocking::Client::builder()
+//!     .credential(Credential { secret_id: "id".into(), secret_key: "key".into(), token: None })
+//!     .build_blocking();
+//! let req = ChatCompletionsRequest {
+//!     model: Some("hunyuan-lite".into()),
+//!     messages: vec![Message { role: "user".into(), content: "Hello".into(), ..Default::default() }],
+//!     temperature: None,
+//!     top_p: None,
+//!     stream: None,
+//!     tools: None,
+//!     n: None,
+//!     seed: None,
+//! };
+//! let resp = client.chat_completions(&req)?;
+//! # Ok::<(), tencentcloud_hunyuan_sdk::SdkError>(())
+//! ```
+//!
+//! Methods block the calling thread and panic when called from within an
+//! async runtime; use the async client there.
+use crate::client::{
+    ClientBuilder, CredentialSet, Dynamic, NoCredential, RequestOptions, SdkResult,
+};
+use crate::models::{
+    ChatCompletionsChunk, ChatCompletionsRequest, ChatCompletionsResponse, GetEmbeddingRequest,
+    GetEmbeddingResponse,
+};
+use crate::stream::ChatCompletionsStream;
+use futures_util::StreamExt;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Blocking counterpart of [`crate::Client`].
+#[derive(Clone)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Returns a [`ClientBuilder`]; finish it with `build_blocking`.
+    pub fn builder() -> ClientBuilder<NoCredential> {
+        crate::Client::builder()
+    }
+
+    /// Wraps an async client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be created.
+    pub fn new(inner: crate::Client) -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create the blocking client's runtime");
+        Self {
+            inner,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /// Returns the wrapped async client.
+    pub fn inner(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    /// Calls the `ChatCompletions` action.
+    pub fn chat_completions(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<ChatCompletionsResponse> {
+        self.runtime.block_on(self.inner.chat_completions(req))
+    }
+
+    /// Calls the `ChatCompletions` action with `Stream` enabled and returns an
+    /// iterator over the streamed chunks.
+    pub fn chat_completions_stream(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<ChatCompletionsIter> {
+        let stream = self
+            .runtime
+            .block_on(self.inner.chat_completions_stream(req))?;
+        Ok(ChatCompletionsIter {
+            stream,
+            runtime: self.runtime.clone(),
+        })
+    }
+
+    /// Calls the `GetEmbedding` action.
+    pub fn get_embedding(&self, req: &GetEmbeddingRequest) -> SdkResult<GetEmbeddingResponse> {
+        self.runtime.block_on(self.inner.get_embedding(req))
+    }
+
+    /// Calls any Hunyuan API action with a JSON body and returns the raw JSON
+    /// response.
+    pub fn call_raw(
+        &self,
+        action: impl AsRef<str>,
+        body: &serde_json::Value,
+        options: &RequestOptions,
+    ) -> SdkResult<serde_json::Value> {
+        self.runtime
+            .block_on(self.inner.call_raw(action, body, options))
+    }
+}
+
+impl From<crate::Client> for Client {
+    fn from(inner: crate::Client) -> Self {
+        Self::new(inner)
+    }
+}
+
+/// Blocking iterator over a streamed `ChatCompletions` response, returned by
+/// [`Client::chat_completions_stream`].
+pub struct ChatCompletionsIter {
+    stream: ChatCompletionsStream,
+    runtime: Arc<Runtime>,
+}
+
+impl Iterator for ChatCompletionsIter {
+    type Item = SdkResult<ChatCompletionsChunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+impl ClientBuilder<Dynamic> {
+    /// Build a blocking [`Client`]. Panics if credentials are not provided.
+    pub fn build_blocking(self) -> Client {
+        Client::new(self.build())
+    }
+}
+
+impl ClientBuilder<CredentialSet> {
+    /// Build a blocking [`Client`].
+    pub fn build_blocking(self) -> Client {
+        Client::new(self.build())
+    }
+}
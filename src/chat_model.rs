@@ -0,0 +1,280 @@
+//! Provider-neutral chat model interface.
+//!
+//! Enabled with the `model-trait` feature. [`ChatModel`] is a minimal
+//! interface frameworks can program against to swap LLM providers: plain
+//! role/content messages in, text out, with errors mapped to the
+//! provider-neutral [`ModelError`]. It is implemented for [`Client`] and for
+//! [`HunyuanChatModel`], which pins the model to use.
+//!
+//! ```no_run
+//! use tencentcloud_hunyuan_sdk::chat_model::{ChatMessage, ChatModel, ModelError};
+//!
+//! async fn answer(model: &dyn ChatModel, question: &str) -> Result<String, ModelError> {
+//!     model.generate(&[ChatMessage::user(question)]).await
+//! }
+//! ```
+use crate::client::{Client, SdkError};
+pub use crate::models::Role;
+
+use crate::models::{ChatCompletionsRequest, Message};
+use crate::transport::BoxFuture;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use std::pin::Pin;
+use thiserror::Error;
+
+/// Model used by the [`ChatModel`] impl of [`Client`].
+pub const DEFAULT_CHAT_MODEL: &str = "hunyuan-lite";
+
+/// A provider-neutral chat message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+impl ChatMessage {
+    /// A system message.
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::System,
+            content: content.into(),
+        }
+    }
+
+    /// A user message.
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+
+    /// An assistant message.
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            content: content.into(),
+        }
+    }
+}
+
+/// Static description of a [`ChatModel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelInfo {
+    /// Provider name, e.g. `hunyuan`.
+    pub provider: String,
+    /// Model name, e.g. `hunyuan-lite`.
+    pub model: String,
+    /// Whether `generate_stream` streams incrementally.
+    pub supports_streaming: bool,
+}
+
+/// Provider-neutral failure of a [`ChatModel`] call.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ModelError {
+    /// The provider throttled the call; retry later.
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+    /// Credentials were missing, invalid or lacked permission.
+    #[error("authentication failed: {0}")]
+    Authentication(String),
+    /// The request was rejected as invalid; retrying will not help.
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    /// A transient failure such as a network error or a provider outage.
+    #[error("provider unavailable: {0}")]
+    Unavailable(String),
+    /// Any other failure.
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<SdkError> for ModelError {
+    fn from(err: SdkError) -> Self {
+        let message = err.to_string();
+        if let SdkError::RateLimited { .. } = &err {
+            return ModelError::RateLimited(message);
+        }
+        if let SdkError::Service { code, .. } = &err {
+            if code.starts_with("RequestLimitExceeded") || code == "HTTP_429" {
+                return ModelError::RateLimited(message);
+            }
+            if code.starts_with("AuthFailure") || code.starts_with("UnauthorizedOperation") {
+                return ModelError::Authentication(message);
+            }
+            if code.starts_with("InvalidParameter")
+                || code.starts_with("MissingParameter")
+                || code.starts_with("UnknownParameter")
+            {
+                return ModelError::InvalidRequest(message);
+            }
+        }
+        if err.is_retryable() {
+            ModelError::Unavailable(message)
+        } else {
+            ModelError::Other(message)
+        }
+    }
+}
+
+/// Incremental text of a streamed reply.
+pub type TextStream = Pin<Box<dyn Stream<Item = Result<String, ModelError>> + Send>>;
+
+/// A chat model, independent of the provider behind it.
+pub trait ChatModel: Send + Sync {
+    /// Describes the model.
+    fn info(&self) -> ModelInfo;
+
+    /// Generates the reply to `messages`.
+    fn generate<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+    ) -> BoxFuture<'a, Result<String, ModelError>>;
+
+    /// Generates the reply to `messages` as a stream of text pieces.
+    fn generate_stream<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+    ) -> BoxFuture<'a, Result<TextStream, ModelError>>;
+
+    /// Counts the tokens `text` takes up for this model.
+    fn count_tokens<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<u32, ModelError>>;
+}
+
+/// A [`Client`] bound to a model, created with [`HunyuanChatModel::new`].
+#[derive(Clone)]
+pub struct HunyuanChatModel {
+    client: Client,
+    model: String,
+}
+
+impl HunyuanChatModel {
+    /// Uses `client` with `model` for every call.
+    pub fn new(client: Client, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+        }
+    }
+}
+
+impl ChatModel for HunyuanChatModel {
+    fn info(&self) -> ModelInfo {
+        info(&self.model)
+    }
+
+    fn generate<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+    ) -> BoxFuture<'a, Result<String, ModelError>> {
+        Box::pin(generate(&self.client, &self.model, messages))
+    }
+
+    fn generate_stream<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+    ) -> BoxFuture<'a, Result<TextStream, ModelError>> {
+        Box::pin(generate_stream(&self.client, &self.model, messages))
+    }
+
+    fn count_tokens<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<u32, ModelError>> {
+        Box::pin(count_tokens(&self.client, text))
+    }
+}
+
+/// Uses [`DEFAULT_CHAT_MODEL`]; wrap the client in a [`HunyuanChatModel`]
+/// to pick another model.
+impl ChatModel for Client {
+    fn info(&self) -> ModelInfo {
+        info(DEFAULT_CHAT_MODEL)
+    }
+
+    fn generate<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+    ) -> BoxFuture<'a, Result<String, ModelError>> {
+        Box::pin(generate(self, DEFAULT_CHAT_MODEL, messages))
+    }
+
+    fn generate_stream<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+    ) -> BoxFuture<'a, Result<TextStream, ModelError>> {
+        Box::pin(generate_stream(self, DEFAULT_CHAT_MODEL, messages))
+    }
+
+    fn count_tokens<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<u32, ModelError>> {
+        Box::pin(count_tokens(self, text))
+    }
+}
+
+fn info(model: &str) -> ModelInfo {
+    ModelInfo {
+        provider: "hunyuan".to_string(),
+        model: model.to_string(),
+        supports_streaming: true,
+    }
+}
+
+fn request(model: &str, messages: &[ChatMessage]) -> ChatCompletionsRequest {
+    ChatCompletionsRequest {
+        model: Some(model.to_string()),
+        messages: messages
+            .iter()
+            .map(|m| Message {
+                role: m.role.as_str().to_string(),
+                content: m.content.clone(),
+                ..Default::default()
+            })
+            .collect(),
+        temperature: None,
+        top_p: None,
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    }
+}
+
+async fn generate(
+    client: &Client,
+    model: &str,
+    messages: &[ChatMessage],
+) -> Result<String, ModelError> {
+    let resp = client.chat_completions(&request(model, messages)).await?;
+    Ok(resp
+        .response
+        .choices
+        .and_then(|choices| choices.into_iter().next()?.message?.content)
+        .unwrap_or_default())
+}
+
+async fn generate_stream(
+    client: &Client,
+    model: &str,
+    messages: &[ChatMessage],
+) -> Result<TextStream, ModelError> {
+    let stream = client
+        .chat_completions_stream(&request(model, messages))
+        .await?;
+    Ok(Box::pin(stream.filter_map(|chunk| async move {
+        match chunk {
+            Ok(chunk) => chunk
+                .choices?
+                .into_iter()
+                .find(|c| c.index.unwrap_or(0) == 0)?
+                .delta?
+                .content
+                .filter(|text| !text.is_empty())
+                .map(Ok),
+            Err(err) => Some(Err(err.into())),
+        }
+    })))
+}
+
+/// Uses the `GetTokenCount` action.
+async fn count_tokens(client: &Client, text: &str) -> Result<u32, ModelError> {
+    Ok(client.get_token_count(text).await?)
+}
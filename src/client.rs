@@ -1,30 +1,81 @@
-use crate::models::{ChatCompletionsRequest, ChatCompletionsResponse, TencentCloudErrorResponse};
-use reqwest::header::{HeaderMap, HeaderValue};
+use crate::action::Action;
+use crate::clock::{Clock, ServerTime, SystemClock};
+use crate::coalesce::Coalescer;
+use crate::credential::CredentialProvider;
+use crate::embedding_cache::EmbeddingCache;
+use crate::idempotency::{Call, Claim, Idempotency};
+use crate::limiter::{Limiter, Permit};
+use crate::logging::{LogLevel, LogSink, StderrLogSink};
+use crate::metrics::{CallMetrics, MetricsSink};
+use crate::models::{
+    ChatCompletionsRequest, ChatCompletionsResponse, EmbeddingData, EmbeddingUsage,
+    GetEmbeddingRequest, GetEmbeddingResponse, GetEmbeddingResponseInner, Message, Role,
+    TencentCloudErrorResponse, TencentCloudResponse, TextToImageLiteRequest,
+    TextToImageLiteResponse, ENCODING_FORMAT_FLOAT, MAX_EMBEDDING_INPUTS,
+};
+use crate::retry::{retry_after, Failure, RetryPolicy};
+use crate::serializer::BodySerializer;
+use crate::shutdown::{InFlight, Lifecycle};
+use crate::stream::{ChatCompletionsStream, DEFAULT_MAX_SSE_BUFFER_BYTES};
+use crate::tools::{RunOptions, ToolRegistry};
+use crate::trace::{TraceContext, TraceContextProvider};
+use crate::transport::{PreparedRequest, RawResponse, ReqwestTransport, Transport};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_ENCODING};
 use reqwest::Client as HttpClient;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tencentcloud_sign_sdk::{sha256_hex, Tc3Signer};
 use thiserror::Error;
-use time::OffsetDateTime;
 
 const SERVICE: &str = "hunyuan";
 const VERSION: &str = "2023-09-01";
-const ACTION_CHAT_COMPLETIONS: &str = "ChatCompletions";
+/// Default `X-TC-RequestClient`, identifying this crate and version.
+const DEFAULT_REQUEST_CLIENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+const ACTION_CHAT_COMPLETIONS: &str = Action::ChatCompletions.name();
+const ACTION_GET_EMBEDDING: &str = Action::GetEmbedding.name();
+const ACTION_TEXT_TO_IMAGE_LITE: &str = Action::TextToImageLite.name();
+const ACTION_GET_TOKEN_COUNT: &str = Action::GetTokenCount.name();
+
+/// `GetEmbedding` requests in flight at once in [`Client::embed_batch`].
+const EMBED_BATCH_CONCURRENCY: usize = 4;
+const CANONICAL_QUERYSTRING: &str = "";
+const SIGNED_HEADERS: &str = "content-type;host";
 
 /// Credential for authenticating with Tencent Cloud.
 ///
 /// - `secret_id` and `secret_key` are required
 /// - Optional session `token` is sent as `X-TC-Token`
-#[derive(Debug, Clone)]
+///
+/// Credentials can be deserialized (`secret_id`, `secret_key`, `token`) but
+/// deliberately do not implement `Serialize`, so they don't end up in logs or
+/// dumped configs by accident.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct Credential {
     pub secret_id: String,
     pub secret_key: String,
+    #[serde(default)]
     pub token: Option<String>,
 }
 
 /// Supported regions. Use `Region::Custom` to pass a custom region string.
-#[derive(Debug, Clone)]
+///
+/// Serializes as the region name, e.g. `"ap-beijing"`. Deserializes from a
+/// region name, a variant name (`"ApBeijing"`) or `{ "Custom": "..." }`.
+///
+/// New regions may be added in minor releases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Region {
     ApBeijing,
     ApGuangzhou,
@@ -40,10 +91,48 @@ impl Region {
             Region::Custom(s) => s.as_str(),
         }
     }
+
+    /// Parses a region or variant name; unknown names become
+    /// [`Region::Custom`].
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "ap-beijing" | "ApBeijing" => Region::ApBeijing,
+            "ap-guangzhou" | "ApGuangzhou" => Region::ApGuangzhou,
+            other => Region::Custom(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for Region {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Region {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Name(String),
+            Custom {
+                #[serde(rename = "Custom")]
+                custom: String,
+            },
+        }
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Name(name) => Region::from_name(&name),
+            Repr::Custom { custom } => Region::Custom(custom),
+        })
+    }
 }
 
 /// Errors returned by the SDK.
+///
+/// Variants may be added in minor releases, so matches outside this crate
+/// need a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum SdkError {
     #[error("http error: {0}")]
     Http(#[from] reqwest::Error),
@@ -55,6 +144,1017 @@ pub enum SdkError {
         message: String,
         request_id: Option<String>,
     },
+    /// The service throttled the call, either with HTTP 429 or with a
+    /// `RequestLimitExceeded` error code. `retry_after` is the delay asked
+    /// for by a `Retry-After` header, if any; `code` is `HTTP_429` when the
+    /// response carried no error code.
+    #[error(
+        "rate limited: {code}: {message} (request_id={request_id:?}, retry_after={retry_after:?})"
+    )]
+    RateLimited {
+        code: String,
+        message: String,
+        request_id: Option<String>,
+        retry_after: Option<Duration>,
+    },
+    /// The transport failed without a `reqwest` error, e.g. a custom
+    /// [`Transport`] losing its connection.
+    #[error("transport error: {0}")]
+    Transport(String),
+    /// A response exceeded a configured size limit. `status` is set when
+    /// the whole body was over [`ClientBuilder::max_response_bytes`]; the
+    /// read stops at the limit, so `received` is what had arrived by then.
+    /// `request_id` is taken from the `X-TC-RequestId` response header,
+    /// since the body holding `RequestId` is not read.
+    #[error("response too large: {received} bytes exceeds limit of {limit}")]
+    ResponseTooLarge {
+        limit: usize,
+        received: usize,
+        status: Option<u16>,
+        request_id: Option<String>,
+    },
+    /// A streamed event grew past [`ClientBuilder::max_sse_buffer_bytes`]
+    /// without ending, e.g. a malformed event missing its blank line. The
+    /// stream ends with this error and releases the buffered bytes.
+    #[error("stream event too large: {size} bytes without an end exceeds limit of {limit}")]
+    StreamEventTooLarge { limit: usize, size: usize },
+    /// The response body is not a JSON API response, e.g. an HTML error page
+    /// from a proxy. `snippet` holds the start of the body.
+    #[error("unexpected response: status={status} content_type={content_type:?}: {snippet}")]
+    UnexpectedResponse {
+        status: u16,
+        content_type: Option<String>,
+        snippet: String,
+    },
+    /// The request could not be signed, e.g. because the clock returned a
+    /// timestamp outside the range TC3 signing supports.
+    #[error("signing error: {0}")]
+    Signing(String),
+    /// The call was not sent because an argument is invalid, e.g. an action
+    /// name or API version that cannot be sent as a header value, or a
+    /// request field with an unsupported value.
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    /// [`ClientBuilder::try_build`] rejected the builder's settings, e.g. an
+    /// HTTP/2 [`HttpVersionOrder`] without the `http2` feature.
+    #[error("invalid client configuration: {0}")]
+    InvalidConfig(String),
+    /// Data embedded in a response could not be decoded, e.g. malformed
+    /// base64 image data.
+    #[error("decode error: {0}")]
+    Decode(String),
+    /// The call was not made or not finished because the operation it was
+    /// part of was aborted, e.g. a fail-fast batch after an earlier failure.
+    #[error("aborted: {0}")]
+    Aborted(String),
+    /// A successful response carried no choice with a message.
+    #[error("response has no choices (request id: {})", request_id.as_deref().unwrap_or("none"))]
+    EmptyResponse { request_id: Option<String> },
+    /// The account's free or purchased resource packs are used up, or the
+    /// service was stopped for arrears; see [`QUOTA_EXHAUSTED_CODES`].
+    /// Retrying will not help until the account is topped up.
+    #[error("quota exhausted: {code}: {message} (request id: {})", request_id.as_deref().unwrap_or("none"))]
+    QuotaExhausted {
+        code: String,
+        message: String,
+        request_id: Option<String>,
+    },
+    /// A call with the same [`RequestOptions::idempotency_key`] is still in
+    /// flight; this one was not sent.
+    #[error("a call with idempotency key {key:?} is already in flight")]
+    DuplicateRequest { key: String },
+    /// The [`RequestOptions::idempotency_key`] is in flight or remembered
+    /// for a call with a different action or body; this one was not sent.
+    #[error("idempotency key {key:?} was used for a different call")]
+    IdempotencyKeyMismatch { key: String },
+    /// The client was shut down with [`Client::shutdown`]; the call was not
+    /// sent.
+    #[error("client closed")]
+    ClientClosed,
+    /// A polled job was still pending when the wait deadline passed; see
+    /// [`jobs::poll_until`](crate::jobs::poll_until).
+    #[error("job still pending after {waited:?} (last status: {last_status})")]
+    PollTimeout {
+        waited: Duration,
+        last_status: String,
+    },
+}
+
+/// Signs a request for any TencentCloud `service` (e.g. `cvm`) with
+/// TC3-HMAC-SHA256 and returns `(signature, credential_scope)`, where the
+/// scope is `<date>/<service>/tc3_request`.
+///
+/// [`Client`] signs with service `hunyuan`; this exposes the same primitive
+/// for other services, e.g. to test signatures against their documentation.
+/// Fails with [`SdkError::Signing`] for a timestamp before the Unix epoch or
+/// beyond the year 9999.
+#[allow(clippy::too_many_arguments)]
+pub fn tc3_sign_with_service(
+    credential: &Credential,
+    service: &str,
+    method: &str,
+    canonical_uri: &str,
+    canonical_querystring: &str,
+    canonical_headers: &str,
+    signed_headers: &str,
+    hashed_payload: &str,
+    timestamp: i64,
+) -> SdkResult<(String, String)> {
+    // The signer unwraps the date conversion, so reject bad timestamps first.
+    credential_date(timestamp)?;
+    let signer = Tc3Signer::new(
+        credential.secret_id.clone(),
+        credential.secret_key.clone(),
+        service.to_string(),
+        false,
+    );
+    let result = signer.sign(
+        method,
+        canonical_uri,
+        canonical_querystring,
+        canonical_headers,
+        signed_headers,
+        hashed_payload,
+        timestamp,
+    );
+    Ok((result.signature, result.credential_scope))
+}
+
+/// Result type returned by the SDK.
+pub type SdkResult<T> = Result<T, SdkError>;
+
+/// See [`ClientBuilder::prompt_sanitizer`].
+type PromptSanitizer = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// See [`ClientBuilder::retry_if`].
+type RetryPredicate = Arc<dyn Fn(&SdkError) -> bool + Send + Sync>;
+
+impl SdkError {
+    /// Whether retrying the call may succeed: connection failures and
+    /// timeouts, rate limiting, internal errors and 5xx/429 responses.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SdkError::Http(e) => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+            SdkError::Transport(_) | SdkError::RateLimited { .. } => true,
+            SdkError::Serde(_)
+            | SdkError::ResponseTooLarge { .. }
+            | SdkError::StreamEventTooLarge { .. }
+            | SdkError::Signing(_)
+            | SdkError::InvalidRequest(_)
+            | SdkError::InvalidConfig(_)
+            | SdkError::Decode(_)
+            | SdkError::Aborted(_)
+            | SdkError::QuotaExhausted { .. }
+            | SdkError::EmptyResponse { .. }
+            | SdkError::DuplicateRequest { .. }
+            | SdkError::IdempotencyKeyMismatch { .. }
+            | SdkError::ClientClosed
+            | SdkError::PollTimeout { .. } => false,
+            SdkError::Service { code, .. } => {
+                code.starts_with("RequestLimitExceeded")
+                    || code == "InternalError"
+                    || code.starts_with("InternalError.")
+                    || code == "HTTP_429"
+                    || code.starts_with("HTTP_5")
+            }
+            SdkError::UnexpectedResponse { status, .. } => *status == 429 || *status >= 500,
+        }
+    }
+
+    /// A copy of this error for another caller sharing the same call.
+    ///
+    /// `reqwest` and `serde_json` errors cannot be cloned, so they are copied
+    /// as [`SdkError::Transport`] and [`SdkError::Serde`] with the same
+    /// message.
+    pub(crate) fn duplicate(&self) -> SdkError {
+        match self {
+            SdkError::Http(e) => SdkError::Transport(e.to_string()),
+            SdkError::Serde(e) => SdkError::Serde(serde::de::Error::custom(e)),
+            SdkError::Service {
+                code,
+                message,
+                request_id,
+            } => SdkError::Service {
+                code: code.clone(),
+                message: message.clone(),
+                request_id: request_id.clone(),
+            },
+            SdkError::RateLimited {
+                code,
+                message,
+                request_id,
+                retry_after,
+            } => SdkError::RateLimited {
+                code: code.clone(),
+                message: message.clone(),
+                request_id: request_id.clone(),
+                retry_after: *retry_after,
+            },
+            SdkError::Transport(message) => SdkError::Transport(message.clone()),
+            SdkError::ResponseTooLarge {
+                limit,
+                received,
+                status,
+                request_id,
+            } => SdkError::ResponseTooLarge {
+                limit: *limit,
+                received: *received,
+                status: *status,
+                request_id: request_id.clone(),
+            },
+            SdkError::StreamEventTooLarge { limit, size } => SdkError::StreamEventTooLarge {
+                limit: *limit,
+                size: *size,
+            },
+            SdkError::UnexpectedResponse {
+                status,
+                content_type,
+                snippet,
+            } => SdkError::UnexpectedResponse {
+                status: *status,
+                content_type: content_type.clone(),
+                snippet: snippet.clone(),
+            },
+            SdkError::Signing(message) => SdkError::Signing(message.clone()),
+            SdkError::InvalidRequest(message) => SdkError::InvalidRequest(message.clone()),
+            SdkError::InvalidConfig(message) => SdkError::InvalidConfig(message.clone()),
+            SdkError::Decode(message) => SdkError::Decode(message.clone()),
+            SdkError::Aborted(message) => SdkError::Aborted(message.clone()),
+            SdkError::QuotaExhausted {
+                code,
+                message,
+                request_id,
+            } => SdkError::QuotaExhausted {
+                code: code.clone(),
+                message: message.clone(),
+                request_id: request_id.clone(),
+            },
+            SdkError::EmptyResponse { request_id } => SdkError::EmptyResponse {
+                request_id: request_id.clone(),
+            },
+            SdkError::DuplicateRequest { key } => SdkError::DuplicateRequest { key: key.clone() },
+            SdkError::IdempotencyKeyMismatch { key } => {
+                SdkError::IdempotencyKeyMismatch { key: key.clone() }
+            }
+            SdkError::ClientClosed => SdkError::ClientClosed,
+            SdkError::PollTimeout {
+                waited,
+                last_status,
+            } => SdkError::PollTimeout {
+                waited: *waited,
+                last_status: last_status.clone(),
+            },
+        }
+    }
+
+    /// Turns throttling into [`SdkError::RateLimited`]: a service error with
+    /// a `RequestLimitExceeded` code or from an HTTP 429 response, and an
+    /// unexpected response with status 429. Turns a service error with one of
+    /// [`QUOTA_EXHAUSTED_CODES`] into [`SdkError::QuotaExhausted`]. Other
+    /// errors are returned as they are.
+    pub(crate) fn classify(self, retry_after: Option<Duration>) -> SdkError {
+        match self {
+            SdkError::Service {
+                code,
+                message,
+                request_id,
+            } if QUOTA_EXHAUSTED_CODES.contains(&code.as_str()) => SdkError::QuotaExhausted {
+                code,
+                message,
+                request_id,
+            },
+            SdkError::Service {
+                code,
+                message,
+                request_id,
+            } if code.starts_with("RequestLimitExceeded") || code == "HTTP_429" => {
+                SdkError::RateLimited {
+                    code,
+                    message,
+                    request_id,
+                    retry_after,
+                }
+            }
+            SdkError::UnexpectedResponse {
+                status: 429,
+                snippet,
+                ..
+            } => SdkError::RateLimited {
+                code: "HTTP_429".to_string(),
+                message: snippet,
+                request_id: None,
+                retry_after,
+            },
+            other => other,
+        }
+    }
+}
+
+/// Serializable form of an [`SdkError`], e.g. for a JSON error body returned
+/// by a web service wrapping the SDK. Created with [`SdkError::to_repr`].
+///
+/// ```
+/// use tencentcloud_hunyuan_sdk::SdkError;
+///
+/// let err = SdkError::Service {
+///     code: "LimitExceeded".to_string(),
+///     message: "quota used up".to_string(),
+///     request_id: Some("req-1".to_string()),
+/// };
+/// assert_eq!(
+///     serde_json::to_value(err.to_repr()).unwrap(),
+///     serde_json::json!({
+///         "kind": "service",
+///         "code": "LimitExceeded",
+///         "message": "quota used up",
+///         "request_id": "req-1",
+///     })
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdkErrorRepr {
+    /// Snake-case name of the [`SdkError`] variant, e.g. `service` or
+    /// `response_too_large`.
+    pub kind: String,
+    /// Service error code, or `HTTP_<status>` for an unexpected response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// The service's message for service errors, the error's display text
+    /// otherwise.
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl SdkError {
+    /// Projects the error onto a serializable [`SdkErrorRepr`].
+    pub fn to_repr(&self) -> SdkErrorRepr {
+        let kind = match self {
+            SdkError::Http(_) => "http",
+            SdkError::Serde(_) => "serde",
+            SdkError::Service { .. } => "service",
+            SdkError::RateLimited { .. } => "rate_limited",
+            SdkError::Transport(_) => "transport",
+            SdkError::ResponseTooLarge { .. } => "response_too_large",
+            SdkError::StreamEventTooLarge { .. } => "stream_event_too_large",
+            SdkError::UnexpectedResponse { .. } => "unexpected_response",
+            SdkError::Signing(_) => "signing",
+            SdkError::InvalidRequest(_) => "invalid_request",
+            SdkError::InvalidConfig(_) => "invalid_config",
+            SdkError::Decode(_) => "decode",
+            SdkError::Aborted(_) => "aborted",
+            SdkError::QuotaExhausted { .. } => "quota_exhausted",
+            SdkError::EmptyResponse { .. } => "empty_response",
+            SdkError::DuplicateRequest { .. } => "duplicate_request",
+            SdkError::IdempotencyKeyMismatch { .. } => "idempotency_key_mismatch",
+            SdkError::ClientClosed => "client_closed",
+            SdkError::PollTimeout { .. } => "poll_timeout",
+        };
+        let (code, message, request_id) = match self {
+            SdkError::Service {
+                code,
+                message,
+                request_id,
+            }
+            | SdkError::RateLimited {
+                code,
+                message,
+                request_id,
+                ..
+            }
+            | SdkError::QuotaExhausted {
+                code,
+                message,
+                request_id,
+            } => (Some(code.clone()), message.clone(), request_id.clone()),
+            SdkError::UnexpectedResponse { status, .. } => {
+                (Some(format!("HTTP_{}", status)), self.to_string(), None)
+            }
+            SdkError::EmptyResponse { request_id }
+            | SdkError::ResponseTooLarge { request_id, .. } => {
+                (None, self.to_string(), request_id.clone())
+            }
+            _ => (None, self.to_string(), None),
+        };
+        SdkErrorRepr {
+            kind: kind.to_string(),
+            code,
+            message,
+            request_id,
+        }
+    }
+}
+
+/// Service error codes reported as [`SdkError::QuotaExhausted`]: used-up
+/// free or purchased resource packs, charged resources and a service
+/// stopped for arrears.
+pub const QUOTA_EXHAUSTED_CODES: &[&str] = &[
+    "FailedOperation.FreeResourcePackExhausted",
+    "FailedOperation.ResourcePackExhausted",
+    "FailedOperation.ServiceStopArrears",
+    "ResourceInsufficient.ChargeResourceExhaust",
+];
+
+/// Maximum number of characters of a body kept in
+/// [`SdkError::UnexpectedResponse`].
+const SNIPPET_CHARS: usize = 200;
+
+/// Maximum number of body bytes written to a debug log line.
+const LOG_BODY_BYTES: usize = 4096;
+
+/// Detects bodies that cannot be an API response: HTML pages for any status,
+/// plus any non-JSON content type on success.
+pub(crate) fn unexpected_response(
+    status: u16,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Option<SdkError> {
+    let body = body.trim_ascii();
+    let is_html = content_type.is_some_and(|ct| ct.contains("html")) || body.starts_with(b"<");
+    let non_json_success =
+        (200..300).contains(&status) && content_type.is_some_and(|ct| !ct.contains("json"));
+    if !is_html && !non_json_success {
+        return None;
+    }
+    // Only the start of the body is decoded; a character takes at most 4 bytes.
+    let prefix = &body[..body.len().min(SNIPPET_CHARS * 4)];
+    let text = String::from_utf8_lossy(prefix);
+    let text = text.trim();
+    let mut snippet: String = text.chars().take(SNIPPET_CHARS).collect();
+    if prefix.len() < body.len() || text.chars().count() > SNIPPET_CHARS {
+        snippet.push_str("...");
+    }
+    Some(SdkError::UnexpectedResponse {
+        status,
+        content_type: content_type.map(str::to_string),
+        snippet,
+    })
+}
+
+/// Checks that the `Index` of the choices in a `ChatCompletions` response
+/// are `0..n` in some order, as [`DeserializationMode::Strict`] requires. A
+/// choice without `Index` counts as `0`.
+fn choice_index_error(status: u16, content_type: Option<&str>, body: &[u8]) -> Option<SdkError> {
+    #[derive(Deserialize)]
+    struct Choice {
+        #[serde(rename = "Index")]
+        index: Option<u32>,
+    }
+    #[derive(Deserialize)]
+    struct Inner {
+        #[serde(rename = "Choices")]
+        choices: Option<Vec<Choice>>,
+    }
+
+    let choices = serde_json::from_slice::<TencentCloudResponse<Inner>>(body)
+        .ok()?
+        .response
+        .choices?;
+    let indices: Vec<u32> = choices.iter().map(|c| c.index.unwrap_or(0)).collect();
+    let mut seen = vec![false; indices.len()];
+    let valid = indices.iter().all(|&i| {
+        seen.get_mut(i as usize)
+            .is_some_and(|seen| !std::mem::replace(seen, true))
+    });
+    if valid {
+        return None;
+    }
+    Some(SdkError::UnexpectedResponse {
+        status,
+        content_type: content_type.map(str::to_string),
+        snippet: format!(
+            "choice indices {:?} are not unique and contiguous from 0",
+            indices
+        ),
+    })
+}
+
+/// Host part of `endpoint`, without any scheme prefix.
+fn host_of(endpoint: &str) -> &str {
+    endpoint
+        .strip_prefix("https://")
+        .or_else(|| endpoint.strip_prefix("http://"))
+        .unwrap_or(endpoint)
+}
+
+/// IP address and port, if any, of an endpoint host such as `10.0.0.8:443`,
+/// `[::1]` or `::1`.
+fn ip_of(host: &str) -> Option<(std::net::IpAddr, Option<u16>)> {
+    if let Ok(addr) = host.parse::<std::net::SocketAddr>() {
+        return Some((addr.ip(), Some(addr.port())));
+    }
+    let bare = host
+        .strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host);
+    bare.parse().ok().map(|ip| (ip, None))
+}
+
+/// URL of `path` on `host` with the endpoint's scheme, defaulting to HTTPS.
+fn request_url(endpoint: &str, host: &str, path: &str) -> String {
+    if endpoint.starts_with("http://") {
+        format!("http://{}{}", host, path)
+    } else {
+        format!("https://{}{}", host, path)
+    }
+}
+
+/// Headers the SDK sets itself, which [`ClientBuilder::default_header`] and
+/// [`RequestOptions::header`] refuse.
+const PROTECTED_HEADERS: &[&str] = &[
+    "Host",
+    "Content-Type",
+    "Authorization",
+    "X-TC-Action",
+    "X-TC-Version",
+    "X-TC-Region",
+    "X-TC-Timestamp",
+    "X-TC-Token",
+    "X-TC-RequestClient",
+];
+
+fn is_protected(name: &HeaderName) -> bool {
+    PROTECTED_HEADERS
+        .iter()
+        .any(|protected| name.as_str().eq_ignore_ascii_case(protected))
+}
+
+/// Parses a custom header, failing with a message naming `what` if it is
+/// invalid or one the SDK sets itself.
+fn custom_header(what: &str, name: &str, value: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let name = HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| format!("{} {:?} is not a valid header name", what, name))?;
+    if is_protected(&name) {
+        return Err(format!(
+            "{} {:?} is set by the SDK and cannot be overridden",
+            what,
+            name.as_str()
+        ));
+    }
+    let value = HeaderValue::from_str(value)
+        .map_err(|_| format!("{} {:?} has an invalid header value", what, name.as_str()))?;
+    Ok((name, value))
+}
+
+/// `value` as the value of the `what` header, or [`SdkError::InvalidConfig`]
+/// naming the client setting it came from.
+fn config_header_value(what: &str, value: &str) -> SdkResult<HeaderValue> {
+    HeaderValue::from_str(value).map_err(|_| {
+        SdkError::InvalidConfig(format!("{} {:?} is not a valid header value", what, value))
+    })
+}
+
+/// Headers that are the same for every request of a client: the extra and
+/// default headers, then the managed headers that do not depend on the call, which
+/// take precedence. Capacity is left for the per-call headers.
+fn static_headers(
+    host: HeaderValue,
+    region: HeaderValue,
+    request_client: HeaderValue,
+    extra_headers: Vec<(HeaderName, HeaderValue)>,
+) -> HeaderMap {
+    let mut headers = HeaderMap::with_capacity(extra_headers.len() + 8);
+    for (name, value) in extra_headers {
+        headers.insert(name, value);
+    }
+    headers.insert("X-TC-RequestClient", request_client);
+    headers.insert("Host", host);
+    headers.insert(
+        "Content-Type",
+        HeaderValue::from_static("application/json; charset=utf-8"),
+    );
+    headers.insert("X-TC-Version", HeaderValue::from_static(VERSION));
+    headers.insert("X-TC-Region", region);
+    headers
+}
+
+/// See [`ResponseMeta::seed`].
+fn effective_seed(req: &ChatCompletionsRequest, resp: &ChatCompletionsResponse) -> Option<u32> {
+    let echoed = resp
+        .response
+        .extra
+        .get("Seed")
+        .and_then(serde_json::Value::as_u64);
+    echoed
+        .and_then(|seed| u32::try_from(seed).ok())
+        .or(req.seed)
+}
+
+/// Default for [`ClientBuilder::trace_id_header`].
+pub(crate) const DEFAULT_TRACE_ID_HEADER: &str = "x-tc-traceid";
+
+/// Default for [`ClientBuilder::max_response_bytes`].
+pub(crate) const DEFAULT_MAX_RESPONSE_BYTES: usize = 8 << 20;
+
+/// Request bodies larger than this are not kept in [`BODY_BUFFER`].
+const MAX_RETAINED_BODY_BYTES: usize = 1 << 20;
+
+thread_local! {
+    /// Scratch buffer request bodies are serialized into, so repeated calls
+    /// on a thread do not regrow a fresh buffer every time.
+    static BODY_BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Serializes `value` as compact JSON into a buffer of exactly its size.
+fn to_body<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<Bytes> {
+    BODY_BUFFER.with(|buffer| {
+        // A serializer calling back into the client on the same thread finds
+        // the buffer in use and falls back to a fresh one.
+        let Ok(mut buffer) = buffer.try_borrow_mut() else {
+            return serde_json::to_vec(value).map(Bytes::from);
+        };
+        buffer.clear();
+        let body =
+            serde_json::to_writer(&mut *buffer, value).map(|()| Bytes::copy_from_slice(&buffer));
+        if buffer.capacity() > MAX_RETAINED_BODY_BYTES {
+            *buffer = Vec::new();
+        }
+        body
+    })
+}
+
+/// `body` as text for a debug log line, cut after [`LOG_BODY_BYTES`] bytes.
+fn log_body(body: &[u8]) -> Cow<'_, str> {
+    if body.len() <= LOG_BODY_BYTES {
+        return String::from_utf8_lossy(body);
+    }
+    let mut text = String::from_utf8_lossy(&body[..LOG_BODY_BYTES]).into_owned();
+    text.push_str(&format!("... ({} bytes)", body.len()));
+    Cow::Owned(text)
+}
+
+/// Reads `RequestId` and `Error` from a Tencent Cloud response envelope, with
+/// or without the outer `Response` object.
+fn read_envelope(body: &[u8]) -> Option<TencentCloudErrorResponse> {
+    serde_json::from_slice::<TencentCloudResponse<TencentCloudErrorResponse>>(body)
+        .map(|r| r.response)
+        .or_else(|_| serde_json::from_slice::<TencentCloudErrorResponse>(body))
+        .ok()
+}
+
+/// The request body as text; the signer hashes a `&str`.
+fn body_text(body: &[u8]) -> SdkResult<&str> {
+    std::str::from_utf8(body)
+        .map_err(|e| SdkError::Signing(format!("request body is not UTF-8: {}", e)))
+}
+
+/// The parts of a request that TC3 signs, built by
+/// [`Client::canonical_request`] for both signing and
+/// [`Client::canonical_request_for`] so the two cannot drift apart.
+struct CanonicalRequest<'a> {
+    method: &'static str,
+    uri: &'a str,
+    headers: String,
+    hashed_payload: String,
+}
+
+impl std::fmt::Display for CanonicalRequest<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            self.method,
+            self.uri,
+            CANONICAL_QUERYSTRING,
+            self.headers,
+            SIGNED_HEADERS,
+            self.hashed_payload
+        )
+    }
+}
+
+/// `value` as the value of the `what` header, or [`SdkError::InvalidRequest`]
+/// if it contains characters a header cannot carry, such as a newline.
+fn header_value(what: &str, value: &str) -> SdkResult<HeaderValue> {
+    HeaderValue::from_str(value).map_err(|_| {
+        SdkError::InvalidRequest(format!("{} {:?} is not a valid header value", what, value))
+    })
+}
+
+/// The `YYYY-MM-DD` date of the TC3 credential scope for `timestamp`.
+fn credential_date(timestamp: i64) -> SdkResult<String> {
+    if timestamp < 0 {
+        return Err(SdkError::Signing(format!(
+            "timestamp {} is before the Unix epoch",
+            timestamp
+        )));
+    }
+    time::OffsetDateTime::from_unix_timestamp(timestamp)
+        .map_err(|e| SdkError::Signing(format!("timestamp {}: {}", timestamp, e)))?
+        .format(time::macros::format_description!("[year]-[month]-[day]"))
+        .map_err(|e| SdkError::Signing(format!("timestamp {}: {}", timestamp, e)))
+}
+
+/// Metadata about a completed call, returned alongside the typed response by
+/// the `*_with_meta` methods.
+#[derive(Debug, Clone)]
+pub struct ResponseMeta {
+    /// `RequestId` reported by the service, if present in the response.
+    pub request_id: Option<String>,
+    /// HTTP status code of the response.
+    pub status: u16,
+    /// Wall-clock time from sending the request to receiving the full body.
+    pub elapsed: Duration,
+    /// Whether `elapsed` exceeded the configured slow-request threshold.
+    pub slow: bool,
+    /// Number of attempts made, including retries.
+    pub attempts: u32,
+    /// Model that served a `ChatCompletions` request, which differs from the
+    /// requested one when the model fallback chain was used.
+    pub model: Option<String>,
+    /// Seed that produced a `ChatCompletions` response: the one the server
+    /// reports in `Seed`, if it does, or else the one sent with
+    /// [`ChatCompletionsRequest::with_seed`]. Log it next to the output to
+    /// reproduce it later.
+    pub seed: Option<u32>,
+    /// Trace id sent with [`RequestOptions::trace_id`].
+    pub trace_id: Option<String>,
+}
+
+/// Outcome of [`Client::warm_up`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarmUp {
+    /// HTTP status of the warm-up request; any status means the connection
+    /// was established.
+    pub status: u16,
+    /// Time taken, including DNS resolution and the TCP and TLS handshakes
+    /// when no pooled connection existed yet.
+    pub elapsed: Duration,
+}
+
+/// Per-call options for [`Client::call`] and [`Client::call_raw`].
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// API version sent as `X-TC-Version` for this call only. Defaults to the
+    /// client's version (`2023-09-01`).
+    pub version: Option<String>,
+    /// Application-defined labels, e.g. tenant or feature, passed to the
+    /// metrics sink and debug logs. Never sent to the server.
+    pub labels: HashMap<String, String>,
+    /// Trace context sent as `traceparent`/`tracestate`, overriding the
+    /// client's [`TraceContextProvider`].
+    pub trace_context: Option<TraceContext>,
+    /// Response length hint for this `ChatCompletions` call, replacing the
+    /// client's [`ClientBuilder::response_token_hint`].
+    pub response_token_hint: Option<u32>,
+    /// Place in line while the client's concurrency limit is reached; see
+    /// [`ClientBuilder::max_concurrent_requests`].
+    pub priority: Priority,
+    /// Key identifying this call across resubmissions; see
+    /// [`RequestOptions::idempotency_key`].
+    pub idempotency_key: Option<String>,
+    /// Headers sent with this call only, as name/value pairs in the order
+    /// they were added; see [`RequestOptions::header`].
+    pub extra_headers: Vec<(String, String)>,
+    /// Response size limit for this call, replacing the client's
+    /// [`ClientBuilder::max_response_bytes`].
+    pub max_response_bytes: Option<usize>,
+    /// Correlation id sent in the client's
+    /// [`ClientBuilder::trace_id_header`]; see [`RequestOptions::trace_id`].
+    pub trace_id: Option<String>,
+}
+
+impl RequestOptions {
+    /// Creates empty options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override `X-TC-Version` for this call, e.g. to migrate one action to a
+    /// newer dated API without changing the whole client. A version that is
+    /// not a valid header value fails the call with
+    /// [`SdkError::InvalidRequest`] before anything is sent.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Attach a label to this call for metrics and logs.
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Send `context` as the W3C `traceparent` and `tracestate` headers of
+    /// this call, so it joins a distributed trace.
+    pub fn trace_context(mut self, context: TraceContext) -> Self {
+        self.trace_context = Some(context);
+        self
+    }
+
+    /// Ask for a response under `tokens` tokens in this call, instead of the
+    /// client's [`ClientBuilder::response_token_hint`].
+    pub fn response_token_hint(mut self, tokens: u32) -> Self {
+        self.response_token_hint = Some(tokens);
+        self
+    }
+
+    /// Wait for a free slot with `priority` when the client's concurrency
+    /// limit is reached, e.g. [`Priority::High`] for a user waiting on the
+    /// answer and [`Priority::Low`] for batch jobs.
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Guard this call against duplicate submission, e.g. a job submission
+    /// resent after a timeout, which would otherwise be billed twice.
+    ///
+    /// The client remembers the key of every call made with one. Another
+    /// call with the same key then fails with [`SdkError::DuplicateRequest`]
+    /// while the first is in flight, and gets the first call's response
+    /// without sending anything for 10 minutes after it succeeded. A call
+    /// reusing the key with a different action or body fails with
+    /// [`SdkError::IdempotencyKeyMismatch`] in the meantime. A failed or
+    /// cancelled call is forgotten, so it can be sent again. Up to 1024 keys
+    /// are remembered across all clones of the client.
+    ///
+    /// The key is also sent as the unsigned `Idempotency-Key` header for
+    /// correlation on the server side, so a key that is not a valid header
+    /// value fails the call with [`SdkError::InvalidRequest`] before anything
+    /// is sent or remembered. Streamed calls are not deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Allow a response of up to `bytes` in this call, instead of the
+    /// client's [`ClientBuilder::max_response_bytes`], e.g. for a large
+    /// batch of embeddings.
+    pub fn max_response_bytes(mut self, bytes: usize) -> Self {
+        self.max_response_bytes = Some(bytes);
+        self
+    }
+
+    /// Propagate `id`, e.g. the correlation id of the incoming request this
+    /// call serves, in the [`ClientBuilder::trace_id_header`] (by default
+    /// `X-TC-TraceId`). The id is not signed; it is added to every debug log
+    /// line of the call and reported in [`ResponseMeta::trace_id`]. An id
+    /// that is not a valid header value fails the call with
+    /// [`SdkError::InvalidRequest`] before anything is sent.
+    pub fn trace_id(mut self, id: impl Into<String>) -> Self {
+        self.trace_id = Some(id.into());
+        self
+    }
+
+    /// Send the header `name` with this call, replacing a
+    /// [`ClientBuilder::default_header`] of the same name. For the same name,
+    /// the last value wins. The header is not signed.
+    ///
+    /// The call fails with [`SdkError::InvalidRequest`] before anything is
+    /// sent if `name` or `value` are not valid header syntax, or if `name` is
+    /// a header the SDK sets itself: `Host`, `Content-Type`, `Authorization`
+    /// and the `X-TC-*` headers of every request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Labels as `k=v` pairs sorted by key, for log lines.
+    fn labels_display(&self) -> String {
+        let mut labels: Vec<_> = self.labels.iter().collect();
+        labels.sort();
+        labels
+            .into_iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// ` trace_id=...` if the call has a trace id, for log lines.
+    fn trace_id_display(&self) -> String {
+        self.trace_id
+            .as_deref()
+            .map(|id| format!(" trace_id={}", id))
+            .unwrap_or_default()
+    }
+}
+
+/// Order in which calls waiting for the concurrency limit of
+/// [`ClientBuilder::max_concurrent_requests`] get a free slot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Priority {
+    /// Served before all other waiting calls.
+    High,
+    /// Served after `High` calls, in arrival order.
+    #[default]
+    Normal,
+    /// Served when no `High` or `Normal` call is waiting, or after being
+    /// passed over 8 times, so it is never starved.
+    Low,
+}
+
+/// How responses are parsed; see [`ClientBuilder::deserialization_mode`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum DeserializationMode {
+    /// Fields the models do not declare are kept in `extra` or skipped.
+    #[default]
+    Lenient,
+    /// A response with fields the models do not declare fails with
+    /// [`SdkError::Decode`] naming their JSON paths.
+    Strict,
+}
+
+/// HTTP versions the built-in `reqwest` client uses, in order of preference;
+/// see [`ClientBuilder::http_version_order`].
+///
+/// The default is [`Http1Only`](Self::Http1Only), with or without the
+/// `http2` feature. The other orders need the feature; without it,
+/// [`ClientBuilder::try_build`] rejects them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum HttpVersionOrder {
+    /// HTTP/1.1 only.
+    #[default]
+    Http1Only,
+    /// Offer HTTP/2 and HTTP/1.1 when negotiating TLS, and use HTTP/1.1 if
+    /// the server does not pick HTTP/2. Plain `http://` endpoints always use
+    /// HTTP/1.1.
+    Http2ThenHttp1,
+    /// HTTP/2 only, without negotiation, also over plain `http://`. Fails
+    /// against servers that do not speak HTTP/2.
+    Http2Only,
+}
+
+impl HttpVersionOrder {
+    /// Whether the order can use HTTP/2, and so needs the `http2` feature.
+    pub fn uses_http2(self) -> bool {
+        !matches!(self, HttpVersionOrder::Http1Only)
+    }
+
+    fn apply(self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        match self {
+            HttpVersionOrder::Http1Only => builder.http1_only(),
+            #[cfg(feature = "http2")]
+            HttpVersionOrder::Http2ThenHttp1 => builder,
+            #[cfg(feature = "http2")]
+            HttpVersionOrder::Http2Only => builder.http2_prior_knowledge(),
+            // Rejected by `ClientBuilder::try_build` before getting here.
+            #[cfg(not(feature = "http2"))]
+            HttpVersionOrder::Http2ThenHttp1 | HttpVersionOrder::Http2Only => builder.http1_only(),
+        }
+    }
+}
+
+/// Options for [`Client::chat_completions_batch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// Maximum number of requests in flight at once. `0` is treated as `1`.
+    pub concurrency: usize,
+    /// Stop at the first failed request. Requests not yet finished then
+    /// fail with [`SdkError::Aborted`].
+    pub fail_fast: bool,
+}
+
+impl Default for BatchOptions {
+    /// Four requests at a time, running every request to completion.
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            fail_fast: false,
+        }
+    }
+}
+
+/// Options for [`Client::chat_structured_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructuredOptions {
+    /// Times the model is shown its unparsable output and the parse error
+    /// and asked to correct it. `0` fails on the first unparsable reply.
+    pub repair_attempts: u32,
+}
+
+impl Default for StructuredOptions {
+    /// One repair attempt.
+    fn default() -> Self {
+        Self { repair_attempts: 1 }
+    }
+}
+
+/// Appended to the last user message by [`Client::chat_structured`].
+const STRUCTURED_FORMAT_NOTE: &str =
+    "Respond with only the JSON value, without markdown fences or commentary.";
+
+/// The JSON inside a model reply: the body of the first ``` fence if there
+/// is one, otherwise the text from the first `{` or `[` to the last `}` or
+/// `]`, otherwise the trimmed reply.
+fn extract_json(reply: &str) -> &str {
+    let reply = reply.trim();
+    if let Some(start) = reply.find("```") {
+        let fenced = &reply[start + 3..];
+        // Skip the info string, e.g. `json`.
+        let body = fenced.find('\n').map_or(fenced, |i| &fenced[i + 1..]);
+        let end = body.find("```").unwrap_or(body.len());
+        return body[..end].trim();
+    }
+    match (reply.find(['{', '[']), reply.rfind(['}', ']'])) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => reply,
+    }
 }
 
 /// Client for calling Hunyuan API actions.
@@ -63,12 +1163,48 @@ pub enum SdkError {
 /// `TENCENTCLOUD_SDK_DEBUG=true`.
 #[derive(Clone)]
 pub struct Client {
-    http: HttpClient,
+    transport: Arc<dyn Transport>,
     credential: Credential,
+    credential_provider: Option<Arc<dyn CredentialProvider>>,
     region: Region,
     endpoint: String,
+    /// Signed and sent `Host`: the host override, or the endpoint's host.
+    host: String,
+    /// Request URL, precomputed from the endpoint; see [`request_url`].
+    url: String,
+    /// URI signed as the TC3 canonical URI: the base URL's path unless set
+    /// with [`ClientBuilder::canonical_uri`].
+    canonical_uri: String,
     debug: bool,
     signer: Tc3Signer,
+    log_sink: Arc<dyn LogSink>,
+    metrics_sink: Option<Arc<dyn MetricsSink>>,
+    slow_request_threshold: Option<Duration>,
+    clock_skew_warning: Option<Duration>,
+    deserialization_mode: DeserializationMode,
+    body_serializer: Option<Arc<dyn BodySerializer>>,
+    retry_policy: Option<RetryPolicy>,
+    retry_predicate: Option<RetryPredicate>,
+    clock: Arc<dyn Clock>,
+    /// Headers shared by every request, built once; see [`static_headers`].
+    static_headers: HeaderMap,
+    trace_context_provider: Option<Arc<dyn TraceContextProvider>>,
+    trace_id_header: HeaderName,
+    prompt_sanitizer: Option<PromptSanitizer>,
+    model_fallback_chain: Vec<String>,
+    default_model: Option<String>,
+    timeout: Option<Duration>,
+    max_sse_buffer_bytes: usize,
+    max_response_bytes: usize,
+    #[cfg(feature = "gzip")]
+    gzip_requests: Option<usize>,
+    embedding_coalescer: Option<Arc<Coalescer<GetEmbeddingResponse>>>,
+    response_token_hint: Option<u32>,
+    embedding_cache: Option<Arc<EmbeddingCache>>,
+    server_time: Arc<ServerTime>,
+    lifecycle: Arc<Lifecycle>,
+    limiter: Option<Arc<Limiter>>,
+    idempotency: Arc<Idempotency>,
 }
 
 /// Builder for [`Client`].
@@ -82,32 +1218,192 @@ pub struct Client {
 ///     .debug(true)
 ///     .build();
 /// ```
-pub struct ClientBuilder {
+///
+/// `ClientBuilder::new()` returns the dynamic builder, whose `build` panics if
+/// no credential was set. [`Client::builder`] returns a typestate builder that
+/// only offers `build` once `credential(...)` has been called.
+#[derive(Default)]
+pub struct ClientBuilder<S = Dynamic> {
     http: Option<HttpClient>,
     credential: Option<Credential>,
+    credential_provider: Option<Arc<dyn CredentialProvider>>,
     region: Option<Region>,
     endpoint: Option<String>,
+    base_path: Option<String>,
+    canonical_uri: Option<String>,
+    host_override: Option<String>,
+    http_version_order: Option<HttpVersionOrder>,
     debug: Option<bool>,
+    log_sink: Option<Arc<dyn LogSink>>,
+    metrics_sink: Option<Arc<dyn MetricsSink>>,
+    slow_request_threshold: Option<Duration>,
+    clock_skew_warning: Option<Duration>,
+    deserialization_mode: Option<DeserializationMode>,
+    transport: Option<Arc<dyn Transport>>,
+    body_serializer: Option<Arc<dyn BodySerializer>>,
+    retry_policy: Option<RetryPolicy>,
+    retry_predicate: Option<RetryPredicate>,
+    clock: Option<Arc<dyn Clock>>,
+    request_client: Option<HeaderValue>,
+    extra_headers: Vec<(HeaderName, HeaderValue)>,
+    /// First invalid setting, reported by `try_build`.
+    config_error: Option<String>,
+    model_fallback_chain: Vec<String>,
+    default_model: Option<String>,
+    timeout: Option<Duration>,
+    warm_up: bool,
+    trace_context_provider: Option<Arc<dyn TraceContextProvider>>,
+    trace_id_header: Option<HeaderName>,
+    prompt_sanitizer: Option<PromptSanitizer>,
+    max_sse_buffer_bytes: Option<usize>,
+    max_response_bytes: Option<usize>,
+    #[cfg(feature = "gzip")]
+    gzip_requests: Option<usize>,
+    coalesce_embeddings: bool,
+    response_token_hint: Option<u32>,
+    embedding_cache: Option<usize>,
+    max_concurrent_requests: Option<usize>,
+    state: PhantomData<S>,
 }
 
-impl Default for ClientBuilder {
-    fn default() -> Self {
-        Self {
-            http: None,
-            credential: None,
-            region: None,
-            endpoint: None,
-            debug: None,
-        }
-    }
-}
+/// Builder state: credential presence is checked at runtime.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dynamic;
+
+/// Builder state: no credential set yet, so `build` is unavailable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoCredential;
 
-impl ClientBuilder {
+/// Builder state: a credential has been set and `build` is available.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CredentialSet;
+
+impl ClientBuilder<Dynamic> {
     /// Creates a new builder.
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Set credentials (required unless a credential provider is set).
+    pub fn credential(mut self, credential: Credential) -> Self {
+        self.credential = Some(credential);
+        self
+    }
+
+    /// Ask `provider` for the credential before every request instead of
+    /// using a fixed one.
+    pub fn credential_provider(mut self, provider: Arc<dyn CredentialProvider>) -> Self {
+        self.credential_provider = Some(provider);
+        self
+    }
+
+    /// Build the [`Client`]. Panics if neither credentials nor a credential
+    /// provider are provided, or where [`try_build`](Self::try_build) fails.
+    pub fn build(self) -> Client {
+        self.try_build().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Build the [`Client`], failing with [`SdkError::InvalidConfig`] if
+    /// neither credentials nor a credential provider are provided, or if the
+    /// settings conflict, e.g. an HTTP/2 [`HttpVersionOrder`] without the
+    /// `http2` feature.
+    pub fn try_build(self) -> SdkResult<Client> {
+        self.try_build_inner()
+    }
+}
+
+impl ClientBuilder<NoCredential> {
+    /// Set credentials, making `build` available.
+    pub fn credential(self, credential: Credential) -> ClientBuilder<CredentialSet> {
+        let mut builder = self.into_state();
+        builder.credential = Some(credential);
+        builder
+    }
+
+    /// Set a credential provider, making `build` available.
+    pub fn credential_provider(
+        self,
+        provider: Arc<dyn CredentialProvider>,
+    ) -> ClientBuilder<CredentialSet> {
+        let mut builder = self.into_state();
+        builder.credential_provider = Some(provider);
+        builder
+    }
+}
+
+impl ClientBuilder<CredentialSet> {
+    /// Replace the credentials set earlier.
+    pub fn credential(mut self, credential: Credential) -> Self {
+        self.credential = Some(credential);
+        self
+    }
+
+    /// Ask `provider` for the credential before every request; it takes
+    /// precedence over a fixed credential.
+    pub fn credential_provider(mut self, provider: Arc<dyn CredentialProvider>) -> Self {
+        self.credential_provider = Some(provider);
+        self
+    }
+
+    /// Build the [`Client`]. Panics where [`try_build`](Self::try_build)
+    /// fails.
+    pub fn build(self) -> Client {
+        self.try_build().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Build the [`Client`], failing with [`SdkError::InvalidConfig`] if the
+    /// settings conflict, e.g. an HTTP/2 [`HttpVersionOrder`] without the
+    /// `http2` feature.
+    pub fn try_build(self) -> SdkResult<Client> {
+        self.try_build_inner()
+    }
+}
+
+impl<S> ClientBuilder<S> {
+    fn into_state<T>(self) -> ClientBuilder<T> {
+        ClientBuilder {
+            http: self.http,
+            credential: self.credential,
+            credential_provider: self.credential_provider,
+            region: self.region,
+            endpoint: self.endpoint,
+            base_path: self.base_path,
+            canonical_uri: self.canonical_uri,
+            host_override: self.host_override,
+            http_version_order: self.http_version_order,
+            debug: self.debug,
+            log_sink: self.log_sink,
+            metrics_sink: self.metrics_sink,
+            slow_request_threshold: self.slow_request_threshold,
+            clock_skew_warning: self.clock_skew_warning,
+            deserialization_mode: self.deserialization_mode,
+            transport: self.transport,
+            body_serializer: self.body_serializer,
+            retry_policy: self.retry_policy,
+            retry_predicate: self.retry_predicate,
+            clock: self.clock,
+            request_client: self.request_client,
+            extra_headers: self.extra_headers,
+            config_error: self.config_error,
+            model_fallback_chain: self.model_fallback_chain,
+            default_model: self.default_model,
+            timeout: self.timeout,
+            warm_up: self.warm_up,
+            trace_context_provider: self.trace_context_provider,
+            trace_id_header: self.trace_id_header,
+            prompt_sanitizer: self.prompt_sanitizer,
+            max_sse_buffer_bytes: self.max_sse_buffer_bytes,
+            max_response_bytes: self.max_response_bytes,
+            #[cfg(feature = "gzip")]
+            gzip_requests: self.gzip_requests,
+            coalesce_embeddings: self.coalesce_embeddings,
+            response_token_hint: self.response_token_hint,
+            embedding_cache: self.embedding_cache,
+            max_concurrent_requests: self.max_concurrent_requests,
+            state: PhantomData,
+        }
+    }
+
     /// Returns whether an HTTP client has been set.
     pub fn has_http(&self) -> bool {
         self.http.is_some()
@@ -118,6 +1414,11 @@ impl ClientBuilder {
         self.credential.is_some()
     }
 
+    /// Returns whether a credential provider has been set.
+    pub fn has_credential_provider(&self) -> bool {
+        self.credential_provider.is_some()
+    }
+
     /// Returns whether a region has been set.
     pub fn has_region(&self) -> bool {
         self.region.is_some()
@@ -128,32 +1429,262 @@ impl ClientBuilder {
         self.endpoint.is_some()
     }
 
+    /// Returns whether a base URL path has been set.
+    pub fn has_base_url(&self) -> bool {
+        self.base_path.is_some()
+    }
+
+    /// Returns whether a canonical URI has been set.
+    pub fn has_canonical_uri(&self) -> bool {
+        self.canonical_uri.is_some()
+    }
+
     /// Returns whether debug mode has been set.
     pub fn has_debug(&self) -> bool {
         self.debug.is_some()
     }
 
-    /// Set a custom `reqwest` HTTP client.
+    /// Returns whether a custom log sink has been set.
+    pub fn has_log_sink(&self) -> bool {
+        self.log_sink.is_some()
+    }
+
+    /// Returns whether a metrics sink has been set.
+    pub fn has_metrics_sink(&self) -> bool {
+        self.metrics_sink.is_some()
+    }
+
+    /// Returns whether a slow-request threshold has been set.
+    pub fn has_slow_request_threshold(&self) -> bool {
+        self.slow_request_threshold.is_some()
+    }
+
+    /// Returns whether a clock skew warning threshold has been set.
+    pub fn has_clock_skew_warning(&self) -> bool {
+        self.clock_skew_warning.is_some()
+    }
+
+    /// Returns whether an HTTP version order has been set.
+    pub fn has_http_version_order(&self) -> bool {
+        self.http_version_order.is_some()
+    }
+
+    /// Returns whether a deserialization mode has been set.
+    pub fn has_deserialization_mode(&self) -> bool {
+        self.deserialization_mode.is_some()
+    }
+
+    /// Returns whether a custom transport has been set.
+    pub fn has_transport(&self) -> bool {
+        self.transport.is_some()
+    }
+
+    /// Returns whether a custom body serializer has been set.
+    pub fn has_body_serializer(&self) -> bool {
+        self.body_serializer.is_some()
+    }
+
+    /// Returns whether a retry policy has been set.
+    pub fn has_retry_policy(&self) -> bool {
+        self.retry_policy.is_some()
+    }
+
+    /// Returns whether a retry predicate has been set.
+    pub fn has_retry_if(&self) -> bool {
+        self.retry_predicate.is_some()
+    }
+
+    /// Returns whether a model fallback chain has been set.
+    pub fn has_model_fallback_chain(&self) -> bool {
+        !self.model_fallback_chain.is_empty()
+    }
+
+    /// Returns whether a default model has been set.
+    pub fn has_default_model(&self) -> bool {
+        self.default_model.is_some()
+    }
+
+    /// Returns whether a request timeout has been set.
+    pub fn has_timeout(&self) -> bool {
+        self.timeout.is_some()
+    }
+
+    /// Returns whether a trace context provider has been set.
+    pub fn has_trace_context_provider(&self) -> bool {
+        self.trace_context_provider.is_some()
+    }
+
+    /// Returns whether a trace id header name has been set.
+    pub fn has_trace_id_header(&self) -> bool {
+        self.trace_id_header.is_some()
+    }
+
+    /// Returns whether a prompt sanitizer has been set.
+    pub fn has_prompt_sanitizer(&self) -> bool {
+        self.prompt_sanitizer.is_some()
+    }
+
+    /// Returns whether a maximum SSE buffer size has been set.
+    pub fn has_max_sse_buffer_bytes(&self) -> bool {
+        self.max_sse_buffer_bytes.is_some()
+    }
+
+    /// Returns whether a maximum response size has been set.
+    pub fn has_max_response_bytes(&self) -> bool {
+        self.max_response_bytes.is_some()
+    }
+
+    /// Returns whether request compression has been enabled.
+    #[cfg(feature = "gzip")]
+    pub fn has_gzip_requests(&self) -> bool {
+        self.gzip_requests.is_some()
+    }
+
+    /// Returns whether embedding request coalescing has been enabled.
+    pub fn has_coalesce_embeddings(&self) -> bool {
+        self.coalesce_embeddings
+    }
+
+    /// Returns whether a response token hint has been set.
+    pub fn has_response_token_hint(&self) -> bool {
+        self.response_token_hint.is_some()
+    }
+
+    /// Returns whether an embedding cache has been set.
+    pub fn has_embedding_cache(&self) -> bool {
+        self.embedding_cache.is_some()
+    }
+
+    /// Returns whether a concurrency limit has been set.
+    pub fn has_max_concurrent_requests(&self) -> bool {
+        self.max_concurrent_requests.is_some()
+    }
+
+    /// Returns whether warm-up on build has been enabled.
+    pub fn has_warm_up(&self) -> bool {
+        self.warm_up
+    }
+
+    /// Returns whether a custom clock has been set.
+    pub fn has_clock(&self) -> bool {
+        self.clock.is_some()
+    }
+
+    /// Returns whether a custom `X-TC-RequestClient` has been set.
+    pub fn has_request_client(&self) -> bool {
+        self.request_client.is_some()
+    }
+
+    /// Set a custom `reqwest` HTTP client.
+    ///
+    /// Default headers configured on the supplied client are merged into every
+    /// request, but headers set by the SDK always take precedence: `Host`,
+    /// `Content-Type`, `Authorization` and the `X-TC-*` headers of a request are
+    /// never replaced by client defaults, so the signed values are the ones
+    /// sent. Avoid default `X-TC-Token` headers; they are only overridden when
+    /// the credential carries a token.
     pub fn http(mut self, http: HttpClient) -> Self {
         self.http = Some(http);
         self
     }
-    /// Set credentials (required).
-    pub fn credential(mut self, credential: Credential) -> Self {
-        self.credential = Some(credential);
-        self
-    }
     /// Set target region (defaults to `ApGuangzhou`).
     pub fn region(mut self, region: Region) -> Self {
         self.region = Some(region);
         self
     }
     /// Override the API endpoint (defaults to `hunyuan.tencentcloudapi.com`).
+    ///
+    /// The endpoint may carry an explicit `http://` or `https://` prefix, e.g.
+    /// to target a local mock server; HTTPS is used when no scheme is given.
     pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
         self.endpoint = Some(endpoint.into());
         self
     }
 
+    /// Send requests to `url`, a full URL with scheme, host, optional port
+    /// and optional path, e.g. `https://api.internal.corp/tencent/hunyuan`
+    /// for a gateway in front of Hunyuan, or `http://127.0.0.1:8080` for a
+    /// local mock. Replaces [`endpoint`](Self::endpoint).
+    ///
+    /// The path is also signed as the TC3 canonical URI, for gateways that
+    /// forward the request with the path intact. A gateway that strips the
+    /// prefix before forwarding needs [`canonical_uri`](Self::canonical_uri)
+    /// set to `/` instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `url` is not an `http` or `https` URL, or has a query
+    /// string or fragment.
+    pub fn base_url(mut self, url: impl AsRef<str>) -> Self {
+        let url = url.as_ref();
+        let parsed = reqwest::Url::parse(url)
+            .unwrap_or_else(|e| panic!("base URL {:?} is invalid: {}", url, e));
+        assert!(
+            matches!(parsed.scheme(), "http" | "https"),
+            "base URL {:?} must use http or https",
+            url
+        );
+        assert!(
+            parsed.query().is_none() && parsed.fragment().is_none(),
+            "base URL {:?} must not have a query string or fragment",
+            url
+        );
+        let host = parsed.host_str().expect("http URLs have a host");
+        let origin = match parsed.port() {
+            Some(port) => format!("{}://{}:{}", parsed.scheme(), host, port),
+            None => format!("{}://{}", parsed.scheme(), host),
+        };
+        self.endpoint = Some(origin);
+        self.base_path = Some(parsed.path().to_string());
+        self
+    }
+
+    /// Sign `uri` as the TC3 canonical URI instead of the path of the
+    /// [`base_url`](Self::base_url), which is `/` by default. Set it to the
+    /// path the service finally receives, e.g. `/` behind a gateway that
+    /// strips its prefix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `uri` does not start with `/`.
+    pub fn canonical_uri(mut self, uri: impl Into<String>) -> Self {
+        let uri = uri.into();
+        assert!(
+            uri.starts_with('/'),
+            "canonical URI {:?} must start with /",
+            uri
+        );
+        self.canonical_uri = Some(uri);
+        self
+    }
+
+    /// Sign and send `Host: host` instead of the endpoint's host, e.g. to
+    /// pin calls to an IP address with `endpoint("https://10.0.0.8")` while
+    /// presenting the logical host name.
+    ///
+    /// When the endpoint is an IP address and the client builds its own
+    /// `reqwest` client, the connection is made to that address under the
+    /// override's name, so TLS SNI and certificate checks use the override
+    /// as well. With a custom [`http`](Self::http) or
+    /// [`transport`](Self::transport), only the `Host` header changes.
+    pub fn host_override(mut self, host: impl Into<String>) -> Self {
+        self.host_override = Some(host.into());
+        self
+    }
+
+    /// Choose which HTTP versions the built-in `reqwest` client speaks, e.g.
+    /// [`HttpVersionOrder::Http2ThenHttp1`] to use HTTP/2 where the endpoint
+    /// offers it. The default is [`HttpVersionOrder::Http1Only`]. Orders
+    /// using HTTP/2 need the `http2` feature; without it,
+    /// [`try_build`](Self::try_build) fails and `build` panics.
+    ///
+    /// Ignored with a custom [`http`](Self::http) client or
+    /// [`transport`](Self::transport); configure those directly.
+    pub fn http_version_order(mut self, order: HttpVersionOrder) -> Self {
+        self.http_version_order = Some(order);
+        self
+    }
+
     /// Enable or disable SDK debug logs. Can also be controlled via the
     /// `TENCENTCLOUD_SDK_DEBUG` env var (`true`/`1`/`on`).
     pub fn debug(mut self, debug: bool) -> Self {
@@ -161,39 +1692,439 @@ impl ClientBuilder {
         self
     }
 
-    /// Build the [`Client`]. Panics if credentials are not provided.
-    pub fn build(self) -> Client {
-        let http = self.http.unwrap_or_else(|| {
-            #[cfg(feature = "rustls-tls")]
-            {
-                HttpClient::builder()
-                    .use_rustls_tls()
-                    .build()
-                    .expect("reqwest client")
-            }
+    /// Send SDK log lines (debug output and warnings) to a custom sink instead
+    /// of stderr.
+    pub fn log_sink(mut self, sink: Arc<dyn LogSink>) -> Self {
+        self.log_sink = Some(sink);
+        self
+    }
 
-            #[cfg(feature = "native-tls")]
-            {
-                HttpClient::builder()
-                    .use_native_tls()
-                    .build()
-                    .expect("reqwest client")
-            }
+    /// Report every finished call, with its labels, to `sink`.
+    pub fn metrics_sink(mut self, sink: Arc<dyn MetricsSink>) -> Self {
+        self.metrics_sink = Some(sink);
+        self
+    }
+
+    /// Warn when a call takes longer than `threshold`.
+    ///
+    /// The warning is written to the log sink regardless of debug mode and the
+    /// call is flagged via [`ResponseMeta::slow`]; the result is unaffected.
+    pub fn slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// Warn when the clock is more than `threshold` away from the server's
+    /// time, e.g. 60 seconds. Off by default.
+    ///
+    /// The server's time is estimated from the `Date` header of the latest
+    /// response. Every request signed while the timestamp differs from that
+    /// estimate by more than `threshold` logs a warning to the log sink,
+    /// regardless of debug mode, before the skew reaches the five minutes
+    /// after which the service rejects signatures with
+    /// `AuthFailure.SignatureExpire`. `Date` only has second precision, so
+    /// thresholds below a few seconds cause false alarms.
+    pub fn clock_skew_warning(mut self, threshold: Duration) -> Self {
+        self.clock_skew_warning = Some(threshold);
+        self
+    }
+
+    /// Choose how strictly responses are parsed. The default,
+    /// [`DeserializationMode::Lenient`], keeps fields the models do not
+    /// declare in their `extra` maps, or skips them, so new service fields
+    /// never break a call. [`DeserializationMode::Strict`] fails such a
+    /// response with [`SdkError::Decode`] listing the JSON path of every
+    /// undeclared field, e.g. `Response.Choices[0].Message.Audio`, to catch
+    /// API drift during development. It also fails a `ChatCompletions`
+    /// response whose choice `Index` values are not unique and contiguous
+    /// from 0 with [`SdkError::UnexpectedResponse`].
+    ///
+    /// Applies to unary calls; streamed chunks are always parsed leniently.
+    /// The check costs an extra pass over the body.
+    pub fn deserialization_mode(mut self, mode: DeserializationMode) -> Self {
+        self.deserialization_mode = Some(mode);
+        self
+    }
+
+    /// Send requests through a custom [`Transport`] instead of `reqwest`.
+    ///
+    /// The transport receives fully signed requests for every call path,
+    /// including streaming. When set, [`ClientBuilder::http`] is ignored.
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Serialize request bodies with a custom [`BodySerializer`], e.g.
+    /// [`SortedKeysJson`](crate::serializer::SortedKeysJson) for
+    /// deterministic key order.
+    ///
+    /// The serialized string is signed and sent as-is. Without a serializer,
+    /// bodies are compact JSON in struct field order.
+    pub fn body_serializer(mut self, serializer: Arc<dyn BodySerializer>) -> Self {
+        self.body_serializer = Some(serializer);
+        self
+    }
+
+    /// Retry failed calls according to `policy`. Off by default.
+    ///
+    /// Only errors for which [`SdkError::is_retryable`] holds are retried,
+    /// unless [`retry_if`](Self::retry_if) decides otherwise. Streaming calls
+    /// retry opening the stream, never after the first chunk.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Retry the errors for which `predicate` returns `true` instead of
+    /// those for which [`SdkError::is_retryable`] holds, e.g. to also retry a
+    /// `FailedOperation` code known to be transient. Call `is_retryable`
+    /// from the predicate to extend the default rather than replace it.
+    ///
+    /// Takes effect with a [`retry_policy`](Self::retry_policy), which still
+    /// sets the number of retries and the backoff. The model fallback chain
+    /// keeps moving on only after errors for which `is_retryable` holds.
+    pub fn retry_if(
+        mut self,
+        predicate: impl Fn(&SdkError) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_predicate = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Take request timestamps from a custom [`Clock`] instead of the system
+    /// time, e.g. a [`FixedClock`](crate::clock::FixedClock) for reproducible
+    /// signatures in tests.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Set the `X-TC-RequestClient` header identifying the caller to the
+    /// backend. Defaults to `tencentcloud-hunyuan-sdk/<crate version>`.
+    ///
+    /// [`try_build`](Self::try_build) fails with [`SdkError::InvalidConfig`]
+    /// if `client` is not a valid header value.
+    pub fn request_client(mut self, client: impl AsRef<str>) -> Self {
+        match HeaderValue::from_str(client.as_ref()) {
+            Ok(value) => self.request_client = Some(value),
+            Err(_) => self.invalid_setting(format!(
+                "request client {:?} is not a valid header value",
+                client.as_ref()
+            )),
+        }
+        self
+    }
+
+    /// Send an additional `X-TC-*` header with every request, e.g. to opt
+    /// into an experimental backend feature.
+    ///
+    /// Extra headers are sent but not signed. Headers managed by the SDK
+    /// (`X-TC-Action`, `X-TC-Version`, `X-TC-Region`, `X-TC-Timestamp`,
+    /// `X-TC-Token`, `X-TC-RequestClient`) take precedence; use
+    /// [`ClientBuilder::request_client`] for the latter.
+    ///
+    /// [`try_build`](Self::try_build) fails with [`SdkError::InvalidConfig`]
+    /// if `name` does not start with `X-TC-` (case-insensitive) or
+    /// `name`/`value` are not valid header syntax.
+    pub fn extra_header(mut self, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        let name = name.as_ref();
+        let header = if name.len() > 5 && name[..5].eq_ignore_ascii_case("x-tc-") {
+            HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("extra header {:?} is not a valid header name", name))
+                .and_then(|header_name| {
+                    HeaderValue::from_str(value.as_ref())
+                        .map(|value| (header_name, value))
+                        .map_err(|_| format!("extra header {:?} has an invalid header value", name))
+                })
+        } else {
+            Err(format!("extra header {:?} must start with X-TC-", name))
+        };
+        match header {
+            Ok(header) => self.extra_headers.push(header),
+            Err(message) => self.invalid_setting(message),
+        }
+        self
+    }
+
+    /// Send a header with every request, e.g. a tenant or routing header
+    /// required by a gateway. Can be called repeatedly; for the same name,
+    /// the last value wins, and [`RequestOptions::header`] replaces it for a
+    /// single call. Default headers are sent but not signed.
+    ///
+    /// [`try_build`](Self::try_build) fails with [`SdkError::InvalidConfig`]
+    /// if `name` or `value` are not valid header syntax, or if `name` is a
+    /// header the SDK sets itself: `Host`, `Content-Type`, `Authorization`,
+    /// `X-TC-Action`, `X-TC-Version`, `X-TC-Region`, `X-TC-Timestamp`,
+    /// `X-TC-Token` and `X-TC-RequestClient`.
+    pub fn default_header(mut self, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        match custom_header("default header", name.as_ref(), value.as_ref()) {
+            Ok(header) => self.extra_headers.push(header),
+            Err(message) => self.invalid_setting(message),
+        }
+        self
+    }
+
+    /// Models to fall back to, in order, when
+    /// [`Client::chat_completions_with_fallback`] fails with a retryable error,
+    /// e.g. `["hunyuan-pro", "hunyuan-standard"]`.
+    pub fn model_fallback_chain(mut self, models: Vec<String>) -> Self {
+        self.model_fallback_chain = models;
+        self
+    }
+
+    /// Model for `ChatCompletions` requests that leave `model` unset.
+    pub fn default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = Some(model.into());
+        self
+    }
+
+    /// Fail an attempt with a retryable [`SdkError::Transport`] when the
+    /// response does not arrive within `timeout`. For streams this bounds the
+    /// time until the stream is open, not the whole stream. No timeout by
+    /// default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Ask `provider` for the trace context of every call that has none in
+    /// its `RequestOptions`, and send it as the W3C `traceparent` and
+    /// `tracestate` headers. These headers are not signed.
+    pub fn trace_context_provider(mut self, provider: Arc<dyn TraceContextProvider>) -> Self {
+        self.trace_context_provider = Some(provider);
+        self
+    }
+
+    /// Send the id given with [`RequestOptions::trace_id`] in the header
+    /// `name` instead of `X-TC-TraceId`, e.g. `X-Request-ID` or
+    /// `X-Correlation-ID` to match the rest of your services.
+    ///
+    /// [`try_build`](Self::try_build) fails with [`SdkError::InvalidConfig`]
+    /// if `name` is not a valid header name or is a header the SDK sets
+    /// itself.
+    pub fn trace_id_header(mut self, name: impl AsRef<str>) -> Self {
+        match custom_header("trace id header", name.as_ref(), "") {
+            Ok((name, _)) => self.trace_id_header = Some(name),
+            Err(message) => self.invalid_setting(message),
+        }
+        self
+    }
+
+    /// Pass the content of every message of a `ChatCompletions` request
+    /// through `sanitizer` before the request is serialized and signed, e.g.
+    /// to redact personal data that must not leave the deployment.
+    ///
+    /// It runs on the client for every chat call, streamed or not, including
+    /// those of sessions and the fallback chain. The caller's request is left
+    /// unchanged; only the copy that is sent carries the sanitized content.
+    /// Requests sent through [`Client::call`] or [`Client::call_raw`] are not
+    /// sanitized.
+    pub fn prompt_sanitizer(
+        mut self,
+        sanitizer: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.prompt_sanitizer = Some(Arc::new(sanitizer));
+        self
+    }
+
+    /// Fail a stream with [`SdkError::StreamEventTooLarge`] once an event that
+    /// has not ended exceeds `bytes`, instead of buffering for as long as the
+    /// server sends without an event terminator. Defaults to 16 MiB.
+    ///
+    /// Complete events are handed out as they arrive, so a stream holds at
+    /// most one unfinished event plus the rest of the last received chunk,
+    /// whatever its total length.
+    pub fn max_sse_buffer_bytes(mut self, bytes: usize) -> Self {
+        self.max_sse_buffer_bytes = Some(bytes);
+        self
+    }
+
+    /// Fail a call with [`SdkError::ResponseTooLarge`] once its response body
+    /// exceeds `bytes`, e.g. an HTML page of a misbehaving gateway. Defaults
+    /// to 8 MiB; [`RequestOptions::max_response_bytes`] changes it for one
+    /// call.
+    ///
+    /// The body is read in chunks and the read stops at the limit, so an
+    /// oversized response is never buffered whole. Streams are bounded by
+    /// [`ClientBuilder::max_sse_buffer_bytes`] instead.
+    pub fn max_response_bytes(mut self, bytes: usize) -> Self {
+        self.max_response_bytes = Some(bytes);
+        self
+    }
+
+    /// Gzip request bodies of at least `min_bytes` and send them with
+    /// `Content-Encoding: gzip`, e.g. to upload very large prompts faster;
+    /// `0` compresses every body.
+    ///
+    /// TC3 signs the hash of the bytes on the wire, so a compressed body is
+    /// hashed after compression. Each attempt compresses anew; debug logs
+    /// show the uncompressed body. Only enable it for endpoints that accept
+    /// compressed requests: the public Hunyuan endpoint does not document
+    /// `Content-Encoding` support.
+    #[cfg(feature = "gzip")]
+    pub fn gzip_requests(mut self, min_bytes: usize) -> Self {
+        self.gzip_requests = Some(min_bytes);
+        self
+    }
+
+    /// Ask the model to keep `ChatCompletions` responses under `tokens`
+    /// tokens by adding a note to the system message of every request; a
+    /// request without a system message gets one.
+    ///
+    /// The action has no `MaxTokens` parameter, so this is an instruction to
+    /// the model rather than a hard limit. Override it per call with
+    /// [`RequestOptions::response_token_hint`].
+    pub fn response_token_hint(mut self, tokens: u32) -> Self {
+        self.response_token_hint = Some(tokens);
+        self
+    }
+
+    /// Share one `GetEmbedding` call among concurrent identical requests made
+    /// through [`Client::get_embedding`]. Off by default.
+    ///
+    /// A request is identical when its serialized body is: same input, input
+    /// list and input type. Callers arriving while such a call is in flight
+    /// wait for it and receive a copy of its response or error, and the
+    /// metrics sink sees a single call. Nothing is cached once the call
+    /// finishes, and at most 1024 distinct requests are tracked at a time;
+    /// beyond that, calls go out unshared.
+    pub fn coalesce_embeddings(mut self, coalesce: bool) -> Self {
+        self.coalesce_embeddings = coalesce;
+        self
+    }
+
+    /// Let at most `limit` calls run at once across all clones of the client.
+    /// Unlimited by default; `0` is treated as `1`.
+    ///
+    /// A call holds its slot from the first attempt until its last retry
+    /// ends; a stream holds it until the stream ends or is dropped. Calls
+    /// beyond the limit wait in line by [`RequestOptions::priority`].
+    pub fn max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = Some(limit);
+        self
+    }
+
+    /// Keep up to `capacity` embedding vectors in an in-memory LRU cache that
+    /// [`Client::get_embedding`] consults before calling `GetEmbedding`; see
+    /// [`embedding_cache`](crate::embedding_cache). Off by default; a
+    /// `capacity` of zero turns it off.
+    pub fn embedding_cache(mut self, capacity: usize) -> Self {
+        self.embedding_cache = (capacity > 0).then_some(capacity);
+        self
+    }
+
+    /// Start [`Client::warm_up`] in the background when the client is built,
+    /// so the first call does not pay for DNS resolution and the TCP and TLS
+    /// handshakes. Off by default.
+    ///
+    /// `build` is synchronous, so the warm-up is spawned on the current Tokio
+    /// runtime and skipped when building outside of one. It costs one extra
+    /// request per client and holds an idle connection that the server may
+    /// close before the first call, so enable it for clients that are used
+    /// soon after being built. Await [`Client::warm_up`] instead to know when
+    /// the connection is ready.
+    pub fn warm_up(mut self, warm_up: bool) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    /// Records `message` for `try_build`, keeping the first one.
+    fn invalid_setting(&mut self, message: String) {
+        self.config_error.get_or_insert(message);
+    }
 
-            #[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
-            {
-                compile_error!("Either 'rustls-tls' or 'native-tls' feature must be enabled")
+    fn try_build_inner(self) -> SdkResult<Client> {
+        if let Some(message) = &self.config_error {
+            return Err(SdkError::InvalidConfig(message.clone()));
+        }
+        if let Some(order) = self.http_version_order {
+            let built_in = self.transport.is_none() && self.http.is_none();
+            if built_in && order.uses_http2() && !cfg!(feature = "http2") {
+                return Err(SdkError::InvalidConfig(format!(
+                    "HTTP version order {:?} needs the `http2` feature",
+                    order
+                )));
             }
-        });
-        let region = self.region.unwrap_or(Region::ApGuangzhou);
+        }
         let endpoint = self
             .endpoint
             .unwrap_or_else(|| format!("{}.tencentcloudapi.com", SERVICE));
-        let credential = self.credential.expect("credential is required");
-        let env_debug = match env::var("TENCENTCLOUD_SDK_DEBUG").ok().as_deref() {
-            Some("1") | Some("true") | Some("TRUE") | Some("on") | Some("ON") => true,
-            _ => false,
+        let host = self
+            .host_override
+            .clone()
+            .unwrap_or_else(|| host_of(&endpoint).to_string());
+        let host_header = config_header_value("host", &host)?;
+        let region = self.region.unwrap_or(Region::ApGuangzhou);
+        let region_header = config_header_value("region", region.as_str())?;
+        // Connect to a pinned IP under the override's name, so that SNI and
+        // certificate checks see the logical host.
+        let pinned = match (&self.host_override, &self.transport, &self.http) {
+            (Some(name), None, None) => {
+                ip_of(host_of(&endpoint)).map(|(ip, port)| (name, ip, port))
+            }
+            _ => None,
+        };
+        let path = self.base_path.as_deref().unwrap_or("/");
+        let url = match pinned {
+            Some((name, _, Some(port))) => {
+                request_url(&endpoint, &format!("{}:{}", name, port), path)
+            }
+            Some((name, _, None)) => request_url(&endpoint, name, path),
+            None => request_url(&endpoint, host_of(&endpoint), path),
+        };
+        let canonical_uri = self
+            .canonical_uri
+            .clone()
+            .unwrap_or_else(|| path.to_string());
+        let http_version_order = self.http_version_order.unwrap_or_default();
+        let resolve = |builder: reqwest::ClientBuilder| {
+            let builder = http_version_order.apply(builder);
+            match pinned {
+                Some((name, ip, _)) => builder.resolve(name, std::net::SocketAddr::new(ip, 0)),
+                None => builder,
+            }
+        };
+        let transport: Arc<dyn Transport> = match self.transport {
+            Some(transport) => transport,
+            None => {
+                let http = self.http.unwrap_or_else(|| {
+                    #[cfg(feature = "rustls-tls")]
+                    {
+                        resolve(HttpClient::builder().use_rustls_tls())
+                            .build()
+                            .expect("reqwest client")
+                    }
+
+                    #[cfg(feature = "native-tls")]
+                    {
+                        resolve(HttpClient::builder().use_native_tls())
+                            .build()
+                            .expect("reqwest client")
+                    }
+
+                    #[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
+                    {
+                        compile_error!(
+                            "Either 'rustls-tls' or 'native-tls' feature must be enabled"
+                        )
+                    }
+                });
+                Arc::new(ReqwestTransport::new(http))
+            }
+        };
+        let credential = match (&self.credential_provider, self.credential) {
+            (Some(provider), _) => provider.credential(),
+            (None, credential) => credential.ok_or_else(|| {
+                SdkError::InvalidConfig(
+                    "a credential or credential provider is required".to_string(),
+                )
+            })?,
         };
+        let env_debug = matches!(
+            env::var("TENCENTCLOUD_SDK_DEBUG").ok().as_deref(),
+            Some("1") | Some("true") | Some("TRUE") | Some("on") | Some("ON")
+        );
         let debug = self.debug.unwrap_or(env_debug);
         let signer = Tc3Signer::new(
             credential.secret_id.clone(),
@@ -201,20 +2132,105 @@ impl ClientBuilder {
             SERVICE.to_string(),
             debug,
         );
-        Client {
-            http,
+        let static_headers = static_headers(
+            host_header,
+            region_header,
+            self.request_client
+                .unwrap_or_else(|| HeaderValue::from_static(DEFAULT_REQUEST_CLIENT)),
+            self.extra_headers,
+        );
+        let client = Client {
+            transport,
             credential,
+            credential_provider: self.credential_provider,
             region,
             endpoint,
+            host,
+            url,
+            canonical_uri,
             debug,
             signer,
+            log_sink: self.log_sink.unwrap_or_else(|| Arc::new(StderrLogSink)),
+            metrics_sink: self.metrics_sink,
+            slow_request_threshold: self.slow_request_threshold,
+            clock_skew_warning: self.clock_skew_warning,
+            deserialization_mode: self.deserialization_mode.unwrap_or_default(),
+            body_serializer: self.body_serializer,
+            retry_policy: self.retry_policy,
+            retry_predicate: self.retry_predicate,
+            clock: self.clock.unwrap_or_else(|| Arc::new(SystemClock)),
+            static_headers,
+            trace_context_provider: self.trace_context_provider,
+            trace_id_header: self
+                .trace_id_header
+                .unwrap_or(HeaderName::from_static(DEFAULT_TRACE_ID_HEADER)),
+            prompt_sanitizer: self.prompt_sanitizer,
+            model_fallback_chain: self.model_fallback_chain,
+            default_model: self.default_model,
+            timeout: self.timeout,
+            max_sse_buffer_bytes: self
+                .max_sse_buffer_bytes
+                .unwrap_or(DEFAULT_MAX_SSE_BUFFER_BYTES),
+            max_response_bytes: self
+                .max_response_bytes
+                .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES),
+            #[cfg(feature = "gzip")]
+            gzip_requests: self.gzip_requests,
+            embedding_coalescer: self
+                .coalesce_embeddings
+                .then(|| Arc::new(Coalescer::default())),
+            response_token_hint: self.response_token_hint,
+            embedding_cache: self
+                .embedding_cache
+                .map(|capacity| Arc::new(EmbeddingCache::new(capacity))),
+            server_time: Arc::default(),
+            lifecycle: Arc::default(),
+            limiter: self
+                .max_concurrent_requests
+                .map(|limit| Arc::new(Limiter::new(limit))),
+            idempotency: Arc::default(),
+        };
+        if self.warm_up {
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    let client = client.clone();
+                    runtime.spawn(async move {
+                        if let Err(err) = client.warm_up().await {
+                            if client.debug {
+                                client.log(
+                                    LogLevel::Debug,
+                                    &format!("[hunyuan-sdk][warm-up] failed: {}", err),
+                                );
+                            }
+                        }
+                    });
+                }
+                Err(_) if debug => client.log(
+                    LogLevel::Debug,
+                    "[hunyuan-sdk][warm-up] skipped: no Tokio runtime to run it on",
+                ),
+                Err(_) => {}
+            }
         }
+        Ok(client)
     }
 }
 
 impl Client {
-    /// Returns a new [`ClientBuilder`].
-    pub fn builder() -> ClientBuilder {
+    /// Returns a new [`ClientBuilder`] that requires a credential at compile
+    /// time:
+    ///
+    /// ```compile_fail
+    /// use tencentcloud_hunyuan_sdk::Client;
+    /// let _client = Client::builder().build(); // no credential: does not compile
+    /// ```
+    pub fn builder() -> ClientBuilder<NoCredential> {
+        ClientBuilder::default()
+    }
+
+    /// Returns the dynamic [`ClientBuilder`], whose `build` panics when no
+    /// credential was set. Same as `ClientBuilder::new()`.
+    pub fn builder_dyn() -> ClientBuilder {
         ClientBuilder::new()
     }
 
@@ -228,18 +2244,183 @@ impl Client {
         &self.endpoint
     }
 
+    /// Returns the URL requests are sent to.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the URI signed as the TC3 canonical URI.
+    pub fn canonical_uri(&self) -> &str {
+        &self.canonical_uri
+    }
+
+    /// Stops accepting calls and returns a future that resolves once every
+    /// call in flight has finished, e.g. before a service exits.
+    ///
+    /// Applies to all clones of this client. From the moment this is called,
+    /// new calls fail with [`SdkError::ClientClosed`]. An open stream counts
+    /// as in flight until it ends or is dropped.
+    pub fn shutdown(&self) -> impl Future<Output = ()> + Send + 'static {
+        self.lifecycle.close();
+        let lifecycle = self.lifecycle.clone();
+        async move { lifecycle.drained().await }
+    }
+
+    /// Number of calls in flight across all clones of this client, including
+    /// open streams.
+    pub fn in_flight(&self) -> usize {
+        self.lifecycle.in_flight()
+    }
+
+    /// Registers a call, failing once the client is shut down.
+    fn enter(&self) -> SdkResult<InFlight> {
+        self.lifecycle.enter().ok_or(SdkError::ClientClosed)
+    }
+
+    /// Waits for a slot under the concurrency limit, if there is one.
+    async fn acquire(&self, priority: Priority) -> Option<Permit> {
+        match &self.limiter {
+            Some(limiter) => Some(limiter.acquire(priority).await),
+            None => None,
+        }
+    }
+
     /// Returns whether debug mode is enabled for this client.
     pub fn debug(&self) -> bool {
         self.debug
     }
 
     /// Returns a reference to the credentials used by this client.
+    ///
+    /// With a credential provider, this is the credential it returned when
+    /// the client was built; see [`Client::current_credential`].
     pub fn credential(&self) -> &Credential {
         &self.credential
     }
 
+    /// Returns the credential the next request will be signed with, asking
+    /// the credential provider if one is set.
+    pub fn current_credential(&self) -> Credential {
+        self.signing_credential().0.into_owned()
+    }
+
+    /// Returns whether the next request will carry an `X-TC-Token` header,
+    /// i.e. whether [`Client::current_credential`] has a session token.
+    pub fn sends_token(&self) -> bool {
+        self.current_credential().token.is_some()
+    }
+
+    /// Credential for the next request and a signer for it.
+    fn signing_credential(&self) -> (Cow<'_, Credential>, Cow<'_, Tc3Signer>) {
+        match &self.credential_provider {
+            None => (Cow::Borrowed(&self.credential), Cow::Borrowed(&self.signer)),
+            Some(provider) => {
+                let credential = provider.credential();
+                let signer = Tc3Signer::new(
+                    credential.secret_id.clone(),
+                    credential.secret_key.clone(),
+                    SERVICE.to_string(),
+                    self.debug,
+                );
+                (Cow::Owned(credential), Cow::Owned(signer))
+            }
+        }
+    }
+
+    /// Returns the slow-request threshold configured for this client.
+    pub fn slow_request_threshold(&self) -> Option<Duration> {
+        self.slow_request_threshold
+    }
+
+    /// Returns the clock skew warning threshold configured for this client.
+    pub fn clock_skew_warning(&self) -> Option<Duration> {
+        self.clock_skew_warning
+    }
+
+    /// Returns how this client parses responses.
+    pub fn deserialization_mode(&self) -> DeserializationMode {
+        self.deserialization_mode
+    }
+
+    /// Returns the header carrying [`RequestOptions::trace_id`].
+    pub fn trace_id_header(&self) -> &str {
+        self.trace_id_header.as_str()
+    }
+
+    /// Returns the retry policy configured for this client.
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
+
+    /// Returns the model fallback chain configured for this client.
+    pub fn model_fallback_chain(&self) -> &[String] {
+        &self.model_fallback_chain
+    }
+
+    /// Returns the default model configured for this client.
+    pub fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+
+    /// Returns the embedding cache, if one was configured with
+    /// [`ClientBuilder::embedding_cache`].
+    pub fn embedding_cache(&self) -> Option<&EmbeddingCache> {
+        self.embedding_cache.as_deref()
+    }
+
+    /// Returns the request timeout configured for this client.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// `req` with the default model filled in if it names none, and the
+    /// response token hint of `options` or the client added.
+    fn with_client_defaults<'a>(
+        &self,
+        req: &'a ChatCompletionsRequest,
+        options: &RequestOptions,
+    ) -> Cow<'a, ChatCompletionsRequest> {
+        let mut req = match (&req.model, &self.default_model) {
+            (None, Some(model)) => Cow::Owned(ChatCompletionsRequest {
+                model: Some(model.clone()),
+                ..req.clone()
+            }),
+            _ => Cow::Borrowed(req),
+        };
+        if let Some(sanitize) = &self.prompt_sanitizer {
+            for message in &mut req.to_mut().messages {
+                message.content = sanitize(&message.content);
+            }
+        }
+        if let Some(tokens) = options.response_token_hint.or(self.response_token_hint) {
+            let note = format!("Keep the response under {} tokens.", tokens);
+            let messages = &mut req.to_mut().messages;
+            match messages.first_mut() {
+                Some(first) if first.role == Role::System.as_str() => {
+                    first.content = format!("{}\n\n{}", first.content, note);
+                }
+                _ => messages.insert(0, Message::new(Role::System, note)),
+            }
+        }
+        req
+    }
+
+    /// Host signed and sent in the `Host` header.
+    fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn log(&self, level: LogLevel, message: &str) {
+        self.log_sink.log(level, message);
+    }
+
     /// Signs a request using TC3-HMAC-SHA256 algorithm.
     /// This method is public for testing purposes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timestamp` cannot be signed; see [`Client::try_tc3_sign`].
+    #[allow(clippy::too_many_arguments)]
     pub fn tc3_sign(
         &self,
         method: &str,
@@ -250,7 +2431,7 @@ impl Client {
         hashed_payload: &str,
         timestamp: i64,
     ) -> (String, String) {
-        let result = self.signer.sign(
+        self.try_tc3_sign(
             method,
             canonical_uri,
             canonical_querystring,
@@ -258,193 +2439,1376 @@ impl Client {
             signed_headers,
             hashed_payload,
             timestamp,
-        );
-        (result.signature, result.credential_scope)
+        )
+        .unwrap_or_else(|e| panic!("{}", e))
     }
 
-    /// Builds the headers for a request.
-    /// This method is public for testing purposes.
-    pub fn build_headers(&self, action: &str, _json_body: &str, timestamp: i64) -> HeaderMap {
-        let mut headers = HeaderMap::new();
-        headers.insert("Host", HeaderValue::from_str(&self.endpoint).unwrap());
-        headers.insert(
-            "Content-Type",
-            HeaderValue::from_static("application/json; charset=utf-8"),
-        );
-        headers.insert("X-TC-Action", HeaderValue::from_str(action).unwrap());
-        headers.insert("X-TC-Version", HeaderValue::from_static(VERSION));
-        headers.insert(
-            "X-TC-Region",
-            HeaderValue::from_str(self.region.as_str()).unwrap(),
-        );
-        headers.insert(
-            "X-TC-Timestamp",
-            HeaderValue::from_str(&timestamp.to_string()).unwrap(),
-        );
-        if let Some(token) = &self.credential.token {
-            headers.insert("X-TC-Token", HeaderValue::from_str(token).unwrap());
-        }
-        headers
+    /// Like [`Client::tc3_sign`], but returns [`SdkError::Signing`] for a
+    /// timestamp before the Unix epoch or beyond the year 9999 instead of
+    /// panicking.
+    ///
+    /// Signs for service `hunyuan`; see [`tc3_sign_with_service`] for other
+    /// services.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_tc3_sign(
+        &self,
+        method: &str,
+        canonical_uri: &str,
+        canonical_querystring: &str,
+        canonical_headers: &str,
+        signed_headers: &str,
+        hashed_payload: &str,
+        timestamp: i64,
+    ) -> SdkResult<(String, String)> {
+        // The signer unwraps the date conversion, so reject bad timestamps first.
+        credential_date(timestamp)?;
+        let (_, signer) = self.signing_credential();
+        let result = signer.sign(
+            method,
+            canonical_uri,
+            canonical_querystring,
+            canonical_headers,
+            signed_headers,
+            hashed_payload,
+            timestamp,
+        );
+        Ok((result.signature, result.credential_scope))
     }
 
-    /// Calls a Hunyuan API action with a JSON request body and deserializes the
-    /// JSON response into `TResp`.
-    async fn call_action<TReq: Serialize, TResp: DeserializeOwned>(
+    /// Builds the headers for a request.
+    /// This method is public for testing purposes.
+    ///
+    /// Fails with [`SdkError::InvalidRequest`] if `action` or the session
+    /// token is not a valid header value.
+    pub fn build_headers(
         &self,
         action: &str,
-        req: &TReq,
-    ) -> Result<TResp, SdkError> {
-        let method = "POST";
-        let canonical_uri = "/";
-        let canonical_querystring = "";
+        _json_body: &str,
+        timestamp: i64,
+    ) -> SdkResult<HeaderMap> {
+        let (credential, _) = self.signing_credential();
+        self.build_headers_with(action, timestamp, &credential, &RequestOptions::default())
+    }
 
-        let body = serde_json::to_string(req)?;
-        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    /// Builds the headers for a request signed with `credential`, applying
+    /// per-call `options`.
+    fn build_headers_with(
+        &self,
+        action: &str,
+        timestamp: i64,
+        credential: &Credential,
+        options: &RequestOptions,
+    ) -> SdkResult<HeaderMap> {
+        // Cloning shares the header values; only the map itself is allocated.
+        let mut headers = self.static_headers.clone();
+        for (name, value) in &options.extra_headers {
+            let (name, value) =
+                custom_header("header", name, value).map_err(SdkError::InvalidRequest)?;
+            headers.insert(name, value);
+        }
+        headers.insert("X-TC-Action", header_value("action", action)?);
+        if let Some(version) = &options.version {
+            headers.insert("X-TC-Version", header_value("version", version)?);
+        }
+        headers.insert(
+            "X-TC-Timestamp",
+            HeaderValue::from_str(&timestamp.to_string()).unwrap(),
+        );
+        if let Some(token) = &credential.token {
+            headers.insert("X-TC-Token", header_value("session token", token)?);
+        }
+        let trace_context = options.trace_context.clone().or_else(|| {
+            self.trace_context_provider
+                .as_ref()
+                .and_then(|provider| provider.trace_context())
+        });
+        if let Some(key) = &options.idempotency_key {
+            headers.insert("Idempotency-Key", header_value("idempotency key", key)?);
+        }
+        if let Some(id) = &options.trace_id {
+            headers.insert(self.trace_id_header.clone(), header_value("trace id", id)?);
+        }
+        if let Some(context) = trace_context {
+            headers.insert(
+                "traceparent",
+                header_value("traceparent", context.traceparent())?,
+            );
+            if let Some(tracestate) = context.tracestate() {
+                headers.insert("tracestate", header_value("tracestate", tracestate)?);
+            }
+        }
+        Ok(headers)
+    }
 
-        let mut headers = self.build_headers(action, &body, timestamp);
+    /// Signs `body` for `action` and returns the request exactly as it will be
+    /// handed to the transport.
+    fn prepare_request(
+        &self,
+        action: &str,
+        body: Bytes,
+        timestamp: i64,
+        options: &RequestOptions,
+    ) -> SdkResult<PreparedRequest> {
+        let (credential, signer) = self.signing_credential();
+        let mut headers = self.build_headers_with(action, timestamp, &credential, options)?;
 
-        // Headers for signing
-        let host = self.endpoint.clone();
-        let canonical_headers = format!(
-            "content-type:application/json; charset=utf-8\nhost:{}\n",
-            host
-        );
-        let signed_headers = "content-type;host";
-        let hashed_payload = sha256_hex(&body);
-        let result = self.signer.sign(
-            method,
-            canonical_uri,
-            canonical_querystring,
-            &canonical_headers,
-            signed_headers,
-            &hashed_payload,
+        let text = body_text(&body)?;
+        let (sent, hashed_payload, compressed) = self.wire_body(&body, text);
+        if compressed {
+            headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        }
+        credential_date(timestamp)?;
+        let canonical = self.canonical_request(action, hashed_payload);
+        let result = signer.sign(
+            canonical.method,
+            canonical.uri,
+            CANONICAL_QUERYSTRING,
+            &canonical.headers,
+            SIGNED_HEADERS,
+            &canonical.hashed_payload,
             timestamp,
         );
+        let method = canonical.method;
 
-        let authorization = self
-            .signer
-            .create_authorization_header(&result, signed_headers);
+        let authorization = signer.create_authorization_header(&result, SIGNED_HEADERS);
         headers.insert(
             "Authorization",
-            HeaderValue::from_str(&authorization).unwrap(),
+            header_value("Authorization", &authorization)?,
         );
 
-        let url = format!("https://{}/", self.endpoint);
+        let url = self.url.clone();
 
         if self.debug {
-            fn mask(v: &str) -> String {
-                let keep = 8usize;
-                if v.len() <= keep * 2 {
-                    return "***".to_string();
-                }
-                format!("{}...{}", &v[..keep], &v[v.len() - keep..])
+            self.log_request(action, options, &url, &headers, text);
+        }
+
+        Ok(PreparedRequest {
+            method: method.to_string(),
+            url,
+            headers,
+            body: sent,
+        })
+    }
+
+    /// The bytes sent for `body`, gzip-compressed when
+    /// [`ClientBuilder::gzip_requests`] applies, the hex SHA-256 of them that
+    /// TC3 signs, and whether they were compressed. The hash always covers
+    /// the bytes on the wire.
+    fn wire_body(&self, body: &Bytes, text: &str) -> (Bytes, String, bool) {
+        #[cfg(feature = "gzip")]
+        if let Some(min_bytes) = self.gzip_requests {
+            if body.len() >= min_bytes {
+                use flate2::write::GzEncoder;
+                use sha2::{Digest, Sha256};
+                use std::io::Write;
+
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::fast());
+                encoder
+                    .write_all(body)
+                    .expect("writing to a Vec cannot fail");
+                let compressed = encoder.finish().expect("writing to a Vec cannot fail");
+                let hash = format!("{:x}", Sha256::digest(&compressed));
+                return (Bytes::from(compressed), hash, true);
+            }
+        }
+        (body.clone(), sha256_hex(text), false)
+    }
+
+    fn log_request(
+        &self,
+        action: &str,
+        options: &RequestOptions,
+        url: &str,
+        headers: &HeaderMap,
+        body: &str,
+    ) {
+        fn mask(v: &str) -> String {
+            let keep = 8usize;
+            if v.len() <= keep * 2 {
+                return "***".to_string();
             }
-            let auth_masked = headers
-                .get("Authorization")
+            format!("{}...{}", &v[..keep], &v[v.len() - keep..])
+        }
+        let auth_masked = headers
+            .get("Authorization")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| match v.split_once("Signature=") {
+                Some((prefix, sig)) => format!("{}Signature={}", prefix, mask(sig)),
+                None => mask(v),
+            })
+            .unwrap_or_else(|| "<missing>".into());
+        let token_present = headers.get("X-TC-Token").is_some();
+        let mut line = format!(
+            "[hunyuan-sdk][request] action={} url={} region={} token_present={}",
+            action,
+            url,
+            self.region.as_str(),
+            token_present
+        );
+        if !options.labels.is_empty() {
+            line.push_str(&format!(" labels={}", options.labels_display()));
+        }
+        line.push_str(&options.trace_id_display());
+        self.log(LogLevel::Debug, &line);
+        let header = |name: &str| {
+            headers
+                .get(name)
                 .and_then(|v| v.to_str().ok())
-                .map(|v| match v.split_once("Signature=") {
-                    Some((prefix, sig)) => format!("{}Signature={}", prefix, mask(sig)),
-                    None => mask(v),
+                .unwrap_or("")
+                .to_string()
+        };
+        self.log(
+            LogLevel::Debug,
+            &format!(
+                "[hunyuan-sdk][request-headers] Host={} Content-Type={} X-TC-Action={} X-TC-Version={} X-TC-Region={} X-TC-Timestamp={} Authorization={}",
+                header("Host"),
+                header("Content-Type"),
+                header("X-TC-Action"),
+                header("X-TC-Version"),
+                header("X-TC-Region"),
+                header("X-TC-Timestamp"),
+                auth_masked
+            ),
+        );
+        self.log(
+            LogLevel::Debug,
+            &format!("[hunyuan-sdk][request-body] {}", log_body(body.as_bytes())),
+        );
+    }
+
+    /// What TC3 signs for `action` with a body hashing to `hashed_payload`:
+    /// the action's HTTP method, the canonical URI, and the canonical
+    /// `content-type` and `host` headers, each terminated by a newline.
+    fn canonical_request(&self, action: &str, hashed_payload: String) -> CanonicalRequest<'_> {
+        CanonicalRequest {
+            method: Action::from_name(action).map_or("POST", Action::method),
+            uri: &self.canonical_uri,
+            headers: format!(
+                "content-type:application/json; charset=utf-8\nhost:{}\n",
+                self.host()
+            ),
+            hashed_payload,
+        }
+    }
+
+    /// Returns the TC3 canonical request this client signs when sending `req`
+    /// as `action` at `timestamp`: the method, URI, query string, canonical
+    /// headers, signed headers and body hash, separated by newlines.
+    ///
+    /// This is the string to diff against Tencent's signature tool when the
+    /// service answers `AuthFailure.SignatureFailure`. The body is serialized
+    /// exactly as for a real call, including any custom body serializer.
+    /// `action` selects the HTTP method; apart from that, it and `timestamp`
+    /// travel in unsigned headers and the string to sign, so they do not
+    /// appear in the canonical request itself. Fails like the call would for
+    /// a body that cannot be serialized or a `timestamp` that cannot be
+    /// signed.
+    pub fn canonical_request_for<TReq: Serialize>(
+        &self,
+        action: &str,
+        req: &TReq,
+        timestamp: i64,
+    ) -> SdkResult<String> {
+        credential_date(timestamp)?;
+        let body = self.encode_body(req)?;
+        let (_, hashed_payload, _) = self.wire_body(&body, body_text(&body)?);
+        Ok(self.canonical_request(action, hashed_payload).to_string())
+    }
+
+    /// Returns the complete headers of a request sending `body` as
+    /// `action` now, for sending it through an HTTP stack of your own:
+    /// `Host`, `Content-Type`, the `X-TC-*` headers, default headers and
+    /// `Authorization`. POST `body` unchanged to [`Client::url`] with them.
+    ///
+    /// The headers come from the same code path as a call's, so they match
+    /// what the client itself would send. If `ClientBuilder::gzip_requests`
+    /// (feature `gzip`) applies to `body`, the headers are for the
+    /// compressed body; use [`Client::signed_request_for`] to get it. Fails
+    /// with [`SdkError::Signing`] if `body` is not UTF-8.
+    pub fn signed_headers_for(&self, action: &str, body: &[u8]) -> SdkResult<HeaderMap> {
+        self.signed_request_for(action, body).map(|req| req.headers)
+    }
+
+    /// Like [`Client::signed_headers_for`], returning the whole request as
+    /// it would be handed to the transport: method, URL, headers and the
+    /// body as it must be sent.
+    pub fn signed_request_for(&self, action: &str, body: &[u8]) -> SdkResult<PreparedRequest> {
+        self.prepare_request(
+            action,
+            Bytes::copy_from_slice(body),
+            self.clock.unix_timestamp(),
+            &RequestOptions::default(),
+        )
+    }
+
+    /// Serializes `req` into the request body.
+    ///
+    /// Called once per call: the body is immutable across retries, and each
+    /// attempt only gets a fresh timestamp and signature. Attempts share the
+    /// returned buffer, so retrying copies no body bytes. A failure is
+    /// reported to the metrics sink as a failed single-attempt call.
+    fn serialize_body<TReq: Serialize>(
+        &self,
+        action: &str,
+        req: &TReq,
+        options: &RequestOptions,
+    ) -> SdkResult<Bytes> {
+        let body = self.encode_body(req);
+        if let Err(error) = &body {
+            self.record_metrics(action, options, Instant::now(), 1, Some(error));
+        }
+        body
+    }
+
+    /// Encodes `req` with the custom body serializer, if any, or as JSON.
+    ///
+    /// The serializer's [`Value`](serde_json::Value) is parsed from the JSON
+    /// text rather than built with `to_value`, which would widen `f32`
+    /// fields such as `Temperature: 0.7` to `0.699999988079071`.
+    fn encode_body<TReq: Serialize>(&self, req: &TReq) -> SdkResult<Bytes> {
+        match &self.body_serializer {
+            Some(serializer) => serde_json::to_vec(req)
+                .and_then(|json| serde_json::from_slice(&json))
+                .map_err(SdkError::from)
+                .and_then(|value| serializer.serialize(&value))
+                .map(Bytes::from),
+            None => to_body(req).map_err(SdkError::from),
+        }
+    }
+
+    /// Signs the serialized `body` and sends it through the transport.
+    async fn send(
+        &self,
+        action: &str,
+        body: &Bytes,
+        options: &RequestOptions,
+    ) -> SdkResult<RawResponse> {
+        let timestamp = self.clock.unix_timestamp();
+        self.check_clock_skew(action, timestamp);
+        let prepared = self.prepare_request(action, body.clone(), timestamp, options)?;
+        let resp = self.execute(prepared).await?;
+        if self.clock_skew_warning.is_some() {
+            self.server_time.observe(&resp.headers);
+        }
+        Ok(resp)
+    }
+
+    /// Warns if `timestamp` is further from the estimated server time than
+    /// the [`ClientBuilder::clock_skew_warning`] threshold.
+    fn check_clock_skew(&self, action: &str, timestamp: i64) {
+        let Some(threshold) = self.clock_skew_warning else {
+            return;
+        };
+        let Some(server) = self.server_time.estimate() else {
+            return;
+        };
+        let skew = timestamp - server;
+        if skew.unsigned_abs() > threshold.as_secs() {
+            self.log(
+                LogLevel::Warn,
+                &format!(
+                    "[hunyuan-sdk][warn] clock skew action={} skew_secs={} threshold_secs={}: local time is {} the server's; signatures expire beyond 300s",
+                    action,
+                    skew,
+                    threshold.as_secs(),
+                    if skew > 0 { "ahead of" } else { "behind" }
+                ),
+            );
+        }
+    }
+
+    /// Hands `prepared` to the transport, applying the request timeout.
+    async fn execute(&self, prepared: PreparedRequest) -> SdkResult<RawResponse> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.transport.execute(prepared))
+                .await
+                .map_err(|_| {
+                    SdkError::Transport(format!(
+                        "request timed out after {}ms",
+                        timeout.as_millis()
+                    ))
+                })?,
+            None => self.transport.execute(prepared).await,
+        }
+    }
+
+    /// Turns the `Error` of a Tencent Cloud envelope, if any, into
+    /// [`SdkError::Service`].
+    fn envelope_error(
+        &self,
+        status: u16,
+        err: TencentCloudErrorResponse,
+        options: &RequestOptions,
+    ) -> Option<SdkError> {
+        let e = err.error?;
+        if self.debug {
+            self.log(
+                LogLevel::Debug,
+                &format!(
+                    "[hunyuan-sdk][response][error] status={}{} code={} message={} request_id={:?}",
+                    status,
+                    options.trace_id_display(),
+                    e.code,
+                    e.message,
+                    err.request_id
+                ),
+            );
+        }
+        Some(SdkError::Service {
+            code: e.code,
+            message: e.message,
+            request_id: err.request_id,
+        })
+    }
+
+    /// Maps a non-success response body to [`SdkError::Service`].
+    fn service_error(&self, status: u16, body: &[u8], options: &RequestOptions) -> SdkError {
+        if let Some(err) = read_envelope(body).and_then(|e| self.envelope_error(status, e, options))
+        {
+            return err;
+        }
+        if self.debug {
+            self.log(
+                LogLevel::Debug,
+                &format!(
+                    "[hunyuan-sdk][response][error] status={}{} body={}",
+                    status,
+                    options.trace_id_display(),
+                    log_body(body)
+                ),
+            );
+        }
+        SdkError::Service {
+            code: format!("HTTP_{}", status),
+            message: String::from_utf8_lossy(body).into_owned(),
+            request_id: None,
+        }
+    }
+
+    /// Calls a Hunyuan API action with a JSON request body and deserializes the
+    /// JSON response into `TResp`.
+    async fn call_action<TReq: Serialize, TResp: DeserializeOwned>(
+        &self,
+        action: &str,
+        req: &TReq,
+    ) -> SdkResult<TResp> {
+        self.call_action_with_meta(action, req, &RequestOptions::default())
+            .await
+            .map(|(resp, _)| resp)
+    }
+
+    /// Like [`Client::call_action`], but also returns [`ResponseMeta`].
+    async fn call_action_with_meta<TReq: Serialize, TResp: DeserializeOwned>(
+        &self,
+        action: &str,
+        req: &TReq,
+        options: &RequestOptions,
+    ) -> SdkResult<(TResp, ResponseMeta)> {
+        let _in_flight = self.enter()?;
+        let body = self.serialize_body(action, req, options)?;
+        if let Some(key) = &options.idempotency_key {
+            // Checked before claiming, so a key the server would never see
+            // does not deduplicate locally either.
+            header_value("idempotency key", key)?;
+        }
+        let key_guard = match &options.idempotency_key {
+            Some(key) => match self.idempotency.claim(key, Call::new(action, &body)) {
+                Claim::New(guard) => Some(guard),
+                Claim::InFlight => return Err(SdkError::DuplicateRequest { key: key.clone() }),
+                Claim::Mismatch => {
+                    return Err(SdkError::IdempotencyKeyMismatch { key: key.clone() })
+                }
+                Claim::Done(response, meta) => {
+                    return Ok((serde_json::from_value(response)?, meta))
+                }
+            },
+            None => None,
+        };
+        let _permit = self.acquire(options.priority).await;
+        if let Some(key_guard) = key_guard {
+            // Kept as JSON so a later call with the key can be answered.
+            let ((response, mut meta), attempts) = self
+                .with_retries(action, options, || {
+                    self.call_once::<serde_json::Value>(action, &body, options)
                 })
-                .unwrap_or_else(|| "<missing>".into());
-            let token_present = headers.get("X-TC-Token").is_some();
-            eprintln!(
-                "[hunyuan-sdk][request] action={} url={} region={} token_present={}",
+                .await?;
+            meta.attempts = attempts;
+            let parsed = serde_json::from_value(response.clone())?;
+            key_guard.succeeded(response, meta.clone());
+            return Ok((parsed, meta));
+        }
+        let ((parsed, mut meta), attempts) = self
+            .with_retries(action, options, || self.call_once(action, &body, options))
+            .await?;
+        meta.attempts = attempts;
+        Ok((parsed, meta))
+    }
+
+    /// Runs `attempt` until it succeeds, fails with a non-retryable error or
+    /// the retry policy is exhausted. Returns the result and attempt count,
+    /// and reports the call to the metrics sink.
+    async fn with_retries<T, F, Fut>(
+        &self,
+        action: &str,
+        options: &RequestOptions,
+        attempt: F,
+    ) -> SdkResult<(T, u32)>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Failure>>,
+    {
+        let (result, attempts) = self.with_retries_counted(action, options, attempt).await;
+        result.map(|value| (value, attempts))
+    }
+
+    /// Like [`Client::with_retries`], but reports the attempt count whether
+    /// the call succeeds or fails.
+    async fn with_retries_counted<T, F, Fut>(
+        &self,
+        action: &str,
+        options: &RequestOptions,
+        mut attempt: F,
+    ) -> (SdkResult<T>, u32)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Failure>>,
+    {
+        let started = Instant::now();
+        let mut retries = 0;
+        loop {
+            let failure = match attempt().await {
+                Ok(value) => {
+                    self.record_metrics(action, options, started, retries + 1, None);
+                    return (Ok(value), retries + 1);
+                }
+                Err(failure) => failure,
+            };
+            let policy = match &self.retry_policy {
+                Some(policy)
+                    if retries < policy.max_retries && self.should_retry(&failure.error) =>
+                {
+                    policy
+                }
+                _ => {
+                    let error = failure.error;
+                    self.record_metrics(action, options, started, retries + 1, Some(&error));
+                    return (Err(error), retries + 1);
+                }
+            };
+            let delay = policy.backoff(retries, failure.retry_after);
+            if self.debug {
+                self.log(
+                    LogLevel::Debug,
+                    &format!(
+                        "[hunyuan-sdk][retry] action={} retry={} delay_ms={} error={}{}",
+                        action,
+                        retries + 1,
+                        delay.as_millis(),
+                        failure.error,
+                        options.trace_id_display()
+                    ),
+                );
+            }
+            tokio::time::sleep(delay).await;
+            retries += 1;
+        }
+    }
+
+    /// Whether `error` is retried: see [`ClientBuilder::retry_if`].
+    fn should_retry(&self, error: &SdkError) -> bool {
+        match &self.retry_predicate {
+            Some(predicate) => predicate(error),
+            None => error.is_retryable(),
+        }
+    }
+
+    fn record_metrics(
+        &self,
+        action: &str,
+        options: &RequestOptions,
+        started: Instant,
+        attempts: u32,
+        error: Option<&SdkError>,
+    ) {
+        if let Some(sink) = &self.metrics_sink {
+            sink.record(&CallMetrics {
                 action,
-                url,
-                self.region.as_str(),
-                token_present
-            );
-            let ct = headers
-                .get("Content-Type")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("");
-            let host = headers
-                .get("Host")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("");
-            let act = headers
-                .get("X-TC-Action")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("");
-            let ver = headers
-                .get("X-TC-Version")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("");
-            let reg = headers
-                .get("X-TC-Region")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("");
-            let ts = headers
-                .get("X-TC-Timestamp")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("");
-            eprintln!(
-                "[hunyuan-sdk][request-headers] Host={} Content-Type={} X-TC-Action={} X-TC-Version={} X-TC-Region={} X-TC-Timestamp={} Authorization={}",
-                host, ct, act, ver, reg, ts, auth_masked
+                labels: &options.labels,
+                elapsed: started.elapsed(),
+                attempts,
+                error,
+            });
+        }
+    }
+
+    /// Single attempt of [`Client::call_action_with_meta`].
+    async fn call_once<TResp: DeserializeOwned>(
+        &self,
+        action: &str,
+        body: &Bytes,
+        options: &RequestOptions,
+    ) -> Result<(TResp, ResponseMeta), Failure> {
+        self.call_once_raw(action, body, options)
+            .await
+            .map(|(parsed, meta, _)| (parsed, meta))
+    }
+
+    /// Like [`Client::call_once`], also returning the response body.
+    async fn call_once_raw<TResp: DeserializeOwned>(
+        &self,
+        action: &str,
+        body: &Bytes,
+        options: &RequestOptions,
+    ) -> Result<(TResp, ResponseMeta, Bytes), Failure> {
+        let started = Instant::now();
+        let resp = self.send(action, body, options).await?;
+        let retry_after = retry_after(&resp.headers);
+        self.read_response(action, options, started, resp)
+            .await
+            .map_err(|error| Failure {
+                error: error.classify(retry_after),
+                retry_after,
+            })
+    }
+
+    /// Reads and decodes a unary response, returning the body along.
+    async fn read_response<TResp: DeserializeOwned>(
+        &self,
+        action: &str,
+        options: &RequestOptions,
+        started: Instant,
+        resp: RawResponse,
+    ) -> SdkResult<(TResp, ResponseMeta, Bytes)> {
+        let status = resp.status;
+        let content_type = resp.content_type().map(str::to_string);
+        let limit = options
+            .max_response_bytes
+            .unwrap_or(self.max_response_bytes);
+        let body = resp.into_bytes_limited(limit).await?;
+        let elapsed = started.elapsed();
+        let slow = self.check_slow(action, options, elapsed);
+
+        if self.debug {
+            self.log(
+                LogLevel::Debug,
+                &format!(
+                    "[hunyuan-sdk][response] status={}{} body={}",
+                    status,
+                    options.trace_id_display(),
+                    log_body(&body)
+                ),
             );
-            eprintln!("[hunyuan-sdk][request-body] {}", body);
         }
 
+        if let Some(err) = unexpected_response(status, content_type.as_deref(), &body) {
+            return Err(err);
+        }
+        if !(200..300).contains(&status) {
+            return Err(self.service_error(status, &body, options));
+        }
+        // Tencent Cloud reports most service errors with HTTP 200. The body is
+        // parsed from bytes, without building a `String` of it.
+        let envelope = read_envelope(&body);
+        let request_id = envelope.as_ref().and_then(|e| e.request_id.clone());
+        if let Some(err) = envelope.and_then(|e| self.envelope_error(status, e, options)) {
+            return Err(err);
+        }
+
+        let parsed: TResp = match self.deserialization_mode {
+            DeserializationMode::Lenient => serde_json::from_slice(&body)?,
+            DeserializationMode::Strict => {
+                let (parsed, unknown) = crate::strict::from_slice(&body)?;
+                if !unknown.is_empty() {
+                    return Err(SdkError::Decode(format!(
+                        "{} response has fields the SDK does not declare: {}",
+                        action,
+                        unknown.join(", ")
+                    )));
+                }
+                if action == ACTION_CHAT_COMPLETIONS {
+                    if let Some(err) = choice_index_error(status, content_type.as_deref(), &body) {
+                        return Err(err);
+                    }
+                }
+                parsed
+            }
+        };
+        let meta = ResponseMeta {
+            request_id,
+            status,
+            elapsed,
+            slow,
+            attempts: 1,
+            model: None,
+            seed: None,
+            trace_id: options.trace_id.clone(),
+        };
+        Ok((parsed, meta, body))
+    }
+
+    /// Emits a warning if `elapsed` exceeds the slow-request threshold and
+    /// returns whether it did.
+    fn check_slow(&self, action: &str, options: &RequestOptions, elapsed: Duration) -> bool {
+        match self.slow_request_threshold {
+            Some(threshold) if elapsed > threshold => {
+                let mut message = format!(
+                    "[hunyuan-sdk][warn] slow request action={} elapsed_ms={} threshold_ms={}",
+                    action,
+                    elapsed.as_millis(),
+                    threshold.as_millis()
+                );
+                if !options.labels.is_empty() {
+                    message.push_str(&format!(" labels={}", options.labels_display()));
+                }
+                message.push_str(&options.trace_id_display());
+                self.log(LogLevel::Warn, &message);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Connects to the endpoint ahead of the first call, so DNS resolution
+    /// and the TCP and TLS handshakes don't delay it.
+    ///
+    /// Sends an unsigned `HEAD /` and drains the response, leaving the
+    /// connection in the transport's pool for the next call. Call it during
+    /// startup; an error only means the warm-up did not help, the client
+    /// stays usable.
+    pub async fn warm_up(&self) -> SdkResult<WarmUp> {
+        let started = Instant::now();
+        let url = self.url.clone();
         let resp = self
-            .http
-            .post(url)
-            .headers(headers)
-            .body(body)
-            .send()
+            .execute(PreparedRequest {
+                method: "HEAD".to_string(),
+                url: url.clone(),
+                headers: HeaderMap::new(),
+                body: Bytes::new(),
+            })
             .await?;
-        let status = resp.status();
-        let text = resp.text().await?;
-
+        let status = resp.status;
+        resp.bytes().await?;
+        let warm_up = WarmUp {
+            status,
+            elapsed: started.elapsed(),
+        };
         if self.debug {
-            eprintln!(
-                "[hunyuan-sdk][response] status={} body={}",
-                status.as_u16(),
-                text
+            self.log(
+                LogLevel::Debug,
+                &format!(
+                    "[hunyuan-sdk][warm-up] url={} status={} elapsed_ms={}",
+                    url,
+                    status,
+                    warm_up.elapsed.as_millis()
+                ),
             );
         }
+        Ok(warm_up)
+    }
+
+    /// Calls any Hunyuan API action with a typed request and response.
+    ///
+    /// `TResp` is deserialized from the full response, including the
+    /// `Response` envelope. `action` is an [`Action`] or the name of any
+    /// other action. An action name that is not a valid header value fails
+    /// with [`SdkError::InvalidRequest`] before anything is sent.
+    pub async fn call<TReq: Serialize, TResp: DeserializeOwned>(
+        &self,
+        action: impl AsRef<str>,
+        req: &TReq,
+        options: &RequestOptions,
+    ) -> SdkResult<TResp> {
+        self.call_action_with_meta(action.as_ref(), req, options)
+            .await
+            .map(|(resp, _)| resp)
+    }
+
+    /// Like [`Client::call`], also returning [`ResponseMeta`], e.g. to log
+    /// the request id next to the [`RequestOptions::trace_id`] of the call.
+    pub async fn call_with_meta<TReq: Serialize, TResp: DeserializeOwned>(
+        &self,
+        action: impl AsRef<str>,
+        req: &TReq,
+        options: &RequestOptions,
+    ) -> SdkResult<(TResp, ResponseMeta)> {
+        self.call_action_with_meta(action.as_ref(), req, options)
+            .await
+    }
 
-        if !status.is_success() {
-            // Try to decode TencentCloud style error
-            let err: Result<TencentCloudErrorResponse, _> = serde_json::from_str(&text);
-            if let Ok(err) = err {
-                if let Some(e) = err.error {
+    /// Calls any Hunyuan API action with a JSON body and returns the raw JSON
+    /// response.
+    pub async fn call_raw(
+        &self,
+        action: impl AsRef<str>,
+        body: &serde_json::Value,
+        options: &RequestOptions,
+    ) -> SdkResult<serde_json::Value> {
+        self.call(action, body, options).await
+    }
+
+    /// Calls the `ChatCompletions` action.
+    pub async fn chat_completions(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<ChatCompletionsResponse> {
+        self.call_action(
+            ACTION_CHAT_COMPLETIONS,
+            &self.with_client_defaults(req, &RequestOptions::default()),
+        )
+        .await
+    }
+
+    /// Calls the `ChatCompletions` action with per-call `options`, e.g. labels.
+    pub async fn chat_completions_with_options(
+        &self,
+        req: &ChatCompletionsRequest,
+        options: &RequestOptions,
+    ) -> SdkResult<ChatCompletionsResponse> {
+        self.call(
+            ACTION_CHAT_COMPLETIONS,
+            &self.with_client_defaults(req, options),
+            options,
+        )
+        .await
+    }
+
+    /// Calls the `ChatCompletions` action and also returns [`ResponseMeta`].
+    pub async fn chat_completions_with_meta(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<(ChatCompletionsResponse, ResponseMeta)> {
+        let req = self.with_client_defaults(req, &RequestOptions::default());
+        let (resp, mut meta) = self
+            .call_action_with_meta(ACTION_CHAT_COMPLETIONS, &req, &RequestOptions::default())
+            .await?;
+        meta.model = req.model.clone();
+        meta.seed = effective_seed(&req, &resp);
+        Ok((resp, meta))
+    }
+
+    /// Calls the `ChatCompletions` action and also returns the response body
+    /// exactly as the server sent it, e.g. to inspect fields the typed
+    /// response drops. Other calls do not keep the body.
+    pub async fn chat_completions_raw(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<(ChatCompletionsResponse, String)> {
+        let _in_flight = self.enter()?;
+        let options = RequestOptions::default();
+        let req = self.with_client_defaults(req, &options);
+        let body = self.serialize_body(ACTION_CHAT_COMPLETIONS, &req, &options)?;
+        let _permit = self.acquire(options.priority).await;
+        let ((resp, _, raw), _) = self
+            .with_retries(ACTION_CHAT_COMPLETIONS, &options, || {
+                self.call_once_raw(ACTION_CHAT_COMPLETIONS, &body, &options)
+            })
+            .await?;
+        Ok((resp, String::from_utf8_lossy(&raw).into_owned()))
+    }
+
+    /// Calls the `ChatCompletions` action, moving on to the next model of the
+    /// model fallback chain whenever a model fails with an error the client
+    /// retries (after its own retries; see [`ClientBuilder::retry_if`]). The
+    /// requested model, if any, is tried first.
+    ///
+    /// [`ResponseMeta::model`] names the model that served the request and
+    /// [`ResponseMeta::attempts`] counts attempts across all models. The error
+    /// of the last model tried is returned if none succeeds.
+    pub async fn chat_completions_with_fallback(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<(ChatCompletionsResponse, ResponseMeta)> {
+        let requested = self.with_client_defaults(req, &RequestOptions::default());
+        let mut models: Vec<Option<&str>> = Vec::new();
+        if requested.model.is_some() || self.model_fallback_chain.is_empty() {
+            models.push(requested.model.as_deref());
+        }
+        for model in &self.model_fallback_chain {
+            if !models.contains(&Some(model.as_str())) {
+                models.push(Some(model));
+            }
+        }
+        let _in_flight = self.enter()?;
+        let options = RequestOptions::default();
+        let mut attempts = 0;
+        let mut last_error = None;
+        for model in models {
+            let mut req = (*requested).clone();
+            req.model = model.map(str::to_string);
+            let body = self.serialize_body(ACTION_CHAT_COMPLETIONS, &req, &options)?;
+            let _permit = self.acquire(options.priority).await;
+            let (result, tried) = self
+                .with_retries_counted(ACTION_CHAT_COMPLETIONS, &options, || {
+                    self.call_once(ACTION_CHAT_COMPLETIONS, &body, &options)
+                })
+                .await;
+            attempts += tried;
+            match result {
+                Ok((resp, mut meta)) => {
+                    meta.attempts = attempts;
+                    meta.seed = effective_seed(&req, &resp);
+                    meta.model = req.model;
+                    return Ok((resp, meta));
+                }
+                // Retried as far as the policy allows; try the next model.
+                Err(err) if self.should_retry(&err) => {
                     if self.debug {
-                        eprintln!(
-                            "[hunyuan-sdk][response][error] status={} code={} message={} request_id={:?}",
-                            status.as_u16(), e.code, e.message, err.request_id
+                        self.log(
+                            LogLevel::Debug,
+                            &format!(
+                                "[hunyuan-sdk][fallback] model={} error={}",
+                                req.model.as_deref().unwrap_or("<default>"),
+                                err
+                            ),
                         );
                     }
-                    return Err(SdkError::Service {
-                        code: e.code,
-                        message: e.message,
-                        request_id: err.request_id,
-                    });
+                    last_error = Some(err);
                 }
+                Err(err) => return Err(err),
             }
-            if self.debug {
-                eprintln!(
-                    "[hunyuan-sdk][response][error] status={} body={}",
-                    status.as_u16(),
-                    text
-                );
+        }
+        Err(last_error.expect("at least one model is tried"))
+    }
+
+    /// Calls the `ChatCompletions` action for every request, with at most
+    /// `options.concurrency` in flight, and returns the results in the order
+    /// of `requests`.
+    ///
+    /// Each request is a regular call: it is retried per the retry policy and
+    /// reported to the metrics sink. With `options.fail_fast`, the first
+    /// failure cancels the requests in flight and skips the rest; those fail
+    /// with [`SdkError::Aborted`].
+    pub async fn chat_completions_batch(
+        &self,
+        requests: Vec<ChatCompletionsRequest>,
+        options: BatchOptions,
+    ) -> Vec<SdkResult<ChatCompletionsResponse>> {
+        let mut results: Vec<Option<SdkResult<ChatCompletionsResponse>>> =
+            requests.iter().map(|_| None).collect();
+        let mut pending = futures_util::stream::iter(requests.into_iter().enumerate())
+            .map(|(index, req)| async move { (index, self.chat_completions(&req).await) })
+            .buffer_unordered(options.concurrency.max(1));
+        while let Some((index, result)) = pending.next().await {
+            let failed = result.is_err();
+            results[index] = Some(result);
+            if failed && options.fail_fast {
+                break;
             }
-            return Err(SdkError::Service {
-                code: format!("HTTP_{}", status.as_u16()),
-                message: text,
-                request_id: None,
+        }
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| {
+                    Err(SdkError::Aborted(
+                        "batch stopped after an earlier request failed".to_string(),
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Asks the model for JSON and deserializes its reply into `T`.
+    ///
+    /// `instructions` (e.g. a description of the expected fields) and a note
+    /// to answer with bare JSON are appended to the last user message of
+    /// `req`. Markdown fences and prose around the JSON are stripped before
+    /// parsing. If the reply still does not parse, the model is shown the
+    /// parse error and asked to correct its output, once by default; see
+    /// [`chat_structured_with_options`](Self::chat_structured_with_options).
+    ///
+    /// ChatCompletions has no JSON mode, so the format relies on the prompt.
+    /// Fails with [`SdkError::Decode`] if no reply parses.
+    pub async fn chat_structured<T: DeserializeOwned>(
+        &self,
+        req: &ChatCompletionsRequest,
+        instructions: &str,
+    ) -> SdkResult<T> {
+        self.chat_structured_with_options(req, instructions, StructuredOptions::default())
+            .await
+    }
+
+    /// Like [`Client::chat_structured`], with the number of repair attempts
+    /// set by `options`.
+    pub async fn chat_structured_with_options<T: DeserializeOwned>(
+        &self,
+        req: &ChatCompletionsRequest,
+        instructions: &str,
+        options: StructuredOptions,
+    ) -> SdkResult<T> {
+        let mut req = req.clone();
+        let note = format!("{}\n\n{}", instructions, STRUCTURED_FORMAT_NOTE);
+        match req
+            .messages
+            .iter_mut()
+            .rev()
+            .find(|m| m.role == Role::User.as_str())
+        {
+            Some(message) => {
+                message.content.push_str("\n\n");
+                message.content.push_str(note.trim_start());
+            }
+            None => req
+                .messages
+                .push(Message::new(Role::User, note.trim_start())),
+        }
+        let mut repairs = 0;
+        loop {
+            let resp = self.chat_completions(&req).await?;
+            let reply = resp
+                .messages()
+                .next()
+                .and_then(|m| m.content.clone())
+                .unwrap_or_default();
+            let error = match serde_json::from_str(extract_json(&reply)) {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            if repairs == options.repair_attempts {
+                return Err(SdkError::Decode(format!(
+                    "model reply is not valid JSON for the requested type after {} repair attempt(s): {}",
+                    repairs, error
+                )));
+            }
+            repairs += 1;
+            req.messages.push(Message::new(Role::Assistant, reply));
+            req.messages.push(Message::new(
+                Role::User,
+                format!(
+                    "Your reply could not be parsed: {}. Reply again with the corrected JSON only.",
+                    error
+                ),
+            ));
+        }
+    }
+
+    /// Sends `req` and returns the text of each completion in `Index` order,
+    /// e.g. after [`ChatCompletionsRequest::with_n`] asked for several.
+    ///
+    /// A choice whose message has no content counts as empty text. Fails with
+    /// [`SdkError::EmptyResponse`] if the response has no choice with a
+    /// message.
+    pub async fn chat_completions_alternatives(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<Vec<String>> {
+        let resp = self.chat_completions(req).await?;
+        let texts: Vec<String> = resp
+            .messages()
+            .map(|m| m.content.clone().unwrap_or_default())
+            .collect();
+        if texts.is_empty() {
+            return Err(SdkError::EmptyResponse {
+                request_id: resp.response.request_id,
             });
         }
+        Ok(texts)
+    }
 
-        let parsed: TResp = serde_json::from_str(&text)?;
-        Ok(parsed)
+    /// Answers `messages` with the functions of `registry` available to the
+    /// model; see the [`tools`](crate::tools) module.
+    ///
+    /// Whenever the model replies with tool calls, the reply and one `tool`
+    /// message per call are appended to `messages` and the conversation is
+    /// sent again. Once the model replies without tool calls, that reply is
+    /// appended too and its response returned. Fails with
+    /// [`SdkError::Aborted`] if the model still calls tools after
+    /// `options.max_rounds` rounds; `messages` then holds every answered
+    /// round.
+    pub async fn run_with_tools(
+        &self,
+        messages: &mut Vec<Message>,
+        registry: &ToolRegistry,
+        options: RunOptions,
+    ) -> SdkResult<ChatCompletionsResponse> {
+        let tools = (!registry.is_empty()).then(|| registry.tools());
+        let mut rounds = 0;
+        loop {
+            let req = ChatCompletionsRequest {
+                model: options.model.clone(),
+                messages: messages.clone(),
+                tools: tools.clone(),
+                ..Default::default()
+            };
+            let resp = self.chat_completions(&req).await?;
+            let reply = resp.messages().next().cloned().unwrap_or_default();
+            let calls = reply.tool_calls.unwrap_or_default();
+            if calls.is_empty() {
+                messages.push(Message::new(
+                    Role::Assistant,
+                    reply.content.unwrap_or_default(),
+                ));
+                return Ok(resp);
+            }
+            if rounds == options.max_rounds {
+                return Err(SdkError::Aborted(format!(
+                    "model still calls tools after {} round(s)",
+                    rounds
+                )));
+            }
+            rounds += 1;
+            let mut results = Vec::with_capacity(calls.len());
+            for call in &calls {
+                results.push(Message::tool_result(&call.id, registry.call(call).await));
+            }
+            messages.push(Message {
+                tool_calls: Some(calls),
+                ..Message::new(Role::Assistant, reply.content.unwrap_or_default())
+            });
+            messages.extend(results);
+        }
     }
 
-    /// Calls the `ChatCompletions` action.
-    pub async fn chat_completions(
+    /// Calls the `GetEmbedding` action. Fails without sending anything if
+    /// `input_type` is not a supported value.
+    ///
+    /// With an [embedding cache](ClientBuilder::embedding_cache), only the
+    /// texts not found in it are sent. The response then lists the vectors
+    /// of all texts in input order, with the `RequestId` and `Usage` of that
+    /// call; when every text was cached, nothing is sent, `RequestId` is
+    /// unset and usage is zero. Requests for base64 vectors bypass the cache.
+    pub async fn get_embedding(
+        &self,
+        req: &GetEmbeddingRequest,
+    ) -> SdkResult<GetEmbeddingResponse> {
+        req.validate()?;
+        let texts: Vec<&str> = match (&req.input, &req.input_list) {
+            (Some(input), None) => vec![input.as_str()],
+            (None, Some(list)) => list.iter().map(String::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let cacheable = !texts.is_empty()
+            && matches!(
+                req.encoding_format.as_deref(),
+                None | Some(ENCODING_FORMAT_FLOAT)
+            );
+        let Some(cache) = self.embedding_cache.as_ref().filter(|_| cacheable) else {
+            return self.fetch_embedding(req).await;
+        };
+        let keys: Vec<_> = texts
+            .iter()
+            .map(|text| EmbeddingCache::key(req.input_type.as_deref(), text))
+            .collect();
+        let mut vectors: Vec<Option<Arc<[f64]>>> = keys.iter().map(|key| cache.get(key)).collect();
+        let missing: Vec<usize> = (0..texts.len()).filter(|&i| vectors[i].is_none()).collect();
+
+        let mut inner = GetEmbeddingResponseInner {
+            usage: Some(EmbeddingUsage {
+                prompt_tokens: Some(0),
+                total_tokens: Some(0),
+            }),
+            ..Default::default()
+        };
+        if !missing.is_empty() {
+            let missing_texts = missing.iter().map(|&i| texts[i].to_string());
+            let sub = GetEmbeddingRequest {
+                input: req.input.as_ref().map(|_| texts[0].to_string()),
+                input_list: req.input_list.as_ref().map(|_| missing_texts.collect()),
+                input_type: req.input_type.clone(),
+                encoding_format: req.encoding_format.clone(),
+            };
+            let fetched = self.fetch_embedding(&sub).await?.response;
+            let data = fetched.data.as_deref().unwrap_or_default();
+            if data.len() != missing.len() {
+                return Err(SdkError::Decode(format!(
+                    "GetEmbedding returned {} embeddings for {} inputs",
+                    data.len(),
+                    missing.len()
+                )));
+            }
+            for (position, item) in data.iter().enumerate() {
+                let slot = item.index.map_or(position, |index| index as usize);
+                let (Some(&i), Some(embedding)) = (missing.get(slot), &item.embedding) else {
+                    return Err(SdkError::Decode(format!(
+                        "GetEmbedding returned no embedding for input {}",
+                        slot
+                    )));
+                };
+                let vector: Arc<[f64]> = embedding.as_slice().into();
+                cache.insert(keys[i].clone(), vector.clone());
+                vectors[i] = Some(vector);
+            }
+            inner = GetEmbeddingResponseInner {
+                data: None,
+                ..fetched
+            };
+        }
+        let data = vectors
+            .into_iter()
+            .enumerate()
+            .map(|(i, vector)| {
+                let embedding = vector.ok_or_else(|| {
+                    SdkError::Decode(format!(
+                        "GetEmbedding returned no embedding for input {}",
+                        i
+                    ))
+                })?;
+                Ok(EmbeddingData {
+                    embedding: Some(embedding.to_vec()),
+                    index: Some(i as u32),
+                    object: Some("embedding".to_string()),
+                    ..Default::default()
+                })
+            })
+            .collect::<SdkResult<Vec<_>>>()?;
+        inner.data = Some(data);
+        Ok(TencentCloudResponse { response: inner })
+    }
+
+    /// Sends `req` as a `GetEmbedding` call, sharing it with concurrent
+    /// identical requests when coalescing is enabled.
+    async fn fetch_embedding(&self, req: &GetEmbeddingRequest) -> SdkResult<GetEmbeddingResponse> {
+        let Some(coalescer) = &self.embedding_coalescer else {
+            return self.call_action(ACTION_GET_EMBEDDING, req).await;
+        };
+        let options = RequestOptions::default();
+        let body = self.serialize_body(ACTION_GET_EMBEDDING, req, &options)?;
+        let client = self.clone();
+        let in_flight = self.enter()?;
+        let call = {
+            let body = body.clone();
+            async move {
+                let _in_flight = in_flight;
+                let _permit = client.acquire(options.priority).await;
+                client
+                    .with_retries(ACTION_GET_EMBEDDING, &options, || {
+                        client.call_once(ACTION_GET_EMBEDDING, &body, &options)
+                    })
+                    .await
+                    .map(|((resp, _), _)| resp)
+            }
+        };
+        coalescer.run(body, call).await
+    }
+
+    /// Embeds `texts` and returns their vectors in input order.
+    ///
+    /// Texts are sent in `GetEmbedding` requests of at most
+    /// [`MAX_EMBEDDING_INPUTS`], up to four at a time. Vectors are read with
+    /// [`EmbeddingData::vector`](crate::models::EmbeddingData::vector), so
+    /// either encoding works. Fails with the first error; vectors already
+    /// received are discarded.
+    pub async fn embed_batch(&self, texts: Vec<String>) -> SdkResult<Vec<Vec<f32>>> {
+        let mut vectors = vec![Vec::new(); texts.len()];
+        let mut pending =
+            futures_util::stream::iter(texts.chunks(MAX_EMBEDDING_INPUTS).enumerate())
+                .map(|(n, chunk)| async move {
+                    let req = GetEmbeddingRequest {
+                        input_list: Some(chunk.to_vec()),
+                        ..Default::default()
+                    };
+                    let resp = self.get_embedding(&req).await?;
+                    SdkResult::Ok((n * MAX_EMBEDDING_INPUTS, chunk.len(), resp))
+                })
+                .buffer_unordered(EMBED_BATCH_CONCURRENCY);
+        while let Some((offset, len, resp)) = pending.next().await.transpose()? {
+            let mut data = resp.response.data.unwrap_or_default();
+            if data.len() != len {
+                return Err(SdkError::Decode(format!(
+                    "GetEmbedding returned {} vectors for {} inputs",
+                    data.len(),
+                    len
+                )));
+            }
+            data.sort_by_key(|d| d.index);
+            for (slot, d) in vectors[offset..offset + len].iter_mut().zip(data) {
+                *slot = d.vector()?;
+            }
+        }
+        Ok(vectors)
+    }
+
+    /// Calls the `GetTokenCount` action and returns the number of tokens in
+    /// `prompt`. For a free, approximate count see
+    /// [`estimate_tokens`](crate::models::estimate_tokens).
+    pub async fn get_token_count(&self, prompt: &str) -> SdkResult<u32> {
+        let resp = self
+            .call_raw(
+                ACTION_GET_TOKEN_COUNT,
+                &serde_json::json!({ "Prompt": prompt }),
+                &RequestOptions::default(),
+            )
+            .await?;
+        resp["Response"]["TokenCount"]
+            .as_u64()
+            .map(|n| n as u32)
+            .ok_or_else(|| SdkError::Decode("GetTokenCount returned no TokenCount".to_string()))
+    }
+
+    /// Calls the `TextToImageLite` action. With `RspImgType` set to `base64`,
+    /// decode the result with
+    /// [`decode_image`](crate::models::TextToImageLiteResponseInner::decode_image).
+    pub async fn text_to_image_lite(
+        &self,
+        req: &TextToImageLiteRequest,
+    ) -> SdkResult<TextToImageLiteResponse> {
+        self.call_action(ACTION_TEXT_TO_IMAGE_LITE, req).await
+    }
+
+    /// Calls the `ChatCompletions` action with `Stream` enabled and returns the
+    /// response as a stream of
+    /// [`ChatCompletionsChunk`](crate::models::ChatCompletionsChunk)s.
+    ///
+    /// The slow-request threshold applies to the time until the first chunk.
+    pub async fn chat_completions_stream(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<ChatCompletionsStream> {
+        self.chat_completions_stream_with_options(req, &RequestOptions::default())
+            .await
+    }
+
+    /// Like [`Client::chat_completions_stream`], with per-call `options`.
+    /// Metrics are reported once the stream is open.
+    pub async fn chat_completions_stream_with_options(
         &self,
         req: &ChatCompletionsRequest,
-    ) -> Result<ChatCompletionsResponse, SdkError> {
-        self.call_action(ACTION_CHAT_COMPLETIONS, req).await
+        options: &RequestOptions,
+    ) -> SdkResult<ChatCompletionsStream> {
+        let in_flight = self.enter()?;
+        let mut req = self.with_client_defaults(req, options).into_owned();
+        req.stream = Some(true);
+        let body = self.serialize_body(ACTION_CHAT_COMPLETIONS, &req, options)?;
+        let permit = self.acquire(options.priority).await;
+        self.with_retries(ACTION_CHAT_COMPLETIONS, options, || {
+            self.open_stream(&body, options)
+        })
+        .await
+        .map(|(stream, _)| stream.hold(in_flight, permit))
+    }
+
+    /// Single attempt of [`Client::chat_completions_stream_with_options`].
+    async fn open_stream(
+        &self,
+        body: &Bytes,
+        options: &RequestOptions,
+    ) -> Result<ChatCompletionsStream, Failure> {
+        let started = Instant::now();
+        let resp = self.send(ACTION_CHAT_COMPLETIONS, body, options).await?;
+        let status = resp.status;
+        let retry_after = retry_after(&resp.headers);
+        let is_event_stream = resp
+            .content_type()
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+        if !(200..300).contains(&status) || !is_event_stream {
+            // Errors arrive as a regular JSON envelope instead of SSE.
+            let content_type = resp.content_type().map(str::to_string);
+            let limit = options
+                .max_response_bytes
+                .unwrap_or(self.max_response_bytes);
+            let body = resp.into_bytes_limited(limit).await?;
+            let error = unexpected_response(status, content_type.as_deref(), &body)
+                .unwrap_or_else(|| self.service_error(status, &body, options))
+                .classify(retry_after);
+            return Err(Failure { error, retry_after });
+        }
+        let client = self.clone();
+        let options = options.clone();
+        Ok(ChatCompletionsStream::new(
+            resp.body,
+            Box::new(move || {
+                client.check_slow(ACTION_CHAT_COMPLETIONS, &options, started.elapsed());
+            }),
+        )
+        .max_buffered(self.max_sse_buffer_bytes))
     }
 }
@@ -0,0 +1,68 @@
+//! Time source for request timestamps.
+//!
+//! Every request is signed with the current Unix time taken from a [`Clock`].
+//! The default [`SystemClock`] reads the system time; set another one with
+//! `ClientBuilder::clock`, e.g. a [`FixedClock`] to produce reproducible
+//! signatures in tests.
+//!
+//! With `ClientBuilder::clock_skew_warning`, the client also estimates the
+//! server's time from the `Date` header of its responses and warns when the
+//! clock drifts away from it, before signatures start to expire.
+use reqwest::header::{HeaderMap, DATE};
+use std::sync::Mutex;
+use std::time::Instant;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
+
+/// Source of the timestamp used for `X-TC-Timestamp` and signing.
+pub trait Clock: Send + Sync {
+    /// Current Unix time in seconds.
+    fn unix_timestamp(&self) -> i64;
+}
+
+/// Default clock reading the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn unix_timestamp(&self) -> i64 {
+        OffsetDateTime::now_utc().unix_timestamp()
+    }
+}
+
+/// Clock that always returns the same Unix time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+    fn unix_timestamp(&self) -> i64 {
+        self.0
+    }
+}
+
+/// Estimate of the server's time, from the `Date` header of the latest
+/// response, used to warn about clock skew.
+#[derive(Debug, Default)]
+pub(crate) struct ServerTime {
+    /// Server time in Unix seconds and when it was received.
+    last: Mutex<Option<(i64, Instant)>>,
+}
+
+impl ServerTime {
+    /// Records the `Date` header of a response, if it has a valid one.
+    pub fn observe(&self, headers: &HeaderMap) {
+        let date = headers
+            .get(DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| OffsetDateTime::parse(value.trim(), &Rfc2822).ok());
+        if let Some(date) = date {
+            *self.last.lock().unwrap() = Some((date.unix_timestamp(), Instant::now()));
+        }
+    }
+
+    /// Current server time in Unix seconds, once a response was observed.
+    pub fn estimate(&self) -> Option<i64> {
+        let (server, at) = (*self.last.lock().unwrap())?;
+        Some(server + at.elapsed().as_secs() as i64)
+    }
+}
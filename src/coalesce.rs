@@ -0,0 +1,95 @@
+//! Sharing one in-flight call among concurrent identical requests.
+use crate::client::{SdkError, SdkResult};
+use bytes::Bytes;
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Most distinct requests tracked at once; further ones are sent without
+/// coalescing until some finish.
+const MAX_IN_FLIGHT: usize = 1024;
+
+type Flight<T> = Shared<BoxFuture<'static, Result<T, Arc<SdkError>>>>;
+
+/// Runs at most one call per request body at a time. Callers arriving while
+/// a call for the same body is in flight wait for it and get a copy of its
+/// result, error included.
+///
+/// Only in-flight calls are tracked: an entry is removed as soon as its call
+/// finishes or every caller waiting on it has gone away.
+pub(crate) struct Coalescer<T> {
+    flights: Mutex<HashMap<Bytes, Flight<T>>>,
+}
+
+impl<T> Default for Coalescer<T> {
+    fn default() -> Self {
+        Self {
+            flights: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Coalescer<T> {
+    /// Returns the result of the call in flight for `body`, or starts `call`
+    /// for it.
+    pub async fn run<F>(&self, body: Bytes, call: F) -> SdkResult<T>
+    where
+        F: Future<Output = SdkResult<T>> + Send + 'static,
+    {
+        // `Err` hands the call back to run unshared, outside the lock.
+        let flight = {
+            let mut flights = self.flights.lock().unwrap();
+            match flights.get(&body) {
+                Some(flight) => Ok(flight.clone()),
+                None if flights.len() >= MAX_IN_FLIGHT => Err(call),
+                None => {
+                    let flight = call.map(|result| result.map_err(Arc::new)).boxed().shared();
+                    flights.insert(body.clone(), flight.clone());
+                    Ok(flight)
+                }
+            }
+        };
+        let flight = match flight {
+            Ok(flight) => flight,
+            Err(call) => return call.await,
+        };
+        let waiter = Waiter {
+            coalescer: self,
+            body,
+            flight,
+        };
+        // Await a clone: a polled-out `Shared` forgets its flight, and the
+        // waiter still needs it to find and remove the map entry.
+        let result = waiter.flight.clone().await;
+        drop(waiter);
+        // The last waiter to finish takes the original error; the others
+        // get a copy.
+        result.map_err(|error| Arc::try_unwrap(error).unwrap_or_else(|error| error.duplicate()))
+    }
+}
+
+/// A caller waiting on a flight. Dropping it, whether the call finished or
+/// the caller was cancelled, removes the flight once nobody else needs it.
+struct Waiter<'a, T> {
+    coalescer: &'a Coalescer<T>,
+    body: Bytes,
+    flight: Flight<T>,
+}
+
+impl<T> Drop for Waiter<'_, T> {
+    fn drop(&mut self) {
+        let mut flights = self.coalescer.flights.lock().unwrap();
+        let Some(current) = flights.get(&self.body) else {
+            return;
+        };
+        if !current.ptr_eq(&self.flight) {
+            return;
+        }
+        // Finished, or only the map and this waiter still hold it; a
+        // cancelled caller's awaited clone is dropped before its waiter.
+        if self.flight.peek().is_some() || self.flight.strong_count() == Some(2) {
+            flights.remove(&self.body);
+        }
+    }
+}
@@ -0,0 +1,2 @@
+//! Conversions between Hunyuan models and other APIs' wire formats.
+pub mod openai;
@@ -0,0 +1,682 @@
+//! Conversion between OpenAI Chat Completions JSON and Hunyuan models.
+//!
+//! Meant for putting Hunyuan behind an OpenAI-style proxy: convert the
+//! incoming request with [`from_openai_request`], call the client, and
+//! convert the result back with [`to_openai_response`], or chunk by chunk
+//! with [`to_openai_sse_event`] followed by [`OPENAI_SSE_DONE`] when
+//! streaming.
+//!
+//! ```
+//! use serde_json::json;
+//! use tencentcloud_hunyuan_sdk::compat::openai::from_openai_request;
+//!
+//! let converted = from_openai_request(json!({
+//!     "model": "hunyuan-lite",
+//!     "messages": [{"role": "user", "content": "Hello"}],
+//!     "max_tokens": 64
+//! }))
+//! .unwrap();
+//! assert_eq!(converted.value.messages[0].content, "Hello");
+//! assert_eq!(converted.warnings[0].field, "max_tokens");
+//! ```
+//!
+//! OpenAI fields without a Hunyuan counterpart are dropped and reported as
+//! [`Warning`]s rather than rejected, so the caller decides whether to fail.
+//!
+//! The reverse direction ([`to_openai_request`], [`from_openai_response`],
+//! [`from_openai_chunk`]) backs [`OpenAiCompatClient`], which talks to
+//! Hunyuan's OpenAI-compatible endpoint with the same models as `Client`.
+use crate::client::{SdkError, SdkResult};
+use crate::models::{
+    ChatChoice, ChatChoiceDelta, ChatChoiceMessage, ChatChunkChoice, ChatCompletionsChunk,
+    ChatCompletionsRequest, ChatCompletionsResponse, ChatCompletionsResponseInner, Message,
+    TencentCloudResponse, Tool, ToolCall, ToolCallFunction, ToolFunction, Usage,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+mod client;
+
+pub use client::{OpenAiCompatClient, OpenAiCompatClientBuilder};
+
+/// Terminator of an OpenAI event stream, sent after the last chunk.
+pub const OPENAI_SSE_DONE: &str = "data: [DONE]\n\n";
+
+/// `model` reported in converted responses; Hunyuan responses do not name
+/// the model. Overwrite it with the requested model if clients rely on it.
+pub const DEFAULT_MODEL: &str = "hunyuan";
+
+/// A field of the OpenAI request that could not be carried over as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// Path of the field, e.g. `max_tokens` or `messages[1].name`.
+    pub field: String,
+    /// What happened to the field.
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// A converted value together with the warnings raised while converting it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Converted<T> {
+    pub value: T,
+    pub warnings: Vec<Warning>,
+}
+
+/// Converts an OpenAI `POST /v1/chat/completions` body into a
+/// [`ChatCompletionsRequest`].
+///
+/// `model`, `messages`, `temperature`, `top_p`, `stream`, `n`, `seed` and
+/// `tools` are mapped, as are the `tool_calls` and `tool_call_id` of
+/// messages; message content given as an array of parts is joined from its
+/// text parts. The `developer` role becomes `system`. Every other field is
+/// dropped with a [`Warning`]. Fails with [`SdkError::InvalidRequest`] when
+/// the body is not an object, `messages` is missing, or a mapped field has
+/// the wrong type.
+pub fn from_openai_request(body: Value) -> SdkResult<Converted<ChatCompletionsRequest>> {
+    let Value::Object(fields) = body else {
+        return Err(invalid("request body must be a JSON object"));
+    };
+    let mut warnings = Vec::new();
+    let mut req = ChatCompletionsRequest {
+        model: None,
+        messages: Vec::new(),
+        temperature: None,
+        top_p: None,
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    };
+    let mut has_messages = false;
+    for (name, value) in fields {
+        match name.as_str() {
+            "model" => req.model = Some(string(&name, value)?),
+            "messages" => {
+                let Value::Array(items) = value else {
+                    return Err(invalid("`messages` must be an array"));
+                };
+                for (i, item) in items.into_iter().enumerate() {
+                    req.messages.push(message(i, item, &mut warnings)?);
+                }
+                has_messages = true;
+            }
+            "temperature" => req.temperature = number(&name, value)?,
+            "top_p" => req.top_p = number(&name, value)?,
+            "stream" => {
+                req.stream = match value {
+                    Value::Null => None,
+                    Value::Bool(b) => Some(b),
+                    _ => return Err(invalid("`stream` must be a boolean")),
+                }
+            }
+            "n" => req.n = unsigned(&name, value)?,
+            "seed" => req.seed = unsigned(&name, value)?,
+            "tools" => {
+                let tools: Option<Vec<OpenAiTool>> = serde_json::from_value(value)
+                    .map_err(|e| invalid(&format!("`tools` must be an array of tools: {}", e)))?;
+                req.tools = tools.map(|tools| tools.into_iter().map(Into::into).collect());
+            }
+            _ if value.is_null() => {}
+            _ => warnings.push(Warning {
+                field: name,
+                message: "not supported by Hunyuan; dropped".to_string(),
+            }),
+        }
+    }
+    if !has_messages {
+        return Err(invalid("`messages` is required"));
+    }
+    Ok(Converted {
+        value: req,
+        warnings,
+    })
+}
+
+/// Converts a [`ChatCompletionsResponse`] into an OpenAI `chat.completion`
+/// object. `Id` falls back to the `RequestId`, `created` is taken from a
+/// `Created` field when the service sent one, and `model` is
+/// [`DEFAULT_MODEL`].
+pub fn to_openai_response(resp: &ChatCompletionsResponse) -> Value {
+    let inner = &resp.response;
+    let choices: Vec<Value> = inner
+        .choices
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, choice)| {
+            let mut message = json!({
+                "role": role(choice.message.as_ref()).unwrap_or("assistant"),
+                "content": choice.message.as_ref().and_then(|m| m.content.as_deref()),
+            });
+            if let Some(calls) = choice.message.as_ref().and_then(|m| m.tool_calls.as_ref()) {
+                message["tool_calls"] = tool_calls_json(calls);
+            }
+            json!({
+                "index": choice.index.unwrap_or(i as u32),
+                "message": message,
+                "logprobs": null,
+                "finish_reason": choice.finish_reason.as_deref().map(finish_reason),
+            })
+        })
+        .collect();
+    let mut out = json!({
+        "id": inner.id.as_deref().or(inner.request_id.as_deref()).unwrap_or_default(),
+        "object": "chat.completion",
+        "created": created(&inner.extra),
+        "model": DEFAULT_MODEL,
+        "choices": choices,
+    });
+    if let Some(usage) = &inner.usage {
+        out["usage"] = usage_json(usage);
+    }
+    out
+}
+
+/// Converts a streamed [`ChatCompletionsChunk`] into an OpenAI
+/// `chat.completion.chunk` object. `delta` only carries the fields the chunk
+/// has, as OpenAI streams do.
+pub fn to_openai_chunk(chunk: &ChatCompletionsChunk) -> Value {
+    let choices: Vec<Value> = chunk
+        .choices
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, choice)| {
+            let mut delta = Map::new();
+            if let Some(role) = choice.delta.as_ref().and_then(|d| d.role.as_deref()) {
+                delta.insert("role".to_string(), role.into());
+            }
+            if let Some(content) = choice.delta.as_ref().and_then(|d| d.content.as_deref()) {
+                delta.insert("content".to_string(), content.into());
+            }
+            if let Some(calls) = choice.delta.as_ref().and_then(|d| d.tool_calls.as_ref()) {
+                delta.insert("tool_calls".to_string(), tool_calls_json(calls));
+            }
+            json!({
+                "index": choice.index.unwrap_or(i as u32),
+                "delta": delta,
+                "logprobs": null,
+                "finish_reason": choice.finish_reason.as_deref().map(finish_reason),
+            })
+        })
+        .collect();
+    let mut out = json!({
+        "id": chunk.id.as_deref().unwrap_or_default(),
+        "object": "chat.completion.chunk",
+        "created": chunk.created.unwrap_or_default(),
+        "model": DEFAULT_MODEL,
+        "choices": choices,
+    });
+    if let Some(usage) = &chunk.usage {
+        out["usage"] = usage_json(usage);
+    }
+    out
+}
+
+/// Formats a chunk as one OpenAI SSE event (`data: {...}\n\n`).
+pub fn to_openai_sse_event(chunk: &ChatCompletionsChunk) -> String {
+    format!("data: {}\n\n", to_openai_chunk(chunk))
+}
+
+/// Converts a [`ChatCompletionsRequest`] into an OpenAI request body.
+pub fn to_openai_request(req: &ChatCompletionsRequest) -> Value {
+    // Going through text keeps `0.7f32` as `0.7` rather than its `f64` widening.
+    let text = serde_json::to_string(&OpenAiRequest::from(req)).expect("request serializes");
+    serde_json::from_str(&text).expect("serialized request is valid JSON")
+}
+
+/// Converts an OpenAI `chat.completion` object into a
+/// [`ChatCompletionsResponse`]. `id` becomes both `Id` and `RequestId`, and
+/// `created` is kept as `Created`.
+pub fn from_openai_response(body: Value) -> SdkResult<ChatCompletionsResponse> {
+    let resp: OpenAiResponse = serde_json::from_value(body)?;
+    Ok(resp.into_response())
+}
+
+/// Converts an OpenAI `chat.completion.chunk` object into a
+/// [`ChatCompletionsChunk`].
+pub fn from_openai_chunk(body: Value) -> SdkResult<ChatCompletionsChunk> {
+    let chunk: OpenAiResponse = serde_json::from_value(body)?;
+    Ok(chunk.into_chunk())
+}
+
+/// Request body in OpenAI's shape. Serialized directly so that `f32`
+/// parameters keep their short form.
+#[derive(Serialize)]
+struct OpenAiRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<&'a str>,
+    messages: Vec<OpenAiMessage<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<OpenAiTool>>,
+}
+
+#[derive(Serialize)]
+struct OpenAiMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<OpenAiToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<&'a str>,
+}
+
+/// Tool in OpenAI's shape, where `parameters` is a JSON Schema object
+/// rather than the string Hunyuan takes.
+#[derive(Serialize, Deserialize)]
+struct OpenAiTool {
+    #[serde(rename = "type")]
+    tool_type: String,
+    function: OpenAiToolFunction,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OpenAiToolFunction {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    parameters: Value,
+}
+
+/// Tool call in OpenAI's shape. Streamed fragments leave out the fields
+/// already sent, so missing ones deserialize as empty and empty ones are
+/// not serialized.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct OpenAiToolCall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<u32>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    id: String,
+    #[serde(rename = "type", skip_serializing_if = "String::is_empty")]
+    call_type: String,
+    function: OpenAiToolCallFunction,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct OpenAiToolCallFunction {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    name: String,
+    arguments: String,
+}
+
+impl From<&Tool> for OpenAiTool {
+    fn from(tool: &Tool) -> Self {
+        let parameters = &tool.function.parameters;
+        Self {
+            tool_type: tool.tool_type.clone(),
+            function: OpenAiToolFunction {
+                name: tool.function.name.clone(),
+                description: tool.function.description.clone(),
+                // Sent as given if it is not JSON; the endpoint reports it.
+                parameters: serde_json::from_str(parameters)
+                    .unwrap_or_else(|_| Value::String(parameters.clone())),
+            },
+        }
+    }
+}
+
+impl From<OpenAiTool> for Tool {
+    fn from(tool: OpenAiTool) -> Self {
+        let parameters = match tool.function.parameters {
+            Value::Null => json!({"type": "object", "properties": {}}).to_string(),
+            Value::String(s) => s,
+            schema => schema.to_string(),
+        };
+        Tool {
+            tool_type: tool.tool_type,
+            function: ToolFunction {
+                name: tool.function.name,
+                description: tool.function.description,
+                parameters,
+            },
+        }
+    }
+}
+
+impl From<&ToolCall> for OpenAiToolCall {
+    fn from(call: &ToolCall) -> Self {
+        Self {
+            index: call.index,
+            id: call.id.clone(),
+            call_type: call.call_type.clone(),
+            function: OpenAiToolCallFunction {
+                name: call.function.name.clone(),
+                arguments: call.function.arguments.clone(),
+            },
+        }
+    }
+}
+
+impl From<OpenAiToolCall> for ToolCall {
+    fn from(call: OpenAiToolCall) -> Self {
+        ToolCall {
+            id: call.id,
+            call_type: call.call_type,
+            function: ToolCallFunction {
+                name: call.function.name,
+                arguments: call.function.arguments,
+            },
+            index: call.index,
+        }
+    }
+}
+
+impl<'a> From<&'a ChatCompletionsRequest> for OpenAiRequest<'a> {
+    fn from(req: &'a ChatCompletionsRequest) -> Self {
+        Self {
+            model: req.model.as_deref(),
+            messages: req
+                .messages
+                .iter()
+                .map(|m| OpenAiMessage {
+                    role: &m.role,
+                    content: &m.content,
+                    tool_calls: m
+                        .tool_calls
+                        .as_ref()
+                        .map(|calls| calls.iter().map(Into::into).collect()),
+                    tool_call_id: m.tool_call_id.as_deref(),
+                })
+                .collect(),
+            temperature: req.temperature,
+            top_p: req.top_p,
+            stream: req.stream,
+            n: req.n,
+            seed: req.seed,
+            tools: req
+                .tools
+                .as_ref()
+                .map(|tools| tools.iter().map(Into::into).collect()),
+        }
+    }
+}
+
+/// `chat.completion` and `chat.completion.chunk` objects; the former carries
+/// `message`, the latter `delta` in its choices.
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    id: Option<String>,
+    created: Option<i64>,
+    #[serde(default)]
+    choices: Vec<OpenAiChoice>,
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiChoice {
+    index: Option<u32>,
+    message: Option<OpenAiChoiceMessage>,
+    delta: Option<OpenAiChoiceMessage>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiChoiceMessage {
+    role: Option<String>,
+    content: Option<String>,
+    tool_calls: Option<Vec<OpenAiToolCall>>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiUsage {
+    prompt_tokens: Option<u32>,
+    completion_tokens: Option<u32>,
+    total_tokens: Option<u32>,
+}
+
+impl From<OpenAiChoiceMessage> for ChatChoiceMessage {
+    fn from(m: OpenAiChoiceMessage) -> Self {
+        ChatChoiceMessage {
+            role: m.role,
+            content: m.content,
+            tool_calls: tool_calls(m.tool_calls),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<OpenAiChoiceMessage> for ChatChoiceDelta {
+    fn from(m: OpenAiChoiceMessage) -> Self {
+        ChatChoiceDelta {
+            role: m.role,
+            content: m.content,
+            tool_calls: tool_calls(m.tool_calls),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<OpenAiUsage> for Usage {
+    fn from(u: OpenAiUsage) -> Self {
+        Usage {
+            prompt_tokens: u.prompt_tokens,
+            completion_tokens: u.completion_tokens,
+            total_tokens: u.total_tokens,
+            ..Default::default()
+        }
+    }
+}
+
+impl OpenAiResponse {
+    fn into_response(self) -> ChatCompletionsResponse {
+        let mut extra = Map::new();
+        if let Some(created) = self.created {
+            extra.insert("Created".to_string(), created.into());
+        }
+        let choices = self
+            .choices
+            .into_iter()
+            .map(|c| ChatChoice {
+                index: c.index,
+                message: c.message.map(Into::into),
+                finish_reason: c.finish_reason.map(from_openai_finish_reason),
+                ..Default::default()
+            })
+            .collect();
+        TencentCloudResponse {
+            response: ChatCompletionsResponseInner {
+                request_id: self.id.clone(),
+                id: self.id,
+                choices: Some(choices),
+                usage: self.usage.map(Into::into),
+                extra,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn into_chunk(self) -> ChatCompletionsChunk {
+        let choices = self
+            .choices
+            .into_iter()
+            .map(|c| ChatChunkChoice {
+                index: c.index,
+                delta: c.delta.map(Into::into),
+                finish_reason: c.finish_reason.map(from_openai_finish_reason),
+                ..Default::default()
+            })
+            .collect();
+        ChatCompletionsChunk {
+            id: self.id,
+            created: self.created,
+            choices: Some(choices),
+            usage: self.usage.map(Into::into),
+            ..Default::default()
+        }
+    }
+}
+
+/// Hunyuan spelling of an OpenAI finish reason.
+fn from_openai_finish_reason(reason: String) -> String {
+    match reason.as_str() {
+        "content_filter" => "sensitive".to_string(),
+        _ => reason,
+    }
+}
+
+fn invalid(message: &str) -> SdkError {
+    SdkError::InvalidRequest(format!("invalid OpenAI request: {}", message))
+}
+
+fn string(field: &str, value: Value) -> SdkResult<String> {
+    match value {
+        Value::String(s) => Ok(s),
+        _ => Err(invalid(&format!("`{}` must be a string", field))),
+    }
+}
+
+fn unsigned(field: &str, value: Value) -> SdkResult<Option<u32>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Number(n) => match n.as_u64().map(u32::try_from) {
+            Some(Ok(n)) => Ok(Some(n)),
+            _ => Err(invalid(&format!("`{}` must fit in a u32", field))),
+        },
+        _ => Err(invalid(&format!("`{}` must be a number", field))),
+    }
+}
+
+fn number(field: &str, value: Value) -> SdkResult<Option<f32>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Number(n) => Ok(n.as_f64().map(|n| n as f32)),
+        _ => Err(invalid(&format!("`{}` must be a number", field))),
+    }
+}
+
+fn message(index: usize, value: Value, warnings: &mut Vec<Warning>) -> SdkResult<Message> {
+    let path = format!("messages[{}]", index);
+    let Value::Object(fields) = value else {
+        return Err(invalid(&format!("`{}` must be an object", path)));
+    };
+    let mut role = None;
+    let mut content = String::new();
+    let mut tool_calls = None;
+    let mut tool_call_id = None;
+    for (name, value) in fields {
+        match name.as_str() {
+            "role" => {
+                let r = string(&format!("{}.role", path), value)?;
+                role = Some(if r == "developer" {
+                    warnings.push(Warning {
+                        field: format!("{}.role", path),
+                        message: "`developer` sent as `system`".to_string(),
+                    });
+                    "system".to_string()
+                } else {
+                    r
+                });
+            }
+            "content" => content = message_content(&path, value, warnings)?,
+            "tool_calls" => {
+                let calls: Option<Vec<OpenAiToolCall>> =
+                    serde_json::from_value(value).map_err(|e| {
+                        invalid(&format!(
+                            "`{}.tool_calls` must be an array of tool calls: {}",
+                            path, e
+                        ))
+                    })?;
+                tool_calls = self::tool_calls(calls);
+            }
+            "tool_call_id" if !value.is_null() => {
+                tool_call_id = Some(string(&format!("{}.tool_call_id", path), value)?);
+            }
+            _ if value.is_null() => {}
+            _ => warnings.push(Warning {
+                field: format!("{}.{}", path, name),
+                message: "not supported by Hunyuan; dropped".to_string(),
+            }),
+        }
+    }
+    let role = role.ok_or_else(|| invalid(&format!("`{}.role` is required", path)))?;
+    Ok(Message {
+        role,
+        content,
+        tool_call_id,
+        tool_calls,
+    })
+}
+
+/// Content as a string, or the text parts of a content array joined together.
+fn message_content(path: &str, value: Value, warnings: &mut Vec<Warning>) -> SdkResult<String> {
+    match value {
+        Value::Null => Ok(String::new()),
+        Value::String(s) => Ok(s),
+        Value::Array(parts) => {
+            let mut text = String::new();
+            for (i, part) in parts.iter().enumerate() {
+                match (part["type"].as_str(), part["text"].as_str()) {
+                    (Some("text"), Some(t)) => text.push_str(t),
+                    (kind, _) => warnings.push(Warning {
+                        field: format!("{}.content[{}]", path, i),
+                        message: format!(
+                            "content part of type `{}` not supported; dropped",
+                            kind.unwrap_or("unknown")
+                        ),
+                    }),
+                }
+            }
+            Ok(text)
+        }
+        _ => Err(invalid(&format!(
+            "`{}.content` must be a string or an array of parts",
+            path
+        ))),
+    }
+}
+
+fn tool_calls(calls: Option<Vec<OpenAiToolCall>>) -> Option<Vec<ToolCall>> {
+    calls.map(|calls| calls.into_iter().map(Into::into).collect())
+}
+
+fn tool_calls_json(calls: &[ToolCall]) -> Value {
+    let calls: Vec<OpenAiToolCall> = calls.iter().map(Into::into).collect();
+    serde_json::to_value(calls).expect("tool calls serialize")
+}
+
+fn role(message: Option<&ChatChoiceMessage>) -> Option<&str> {
+    message.and_then(|m| m.role.as_deref())
+}
+
+/// OpenAI spelling of a Hunyuan finish reason.
+fn finish_reason(reason: &str) -> &str {
+    match reason {
+        "sensitive" => "content_filter",
+        other => other,
+    }
+}
+
+fn created(extra: &Map<String, Value>) -> i64 {
+    extra
+        .get("Created")
+        .and_then(Value::as_i64)
+        .unwrap_or_default()
+}
+
+fn usage_json(usage: &Usage) -> Value {
+    json!({
+        "prompt_tokens": usage.prompt_tokens.unwrap_or_default(),
+        "completion_tokens": usage.completion_tokens.unwrap_or_default(),
+        "total_tokens": usage.total_tokens.unwrap_or_default(),
+    })
+}
@@ -0,0 +1,351 @@
+//! Client for Hunyuan's OpenAI-compatible endpoint.
+use super::{from_openai_chunk, OpenAiRequest, OpenAiResponse};
+use crate::api::HunyuanApi;
+use crate::client::{unexpected_response, SdkError, SdkResult};
+use crate::models::{
+    ChatCompletionsChunk, ChatCompletionsRequest, ChatCompletionsResponse, EmbeddingData,
+    EmbeddingUsage, GetEmbeddingRequest, GetEmbeddingResponse, GetEmbeddingResponseInner,
+    TencentCloudResponse,
+};
+use crate::stream::ChatCompletionsStream;
+use crate::transport::{BoxFuture, PreparedRequest, RawResponse, ReqwestTransport, Transport};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Base URL of Hunyuan's OpenAI-compatible API.
+const DEFAULT_BASE_URL: &str = "https://api.hunyuan.cloud.tencent.com/v1";
+/// Model used for `get_embedding`, which has no model field of its own.
+const DEFAULT_EMBEDDING_MODEL: &str = "hunyuan-embedding";
+
+/// Client for Hunyuan's OpenAI-compatible endpoint.
+///
+/// Requests are authenticated with `Authorization: Bearer <api_key>` instead
+/// of TC3 signing and sent as OpenAI JSON to `/chat/completions` and
+/// `/embeddings` under the base URL. Requests and responses use the same
+/// models as [`Client`](crate::Client), and it implements [`HunyuanApi`], so
+/// code written against that trait works with either.
+///
+/// ```no_run
+/// use tencentcloud_hunyuan_sdk::compat::openai::OpenAiCompatClient;
+/// use tencentcloud_hunyuan_sdk::models::{ChatCompletionsRequest, Message};
+///
+/// # async fn run() -> Result<(), tencentcloud_hunyuan_sdk::SdkError> {
+/// let client = OpenAiCompatClient::builder("sk-...").build();
+/// let req = ChatCompletionsRequest {
+///     model: Some("hunyuan-turbo".into()),
+///     messages: vec![Message { role: "user".into(), content: "Hello".into(), ..Default::default() }],
+///     temperature: None,
+///     top_p: None,
+///     stream: None,
+///     tools: None,
+///     n: None,
+///     seed: None,
+/// };
+/// let resp = client.chat_completions(&req).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct OpenAiCompatClient {
+    transport: Arc<dyn Transport>,
+    authorization: HeaderValue,
+    base_url: String,
+    default_model: Option<String>,
+    embedding_model: String,
+}
+
+/// Builder for [`OpenAiCompatClient`].
+pub struct OpenAiCompatClientBuilder {
+    api_key: String,
+    transport: Option<Arc<dyn Transport>>,
+    base_url: Option<String>,
+    default_model: Option<String>,
+    embedding_model: Option<String>,
+}
+
+impl OpenAiCompatClientBuilder {
+    /// Override the base URL (defaults to
+    /// `https://api.hunyuan.cloud.tencent.com/v1`), e.g. to target a mock
+    /// server.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Send requests through a custom [`Transport`] instead of `reqwest`.
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Model for chat requests that leave `model` unset. The endpoint
+    /// requires a model, so such requests fail without one.
+    pub fn default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = Some(model.into());
+        self
+    }
+
+    /// Model used by `get_embedding` (defaults to `hunyuan-embedding`).
+    pub fn embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = Some(model.into());
+        self
+    }
+
+    /// Build the [`OpenAiCompatClient`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the API key is not a valid header value.
+    pub fn build(self) -> OpenAiCompatClient {
+        let mut authorization = HeaderValue::from_str(&format!("Bearer {}", self.api_key))
+            .expect("API key is a valid header value");
+        authorization.set_sensitive(true);
+        OpenAiCompatClient {
+            transport: self
+                .transport
+                .unwrap_or_else(|| Arc::new(ReqwestTransport::new(reqwest::Client::new()))),
+            authorization,
+            base_url: self
+                .base_url
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            default_model: self.default_model,
+            embedding_model: self
+                .embedding_model
+                .unwrap_or_else(|| DEFAULT_EMBEDDING_MODEL.to_string()),
+        }
+    }
+}
+
+impl OpenAiCompatClient {
+    /// Returns a builder for a client authenticating with `api_key`.
+    pub fn builder(api_key: impl Into<String>) -> OpenAiCompatClientBuilder {
+        OpenAiCompatClientBuilder {
+            api_key: api_key.into(),
+            transport: None,
+            base_url: None,
+            default_model: None,
+            embedding_model: None,
+        }
+    }
+
+    /// Returns the base URL requests are sent to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Calls `POST /chat/completions`.
+    pub async fn chat_completions(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<ChatCompletionsResponse> {
+        let body = self.chat_body(req, None)?;
+        let text = self
+            .read(self.send("chat/completions", body).await?)
+            .await?;
+        let resp: OpenAiResponse = serde_json::from_str(&text)?;
+        Ok(resp.into_response())
+    }
+
+    /// Calls `POST /chat/completions` with `stream` enabled. Chunks are
+    /// converted to [`ChatCompletionsChunk`]s as they arrive.
+    pub async fn chat_completions_stream(
+        &self,
+        req: &ChatCompletionsRequest,
+    ) -> SdkResult<ChatCompletionsStream> {
+        let body = self.chat_body(req, Some(true))?;
+        let resp = self.send("chat/completions", body).await?;
+        let is_event_stream = resp
+            .content_type()
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+        if !(200..300).contains(&resp.status) || !is_event_stream {
+            // Errors arrive as a regular JSON body instead of SSE.
+            let status = resp.status;
+            let content_type = resp.content_type().map(str::to_string);
+            let text = self.read(resp).await?;
+            return Err(SdkError::UnexpectedResponse {
+                status,
+                content_type,
+                snippet: text.chars().take(200).collect(),
+            });
+        }
+        Ok(ChatCompletionsStream::with_parser(
+            resp.body,
+            Box::new(|| {}),
+            parse_chunk,
+        ))
+    }
+
+    /// Calls `POST /embeddings` with the configured embedding model.
+    pub async fn get_embedding(
+        &self,
+        req: &GetEmbeddingRequest,
+    ) -> SdkResult<GetEmbeddingResponse> {
+        let input = match (&req.input, &req.input_list) {
+            (_, Some(list)) => serde_json::json!(list),
+            (Some(input), None) => serde_json::json!(input),
+            (None, None) => serde_json::json!([]),
+        };
+        let body = serde_json::to_vec(&EmbeddingRequest {
+            model: &self.embedding_model,
+            input,
+        })?;
+        let text = self.read(self.send("embeddings", body).await?).await?;
+        let resp: EmbeddingResponse = serde_json::from_str(&text)?;
+        Ok(TencentCloudResponse {
+            response: GetEmbeddingResponseInner {
+                request_id: resp.id,
+                data: Some(
+                    resp.data
+                        .into_iter()
+                        .map(|d| EmbeddingData {
+                            embedding: Some(d.embedding),
+                            index: d.index,
+                            object: d.object,
+                            ..Default::default()
+                        })
+                        .collect(),
+                ),
+                usage: resp.usage.map(|u| EmbeddingUsage {
+                    prompt_tokens: u.prompt_tokens,
+                    total_tokens: u.total_tokens,
+                }),
+                ..Default::default()
+            },
+        })
+    }
+
+    fn chat_body(&self, req: &ChatCompletionsRequest, stream: Option<bool>) -> SdkResult<Vec<u8>> {
+        let mut body = OpenAiRequest::from(req);
+        if body.model.is_none() {
+            body.model = self.default_model.as_deref();
+        }
+        if body.model.is_none() {
+            return Err(SdkError::InvalidRequest(
+                "model is required by the OpenAI-compatible endpoint; set it on the request or with default_model"
+                    .to_string(),
+            ));
+        }
+        if stream.is_some() {
+            body.stream = stream;
+        }
+        Ok(serde_json::to_vec(&body)?)
+    }
+
+    async fn send(&self, path: &str, body: Vec<u8>) -> SdkResult<RawResponse> {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, self.authorization.clone());
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        self.transport
+            .execute(PreparedRequest {
+                method: "POST".to_string(),
+                url: format!("{}/{}", self.base_url, path),
+                headers,
+                body: body.into(),
+            })
+            .await
+    }
+
+    /// Reads a unary response, mapping OpenAI error bodies to
+    /// [`SdkError::Service`].
+    async fn read(&self, resp: RawResponse) -> SdkResult<String> {
+        let status = resp.status;
+        let content_type = resp.content_type().map(str::to_string);
+        let text = String::from_utf8_lossy(&resp.bytes().await?).into_owned();
+        if let Some(err) = unexpected_response(status, content_type.as_deref(), text.as_bytes()) {
+            return Err(err);
+        }
+        if let Ok(ErrorEnvelope { error }) = serde_json::from_str(&text) {
+            return Err(SdkError::Service {
+                code: error
+                    .code
+                    .map(|c| match c {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    })
+                    .or(error.kind)
+                    .unwrap_or_else(|| format!("HTTP_{}", status)),
+                message: error.message.unwrap_or_default(),
+                request_id: None,
+            });
+        }
+        if !(200..300).contains(&status) {
+            return Err(SdkError::Service {
+                code: format!("HTTP_{}", status),
+                message: text,
+                request_id: None,
+            });
+        }
+        Ok(text)
+    }
+}
+
+fn parse_chunk(data: &str) -> SdkResult<ChatCompletionsChunk> {
+    from_openai_chunk(serde_json::from_str(data)?)
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    id: Option<String>,
+    #[serde(default)]
+    data: Vec<EmbeddingItem>,
+    usage: Option<EmbeddingUsageJson>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingItem {
+    embedding: Vec<f64>,
+    index: Option<u32>,
+    object: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingUsageJson {
+    prompt_tokens: Option<u32>,
+    total_tokens: Option<u32>,
+}
+
+/// `{"error": {...}}` body of a failed OpenAI-style call.
+#[derive(Deserialize)]
+struct ErrorEnvelope {
+    error: ErrorBody,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    code: Option<serde_json::Value>,
+}
+
+impl HunyuanApi for OpenAiCompatClient {
+    fn chat_completions<'a>(
+        &'a self,
+        req: &'a ChatCompletionsRequest,
+    ) -> BoxFuture<'a, SdkResult<ChatCompletionsResponse>> {
+        Box::pin(OpenAiCompatClient::chat_completions(self, req))
+    }
+
+    fn chat_completions_stream<'a>(
+        &'a self,
+        req: &'a ChatCompletionsRequest,
+    ) -> BoxFuture<'a, SdkResult<ChatCompletionsStream>> {
+        Box::pin(OpenAiCompatClient::chat_completions_stream(self, req))
+    }
+
+    fn get_embedding<'a>(
+        &'a self,
+        req: &'a GetEmbeddingRequest,
+    ) -> BoxFuture<'a, SdkResult<GetEmbeddingResponse>> {
+        Box::pin(OpenAiCompatClient::get_embedding(self, req))
+    }
+}
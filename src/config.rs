@@ -0,0 +1,295 @@
+//! Client settings loaded from configuration files.
+//!
+//! [`ClientConfig`] gathers the builder settings applications usually keep in
+//! YAML or TOML files and works with any serde format. Turn it into a builder
+//! with [`ClientBuilder::from_config`]:
+//!
+//! ```
+//! use tencentcloud_hunyuan_sdk::config::ClientConfig;
+//! use tencentcloud_hunyuan_sdk::{ClientBuilder, Region};
+//!
+//! let config: ClientConfig = serde_json::from_str(
+//!     r#"{
+//!         "region": "ap-beijing",
+//!         "timeout": "30s",
+//!         "retry": { "max_retries": 3 },
+//!         "credential": { "secret_id": "id", "secret_key": "key" }
+//!     }"#,
+//! )?;
+//! let client = ClientBuilder::from_config(config).build();
+//! assert_eq!(client.region(), &Region::ApBeijing);
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+//!
+//! Durations are human-readable strings such as `"500ms"` or `"30s"`. The
+//! credential is read when present but only written back when
+//! [`ClientConfig::serialize_credential`] is set, so dumping a config does
+//! not leak secrets by accident.
+use crate::client::{
+    ClientBuilder, Credential, DeserializationMode, Dynamic, HttpVersionOrder, Region,
+};
+use crate::retry::RetryPolicy;
+use serde::{Deserialize, Serialize, Serializer};
+use std::time::Duration;
+
+/// Serializable [`ClientBuilder`] settings. Unset fields keep the builder's
+/// defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ClientConfig {
+    /// Target region; see [`Region`] for accepted spellings.
+    pub region: Option<Region>,
+    /// API endpoint, e.g. `hunyuan.tencentcloudapi.com`.
+    pub endpoint: Option<String>,
+    /// Full URL including any path; see [`ClientBuilder::base_url`].
+    pub base_url: Option<String>,
+    /// Signed TC3 canonical URI; see [`ClientBuilder::canonical_uri`].
+    pub canonical_uri: Option<String>,
+    /// `Host` to sign and send; see [`ClientBuilder::host_override`].
+    pub host_override: Option<String>,
+    /// `http1_only`, `http2_then_http1` or `http2_only`; see
+    /// [`ClientBuilder::http_version_order`].
+    pub http_version_order: Option<HttpVersionOrder>,
+    /// Per-attempt timeout; see [`ClientBuilder::timeout`].
+    #[serde(with = "humantime_serde")]
+    pub timeout: Option<Duration>,
+    /// Retry policy; missing fields take the [`RetryPolicy`] defaults.
+    pub retry: Option<RetryPolicy>,
+    /// Debug logging; falls back to `TENCENTCLOUD_SDK_DEBUG` when unset.
+    pub debug: Option<bool>,
+    /// Model for `ChatCompletions` requests that leave `model` unset.
+    pub default_model: Option<String>,
+    /// Models to fall back to; see [`ClientBuilder::model_fallback_chain`].
+    pub model_fallback_chain: Vec<String>,
+    /// Threshold for slow-request warnings.
+    #[serde(with = "humantime_serde")]
+    pub slow_request_threshold: Option<Duration>,
+    /// Threshold for clock skew warnings; see
+    /// [`ClientBuilder::clock_skew_warning`].
+    #[serde(with = "humantime_serde")]
+    pub clock_skew_warning: Option<Duration>,
+    /// `X-TC-RequestClient` value.
+    pub request_client: Option<String>,
+    /// Warm up the connection when the client is built; see
+    /// [`ClientBuilder::warm_up`].
+    pub warm_up: Option<bool>,
+    /// Limit for an unfinished streamed event; see
+    /// [`ClientBuilder::max_sse_buffer_bytes`].
+    pub max_sse_buffer_bytes: Option<usize>,
+    /// Limit for a response body; see [`ClientBuilder::max_response_bytes`].
+    pub max_response_bytes: Option<usize>,
+    /// Smallest request body to gzip; see [`ClientBuilder::gzip_requests`].
+    #[cfg(feature = "gzip")]
+    pub gzip_requests: Option<usize>,
+    /// `lenient` or `strict`; see [`ClientBuilder::deserialization_mode`].
+    pub deserialization_mode: Option<DeserializationMode>,
+    /// Header carrying per-call trace ids; see
+    /// [`ClientBuilder::trace_id_header`].
+    pub trace_id_header: Option<String>,
+    /// Share concurrent identical embedding calls; see
+    /// [`ClientBuilder::coalesce_embeddings`].
+    pub coalesce_embeddings: Option<bool>,
+    /// Capacity of the embedding cache, `0` for none; see
+    /// [`ClientBuilder::embedding_cache`].
+    pub embedding_cache: Option<usize>,
+    /// Limit of concurrent calls; see
+    /// [`ClientBuilder::max_concurrent_requests`].
+    pub max_concurrent_requests: Option<usize>,
+    /// Credential to sign requests with. Never serialized unless
+    /// `serialize_credential` is set.
+    pub credential: Option<Credential>,
+    /// Include `credential` when serializing. Off by default and never
+    /// serialized itself.
+    #[serde(skip)]
+    pub serialize_credential: bool,
+}
+
+impl Serialize for ClientConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct CredentialRepr<'a> {
+            secret_id: &'a str,
+            secret_key: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            token: Option<&'a str>,
+        }
+
+        #[derive(Serialize)]
+        struct Repr<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            region: Option<&'a Region>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            endpoint: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            base_url: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            canonical_uri: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            host_override: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            http_version_order: Option<HttpVersionOrder>,
+            #[serde(with = "humantime_serde", skip_serializing_if = "Option::is_none")]
+            timeout: Option<Duration>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            retry: Option<&'a RetryPolicy>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            debug: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            default_model: Option<&'a str>,
+            #[serde(skip_serializing_if = "<[String]>::is_empty")]
+            model_fallback_chain: &'a [String],
+            #[serde(with = "humantime_serde", skip_serializing_if = "Option::is_none")]
+            slow_request_threshold: Option<Duration>,
+            #[serde(with = "humantime_serde", skip_serializing_if = "Option::is_none")]
+            clock_skew_warning: Option<Duration>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            request_client: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            warm_up: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            max_sse_buffer_bytes: Option<usize>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            max_response_bytes: Option<usize>,
+            #[cfg(feature = "gzip")]
+            #[serde(skip_serializing_if = "Option::is_none")]
+            gzip_requests: Option<usize>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            deserialization_mode: Option<DeserializationMode>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            trace_id_header: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            coalesce_embeddings: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            embedding_cache: Option<usize>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            max_concurrent_requests: Option<usize>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            credential: Option<CredentialRepr<'a>>,
+        }
+
+        Repr {
+            region: self.region.as_ref(),
+            endpoint: self.endpoint.as_deref(),
+            base_url: self.base_url.as_deref(),
+            canonical_uri: self.canonical_uri.as_deref(),
+            host_override: self.host_override.as_deref(),
+            http_version_order: self.http_version_order,
+            timeout: self.timeout,
+            retry: self.retry.as_ref(),
+            debug: self.debug,
+            default_model: self.default_model.as_deref(),
+            model_fallback_chain: &self.model_fallback_chain,
+            slow_request_threshold: self.slow_request_threshold,
+            clock_skew_warning: self.clock_skew_warning,
+            request_client: self.request_client.as_deref(),
+            warm_up: self.warm_up,
+            max_sse_buffer_bytes: self.max_sse_buffer_bytes,
+            max_response_bytes: self.max_response_bytes,
+            #[cfg(feature = "gzip")]
+            gzip_requests: self.gzip_requests,
+            deserialization_mode: self.deserialization_mode,
+            trace_id_header: self.trace_id_header.as_deref(),
+            coalesce_embeddings: self.coalesce_embeddings,
+            embedding_cache: self.embedding_cache,
+            max_concurrent_requests: self.max_concurrent_requests,
+            credential: self
+                .credential
+                .as_ref()
+                .filter(|_| self.serialize_credential)
+                .map(|c| CredentialRepr {
+                    secret_id: &c.secret_id,
+                    secret_key: &c.secret_key,
+                    token: c.token.as_deref(),
+                }),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl ClientBuilder<Dynamic> {
+    /// Creates a builder with the settings of `config` applied. Settings not
+    /// covered by the config, such as sinks or a custom transport, can be
+    /// added afterwards.
+    ///
+    /// An invalid `request_client` is reported by
+    /// [`try_build`](ClientBuilder::try_build) as
+    /// [`SdkError::InvalidConfig`](crate::SdkError::InvalidConfig).
+    ///
+    /// # Panics
+    ///
+    /// Panics where [`ClientBuilder::base_url`] does for `base_url`.
+    pub fn from_config(config: ClientConfig) -> Self {
+        let mut builder = ClientBuilder::new().model_fallback_chain(config.model_fallback_chain);
+        if let Some(credential) = config.credential {
+            builder = builder.credential(credential);
+        }
+        if let Some(region) = config.region {
+            builder = builder.region(region);
+        }
+        if let Some(endpoint) = config.endpoint {
+            builder = builder.endpoint(endpoint);
+        }
+        if let Some(url) = config.base_url {
+            builder = builder.base_url(url);
+        }
+        if let Some(uri) = config.canonical_uri {
+            builder = builder.canonical_uri(uri);
+        }
+        if let Some(host) = config.host_override {
+            builder = builder.host_override(host);
+        }
+        if let Some(order) = config.http_version_order {
+            builder = builder.http_version_order(order);
+        }
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(retry) = config.retry {
+            builder = builder.retry_policy(retry);
+        }
+        if let Some(debug) = config.debug {
+            builder = builder.debug(debug);
+        }
+        if let Some(model) = config.default_model {
+            builder = builder.default_model(model);
+        }
+        if let Some(threshold) = config.slow_request_threshold {
+            builder = builder.slow_request_threshold(threshold);
+        }
+        if let Some(threshold) = config.clock_skew_warning {
+            builder = builder.clock_skew_warning(threshold);
+        }
+        if let Some(client) = config.request_client {
+            builder = builder.request_client(client);
+        }
+        if let Some(warm_up) = config.warm_up {
+            builder = builder.warm_up(warm_up);
+        }
+        if let Some(bytes) = config.max_sse_buffer_bytes {
+            builder = builder.max_sse_buffer_bytes(bytes);
+        }
+        if let Some(bytes) = config.max_response_bytes {
+            builder = builder.max_response_bytes(bytes);
+        }
+        #[cfg(feature = "gzip")]
+        if let Some(min_bytes) = config.gzip_requests {
+            builder = builder.gzip_requests(min_bytes);
+        }
+        if let Some(mode) = config.deserialization_mode {
+            builder = builder.deserialization_mode(mode);
+        }
+        if let Some(name) = config.trace_id_header {
+            builder = builder.trace_id_header(name);
+        }
+        if let Some(coalesce) = config.coalesce_embeddings {
+            builder = builder.coalesce_embeddings(coalesce);
+        }
+        if let Some(capacity) = config.embedding_cache {
+            builder = builder.embedding_cache(capacity);
+        }
+        if let Some(limit) = config.max_concurrent_requests {
+            builder = builder.max_concurrent_requests(limit);
+        }
+        builder
+    }
+}
@@ -0,0 +1,113 @@
+//! Persisting chat history.
+//!
+//! A [`Conversation`] is an ordered list of [`Message`]s with a stable JSON
+//! format for storage:
+//!
+//! ```json
+//! {"SchemaVersion":1,"Messages":[{"Role":"user","Content":"Hi"}]}
+//! ```
+//!
+//! Messages use the same field names as the API. The format is versioned:
+//! [`Conversation::from_json`] reads every version up to
+//! [`Conversation::SCHEMA_VERSION`], and fields added to [`Message`] later are
+//! optional, so conversations stored by older releases keep loading.
+use crate::client::{SdkError, SdkResult};
+use crate::models::{ChatCompletionsRequest, Message};
+use serde::{Deserialize, Serialize};
+
+/// An ordered chat history.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Conversation {
+    messages: Vec<Message>,
+}
+
+impl Conversation {
+    /// Version written by [`Conversation::to_json`].
+    pub const SCHEMA_VERSION: u32 = 1;
+
+    /// Creates an empty conversation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a conversation from existing messages.
+    pub fn from_messages(messages: Vec<Message>) -> Self {
+        Self { messages }
+    }
+
+    /// Appends a message.
+    pub fn push(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+
+    /// Returns the messages in order.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Returns the messages, consuming the conversation.
+    pub fn into_messages(self) -> Vec<Message> {
+        self.messages
+    }
+
+    /// Returns a `ChatCompletions` request continuing the conversation.
+    pub fn to_request(&self, model: impl Into<String>) -> ChatCompletionsRequest {
+        ChatCompletionsRequest {
+            model: Some(model.into()),
+            messages: self.messages.clone(),
+            temperature: None,
+            top_p: None,
+            stream: None,
+            tools: None,
+            n: None,
+            seed: None,
+        }
+    }
+
+    /// Serializes the conversation in the versioned storage format.
+    pub fn to_json(&self) -> SdkResult<String> {
+        Ok(serde_json::to_string(&Stored {
+            schema_version: Self::SCHEMA_VERSION,
+            messages: &self.messages,
+        })?)
+    }
+
+    /// Reads a conversation written by [`Conversation::to_json`] of this or
+    /// an earlier release. Fails with [`SdkError::Decode`] for a schema
+    /// version newer than [`Conversation::SCHEMA_VERSION`].
+    pub fn from_json(json: &str) -> SdkResult<Self> {
+        let loaded: Loaded = serde_json::from_str(json)?;
+        if loaded.schema_version == 0 || loaded.schema_version > Self::SCHEMA_VERSION {
+            return Err(SdkError::Decode(format!(
+                "unsupported conversation schema version {} (supported: 1 to {})",
+                loaded.schema_version,
+                Self::SCHEMA_VERSION
+            )));
+        }
+        Ok(Self {
+            messages: loaded.messages,
+        })
+    }
+}
+
+impl From<Vec<Message>> for Conversation {
+    fn from(messages: Vec<Message>) -> Self {
+        Self::from_messages(messages)
+    }
+}
+
+#[derive(Serialize)]
+struct Stored<'a> {
+    #[serde(rename = "SchemaVersion")]
+    schema_version: u32,
+    #[serde(rename = "Messages")]
+    messages: &'a [Message],
+}
+
+#[derive(Deserialize)]
+struct Loaded {
+    #[serde(rename = "SchemaVersion")]
+    schema_version: u32,
+    #[serde(rename = "Messages")]
+    messages: Vec<Message>,
+}
@@ -0,0 +1,89 @@
+//! Sources of request credentials.
+//!
+//! A client signs with the [`Credential`] passed to `ClientBuilder::credential`
+//! unless a [`CredentialProvider`] is set with
+//! `ClientBuilder::credential_provider`. The provider is asked for a
+//! credential before every request, so temporary credentials can be rotated
+//! without rebuilding the client. `X-TC-Token` is sent only when the
+//! credential returned for that request carries a token.
+//!
+//! With the `secrecy` feature, `SecretCredential` keeps the secret key and
+//! token in `secrecy::SecretString`s until a request is signed.
+use crate::client::Credential;
+#[cfg(feature = "secrecy")]
+use secrecy::{ExposeSecret, SecretString};
+
+/// Source of the credential used to sign each request.
+pub trait CredentialProvider: Send + Sync {
+    /// Credential for the next request.
+    ///
+    /// Called once per request attempt, so implementations that refresh from
+    /// a remote source should cache, and keep returning the last good
+    /// credential when a refresh fails.
+    fn credential(&self) -> Credential;
+}
+
+/// A fixed credential.
+impl CredentialProvider for Credential {
+    fn credential(&self) -> Credential {
+        self.clone()
+    }
+}
+
+/// Credential holding the secret key and token as [`SecretString`]s.
+///
+/// Enabled with the `secrecy` feature. The secrets are redacted in `Debug`
+/// output, the type can be deserialized but not serialized, and the secrets
+/// are only exposed to sign a request. Use it as a provider:
+///
+/// ```
+/// use std::sync::Arc;
+/// use tencentcloud_hunyuan_sdk::credential::SecretCredential;
+/// use tencentcloud_hunyuan_sdk::Client;
+///
+/// let credential = SecretCredential::new("AKID...", "secret-key");
+/// assert!(!format!("{:?}", credential).contains("secret-key"));
+/// let client = Client::builder()
+///     .credential_provider(Arc::new(credential))
+///     .build();
+/// ```
+#[cfg(feature = "secrecy")]
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct SecretCredential {
+    pub secret_id: String,
+    pub secret_key: SecretString,
+    #[serde(default)]
+    pub token: Option<SecretString>,
+}
+
+#[cfg(feature = "secrecy")]
+impl SecretCredential {
+    /// Creates a credential without a session token.
+    pub fn new(secret_id: impl Into<String>, secret_key: impl Into<String>) -> Self {
+        Self {
+            secret_id: secret_id.into(),
+            secret_key: SecretString::from(secret_key.into()),
+            token: None,
+        }
+    }
+
+    /// Adds a session token, sent as `X-TC-Token`.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(SecretString::from(token.into()));
+        self
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl CredentialProvider for SecretCredential {
+    fn credential(&self) -> Credential {
+        Credential {
+            secret_id: self.secret_id.clone(),
+            secret_key: self.secret_key.expose_secret().to_string(),
+            token: self
+                .token
+                .as_ref()
+                .map(|token| token.expose_secret().to_string()),
+        }
+    }
+}
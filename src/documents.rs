@@ -0,0 +1,205 @@
+//! Documents as context for a chat, e.g. for document QA.
+//!
+//! [`documents_message`] combines documents into one message. Each document
+//! is enclosed between a begin and an end line naming it:
+//!
+//! ```text
+//! ===== BEGIN DOCUMENT 1: notes.txt =====
+//! ...
+//! ===== END DOCUMENT 1 =====
+//! ```
+//!
+//! The `=====` fence is lengthened until no document contains it, so a
+//! document quoting these lines cannot end its section early.
+//!
+//! ```no_run
+//! use tencentcloud_hunyuan_sdk::documents::{documents_message, read_documents, DocumentOptions};
+//!
+//! let documents = read_documents(["notes.txt", "faq.md"])?;
+//! let message = documents_message(
+//!     &documents,
+//!     &DocumentOptions {
+//!         preamble: Some("Answer using only these documents.".into()),
+//!         max_tokens: Some(8_000),
+//!         ..Default::default()
+//!     },
+//! );
+//! # Ok::<(), std::io::Error>(())
+//! ```
+use crate::models::{estimate_tokens, Message, Role};
+use std::io;
+use std::path::Path;
+
+/// A named text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    /// Shown in the document's delimiter lines, e.g. a file name.
+    pub name: String,
+    pub content: String,
+}
+
+impl Document {
+    /// Creates a document.
+    pub fn new(name: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+
+    /// Reads a UTF-8 text file, named after its file name. A byte order mark
+    /// is dropped and `\r\n` line endings become `\n`. Fails with
+    /// [`io::ErrorKind::InvalidData`] if the file is not valid UTF-8.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+        Ok(Self::new(name, text.replace("\r\n", "\n")))
+    }
+}
+
+/// Reads each file with [`Document::read`], in order.
+pub fn read_documents<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+) -> io::Result<Vec<Document>> {
+    paths.into_iter().map(Document::read).collect()
+}
+
+/// How [`documents_message`] builds its message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentOptions {
+    /// Role of the message, e.g. [`Role::System`] for background context.
+    pub role: Role,
+    /// Text before the documents, e.g. the question or instructions.
+    pub preamble: Option<String>,
+    /// Token budget of the whole message, as counted by [`estimate_tokens`].
+    /// Documents are kept in order; the first one that does not fit is cut
+    /// at a word boundary and marked `[truncated]`, and the remaining ones
+    /// are left out with a note saying how many.
+    pub max_tokens: Option<usize>,
+}
+
+impl Default for DocumentOptions {
+    /// A user message without preamble or budget.
+    fn default() -> Self {
+        Self {
+            role: Role::User,
+            preamble: None,
+            max_tokens: None,
+        }
+    }
+}
+
+const TRUNCATED: &str = "[truncated]";
+
+/// Combines `documents` into one message; see the [module docs](self).
+pub fn documents_message(documents: &[Document], options: &DocumentOptions) -> Message {
+    let fence = fence(documents);
+    let mut out = String::new();
+    if let Some(preamble) = &options.preamble {
+        out.push_str(preamble.trim_end());
+        out.push_str("\n\n");
+    }
+    let mut budget = options.max_tokens.unwrap_or(usize::MAX);
+    budget = budget.saturating_sub(estimate_tokens(&out));
+    for (i, document) in documents.iter().enumerate() {
+        let begin = format!(
+            "{fence} BEGIN DOCUMENT {}: {} {fence}\n",
+            i + 1,
+            single_line(&document.name)
+        );
+        let end = format!("{fence} END DOCUMENT {} {fence}\n", i + 1);
+        // Keep room for the note about the documents after this one.
+        let later = documents.len() - i - 1;
+        let frame = estimate_tokens(&begin) + estimate_tokens(&end) + note_tokens(later);
+        let content = document.content.trim_end_matches('\n');
+        let fits = frame + estimate_tokens(content) <= budget;
+        let kept = if fits {
+            content
+        } else {
+            let room = budget.saturating_sub(frame + estimate_tokens(TRUNCATED));
+            if room == 0 {
+                push_note(&mut out, later + 1);
+                break;
+            }
+            prefix_within(content, room)
+        };
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&begin);
+        out.push_str(kept);
+        if !kept.is_empty() {
+            out.push('\n');
+        }
+        if !fits {
+            out.push_str(TRUNCATED);
+            out.push('\n');
+        }
+        out.push_str(&end);
+        if !fits {
+            push_note(&mut out, later);
+            break;
+        }
+        budget -= frame + estimate_tokens(kept) - note_tokens(later);
+    }
+    Message::new(options.role, out.trim_end())
+}
+
+/// The shortest run of at least five `=` found in no document.
+fn fence(documents: &[Document]) -> String {
+    let mut fence = "=====".to_string();
+    while documents
+        .iter()
+        .any(|d| d.content.contains(&fence) || d.name.contains(&fence))
+    {
+        fence.push('=');
+    }
+    fence
+}
+
+fn single_line(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+fn note(count: usize) -> String {
+    format!("[{} more document(s) omitted]", count)
+}
+
+fn note_tokens(count: usize) -> usize {
+    match count {
+        0 => 0,
+        n => estimate_tokens(&note(n)),
+    }
+}
+
+fn push_note(out: &mut String, count: usize) {
+    if count > 0 {
+        out.push('\n');
+        out.push_str(&note(count));
+        out.push('\n');
+    }
+}
+
+/// The longest prefix of `text` within `tokens`, cut after whitespace if
+/// there is any.
+fn prefix_within(text: &str, tokens: usize) -> &str {
+    let bounds: Vec<usize> = text.char_indices().map(|(i, _)| i).skip(1).collect();
+    // Token estimates only grow as the prefix does.
+    let fitting = bounds.partition_point(|&end| estimate_tokens(&text[..end]) <= tokens);
+    let prefix = match fitting {
+        0 => "",
+        n => &text[..bounds[n - 1]],
+    };
+    match prefix.rfind(char::is_whitespace) {
+        Some(at) if at > 0 => prefix[..at].trim_end(),
+        _ => prefix,
+    }
+}
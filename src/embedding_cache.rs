@@ -0,0 +1,119 @@
+//! In-memory LRU cache of embedding vectors.
+//!
+//! Enable it with `ClientBuilder::embedding_cache(capacity)`. `get_embedding`
+//! then looks up every input text before calling `GetEmbedding`, sends only
+//! the texts it has not seen, and stores the vectors it receives. Entries are
+//! keyed by the input type and a SHA-256 hash of the text, so long documents
+//! are not kept in memory.
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tencentcloud_sign_sdk::sha256_hex;
+
+/// Cache key: the request's `InputType` and the SHA-256 of the input text.
+type Key = (Option<String>, String);
+
+/// Hit and miss counts of an [`EmbeddingCache`], counted per input text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmbeddingCacheStats {
+    /// Texts answered from the cache.
+    pub hits: u64,
+    /// Texts that had to be sent to the service.
+    pub misses: u64,
+    /// Vectors currently stored.
+    pub len: usize,
+    /// Maximum number of vectors stored before the least recently used one
+    /// is evicted.
+    pub capacity: usize,
+}
+
+/// LRU cache of embedding vectors; see the [module docs](self).
+#[derive(Debug)]
+pub struct EmbeddingCache {
+    capacity: usize,
+    state: Mutex<Lru>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    /// Vector and last-use tick per key.
+    entries: HashMap<Key, (Arc<[f64]>, u64)>,
+    /// Keys by last-use tick, least recently used first.
+    order: BTreeMap<u64, Key>,
+    tick: u64,
+}
+
+impl EmbeddingCache {
+    /// Creates a cache holding at most `capacity` vectors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "embedding cache capacity must be positive");
+        Self {
+            capacity,
+            state: Mutex::new(Lru::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the hit and miss counts and the current size.
+    pub fn stats(&self) -> EmbeddingCacheStats {
+        EmbeddingCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            len: self.state.lock().unwrap().entries.len(),
+            capacity: self.capacity,
+        }
+    }
+
+    /// Removes every vector. Hit and miss counts are kept.
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = Lru::default();
+    }
+
+    pub(crate) fn key(input_type: Option<&str>, text: &str) -> Key {
+        (input_type.map(str::to_string), sha256_hex(text))
+    }
+
+    /// Looks up `key`, marking it as recently used and counting a hit or a
+    /// miss.
+    pub(crate) fn get(&self, key: &Key) -> Option<Arc<[f64]>> {
+        let mut state = self.state.lock().unwrap();
+        let Lru {
+            entries,
+            order,
+            tick,
+        } = &mut *state;
+        let Some((vector, used)) = entries.get_mut(key) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        *tick += 1;
+        order.remove(used);
+        order.insert(*tick, key.clone());
+        *used = *tick;
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(vector.clone())
+    }
+
+    /// Stores `vector` under `key`, evicting the least recently used vector
+    /// when full.
+    pub(crate) fn insert(&self, key: Key, vector: Arc<[f64]>) {
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        if let Some((_, used)) = state.entries.insert(key.clone(), (vector, tick)) {
+            state.order.remove(&used);
+        } else if state.entries.len() > self.capacity {
+            if let Some((_, oldest)) = state.order.pop_first() {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.order.insert(tick, key);
+    }
+}
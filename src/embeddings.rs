@@ -0,0 +1,2 @@
+//! Working with embedding vectors returned by `GetEmbedding`.
+pub mod math;
@@ -0,0 +1,125 @@
+//! Similarity and normalization of embedding vectors.
+//!
+//! Vectors are `f64`, as in [`EmbeddingData`](crate::models::EmbeddingData).
+//! Functions taking two vectors fail with [`DimensionMismatch`] instead of
+//! panicking when their lengths differ. None of them allocate, except
+//! [`top_k_similar`] for its `k` results.
+//!
+//! ```
+//! use tencentcloud_hunyuan_sdk::embeddings::math::{cosine_similarity, top_k_similar};
+//!
+//! let corpus = [vec![1.0, 0.0], vec![0.6, 0.8], vec![0.0, 1.0]];
+//! let query = [0.0, 2.0];
+//! assert_eq!(cosine_similarity(&query, &corpus[2]).unwrap(), 1.0);
+//! let best = top_k_similar(&query, &corpus, 2).unwrap();
+//! assert_eq!(best.iter().map(|&(i, _)| i).collect::<Vec<_>>(), [2, 1]);
+//! ```
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use thiserror::Error;
+
+/// Two vectors of different lengths were combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("vector dimensions differ: {left} vs {right}")]
+pub struct DimensionMismatch {
+    pub left: usize,
+    pub right: usize,
+}
+
+fn check(a: &[f64], b: &[f64]) -> Result<(), DimensionMismatch> {
+    if a.len() == b.len() {
+        Ok(())
+    } else {
+        Err(DimensionMismatch {
+            left: a.len(),
+            right: b.len(),
+        })
+    }
+}
+
+/// Dot product of `a` and `b`.
+pub fn dot(a: &[f64], b: &[f64]) -> Result<f64, DimensionMismatch> {
+    check(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x * y).sum())
+}
+
+/// Euclidean length of `v`.
+pub fn l2_norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Cosine of the angle between `a` and `b`, in `[-1, 1]`. `0.0` if either is
+/// a zero vector.
+pub fn cosine_similarity(a: &[f64], b: &[f64]) -> Result<f64, DimensionMismatch> {
+    let dot = dot(a, b)?;
+    let norms = l2_norm(a) * l2_norm(b);
+    if norms == 0.0 {
+        return Ok(0.0);
+    }
+    Ok((dot / norms).clamp(-1.0, 1.0))
+}
+
+/// Scales `v` to unit length. A zero vector is left unchanged.
+pub fn l2_normalize_in_place(v: &mut [f64]) {
+    let norm = l2_norm(v);
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+/// The `k` vectors of `corpus` most cosine-similar to `query`, as
+/// `(index, similarity)` pairs, most similar first; ties keep corpus order.
+///
+/// Fails on the first corpus vector whose length differs from `query`'s.
+pub fn top_k_similar<V: AsRef<[f64]>>(
+    query: &[f64],
+    corpus: &[V],
+    k: usize,
+) -> Result<Vec<(usize, f64)>, DimensionMismatch> {
+    // Min-heap of the best `k` so far; the root is the one to evict.
+    let mut best: BinaryHeap<Reverse<Scored>> = BinaryHeap::with_capacity(k.min(corpus.len()) + 1);
+    for (index, vector) in corpus.iter().enumerate() {
+        let score = cosine_similarity(query, vector.as_ref())?;
+        if k == 0 {
+            continue;
+        }
+        best.push(Reverse(Scored { score, index }));
+        if best.len() > k {
+            best.pop();
+        }
+    }
+    Ok(best
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(s)| (s.index, s.score))
+        .collect())
+}
+
+/// Orders by score, then by lower index, so the heap evicts the lowest
+/// score and, among equals, the later vector.
+struct Scored {
+    score: f64,
+    index: usize,
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Scored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scored {}
@@ -0,0 +1,123 @@
+//! Comparing two `ChatCompletions` responses, e.g. in an offline evaluation
+//! running the same prompts through two models.
+//!
+//! ```no_run
+//! use tencentcloud_hunyuan_sdk::evaluation::compare;
+//! # async fn run(client: tencentcloud_hunyuan_sdk::Client, req: tencentcloud_hunyuan_sdk::models::ChatCompletionsRequest) -> tencentcloud_hunyuan_sdk::SdkResult<()> {
+//!
+//! let a = client.chat_completions(&req.clone().with_model("hunyuan-lite")).await?;
+//! let b = client.chat_completions(&req.with_model("hunyuan-pro")).await?;
+//! let comparison = compare(&a, &b);
+//! println!(
+//!     "completion tokens {:+?}, similarity {:.2}",
+//!     comparison.usage_delta.completion_tokens, comparison.similarity
+//! );
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each response is represented by its first choice in `Index` order.
+use crate::models::{ChatChoice, ChatCompletionsResponse, Usage};
+use std::collections::HashMap;
+
+/// Token usage of `b` minus that of `a`. A count is `None` unless both
+/// responses report it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageDelta {
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub total_tokens: Option<i64>,
+}
+
+/// Differences between two responses; see [`compare`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub usage_delta: UsageDelta,
+    /// `FinishReason` of `a` and of `b`.
+    pub finish_reasons: (Option<String>, Option<String>),
+    /// Length in characters of the content of `a` and of `b`.
+    pub content_lengths: (usize, usize),
+    /// Similarity of the two contents from `0.0` (no character pair in
+    /// common) to `1.0` (the same pairs), as the Dice coefficient of their
+    /// character bigrams. Works for text without spaces, such as Chinese.
+    /// Two empty contents count as identical.
+    pub similarity: f64,
+}
+
+impl Comparison {
+    /// Whether both responses stopped for the same reason.
+    pub fn same_finish_reason(&self) -> bool {
+        self.finish_reasons.0 == self.finish_reasons.1
+    }
+
+    /// Content length of `b` minus that of `a`, in characters.
+    pub fn content_length_delta(&self) -> i64 {
+        self.content_lengths.1 as i64 - self.content_lengths.0 as i64
+    }
+}
+
+/// Summarizes how `b` differs from `a`.
+pub fn compare(a: &ChatCompletionsResponse, b: &ChatCompletionsResponse) -> Comparison {
+    let (choice_a, choice_b) = (first_choice(a), first_choice(b));
+    let (text_a, text_b) = (content(choice_a), content(choice_b));
+    Comparison {
+        usage_delta: usage_delta(a.response.usage.as_ref(), b.response.usage.as_ref()),
+        finish_reasons: (
+            choice_a.and_then(|c| c.finish_reason.clone()),
+            choice_b.and_then(|c| c.finish_reason.clone()),
+        ),
+        content_lengths: (text_a.chars().count(), text_b.chars().count()),
+        similarity: similarity(text_a, text_b),
+    }
+}
+
+fn first_choice(resp: &ChatCompletionsResponse) -> Option<&ChatChoice> {
+    let choices = resp.response.choices.as_deref()?;
+    choices.iter().min_by_key(|c| c.index.unwrap_or(0))
+}
+
+fn content(choice: Option<&ChatChoice>) -> &str {
+    choice
+        .and_then(|c| c.message.as_ref()?.content.as_deref())
+        .unwrap_or("")
+}
+
+fn usage_delta(a: Option<&Usage>, b: Option<&Usage>) -> UsageDelta {
+    let delta =
+        |count: fn(&Usage) -> Option<u32>| Some(i64::from(count(b?)?) - i64::from(count(a?)?));
+    UsageDelta {
+        prompt_tokens: delta(|u| u.prompt_tokens),
+        completion_tokens: delta(|u| u.completion_tokens),
+        total_tokens: delta(|u| u.total_tokens),
+    }
+}
+
+/// Dice coefficient of the character bigrams of `a` and `b`, counted with
+/// multiplicity. A text of one character is its own single "bigram".
+fn similarity(a: &str, b: &str) -> f64 {
+    fn bigrams(text: &str) -> HashMap<(char, Option<char>), usize> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut counts = HashMap::new();
+        match chars.len() {
+            0 => {}
+            1 => *counts.entry((chars[0], None)).or_default() += 1,
+            _ => {
+                for pair in chars.windows(2) {
+                    *counts.entry((pair[0], Some(pair[1]))).or_default() += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    let (a, b) = (bigrams(a), bigrams(b));
+    let total: usize = a.values().chain(b.values()).sum();
+    if total == 0 {
+        return 1.0;
+    }
+    let shared: usize = a
+        .iter()
+        .map(|(pair, n)| (*n).min(b.get(pair).copied().unwrap_or(0)))
+        .sum();
+    2.0 * shared as f64 / total as f64
+}
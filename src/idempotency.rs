@@ -0,0 +1,148 @@
+//! Remembering calls by idempotency key, so a resubmitted call is answered
+//! from the original instead of being sent again.
+use crate::client::ResponseMeta;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tencentcloud_sign_sdk::sha256_hex;
+use tokio::time::Instant;
+
+/// Most keys remembered at once; the oldest finished ones are forgotten first.
+pub(crate) const MAX_KEYS: usize = 1024;
+
+/// How long the result of a successful call is remembered.
+pub(crate) const KEY_TTL: Duration = Duration::from_secs(10 * 60);
+
+enum Entry {
+    InFlight,
+    Done {
+        response: Value,
+        meta: ResponseMeta,
+        at: Instant,
+    },
+}
+
+/// What a key was claimed for: the action and a hash of the request body.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct Call {
+    action: String,
+    body_hash: String,
+}
+
+impl Call {
+    pub fn new(action: &str, body: &[u8]) -> Self {
+        Self {
+            action: action.to_string(),
+            body_hash: sha256_hex(&String::from_utf8_lossy(body)),
+        }
+    }
+}
+
+/// Outcome of [`Idempotency::claim`].
+pub(crate) enum Claim {
+    /// The key is new: the caller sends the call and reports back through
+    /// the guard.
+    New(KeyGuard),
+    /// A call with the key is still in flight.
+    InFlight,
+    /// A call with the key succeeded recently, with this response.
+    Done(Value, ResponseMeta),
+    /// The key is in flight or remembered for a different action or body.
+    Mismatch,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<String, (Call, Entry)>,
+    /// Keys in the order they were claimed.
+    order: VecDeque<String>,
+}
+
+/// Keys of calls in flight or recently succeeded, shared by all clones of a
+/// client.
+#[derive(Default)]
+pub(crate) struct Idempotency {
+    state: Mutex<State>,
+}
+
+impl Idempotency {
+    pub fn claim(self: &Arc<Self>, key: &str, call: Call) -> Claim {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.forget_expired(now);
+        match state.entries.get(key) {
+            Some((claimed, _)) if *claimed != call => return Claim::Mismatch,
+            Some((_, Entry::InFlight)) => return Claim::InFlight,
+            Some((_, Entry::Done { response, meta, .. })) => {
+                return Claim::Done(response.clone(), meta.clone())
+            }
+            None => {}
+        }
+        state
+            .entries
+            .insert(key.to_string(), (call, Entry::InFlight));
+        state.order.push_back(key.to_string());
+        state.evict();
+        Claim::New(KeyGuard {
+            idempotency: self.clone(),
+            key: key.to_string(),
+        })
+    }
+}
+
+impl State {
+    fn forget_expired(&mut self, now: Instant) {
+        let expired = |entry: &Entry| matches!(entry, Entry::Done { at, .. } if now.duration_since(*at) >= KEY_TTL);
+        self.entries.retain(|_, (_, entry)| !expired(entry));
+        let entries = &self.entries;
+        self.order.retain(|key| entries.contains_key(key));
+    }
+
+    /// Forgets the oldest finished calls while over [`MAX_KEYS`]. Calls in
+    /// flight are never forgotten.
+    fn evict(&mut self) {
+        let mut excess = self.entries.len().saturating_sub(MAX_KEYS);
+        let entries = &mut self.entries;
+        self.order.retain(|key| {
+            if excess > 0 && matches!(entries.get(key), Some((_, Entry::Done { .. }))) {
+                entries.remove(key);
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+}
+
+/// A claimed key. Forgotten when dropped unless the call succeeded, so a
+/// failed or cancelled call can be sent again.
+pub(crate) struct KeyGuard {
+    idempotency: Arc<Idempotency>,
+    key: String,
+}
+
+impl KeyGuard {
+    /// Remembers `response` as the result of the call.
+    pub fn succeeded(self, response: Value, meta: ResponseMeta) {
+        let mut state = self.idempotency.state.lock().unwrap();
+        if let Some((_, entry)) = state.entries.get_mut(&self.key) {
+            *entry = Entry::Done {
+                response,
+                meta,
+                at: Instant::now(),
+            };
+        }
+    }
+}
+
+impl Drop for KeyGuard {
+    fn drop(&mut self) {
+        let mut state = self.idempotency.state.lock().unwrap();
+        if matches!(state.entries.get(&self.key), Some((_, Entry::InFlight))) {
+            state.entries.remove(&self.key);
+            let key = &self.key;
+            state.order.retain(|k| k != key);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Decoding of images returned inline as base64.
+use crate::client::{SdkError, SdkResult};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+/// Decodes base64 image data, as returned by image-generation actions, into
+/// bytes. A `data:image/...;base64,` prefix and embedded whitespace are
+/// accepted. Fails with [`SdkError::Decode`] on malformed base64, an empty
+/// payload, or a URL passed instead of data.
+pub fn decode_base64_image(data: &str) -> SdkResult<Vec<u8>> {
+    let data = data.trim();
+    if data.starts_with("http://") || data.starts_with("https://") {
+        return Err(SdkError::Decode(
+            "image is a URL, not base64 data; request RspImgType=base64".to_string(),
+        ));
+    }
+    let data = match data.strip_prefix("data:") {
+        Some(uri) => match uri.split_once(";base64,") {
+            Some((_, payload)) => payload,
+            None => {
+                return Err(SdkError::Decode(
+                    "data URI is not base64-encoded".to_string(),
+                ))
+            }
+        },
+        None => data,
+    };
+    let compact: String = data.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    if compact.is_empty() {
+        return Err(SdkError::Decode("image data is empty".to_string()));
+    }
+    STANDARD
+        .decode(compact)
+        .map_err(|e| SdkError::Decode(format!("malformed base64 image data: {}", e)))
+}
+
+/// MIME type of `bytes` judged by their magic number, for PNG, JPEG, GIF and
+/// WebP images.
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
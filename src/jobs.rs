@@ -0,0 +1,126 @@
+//! Waiting for asynchronous jobs.
+//!
+//! Long-running actions are submitted once and then queried until the job
+//! finishes. [`poll_until`] runs that query loop:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use tencentcloud_hunyuan_sdk::jobs::{poll_until, PollOptions, PollOutcome};
+//! # async fn query_job(id: &str) -> tencentcloud_hunyuan_sdk::SdkResult<(String, Option<String>)> { unimplemented!() }
+//! # async fn run() -> tencentcloud_hunyuan_sdk::SdkResult<()> {
+//!
+//! let options = PollOptions {
+//!     interval: Duration::from_secs(2),
+//!     max_wait: Duration::from_secs(300),
+//!     backoff: 1.5,
+//!     ..Default::default()
+//! };
+//! let url = poll_until(&options, || async {
+//!     let (status, url) = query_job("job-1").await?;
+//!     Ok(match (status.as_str(), url) {
+//!         ("DONE", Some(url)) => PollOutcome::Done(url),
+//!         ("FAIL", _) => PollOutcome::Failed(tencentcloud_hunyuan_sdk::SdkError::Aborted(
+//!             "job failed".to_string(),
+//!         )),
+//!         _ => PollOutcome::Pending(status),
+//!     })
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::client::{SdkError, SdkResult};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// State of a job as reported by one poll.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PollOutcome<T> {
+    /// Not finished yet, with the job's status as reported by the service.
+    Pending(String),
+    /// Finished with a result.
+    Done(T),
+    /// Finished unsuccessfully.
+    Failed(SdkError),
+}
+
+/// Timing of [`poll_until`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollOptions {
+    /// Delay before the second poll.
+    pub interval: Duration,
+    /// Time after the first poll at which to give up.
+    pub max_wait: Duration,
+    /// Factor applied to the delay after every pending poll; `1.0` polls at a
+    /// fixed interval.
+    pub backoff: f64,
+    /// Upper bound for the delay between polls.
+    pub max_interval: Duration,
+    /// Randomize each delay to between half and all of its value, so many
+    /// waiting jobs do not poll in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for PollOptions {
+    /// Every second, for up to five minutes.
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            max_wait: Duration::from_secs(300),
+            backoff: 1.0,
+            max_interval: Duration::from_secs(30),
+            jitter: false,
+        }
+    }
+}
+
+/// Calls `poll` until it reports the job done or failed.
+///
+/// Returns the result of [`PollOutcome::Done`], the error of
+/// [`PollOutcome::Failed`], or the error of `poll` itself. Once
+/// `options.max_wait` has passed, the job is polled one last time; if it is
+/// still pending, fails with [`SdkError::PollTimeout`] carrying the last
+/// status.
+pub async fn poll_until<T, F, Fut>(options: &PollOptions, mut poll: F) -> SdkResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = SdkResult<PollOutcome<T>>>,
+{
+    let start = Instant::now();
+    let deadline = start + options.max_wait;
+    let mut delay = options.interval;
+    loop {
+        let status = match poll().await? {
+            PollOutcome::Done(value) => return Ok(value),
+            PollOutcome::Failed(error) => return Err(error),
+            PollOutcome::Pending(status) => status,
+        };
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(SdkError::PollTimeout {
+                waited: now - start,
+                last_status: status,
+            });
+        }
+        let sleep = if options.jitter {
+            jittered(delay)
+        } else {
+            delay
+        };
+        tokio::time::sleep(sleep.min(deadline - now)).await;
+        delay = Duration::try_from_secs_f64(delay.as_secs_f64() * options.backoff.max(1.0))
+            .unwrap_or(Duration::MAX)
+            .min(options.max_interval);
+    }
+}
+
+/// A random duration between half and all of `delay`.
+fn jittered(delay: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let fraction = 0.5 + (random % 1024) as f64 / 2048.0;
+    delay.mul_f64(fraction)
+}
@@ -5,21 +5,253 @@
 //! Features:
 //! - Async HTTP via `reqwest`
 //! - TC3 signing
-//! - Typed helper for `ChatCompletions`
+//! - Typed helpers for `ChatCompletions`, including SSE streaming, `GetEmbedding` and
+//!   `TextToImageLite`, with base64 image decoding
+//! - Pluggable `Transport` for custom HTTP stacks and mocking in tests
+//! - Object-safe `HunyuanApi` trait for swapping the client in application tests
+//! - Conversion to and from OpenAI-shaped JSON in `compat::openai`
+//! - W3C `traceparent`/`tracestate` propagation in `trace`
+//! - Optional blocking client (`blocking` feature)
+//! - Provider-neutral `ChatModel` trait (`model-trait` feature)
 //!
 //! Debug logging can be enabled with `ClientBuilder::debug(true)` or by setting the
 //! environment variable `TENCENTCLOUD_SDK_DEBUG=true`. Sensitive values are masked
-//! in logs, but request/response bodies may still contain sensitive data.
+//! in logs, but request/response bodies may still contain sensitive data. Log lines
+//! go to stderr unless a custom `LogSink` is set with `ClientBuilder::log_sink`.
 //!
-//! Quick start example is available in the README and under `examples/chat.rs`.
+//! Quick start example is available in the README and under `examples/chat.rs`;
+//! `use tencentcloud_hunyuan_sdk::prelude::*` brings the commonly used types into scope.
+pub mod action;
+pub mod api;
+#[cfg(any(test, feature = "proptest"))]
+pub mod arbitrary;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "model-trait")]
+pub mod chat_model;
 pub mod client;
+pub mod clock;
+mod coalesce;
+pub mod compat;
+pub mod config;
+pub mod conversation;
+pub mod credential;
+pub mod documents;
+pub mod embedding_cache;
+pub mod embeddings;
+pub mod evaluation;
+mod idempotency;
+pub mod image;
+pub mod jobs;
+mod limiter;
+pub mod logging;
+pub mod metrics;
 pub mod models;
+pub mod prelude;
+pub mod prompt;
+pub mod retry;
+pub mod serializer;
+pub mod session;
+mod shutdown;
+pub mod stream;
+mod strict;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod tools;
+pub mod trace;
+pub mod transport;
+
+pub use action::Action;
+pub use api::HunyuanApi;
+pub use client::{
+    BatchOptions, Client, ClientBuilder, Credential, DeserializationMode, HttpVersionOrder,
+    Priority, Region, RequestOptions, ResponseMeta, SdkError, SdkErrorRepr, SdkResult,
+    StructuredOptions, WarmUp,
+};
+pub use clock::Clock;
+pub use config::ClientConfig;
+pub use conversation::Conversation;
+pub use credential::CredentialProvider;
+pub use embedding_cache::{EmbeddingCache, EmbeddingCacheStats};
+pub use logging::{LogLevel, LogSink};
+pub use metrics::{CallMetrics, MetricsSink};
+pub use prompt::{PromptTemplate, TemplateError};
+pub use retry::RetryPolicy;
+pub use serializer::BodySerializer;
+pub use session::{ChatSession, SessionConfig};
+pub use stream::{ChatCompletionsAccumulator, ChatCompletionsStream};
+pub use trace::{TraceContext, TraceContextProvider};
+pub use transport::{PreparedRequest, RawResponse, Transport};
 
 #[cfg(test)]
 mod tests {
-    use crate::client::{Client, ClientBuilder, Credential, Region};
+    use crate::client::{
+        BatchOptions, Client, ClientBuilder, Credential, Region, RequestOptions, SdkResult,
+    };
+    use crate::logging::{LogLevel, LogSink};
+    use crate::metrics::{CallMetrics, MetricsSink};
     use crate::models::{ChatCompletionsRequest, Message};
+    use crate::test_utils::fixtures::{
+        self, ChatResponseFixture, ChunkSequence, EmbeddingResponseFixture,
+    };
+    use crate::test_utils::{FakeHunyuan, Fault, MockResponse, MockTransport};
+    use crate::transport::{BoxFuture, PreparedRequest, RawResponse, Transport};
+    use crate::{HunyuanApi, SdkError};
+    use futures_util::StreamExt;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
     use time::OffsetDateTime;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn chat_ok_body() -> String {
+        let resp = ChatResponseFixture::new()
+            .request_id("req-1")
+            .id("id-1")
+            .content("hi")
+            .usage(1, 1)
+            .build();
+        serde_json::to_string(&resp).unwrap()
+    }
+
+    /// Log sink collecting every line for assertions.
+    #[derive(Default)]
+    struct CaptureSink(Mutex<Vec<(LogLevel, String)>>);
+
+    impl LogSink for CaptureSink {
+        fn log(&self, level: LogLevel, message: &str) {
+            self.0.lock().unwrap().push((level, message.to_string()));
+        }
+    }
+
+    impl CaptureSink {
+        fn lines(&self, level: LogLevel) -> Vec<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(l, _)| *l == level)
+                .map(|(_, m)| m.clone())
+                .collect()
+        }
+    }
+
+    /// Serves one canned HTTP response after `delay` on a local port. Returns
+    /// the `http://` endpoint and a handle resolving to the raw request text.
+    async fn serve_once(
+        delay: Duration,
+        status: u16,
+        body: impl Into<String>,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let body = body.into();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+                let text = String::from_utf8_lossy(&buf).to_string();
+                if let Some(pos) = text.find("\r\n\r\n") {
+                    let content_length = text[..pos]
+                        .lines()
+                        .find_map(|l| {
+                            let (k, v) = l.split_once(':')?;
+                            k.eq_ignore_ascii_case("content-length")
+                                .then(|| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if buf.len() >= pos + 4 + content_length || n == 0 {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            tokio::time::sleep(delay).await;
+            let resp = format!(
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(resp.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf).to_string()
+        });
+        (endpoint, handle)
+    }
+
+    fn test_credential() -> Credential {
+        Credential {
+            secret_id: "test_id".to_string(),
+            secret_key: "test_key".to_string(),
+            token: None,
+        }
+    }
+
+    /// `Authorization` the client should send for `canonical_request` signed
+    /// at `timestamp` with [`test_credential`], derived independently of the
+    /// SDK's signer.
+    fn expected_authorization(timestamp: i64, canonical_request: &str) -> String {
+        use hmac::{Hmac, Mac};
+        use sha2::{Digest, Sha256};
+
+        fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        }
+
+        let date = OffsetDateTime::from_unix_timestamp(timestamp)
+            .unwrap()
+            .date();
+        let date = format!(
+            "{}-{:02}-{:02}",
+            date.year(),
+            u8::from(date.month()),
+            date.day()
+        );
+        let scope = format!("{}/hunyuan/tc3_request", date);
+        let string_to_sign = format!(
+            "TC3-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let credential = test_credential();
+        let secret_date = hmac_sha256(
+            format!("TC3{}", credential.secret_key).as_bytes(),
+            date.as_bytes(),
+        );
+        let secret_signing = hmac_sha256(&hmac_sha256(&secret_date, b"hunyuan"), b"tc3_request");
+        let signature = hex::encode(hmac_sha256(&secret_signing, string_to_sign.as_bytes()));
+        let signed_headers = canonical_request.rsplit('\n').nth(1).unwrap();
+        format!(
+            "TC3-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credential.secret_id, scope, signed_headers, signature
+        )
+    }
+
+    fn chat_request() -> ChatCompletionsRequest {
+        ChatCompletionsRequest {
+            model: Some("hunyuan-lite".to_string()),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: "Hello".to_string(),
+                ..Default::default()
+            }],
+            temperature: None,
+            top_p: None,
+            stream: None,
+            tools: None,
+            n: None,
+            seed: None,
+        }
+    }
 
     #[test]
     fn test_region_as_str() {
@@ -220,6 +452,31 @@ mod tests {
         assert_eq!(client.region().as_str(), "ap-guangzhou");
     }
 
+    #[test]
+    fn test_typestate_builder_accepts_settings_before_credential() {
+        let client = Client::builder()
+            .region(Region::ApBeijing)
+            .debug(false)
+            .credential(Credential {
+                secret_id: "test_id".to_string(),
+                secret_key: "test_key".to_string(),
+                token: None,
+            })
+            .endpoint("custom.endpoint.com")
+            .build();
+
+        assert_eq!(client.region().as_str(), "ap-beijing");
+        assert_eq!(client.endpoint(), "custom.endpoint.com");
+    }
+
+    #[test]
+    fn test_builder_dyn_panics_without_credential() {
+        let result = std::panic::catch_unwind(|| {
+            Client::builder_dyn().build();
+        });
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_tc3_sign_components() {
         let client = ClientBuilder::new()
@@ -247,6 +504,86 @@ mod tests {
         assert!(credential_scope.contains("tc3_request"));
     }
 
+    #[tokio::test]
+    async fn test_canonical_request_for_matches_signed_request() {
+        use sha2::{Digest, Sha256};
+
+        let timestamp = 1_704_164_645;
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("hi")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .clock(Arc::new(crate::clock::FixedClock(timestamp)))
+            .debug(false)
+            .build();
+        let canonical = client
+            .canonical_request_for("ChatCompletions", &chat_request(), timestamp)
+            .unwrap();
+
+        // Method, URI, query string, one line per header, the blank line that
+        // ends the headers block, signed headers, payload hash.
+        let body = serde_json::to_vec(&chat_request()).unwrap();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let lines: Vec<&str> = canonical.split('\n').collect();
+        assert_eq!(
+            lines,
+            [
+                "POST",
+                "/",
+                "",
+                "content-type:application/json; charset=utf-8",
+                "host:hunyuan.tencentcloudapi.com",
+                "",
+                "content-type;host",
+                payload_hash.as_str(),
+            ]
+        );
+
+        // Re-deriving the signature from it reproduces the sent Authorization.
+        client.chat_completions(&chat_request()).await.unwrap();
+        let prepared = transport.requests().remove(0);
+        assert_eq!(
+            prepared.headers["Authorization"],
+            expected_authorization(timestamp, &canonical)
+        );
+
+        assert!(matches!(
+            client.canonical_request_for("ChatCompletions", &chat_request(), -1),
+            Err(SdkError::Signing(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_extreme_timestamps_fail_to_sign_without_panicking() {
+        for timestamp in [i64::MAX, i64::MIN, -1, 253_402_300_800] {
+            let transport = Arc::new(MockTransport::new());
+            let client = ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport.clone())
+                .clock(Arc::new(crate::clock::FixedClock(timestamp)))
+                .retry_policy(crate::RetryPolicy::new(2).initial_backoff(Duration::ZERO))
+                .debug(false)
+                .build();
+
+            let err = client.chat_completions(&chat_request()).await.unwrap_err();
+            assert!(
+                matches!(&err, SdkError::Signing(msg) if msg.contains(&timestamp.to_string())),
+                "timestamp {}: {:?}",
+                timestamp,
+                err
+            );
+            assert!(!err.is_retryable());
+            assert!(transport.requests().is_empty());
+            assert!(client
+                .try_tc3_sign("POST", "/", "", "", "content-type;host", "hash", timestamp)
+                .is_err());
+        }
+    }
+
     #[test]
     fn test_build_headers() {
         let client = ClientBuilder::new()
@@ -258,7 +595,9 @@ mod tests {
             .build();
 
         let timestamp = OffsetDateTime::now_utc().unix_timestamp();
-        let headers = client.build_headers("TestAction", "test_body", timestamp);
+        let headers = client
+            .build_headers("TestAction", "test_body", timestamp)
+            .unwrap();
 
         assert_eq!(headers.get("Host").unwrap(), "hunyuan.tencentcloudapi.com");
         assert_eq!(
@@ -286,16 +625,62 @@ mod tests {
             .build();
 
         let timestamp = OffsetDateTime::now_utc().unix_timestamp();
-        let headers = client.build_headers("TestAction", "test_body", timestamp);
+        let headers = client
+            .build_headers("TestAction", "test_body", timestamp)
+            .unwrap();
 
         assert_eq!(headers.get("X-TC-Token").unwrap(), "test_token");
     }
 
+    #[tokio::test]
+    async fn test_invalid_action_or_version_fails_without_sending() {
+        let transport = Arc::new(MockTransport::new());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let body = serde_json::json!({});
+
+        let err = client
+            .call_raw("Chat\nCompletions", &body, &RequestOptions::new())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, SdkError::InvalidRequest(m) if m.contains("action")),
+            "{:?}",
+            err
+        );
+        assert!(!err.is_retryable());
+
+        let options = RequestOptions::new().version("2023-09-01\r\nX-Injected: 1");
+        let err = client
+            .call_raw("ChatCompletions", &body, &options)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, SdkError::InvalidRequest(m) if m.contains("version")),
+            "{:?}",
+            err
+        );
+
+        assert!(matches!(
+            client.signed_headers_for("Chat\nCompletions", b"{}"),
+            Err(SdkError::InvalidRequest(_))
+        ));
+        assert!(matches!(
+            client.build_headers("Chat\nCompletions", "", 1_704_164_645),
+            Err(SdkError::InvalidRequest(_))
+        ));
+        assert!(transport.requests().is_empty());
+    }
+
     #[test]
     fn test_models_creation() {
         let message = Message {
             role: "user".to_string(),
             content: "Hello, world!".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(message.role, "user");
@@ -307,6 +692,9 @@ mod tests {
             temperature: Some(0.7),
             top_p: Some(0.9),
             stream: Some(false),
+            tools: None,
+            n: None,
+            seed: None,
         };
 
         assert_eq!(request.model, Some("hunyuan-pro".to_string()));
@@ -316,11 +704,408 @@ mod tests {
         assert_eq!(request.stream, Some(false));
     }
 
+    #[test]
+    fn test_static_model_list() {
+        use crate::models::Model;
+        use std::collections::HashSet;
+
+        let all = Model::all();
+        let ids: HashSet<_> = all.iter().map(|m| m.as_str()).collect();
+        assert_eq!(ids.len(), all.len());
+        for model in all {
+            let info = model.info();
+            assert_eq!(info.id, model.as_str());
+            assert!(info.context_window > 0);
+            assert_eq!(Model::from_id(info.id), Some(*model));
+        }
+        assert_eq!(Model::from_id("hunyuan-lite"), Some(Model::Lite));
+        assert!(Model::Vision.info().vision);
+        assert!(Model::FunctionCall.info().function_calling);
+        assert_eq!(Model::from_id("gpt-4"), None);
+        assert_eq!(String::from(Model::Pro), "hunyuan-pro");
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        use crate::models::{estimate_messages_tokens, estimate_tokens, Role};
+
+        let table = [
+            ("", 0),
+            ("   \n\t", 0),
+            ("Hello", 2),
+            ("Hello, world!", 6),
+            ("The quick brown fox jumps over the lazy dog.", 13),
+            ("internationalization", 5),
+            ("你好，世界", 5),
+            ("混元大模型 supports 32k context", 10),
+            ("fn main() { println!(\"hi\"); }", 15),
+        ];
+        for (text, expected) in table {
+            assert_eq!(estimate_tokens(text), expected, "{text:?}");
+        }
+
+        let messages = [
+            Message::new(Role::System, "Be brief."),
+            Message::new(Role::User, "你好"),
+        ];
+        assert_eq!(estimate_messages_tokens(&messages), 4 + 4 + 4 + 2);
+        assert_eq!(estimate_messages_tokens(&[]), 0);
+    }
+
+    #[test]
+    fn test_context_budget() {
+        use crate::models::{ContextBudget, Model, Role};
+
+        let conversation = [
+            Message::new(Role::System, "Be brief."),
+            Message::new(Role::User, "你好"),
+        ];
+        let budget = Model::Code.context_budget(&conversation);
+        assert_eq!(
+            budget,
+            ContextBudget {
+                context_window: 8_000,
+                prompt_tokens: 14,
+            }
+        );
+        assert_eq!(budget.remaining(), 7_986);
+        assert!(!budget.overflows());
+        assert_eq!(budget.overflow(), 0);
+
+        // 9,000 words of one token each, plus the message overhead.
+        let long = vec![Message::new(Role::User, "word ".repeat(9_000))];
+        let budget = Model::Code.context_budget(&long);
+        assert_eq!(budget.prompt_tokens, 9_004);
+        assert_eq!(budget.remaining(), 0);
+        assert!(budget.overflows());
+        assert_eq!(budget.overflow(), 1_004);
+
+        assert_eq!(Model::Lite.context_budget(&long).remaining(), 246_996);
+    }
+
+    #[test]
+    fn test_prompt_template() {
+        use crate::models::Role;
+        use crate::prompt::{PromptTemplate, TemplateError};
+
+        // A placeholder may repeat; values are inserted verbatim.
+        let template = PromptTemplate::new("{name}, meet {other}. Hi {name}!");
+        assert_eq!(template.variables().unwrap(), ["name", "other"]);
+        assert_eq!(
+            template
+                .render(&[("name", "{Ann}"), ("other", "Bo")])
+                .unwrap(),
+            "{Ann}, meet Bo. Hi {Ann}!"
+        );
+
+        assert_eq!(
+            template.render(&[("name", "Ann")]),
+            Err(TemplateError::MissingVariable("other".to_string()))
+        );
+        assert_eq!(
+            template.render(&[("name", "Ann"), ("other", "Bo"), ("extra", "x")]),
+            Err(TemplateError::ExtraVariable("extra".to_string()))
+        );
+        assert_eq!(
+            template.render(&[("name", "Ann"), ("other", "Bo"), ("name", "Cy")]),
+            Err(TemplateError::DuplicateVariable("name".to_string()))
+        );
+
+        // Doubled braces are literal.
+        let json = PromptTemplate::new("Reply as {{\"{key}\": ...}} or }}{{");
+        assert_eq!(
+            json.render(&[("key", "answer")]).unwrap(),
+            "Reply as {\"answer\": ...} or }{"
+        );
+        for (bad, error) in [
+            ("a {b", TemplateError::InvalidPlaceholder(2)),
+            ("a {} b", TemplateError::InvalidPlaceholder(2)),
+            ("a {b c}", TemplateError::InvalidPlaceholder(2)),
+            ("a } b", TemplateError::UnmatchedBrace(2)),
+        ] {
+            assert_eq!(PromptTemplate::new(bad).render(&[]), Err(error), "{bad:?}");
+        }
+
+        // A variable used only by the system template is not extra.
+        let messages = PromptTemplate::new("Summarize: {text}")
+            .system("Write for {audience}.")
+            .to_messages(Role::User, &[("audience", "kids"), ("text", "Rust")])
+            .unwrap();
+        assert_eq!(
+            messages,
+            [
+                Message::new(Role::System, "Write for kids."),
+                Message::new(Role::User, "Summarize: Rust"),
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_poll_until() {
+        use crate::jobs::{poll_until, PollOptions, PollOutcome};
+        use tokio::time::Instant;
+
+        let options = PollOptions {
+            interval: Duration::from_secs(1),
+            max_wait: Duration::from_secs(10),
+            backoff: 2.0,
+            ..Default::default()
+        };
+        // Reports the statuses in turn, recording when each poll happened.
+        let run = |statuses: Vec<&'static str>| {
+            let polls = Arc::new(Mutex::new(Vec::new()));
+            let log = polls.clone();
+            let start = Instant::now();
+            let mut statuses = statuses.into_iter();
+            let result = poll_until(&options, move || {
+                log.lock().unwrap().push(start.elapsed().as_secs_f64());
+                let status = statuses.next().unwrap_or("RUNNING");
+                async move {
+                    match status {
+                        "DONE" => Ok(PollOutcome::Done(42)),
+                        "FAIL" => Ok(PollOutcome::Failed(SdkError::Aborted("gone".to_string()))),
+                        "ERROR" => Err(SdkError::Transport("reset".to_string())),
+                        pending => Ok(PollOutcome::Pending(pending.to_string())),
+                    }
+                }
+            });
+            async move { (result.await, polls.lock().unwrap().clone()) }
+        };
+
+        let (result, polls) = run(vec!["DONE"]).await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(polls, [0.0]);
+
+        // The interval doubles after every pending poll.
+        let (result, polls) = run(vec!["QUEUED", "RUNNING", "RUNNING", "DONE"]).await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(polls, [0.0, 1.0, 3.0, 7.0]);
+
+        let (result, _) = run(vec!["QUEUED", "FAIL"]).await;
+        assert!(matches!(result, Err(SdkError::Aborted(m)) if m == "gone"));
+        let (result, polls) = run(vec!["QUEUED", "ERROR"]).await;
+        assert!(matches!(result, Err(SdkError::Transport(_))));
+        assert_eq!(polls.len(), 2);
+
+        // One last poll at the deadline, then the last status is reported.
+        let (result, polls) =
+            run(vec!["QUEUED", "RUNNING", "RUNNING", "RUNNING", "RENDERING"]).await;
+        assert_eq!(polls, [0.0, 1.0, 3.0, 7.0, 10.0]);
+        match result {
+            Err(SdkError::PollTimeout {
+                waited,
+                last_status,
+            }) => {
+                assert_eq!(waited, Duration::from_secs(10));
+                assert_eq!(last_status, "RENDERING");
+            }
+            other => panic!("expected a poll timeout, got {other:?}"),
+        }
+
+        let jittered = PollOptions {
+            jitter: true,
+            backoff: 1.0,
+            ..options
+        };
+        let start = Instant::now();
+        let mut polls = 0;
+        poll_until(&jittered, || {
+            polls += 1;
+            async move {
+                Ok(match polls {
+                    1..=5 => PollOutcome::Pending("RUNNING".to_string()),
+                    _ => PollOutcome::Done(()),
+                })
+            }
+        })
+        .await
+        .unwrap();
+        let waited = start.elapsed();
+        assert!(waited >= Duration::from_millis(2500) && waited <= Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_sdk_error_repr_for_each_variant() {
+        let http = reqwest::Client::new().get("not a url").build().unwrap_err();
+        let serde = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let cases = [
+            (SdkError::Http(http), "http", None, None),
+            (SdkError::Serde(serde), "serde", None, None),
+            (
+                SdkError::Service {
+                    code: "AuthFailure".to_string(),
+                    message: "bad key".to_string(),
+                    request_id: Some("r-1".to_string()),
+                },
+                "service",
+                Some("AuthFailure"),
+                Some("r-1"),
+            ),
+            (
+                SdkError::Transport("reset".to_string()),
+                "transport",
+                None,
+                None,
+            ),
+            (
+                SdkError::ResponseTooLarge {
+                    limit: 1,
+                    received: 2,
+                    status: Some(200),
+                    request_id: Some("r-2".to_string()),
+                },
+                "response_too_large",
+                None,
+                Some("r-2"),
+            ),
+            (
+                SdkError::StreamEventTooLarge { limit: 1, size: 2 },
+                "stream_event_too_large",
+                None,
+                None,
+            ),
+            (
+                SdkError::UnexpectedResponse {
+                    status: 502,
+                    content_type: Some("text/html".to_string()),
+                    snippet: "<html>".to_string(),
+                },
+                "unexpected_response",
+                Some("HTTP_502"),
+                None,
+            ),
+            (
+                SdkError::Signing("clock".to_string()),
+                "signing",
+                None,
+                None,
+            ),
+            (
+                SdkError::InvalidRequest("action".to_string()),
+                "invalid_request",
+                None,
+                None,
+            ),
+            (
+                SdkError::InvalidConfig("http2".to_string()),
+                "invalid_config",
+                None,
+                None,
+            ),
+            (SdkError::Decode("base64".to_string()), "decode", None, None),
+            (
+                SdkError::Aborted("batch".to_string()),
+                "aborted",
+                None,
+                None,
+            ),
+            (
+                SdkError::PollTimeout {
+                    waited: Duration::from_secs(3),
+                    last_status: "RUNNING".to_string(),
+                },
+                "poll_timeout",
+                None,
+                None,
+            ),
+        ];
+        for (err, kind, code, request_id) in cases {
+            let repr = err.to_repr();
+            let json = serde_json::to_value(&repr).unwrap();
+            assert_eq!(json["kind"], kind);
+            assert_eq!(json.get("code").and_then(|c| c.as_str()), code, "{kind}");
+            assert_eq!(
+                json.get("request_id").and_then(|r| r.as_str()),
+                request_id,
+                "{kind}"
+            );
+            let message = json["message"].as_str().unwrap();
+            if kind == "service" {
+                assert_eq!(message, "bad key");
+            } else {
+                assert_eq!(message, err.to_string());
+            }
+            let back: crate::SdkErrorRepr = serde_json::from_value(json).unwrap();
+            assert_eq!(back, repr);
+        }
+    }
+
+    #[test]
+    fn test_embedding_math() {
+        use crate::embeddings::math::{
+            cosine_similarity, dot, l2_normalize_in_place, top_k_similar, DimensionMismatch,
+        };
+
+        let mismatch = DimensionMismatch { left: 2, right: 3 };
+        assert_eq!(dot(&[1.0, 2.0], &[1.0, 2.0, 3.0]), Err(mismatch));
+        assert_eq!(
+            cosine_similarity(&[1.0, 2.0], &[1.0, 2.0, 3.0]),
+            Err(mismatch)
+        );
+        assert_eq!(dot(&[1.0, 2.0], &[3.0, 4.0]), Ok(11.0));
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), Ok(0.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[-3.0, 0.0]), Ok(-1.0));
+
+        let mut v = [3.0, 4.0];
+        l2_normalize_in_place(&mut v);
+        assert_eq!(v, [0.6, 0.8]);
+        let mut zero = [0.0; 3];
+        l2_normalize_in_place(&mut zero);
+        assert_eq!(zero, [0.0; 3]);
+
+        let corpus = vec![
+            vec![1.0, 0.0],  // 0: cos 0
+            vec![0.0, 5.0],  // 1: cos 1
+            vec![1.0, 1.0],  // 2: cos 0.707
+            vec![0.0, -1.0], // 3: cos -1
+            vec![0.0, 2.0],  // 4: cos 1, ties with 1
+        ];
+        let query = [0.0, 1.0];
+        let indices = |k| -> Vec<usize> {
+            top_k_similar(&query, &corpus, k)
+                .unwrap()
+                .into_iter()
+                .map(|(i, _)| i)
+                .collect()
+        };
+        assert_eq!(indices(3), [1, 4, 2]);
+        assert_eq!(indices(10), [1, 4, 2, 0, 3]);
+        assert!(indices(0).is_empty());
+        let top = top_k_similar(&query, &corpus, 3).unwrap();
+        assert!((top[2].1 - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-12);
+        assert_eq!(
+            top_k_similar(&query, &[vec![1.0, 0.0], vec![1.0]], 1),
+            Err(DimensionMismatch { left: 2, right: 1 })
+        );
+    }
+
+    #[test]
+    fn test_chat_request_mutators_chain() {
+        let template = chat_request();
+        let req = template
+            .clone()
+            .with_model("hunyuan-pro")
+            .with_temperature(0.2)
+            .with_top_p(0.9)
+            .with_stream(false);
+        assert_eq!(req.model.as_deref(), Some("hunyuan-pro"));
+        assert_eq!(req.temperature, Some(0.2));
+        assert_eq!(req.top_p, Some(0.9));
+        assert_eq!(req.stream, Some(false));
+        assert_eq!(req.messages, template.messages);
+        assert_eq!(template.temperature, None);
+
+        // Later calls win.
+        let req = req.with_temperature(1.0);
+        assert_eq!(req.temperature, Some(1.0));
+    }
+
     #[test]
     fn test_serde_serialization() {
         let message = Message {
             role: "user".to_string(),
             content: "Test message".to_string(),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&message).unwrap();
@@ -337,10 +1122,14 @@ mod tests {
             messages: vec![Message {
                 role: "user".to_string(),
                 content: "Test".to_string(),
+                ..Default::default()
             }],
             temperature: None,
             top_p: None,
             stream: None,
+            tools: None,
+            n: None,
+            seed: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -350,6 +1139,5216 @@ mod tests {
         assert_eq!(deserialized.top_p, None);
         assert_eq!(deserialized.stream, None);
     }
-}
 
-pub use client::{Client, ClientBuilder, Credential, Region};
+    #[test]
+    fn test_embedding_input_type_serialization() {
+        use crate::models::{GetEmbeddingRequest, INPUT_TYPE_DOCUMENT, INPUT_TYPE_QUERY};
+
+        for input_type in [INPUT_TYPE_QUERY, INPUT_TYPE_DOCUMENT] {
+            let req = GetEmbeddingRequest {
+                input: Some("text".to_string()),
+                input_type: Some(input_type.to_string()),
+                ..Default::default()
+            };
+            let json = serde_json::to_value(&req).unwrap();
+            assert_eq!(
+                json,
+                serde_json::json!({"Input": "text", "InputType": input_type})
+            );
+            assert_eq!(
+                serde_json::from_value::<GetEmbeddingRequest>(json).unwrap(),
+                req
+            );
+            req.validate().unwrap();
+        }
+
+        // Absent for APIs that don't distinguish input types.
+        let req: GetEmbeddingRequest = serde_json::from_str(r#"{"Input":"text"}"#).unwrap();
+        assert_eq!(req.input_type, None);
+        assert_eq!(serde_json::to_string(&req).unwrap(), r#"{"Input":"text"}"#);
+        req.validate().unwrap();
+
+        let req = GetEmbeddingRequest {
+            input_type: Some("passage".to_string()),
+            ..req
+        };
+        assert!(matches!(req.validate(), Err(SdkError::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn test_base64_embeddings_decode_to_the_float_vectors() {
+        use crate::models::{EmbeddingData, GetEmbeddingRequest, GetEmbeddingResponse};
+        use base64::Engine;
+
+        let req = GetEmbeddingRequest {
+            input: Some("text".to_string()),
+            ..Default::default()
+        }
+        .with_base64_encoding();
+        req.validate().unwrap();
+        assert_eq!(
+            serde_json::to_value(&req).unwrap(),
+            serde_json::json!({"Input": "text", "EncodingFormat": "base64"})
+        );
+        let bad = GetEmbeddingRequest {
+            encoding_format: Some("binary".to_string()),
+            ..req
+        };
+        assert!(matches!(bad.validate(), Err(SdkError::InvalidRequest(_))));
+
+        let vectors: [&[f32]; 3] = [&[0.5, -0.25, 1e-3, f32::MAX], &[-1.0], &[]];
+        for vector in vectors {
+            let bytes: Vec<u8> = vector.iter().flat_map(|x| x.to_le_bytes()).collect();
+            let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
+            let body = |embedding: serde_json::Value| {
+                serde_json::to_vec(&serde_json::json!({"Response": {
+                    "Data": [{"Embedding": embedding, "Index": 0, "Object": "embedding"}],
+                    "RequestId": "r1",
+                }}))
+                .unwrap()
+            };
+            let floats: GetEmbeddingResponse =
+                serde_json::from_slice(&body(serde_json::json!(vector))).unwrap();
+            let (base64, unknown): (GetEmbeddingResponse, _) =
+                crate::strict::from_slice(&body(serde_json::json!(encoded))).unwrap();
+            assert!(unknown.is_empty(), "{:?}", unknown);
+
+            let (floats, base64) = (
+                &floats.response.data.unwrap()[0],
+                &base64.response.data.unwrap()[0],
+            );
+            assert_eq!(base64.embedding, None);
+            assert_eq!(base64.embedding_base64.as_deref(), Some(encoded.as_str()));
+            assert_eq!(base64.vector().unwrap(), floats.vector().unwrap());
+            assert_eq!(base64.vector().unwrap(), vector);
+            // Serialized back in the form it was received.
+            assert_eq!(
+                serde_json::to_value(base64).unwrap()["Embedding"],
+                serde_json::json!(encoded)
+            );
+        }
+
+        let malformed = |encoded: &str| EmbeddingData {
+            embedding_base64: Some(encoded.to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            malformed("not base64!").vector(),
+            Err(SdkError::Decode(_))
+        ));
+        // Six bytes: one f32 and a half.
+        assert!(matches!(
+            malformed("AAAAAAAA").vector(),
+            Err(SdkError::Decode(_))
+        ));
+        assert!(EmbeddingData::default().vector().unwrap().is_empty());
+    }
+
+    /// Transport answering each chat request with its own content after a
+    /// delay that shrinks with the request number, so later requests finish
+    /// first. Content `fail` gets a non-retryable error at once. Tracks the peak
+    /// number of requests in flight.
+    #[derive(Default)]
+    struct BatchTransport {
+        in_flight: Mutex<(usize, usize)>,
+        started: Mutex<Vec<String>>,
+    }
+
+    impl Transport for BatchTransport {
+        fn execute(&self, req: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>> {
+            Box::pin(async move {
+                let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+                let content = body["Messages"][0]["Content"].as_str().unwrap().to_string();
+                self.started.lock().unwrap().push(content.clone());
+                {
+                    let mut in_flight = self.in_flight.lock().unwrap();
+                    in_flight.0 += 1;
+                    in_flight.1 = in_flight.1.max(in_flight.0);
+                }
+                let delay = match content.parse::<u64>() {
+                    Ok(n) => 20 - n.min(10),
+                    Err(_) => 0,
+                };
+                tokio::time::sleep(Duration::from_millis(delay * 5)).await;
+                self.in_flight.lock().unwrap().0 -= 1;
+                let (status, body) = if content == "fail" {
+                    (
+                        400,
+                        fixtures::error_response("InvalidParameter", "bad request"),
+                    )
+                } else {
+                    (
+                        200,
+                        serde_json::to_value(fixtures::chat_completions_response(&content))
+                            .unwrap(),
+                    )
+                };
+                let mut headers = reqwest::header::HeaderMap::new();
+                headers.insert("content-type", "application/json".parse().unwrap());
+                Ok(RawResponse::from_bytes(
+                    status,
+                    headers,
+                    serde_json::to_vec(&body).unwrap(),
+                ))
+            })
+        }
+    }
+
+    fn batch_client(transport: Arc<BatchTransport>) -> Client {
+        ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .debug(false)
+            .build()
+    }
+
+    fn reply(result: &SdkResult<crate::models::ChatCompletionsResponse>) -> &str {
+        let choices = result.as_ref().unwrap().response.choices.as_ref().unwrap();
+        choices[0]
+            .message
+            .as_ref()
+            .unwrap()
+            .content
+            .as_deref()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_batch_preserves_order_and_bounds_concurrency() {
+        let transport = Arc::new(BatchTransport::default());
+        let client = batch_client(transport.clone());
+        let requests = (0..10)
+            .map(|n| fixtures::chat_request(&n.to_string()))
+            .collect();
+
+        let results = client
+            .chat_completions_batch(
+                requests,
+                BatchOptions {
+                    concurrency: 3,
+                    fail_fast: false,
+                },
+            )
+            .await;
+
+        let replies: Vec<&str> = results.iter().map(reply).collect();
+        assert_eq!(replies, ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"]);
+        assert_eq!(transport.in_flight.lock().unwrap().1, 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_calls_keep_their_own_bodies() {
+        let transport = Arc::new(BatchTransport::default());
+        let client = batch_client(transport.clone());
+        // Sizes vary, so a reused buffer leaking a longer earlier body would
+        // leave trailing bytes that fail to parse.
+        let tasks: Vec<_> = (0..200)
+            .map(|n| {
+                let content = format!("req-{}-{}", n, "x".repeat(n * 37 % 1000));
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let result = client
+                        .chat_completions(&fixtures::chat_request(&content))
+                        .await;
+                    (content, result)
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            let (content, result) = task.await.unwrap();
+            assert_eq!(reply(&result), content);
+        }
+        assert_eq!(transport.started.lock().unwrap().len(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_batch_without_fail_fast_runs_every_request() {
+        let transport = Arc::new(BatchTransport::default());
+        let client = batch_client(transport.clone());
+        let requests = ["0", "fail", "2", "3"]
+            .iter()
+            .map(|c| fixtures::chat_request(c))
+            .collect();
+
+        let results = client
+            .chat_completions_batch(requests, BatchOptions::default())
+            .await;
+
+        assert_eq!(reply(&results[0]), "0");
+        assert!(
+            matches!(&results[1], Err(SdkError::Service { code, .. }) if code == "InvalidParameter")
+        );
+        assert_eq!(reply(&results[2]), "2");
+        assert_eq!(reply(&results[3]), "3");
+        assert_eq!(transport.started.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_batch_fail_fast_aborts_remaining_requests() {
+        let transport = Arc::new(BatchTransport::default());
+        let client = batch_client(transport.clone());
+        // "fail" finishes before "0" and "1", which are cancelled in flight;
+        // "2" and "3" are never sent.
+        let requests = ["0", "1", "fail", "2", "3"]
+            .iter()
+            .map(|c| fixtures::chat_request(c))
+            .collect();
+
+        let results = client
+            .chat_completions_batch(
+                requests,
+                BatchOptions {
+                    concurrency: 3,
+                    fail_fast: true,
+                },
+            )
+            .await;
+
+        assert!(matches!(&results[2], Err(SdkError::Service { .. })));
+        for index in [0, 1, 3, 4] {
+            assert!(
+                matches!(&results[index], Err(SdkError::Aborted(_))),
+                "result {}: {:?}",
+                index,
+                results[index].as_ref().map(|_| ())
+            );
+        }
+        assert_eq!(*transport.started.lock().unwrap(), ["0", "1", "fail"]);
+        assert!(!SdkError::Aborted(String::new()).is_retryable());
+    }
+
+    #[test]
+    fn test_conversation_json_round_trip() {
+        use crate::models::Role;
+        use crate::Conversation;
+
+        let mut conversation = Conversation::new();
+        conversation.push(Message::new(Role::System, "You can look up the weather."));
+        conversation.push(Message::new(Role::User, "Weather in Shenzhen?"));
+        conversation.push(Message::new(
+            Role::Assistant,
+            r#"{"name":"get_weather","arguments":{"city":"深圳"}}"#,
+        ));
+        conversation.push(Message::new(Role::Tool, r#"{"temp_c":28}"#));
+        conversation.push(Message::new(Role::Assistant, "It is 28°C in Shenzhen."));
+
+        let json = conversation.to_json().unwrap();
+        assert!(json.starts_with(r#"{"SchemaVersion":1,"Messages":[{"Role":"system","#));
+        assert_eq!(Conversation::from_json(&json).unwrap(), conversation);
+        assert_eq!(
+            conversation.to_request("hunyuan-lite").messages[3].role,
+            "tool"
+        );
+
+        // Stored data may carry fields this release does not know about.
+        let stored = r#"{"SchemaVersion":1,"Messages":[{"Role":"user","Content":"Hi","Name":"x"}],"Title":"t"}"#;
+        assert_eq!(
+            Conversation::from_json(stored).unwrap().messages(),
+            &[Message::new(Role::User, "Hi")]
+        );
+
+        let future = r#"{"SchemaVersion":2,"Messages":[]}"#;
+        assert!(matches!(
+            Conversation::from_json(future),
+            Err(SdkError::Decode(_))
+        ));
+        assert!(matches!(
+            Conversation::from_json(r#"{"Messages":[]}"#),
+            Err(SdkError::Serde(_))
+        ));
+    }
+
+    /// Returns the value of header `name` from a raw HTTP/1.1 request.
+    fn raw_header<'a>(raw: &'a str, name: &str) -> Option<&'a str> {
+        raw.split("\r\n\r\n").next()?.lines().skip(1).find_map(|l| {
+            let (k, v) = l.split_once(':')?;
+            k.eq_ignore_ascii_case(name).then(|| v.trim())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_sdk_headers_override_http_client_defaults() {
+        let (endpoint, server) = serve_once(Duration::ZERO, 200, chat_ok_body()).await;
+        let mut defaults = reqwest::header::HeaderMap::new();
+        defaults.insert("Content-Type", "text/plain".parse().unwrap());
+        defaults.insert("X-TC-Action", "Bogus".parse().unwrap());
+        defaults.insert("X-Custom", "keep".parse().unwrap());
+        let http = reqwest::Client::builder()
+            .default_headers(defaults)
+            .build()
+            .unwrap();
+        let client = ClientBuilder::new()
+            .http(http)
+            .credential(test_credential())
+            .endpoint(endpoint)
+            .debug(false)
+            .build();
+
+        client.chat_completions(&chat_request()).await.unwrap();
+        let raw = server.await.unwrap();
+
+        assert_eq!(
+            raw_header(&raw, "Content-Type"),
+            Some("application/json; charset=utf-8")
+        );
+        assert_eq!(raw_header(&raw, "X-TC-Action"), Some("ChatCompletions"));
+        assert_eq!(raw_header(&raw, "X-Custom"), Some("keep"));
+        assert!(raw_header(&raw, "Authorization")
+            .unwrap()
+            .contains("SignedHeaders=content-type;host"));
+    }
+
+    #[tokio::test]
+    async fn test_base_url_path_prefix_is_sent_and_signed() {
+        use sha2::{Digest, Sha256};
+
+        // Plain HTTP, with the prefix on the request line.
+        let (endpoint, server) = serve_once(Duration::ZERO, 200, chat_ok_body()).await;
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .base_url(format!("{}/tencent/hunyuan", endpoint))
+            .debug(false)
+            .build();
+        client.chat_completions(&chat_request()).await.unwrap();
+        let raw = server.await.unwrap();
+        assert!(
+            raw.starts_with("POST /tencent/hunyuan HTTP/1.1\r\n"),
+            "{}",
+            raw
+        );
+
+        let timestamp = 1_704_164_645;
+        for (canonical_uri, expected) in [(None, "/tencent/hunyuan"), (Some("/"), "/")] {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_json(200, &fixtures::chat_completions_response("hi")),
+            );
+            let builder = ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport.clone())
+                .clock(Arc::new(crate::clock::FixedClock(timestamp)))
+                .base_url("https://api.internal.corp:8443/tencent/hunyuan")
+                .debug(false);
+            let client = match canonical_uri {
+                Some(uri) => builder.canonical_uri(uri),
+                None => builder,
+            }
+            .build();
+            assert_eq!(
+                client.url(),
+                "https://api.internal.corp:8443/tencent/hunyuan"
+            );
+            assert_eq!(client.canonical_uri(), expected);
+            client.chat_completions(&chat_request()).await.unwrap();
+
+            let prepared = transport.requests().remove(0);
+            assert_eq!(
+                prepared.url,
+                "https://api.internal.corp:8443/tencent/hunyuan"
+            );
+            let canonical = format!(
+                "POST\n{}\n\ncontent-type:application/json; charset=utf-8\n\
+                 host:api.internal.corp:8443\n\ncontent-type;host\n{}",
+                expected,
+                hex::encode(Sha256::digest(&prepared.body))
+            );
+            assert_eq!(
+                prepared.header("Authorization").unwrap(),
+                expected_authorization(timestamp, &canonical)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "must not have a query string")]
+    fn test_base_url_rejects_query_string() {
+        let _ = ClientBuilder::new().base_url("https://api.internal.corp/hunyuan?tenant=a");
+    }
+
+    #[tokio::test]
+    async fn test_signed_headers_validate_when_sent_by_another_http_stack() {
+        use sha2::{Digest, Sha256};
+
+        let timestamp = 1_704_164_645;
+        let (endpoint, server) = serve_once(Duration::ZERO, 200, chat_ok_body()).await;
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .endpoint(endpoint)
+            .clock(Arc::new(crate::clock::FixedClock(timestamp)))
+            .debug(false)
+            .build();
+        let body = serde_json::to_vec(&chat_request()).unwrap();
+        let headers = client.signed_headers_for("ChatCompletions", &body).unwrap();
+        for name in [
+            "Host",
+            "Content-Type",
+            "X-TC-Action",
+            "X-TC-Timestamp",
+            "Authorization",
+        ] {
+            assert!(headers.contains_key(name), "missing {}", name);
+        }
+
+        // Sent by a plain reqwest client, as a user-owned stack would.
+        reqwest::Client::new()
+            .post(client.url())
+            .headers(headers.clone())
+            .body(body.clone())
+            .send()
+            .await
+            .unwrap();
+        let raw = server.await.unwrap();
+
+        // Verify as the service would, from the request as received.
+        let (_, received_body) = raw.split_once("\r\n\r\n").unwrap();
+        assert_eq!(received_body.as_bytes(), &body[..]);
+        let canonical = format!(
+            "POST\n/\n\ncontent-type:{}\nhost:{}\n\ncontent-type;host\n{}",
+            raw_header(&raw, "Content-Type").unwrap(),
+            raw_header(&raw, "Host").unwrap(),
+            hex::encode(Sha256::digest(received_body.as_bytes()))
+        );
+        let sent_at = raw_header(&raw, "X-TC-Timestamp").unwrap().parse().unwrap();
+        assert_eq!(
+            raw_header(&raw, "Authorization").unwrap(),
+            expected_authorization(sent_at, &canonical)
+        );
+
+        // The same headers the client sends for a call of its own.
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("hi")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .clock(Arc::new(crate::clock::FixedClock(timestamp)))
+            .debug(false)
+            .build();
+        let _: serde_json::Value = client
+            .call("ChatCompletions", &chat_request(), &RequestOptions::new())
+            .await
+            .unwrap();
+        let sent = transport.requests().remove(0);
+        let body = serde_json::to_vec(&chat_request()).unwrap();
+        assert_eq!(sent.body, body);
+        assert_eq!(
+            client.signed_headers_for("ChatCompletions", &body).unwrap(),
+            sent.headers
+        );
+        assert!(matches!(
+            client.signed_headers_for("ChatCompletions", &[0xff]),
+            Err(SdkError::Signing(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_custom_transport_sees_signed_request() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("hi")),
+        );
+        let client = ClientBuilder::new()
+            .credential(Credential {
+                token: Some("session_token".to_string()),
+                ..test_credential()
+            })
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+
+        let resp = client.chat_completions(&chat_request()).await.unwrap();
+        assert_eq!(resp.response.id.as_deref(), Some("mock-id"));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        let req = &requests[0];
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.url, "https://hunyuan.tencentcloudapi.com/");
+        assert_eq!(req.headers.get("X-TC-Action").unwrap(), "ChatCompletions");
+        assert_eq!(req.headers.get("X-TC-Token").unwrap(), "session_token");
+        let auth = req.headers.get("Authorization").unwrap().to_str().unwrap();
+        assert!(auth.starts_with("TC3-HMAC-SHA256 Credential=test_id/"));
+        assert!(auth.contains("SignedHeaders=content-type;host, Signature="));
+        let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+        assert_eq!(body["Model"], "hunyuan-lite");
+    }
+
+    #[tokio::test]
+    async fn test_stream_goes_through_custom_transport() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_sse(ChunkSequence::from_text("Hello", 3).events()),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+
+        let stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        let text: String = chunks
+            .iter()
+            .map(|c| {
+                c.as_ref().unwrap().choices.as_ref().unwrap()[0]
+                    .delta
+                    .as_ref()
+                    .unwrap()
+                    .content
+                    .clone()
+                    .unwrap_or_default()
+            })
+            .collect();
+        assert_eq!(text, "Hello");
+
+        let requests = transport.requests();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["Stream"], true);
+        assert!(requests[0].headers.contains_key("Authorization"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_sequenced_responses() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_error(500, "InternalError", "try again")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let req = fixtures::chat_request("Hello");
+
+        match client.chat_completions(&req).await {
+            Err(SdkError::Service {
+                code, request_id, ..
+            }) => {
+                assert_eq!(code, "InternalError");
+                assert_eq!(request_id.as_deref(), Some("mock-request-id"));
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        assert!(client.chat_completions(&req).await.is_ok());
+        assert!(client.chat_completions(&req).await.is_ok());
+        transport.assert_called("ChatCompletions", 3);
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_matches_body_and_headers() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .match_header("X-TC-Region", "ap-beijing")
+                .match_body_json(&serde_json::json!({ "Messages": [{ "Content": "Hello" }] }))
+                .respond_json(200, &fixtures::chat_completions_response("matched")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .region(Region::ApBeijing)
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+
+        let resp = client
+            .chat_completions(&fixtures::chat_request("Hello"))
+            .await
+            .unwrap();
+        let choices = resp.response.choices.unwrap();
+        assert_eq!(
+            choices[0].message.as_ref().unwrap().content.as_deref(),
+            Some("matched")
+        );
+
+        match client
+            .chat_completions(&fixtures::chat_request("Bye"))
+            .await
+        {
+            Err(SdkError::Service { code, .. }) => {
+                assert_eq!(code, "MockTransport.NoMatchingExpectation")
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_per_call_version_override() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let body = serde_json::to_value(fixtures::chat_request("Hello")).unwrap();
+
+        let raw = client
+            .call_raw(
+                "ChatCompletions",
+                &body,
+                &RequestOptions::new().version("2024-01-01"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(raw["Response"]["Id"], "mock-id");
+        client
+            .call_raw("ChatCompletions", &body, &RequestOptions::default())
+            .await
+            .unwrap();
+        client
+            .chat_completions(&fixtures::chat_request("Hello"))
+            .await
+            .unwrap();
+
+        let versions: Vec<_> = transport
+            .requests()
+            .iter()
+            .map(|r| r.header("X-TC-Version").unwrap().to_string())
+            .collect();
+        assert_eq!(versions, ["2024-01-01", "2023-09-01", "2023-09-01"]);
+    }
+
+    /// Application code that only knows about `dyn HunyuanApi`.
+    async fn first_reply(api: &dyn HunyuanApi) -> (Option<String>, String) {
+        let req = fixtures::chat_request("Hello");
+        let unary = api.chat_completions(&req).await.unwrap();
+        let unary = unary.response.choices.unwrap()[0]
+            .message
+            .as_ref()
+            .unwrap()
+            .content
+            .clone();
+        let mut streamed = String::new();
+        let mut stream = api.chat_completions_stream(&req).await.unwrap();
+        while let Some(chunk) = stream.next().await {
+            for choice in chunk.unwrap().choices.unwrap_or_default() {
+                if let Some(content) = choice.delta.and_then(|d| d.content) {
+                    streamed.push_str(&content);
+                }
+            }
+        }
+        (unary, streamed)
+    }
+
+    #[tokio::test]
+    async fn test_hunyuan_api_substitution() {
+        let fake = Arc::new(FakeHunyuan::new("fake reply"));
+        let api: Arc<dyn HunyuanApi> = fake.clone();
+        assert_eq!(
+            first_reply(api.as_ref()).await,
+            (Some("fake reply".to_string()), "fake reply".to_string())
+        );
+        assert_eq!(fake.requests().len(), 2);
+        assert_eq!(fake.requests()[0].messages[0].content, "Hello");
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .match_body_json(&serde_json::json!({ "Stream": true }))
+                .respond_sse(ChunkSequence::from_text("real reply", 4).events())
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("real reply")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let api: Arc<dyn HunyuanApi> = Arc::new(client);
+        assert_eq!(
+            first_reply(api.as_ref()).await,
+            (Some("real reply".to_string()), "real reply".to_string())
+        );
+        transport.assert_called("ChatCompletions", 2);
+    }
+
+    #[tokio::test]
+    async fn test_sorted_keys_body_is_deterministic_and_sent_as_signed() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .body_serializer(Arc::new(crate::serializer::SortedKeysJson))
+            .debug(false)
+            .build();
+        let req = ChatCompletionsRequest {
+            model: Some("hunyuan-lite".to_string()),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: "Hello".to_string(),
+                ..Default::default()
+            }],
+            temperature: Some(0.5),
+            top_p: Some(0.25),
+            stream: Some(false),
+            tools: None,
+            n: None,
+            seed: None,
+        };
+
+        client.chat_completions(&req).await.unwrap();
+        client.chat_completions(&req).await.unwrap();
+
+        let requests = transport.requests();
+        let expected = r#"{"Messages":[{"Content":"Hello","Role":"user"}],"Model":"hunyuan-lite","Stream":false,"Temperature":0.5,"TopP":0.25}"#;
+        assert_eq!(std::str::from_utf8(&requests[0].body).unwrap(), expected);
+        assert_eq!(requests[0].body, requests[1].body);
+    }
+
+    #[tokio::test]
+    async fn test_body_serializers_keep_f32_sampling_values_short() {
+        let serializers: [Arc<dyn crate::BodySerializer>; 2] = [
+            Arc::new(crate::serializer::CompactJson),
+            Arc::new(crate::serializer::SortedKeysJson),
+        ];
+        for serializer in serializers {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_json(200, &fixtures::chat_completions_response("ok")),
+            );
+            let client = ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport.clone())
+                .body_serializer(serializer)
+                .debug(false)
+                .build();
+            let req = chat_request().with_temperature(0.7).with_top_p(0.9);
+
+            client.chat_completions(&req).await.unwrap();
+
+            let body = String::from_utf8(transport.requests()[0].body.to_vec()).unwrap();
+            assert!(body.contains(r#""Temperature":0.7,"#), "{}", body);
+            assert!(body.contains(r#""TopP":0.9"#), "{}", body);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixture_builders_round_trip_through_client() {
+        use crate::models::{FinishReason, GetEmbeddingRequest};
+
+        let chunks = ChunkSequence::from_text("你好，世界", 2)
+            .finish_reason(FinishReason::Length)
+            .usage(3, 4)
+            .chunks();
+        assert_eq!(chunks.len(), 3);
+        let last = &chunks[2].choices.as_ref().unwrap()[0];
+        assert_eq!(last.delta.as_ref().unwrap().content.as_deref(), Some("界"));
+        assert_eq!(last.finish_reason.as_deref(), Some("length"));
+        assert_eq!(chunks[2].usage.as_ref().unwrap().total_tokens, Some(7));
+        assert!(chunks[0].usage.is_none());
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(
+                    200,
+                    &ChatResponseFixture::new()
+                        .content("stopped")
+                        .finish_reason(FinishReason::Sensitive)
+                        .usage(10, 5)
+                        .build(),
+                )
+                .expect_action("GetEmbedding")
+                .respond_json(
+                    200,
+                    &EmbeddingResponseFixture::new()
+                        .embedding(vec![0.5, -0.5])
+                        .embedding(vec![0.25, 0.75])
+                        .usage(6)
+                        .build(),
+                ),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+
+        let resp = client.chat_completions(&chat_request()).await.unwrap();
+        let choice = &resp.response.choices.unwrap()[0];
+        assert_eq!(choice.finish_reason.as_deref(), Some("sensitive"));
+        assert_eq!(resp.response.usage.unwrap().total_tokens, Some(15));
+
+        let req = GetEmbeddingRequest {
+            input_list: Some(vec!["a".to_string(), "b".to_string()]),
+            ..Default::default()
+        };
+        let data = client
+            .get_embedding(&req)
+            .await
+            .unwrap()
+            .response
+            .data
+            .unwrap();
+        assert_eq!(data[1].index, Some(1));
+        assert_eq!(data[1].embedding.as_deref(), Some(&[0.25, 0.75][..]));
+        let body: serde_json::Value =
+            serde_json::from_slice(&transport.requests_for("GetEmbedding")[0].body).unwrap();
+        assert_eq!(body, serde_json::json!({ "InputList": ["a", "b"] }));
+
+        let fake = FakeHunyuan::new("unused");
+        let api: &dyn HunyuanApi = &fake;
+        let resp = api.get_embedding(&req).await.unwrap();
+        assert_eq!(resp.response.data.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_embedding_through_client_and_api_trait() {
+        use crate::models::{GetEmbeddingRequest, GetEmbeddingResponse};
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("GetEmbedding")
+                .match_body_json(&serde_json::json!({ "Input": "hello" }))
+                .respond_json(
+                    200,
+                    &EmbeddingResponseFixture::new()
+                        .embedding(vec![0.5, -0.25])
+                        .usage(3)
+                        .request_id("req-embed")
+                        .build(),
+                )
+                .expect_action("GetEmbedding")
+                .match_body_json(&serde_json::json!({ "Input": "world" }))
+                .respond_json(
+                    200,
+                    &EmbeddingResponseFixture::new()
+                        .embedding(vec![1.0, 0.0])
+                        .build(),
+                ),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let req = GetEmbeddingRequest {
+            input: Some("hello".to_string()),
+            ..Default::default()
+        };
+
+        let resp = client.get_embedding(&req).await.unwrap().response;
+        assert_eq!(resp.request_id.as_deref(), Some("req-embed"));
+        let data = resp.data.unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].index, Some(0));
+        assert_eq!(data[0].embedding.as_deref(), Some(&[0.5, -0.25][..]));
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, Some(3));
+        assert_eq!(usage.total_tokens, Some(3));
+
+        let api: &dyn HunyuanApi = &client;
+        let req = GetEmbeddingRequest {
+            input: Some("world".to_string()),
+            ..Default::default()
+        };
+        let resp: GetEmbeddingResponse = api.get_embedding(&req).await.unwrap();
+        assert_eq!(
+            resp.response.data.unwrap()[0].embedding.as_deref(),
+            Some(&[1.0, 0.0][..])
+        );
+        transport.assert_called("GetEmbedding", 2);
+    }
+
+    #[test]
+    fn test_get_embedding_models_match_the_wire_format() {
+        use crate::models::{GetEmbeddingRequest, GetEmbeddingResponse};
+
+        let req = GetEmbeddingRequest {
+            input_list: Some(vec!["a".to_string()]),
+            input_type: Some(crate::models::INPUT_TYPE_QUERY.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&req).unwrap(),
+            serde_json::json!({ "InputList": ["a"], "InputType": "query" })
+        );
+
+        let resp: GetEmbeddingResponse = serde_json::from_value(serde_json::json!({
+            "Response": {
+                "RequestId": "req-1",
+                "Data": [{ "Embedding": [0.125, 2.0], "Index": 0, "Object": "embedding" }],
+                "Usage": { "PromptTokens": 2, "TotalTokens": 2 }
+            }
+        }))
+        .unwrap();
+        let inner = resp.response;
+        assert_eq!(inner.request_id.as_deref(), Some("req-1"));
+        let data = &inner.data.as_ref().unwrap()[0];
+        assert_eq!(data.embedding.as_deref(), Some(&[0.125, 2.0][..]));
+        assert_eq!(data.index, Some(0));
+        assert_eq!(data.object.as_deref(), Some("embedding"));
+        assert_eq!(inner.usage.unwrap().total_tokens, Some(2));
+    }
+
+    #[test]
+    fn test_finish_reason_wire_values() {
+        use crate::models::FinishReason;
+
+        for (reason, wire) in [
+            (FinishReason::Stop, "stop"),
+            (FinishReason::Length, "length"),
+            (FinishReason::Sensitive, "sensitive"),
+            (FinishReason::ToolCalls, "tool_calls"),
+        ] {
+            assert_eq!(reason.as_str(), wire);
+            let resp = ChatResponseFixture::new().finish_reason(reason).build();
+            let choice = &resp.response.choices.as_ref().unwrap()[0];
+            assert_eq!(choice.finish_reason.as_deref(), Some(wire));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_citation_offsets_survive_accumulation() {
+        let events = [
+            r#"{"Id":"e-1","Choices":[{"Index":0,"Delta":{"Role":"assistant","Content":"Rust 1.0 "}}],"SearchInfo":{"SearchResults":[{"Index":1,"Title":"Announcing Rust 1.0","Url":"https://blog.rust-lang.org/2015/05/15/Rust-1.0.html"}]}}"#,
+            r#"{"Id":"e-1","Choices":[{"Index":0,"Delta":{"Content":"于 2015 年发布。"}}],"SearchInfo":{"Citations":[{"Index":1,"Offset":20}]}}"#,
+            r#"{"Id":"e-1","Choices":[{"Index":0,"Delta":{"Content":"它强调内存安全。"}}],"SearchInfo":{"SearchResults":[{"Index":2,"Title":"The Rust Book","Url":"https://doc.rust-lang.org/book/"}],"Citations":[{"Index":1,"Offset":20},{"Index":2,"Offset":28}]}}"#,
+            r#"{"Id":"e-1","Choices":[{"Index":0,"Delta":{"Content":""},"FinishReason":"stop"}],"SearchInfo":{"Citations":[{"Index":2,"Offset":28},{"Index":1,"Offset":28}]}}"#,
+        ];
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_sse(events),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .debug(false)
+            .build();
+
+        let mut stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        let mut acc = crate::ChatCompletionsAccumulator::new();
+        let mut seen_offsets = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.unwrap();
+            if let Some(info) = &chunk.search_info {
+                seen_offsets.extend(info.citations.iter().flatten().map(|c| c.offset));
+            }
+            acc.push(&chunk).unwrap();
+        }
+
+        assert_eq!(seen_offsets, [20, 20, 28, 28, 28]);
+        let content = acc.content().to_string();
+        assert_eq!(content, "Rust 1.0 于 2015 年发布。它强调内存安全。");
+        assert_eq!(
+            content.chars().take(20).collect::<String>(),
+            "Rust 1.0 于 2015 年发布。"
+        );
+        assert_eq!(acc.citations().len(), 3);
+        let map = acc.citation_map();
+        let urls = |offset| -> Vec<&str> {
+            map[&offset]
+                .iter()
+                .map(|r| r.url.as_deref().unwrap())
+                .collect()
+        };
+        assert_eq!(
+            urls(20),
+            ["https://blog.rust-lang.org/2015/05/15/Rust-1.0.html"]
+        );
+        assert_eq!(
+            urls(28),
+            [
+                "https://doc.rust-lang.org/book/",
+                "https://blog.rust-lang.org/2015/05/15/Rust-1.0.html"
+            ]
+        );
+
+        let resp = acc.into_response();
+        assert_eq!(resp.response.id.as_deref(), Some("e-1"));
+        let info = resp.response.search_info.unwrap();
+        assert_eq!(info.search_results.unwrap().len(), 2);
+        assert_eq!(info.citations.unwrap().len(), 3);
+    }
+
+    fn retrying_client(transport: Arc<MockTransport>, policy: crate::RetryPolicy) -> Client {
+        ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .retry_policy(policy)
+            .debug(false)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_from_injected_faults() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::ConnectionReset)
+                .respond_fault(Fault::RateLimited {
+                    retry_after_secs: 0,
+                })
+                .respond_json(200, &fixtures::chat_completions_response("cut"))
+                .truncate_body(10)
+                .respond_json(200, &fixtures::chat_completions_response("recovered"))
+                .with_latency(Duration::from_millis(60)),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .retry_policy(crate::RetryPolicy::new(3).initial_backoff(Duration::from_millis(1)))
+            .slow_request_threshold(Duration::from_millis(20))
+            .log_sink(Arc::new(CaptureSink::default()))
+            .debug(false)
+            .build();
+
+        let (resp, meta) = client
+            .chat_completions_with_meta(&chat_request())
+            .await
+            .unwrap();
+        let choice = &resp.response.choices.unwrap()[0];
+        assert_eq!(
+            choice.message.as_ref().unwrap().content.as_deref(),
+            Some("recovered")
+        );
+        assert_eq!(meta.attempts, 4);
+        assert!(meta.slow);
+        transport.assert_called("ChatCompletions", 4);
+    }
+
+    #[tokio::test]
+    async fn test_prompt_sanitizer_redacts_before_signing() {
+        use sha2::{Digest, Sha256};
+
+        let timestamp = 1_704_164_645;
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("hi")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .clock(Arc::new(crate::clock::FixedClock(timestamp)))
+            .prompt_sanitizer(|text| {
+                text.split(' ')
+                    .map(|word| match word.contains('@') {
+                        true => "[email]",
+                        false => word,
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .debug(false)
+            .build();
+
+        let mut req = chat_request();
+        req.messages[0].content = "Mail ann@example.com about it".to_string();
+        client.chat_completions(&req).await.unwrap();
+        assert_eq!(req.messages[0].content, "Mail ann@example.com about it");
+
+        let prepared = transport.requests().remove(0);
+        let sent: serde_json::Value = serde_json::from_slice(&prepared.body).unwrap();
+        assert_eq!(sent["Messages"][0]["Content"], "Mail [email] about it");
+        assert!(!String::from_utf8_lossy(&prepared.body).contains("ann@example.com"));
+
+        // The signature covers the sanitized body.
+        let canonical = format!(
+            "POST\n/\n\ncontent-type:application/json; charset=utf-8\n\
+             host:hunyuan.tencentcloudapi.com\n\ncontent-type;host\n{}",
+            hex::encode(Sha256::digest(&prepared.body))
+        );
+        assert_eq!(
+            prepared.header("Authorization").unwrap(),
+            expected_authorization(timestamp, &canonical)
+        );
+    }
+
+    #[test]
+    fn test_compare_responses() {
+        use crate::evaluation::{compare, UsageDelta};
+        use crate::models::FinishReason;
+
+        let a = ChatResponseFixture::new()
+            .content("The capital of France is Paris.")
+            .usage(12, 8)
+            .build();
+        let b = ChatResponseFixture::new()
+            .content("The capital of France is Paris, on the Seine, which")
+            .finish_reason(FinishReason::Length)
+            .usage(12, 20)
+            .build();
+
+        let comparison = compare(&a, &b);
+        assert_eq!(
+            comparison.usage_delta,
+            UsageDelta {
+                prompt_tokens: Some(0),
+                completion_tokens: Some(12),
+                total_tokens: Some(12),
+            }
+        );
+        assert_eq!(
+            comparison.finish_reasons,
+            (Some("stop".to_string()), Some("length".to_string()))
+        );
+        assert!(!comparison.same_finish_reason());
+        assert_eq!(comparison.content_lengths, (31, 51));
+        assert_eq!(comparison.content_length_delta(), 20);
+        assert!(
+            comparison.similarity > 0.6 && comparison.similarity < 1.0,
+            "{}",
+            comparison.similarity
+        );
+
+        let same = compare(&a, &a);
+        assert_eq!(same.similarity, 1.0);
+        assert_eq!(same.usage_delta.total_tokens, Some(0));
+        let unrelated = compare(&a, &ChatResponseFixture::new().content("巴黎").build());
+        assert_eq!(unrelated.similarity, 0.0);
+        assert_eq!(unrelated.usage_delta, UsageDelta::default());
+    }
+
+    #[tokio::test]
+    async fn test_seed_round_trips_and_reaches_meta() {
+        let req = chat_request().with_seed(42);
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["Seed"], 42);
+        assert_eq!(
+            serde_json::from_value::<ChatCompletionsRequest>(json).unwrap(),
+            req
+        );
+        assert!(serde_json::to_value(chat_request())
+            .unwrap()
+            .get("Seed")
+            .is_none());
+
+        let mut echoed = serde_json::to_value(fixtures::chat_completions_response("hi")).unwrap();
+        echoed["Response"]["Seed"] = 7.into();
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("hi"))
+                .respond_json(200, &echoed)
+                .respond_json(200, &fixtures::chat_completions_response("hi")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+
+        // The seed sent, unless the server reports the one it used.
+        let (_, meta) = client.chat_completions_with_meta(&req).await.unwrap();
+        assert_eq!(meta.seed, Some(42));
+        let (_, meta) = client.chat_completions_with_meta(&req).await.unwrap();
+        assert_eq!(meta.seed, Some(7));
+        let (_, meta) = client
+            .chat_completions_with_meta(&chat_request())
+            .await
+            .unwrap();
+        assert_eq!(meta.seed, None);
+
+        let sent: serde_json::Value =
+            serde_json::from_slice(&transport.requests()[0].body).unwrap();
+        assert_eq!(sent["Seed"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_strict_mode_names_undeclared_fields() {
+        use crate::DeserializationMode;
+
+        let mut body = serde_json::to_value(fixtures::chat_completions_response("hi")).unwrap();
+        body["Response"]["Choices"][0]["Message"]["Audio"] = "future field".into();
+        body["Response"]["Usage"]["CachedTokens"] = 3.into();
+        let client = |mode| {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_json(200, &body),
+            );
+            ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport)
+                .deserialization_mode(mode)
+                .debug(false)
+                .build()
+        };
+
+        let resp = client(DeserializationMode::Lenient)
+            .chat_completions(&chat_request())
+            .await
+            .unwrap();
+        let message = resp.messages().next().unwrap();
+        assert_eq!(message.content.as_deref(), Some("hi"));
+        assert_eq!(message.extra["Audio"], "future field");
+
+        let err = client(DeserializationMode::Strict)
+            .chat_completions(&chat_request())
+            .await
+            .unwrap_err();
+        let SdkError::Decode(message) = &err else {
+            panic!("{:?}", err);
+        };
+        assert_eq!(
+            message,
+            "ChatCompletions response has fields the SDK does not declare: \
+             Response.Choices[0].Message.Audio, Response.Usage.CachedTokens"
+        );
+        assert!(!err.is_retryable());
+
+        // Declared fields alone pass, and untyped calls are never checked.
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("hi"))
+                .respond_json(200, &body),
+        );
+        let strict = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .deserialization_mode(DeserializationMode::Strict)
+            .debug(false)
+            .build();
+        assert!(strict.chat_completions(&chat_request()).await.is_ok());
+        let raw = strict
+            .call_raw(
+                "ChatCompletions",
+                &serde_json::json!({}),
+                &RequestOptions::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(raw, body);
+    }
+
+    #[tokio::test]
+    async fn test_strict_mode_rejects_duplicated_choice_indices() {
+        use crate::DeserializationMode;
+
+        let with_indices = |indices: &[u32]| {
+            let mut body = serde_json::to_value(fixtures::chat_completions_response("hi")).unwrap();
+            let choice = body["Response"]["Choices"][0].clone();
+            body["Response"]["Choices"] = indices
+                .iter()
+                .map(|&i| {
+                    let mut choice = choice.clone();
+                    choice["Index"] = i.into();
+                    choice
+                })
+                .collect();
+            body
+        };
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &with_indices(&[0, 0]))
+                .respond_json(200, &with_indices(&[1, 2]))
+                .respond_json(200, &with_indices(&[1, 0]))
+                .respond_json(200, &with_indices(&[0, 0])),
+        );
+        let client = |mode| {
+            ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport.clone())
+                .deserialization_mode(mode)
+                .debug(false)
+                .build()
+        };
+        let strict = client(DeserializationMode::Strict);
+
+        let err = strict.chat_completions(&chat_request()).await.unwrap_err();
+        let SdkError::UnexpectedResponse {
+            status, snippet, ..
+        } = &err
+        else {
+            panic!("{:?}", err);
+        };
+        assert_eq!(*status, 200);
+        assert_eq!(
+            snippet,
+            "choice indices [0, 0] are not unique and contiguous from 0"
+        );
+        assert!(!err.is_retryable());
+        assert!(matches!(
+            strict.chat_completions(&chat_request()).await,
+            Err(SdkError::UnexpectedResponse { .. })
+        ));
+        // Any order of 0..n passes.
+        let resp = strict.chat_completions(&chat_request()).await.unwrap();
+        assert_eq!(resp.response.choices.unwrap().len(), 2);
+
+        // Not checked unless opted in.
+        let resp = client(DeserializationMode::Lenient)
+            .chat_completions(&chat_request())
+            .await
+            .unwrap();
+        assert_eq!(resp.response.choices.unwrap().len(), 2);
+        assert_eq!(transport.requests().len(), 4);
+    }
+
+    /// Compact JSON serializer counting how often it runs.
+    #[derive(Default)]
+    struct CountingSerializer(std::sync::atomic::AtomicUsize);
+
+    impl crate::BodySerializer for CountingSerializer {
+        fn serialize(&self, body: &serde_json::Value) -> crate::SdkResult<String> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            crate::serializer::CompactJson.serialize(body)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_body_serialized_once_across_retries() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::ConnectionReset)
+                .respond_fault(Fault::ConnectionReset)
+                .respond_json(200, &fixtures::chat_completions_response("ok"))
+                .respond_fault(Fault::ConnectionReset)
+                .respond_fault(Fault::ConnectionReset)
+                .respond_sse(ChunkSequence::from_text("ok", 2).events()),
+        );
+        let serializer = Arc::new(CountingSerializer::default());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .body_serializer(serializer.clone())
+            .retry_policy(crate::RetryPolicy::new(2).initial_backoff(Duration::ZERO))
+            .debug(false)
+            .build();
+        let count = || serializer.0.load(std::sync::atomic::Ordering::SeqCst);
+
+        let (_, meta) = client
+            .chat_completions_with_meta(&chat_request())
+            .await
+            .unwrap();
+        assert_eq!(meta.attempts, 3);
+        assert_eq!(count(), 1);
+
+        client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        assert_eq!(count(), 2);
+
+        // Every attempt sent the same body; only the signature may differ.
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 6);
+        assert!(requests[..3].iter().all(|r| r.body == requests[0].body));
+        assert!(requests[3..].iter().all(|r| r.body == requests[3].body));
+        // ...from the same buffer, without copying it per attempt.
+        assert!(requests[..3]
+            .iter()
+            .all(|r| r.body.as_ptr() == requests[0].body.as_ptr()));
+        assert!(requests[3..]
+            .iter()
+            .all(|r| r.body.as_ptr() == requests[3].body.as_ptr()));
+    }
+
+    #[tokio::test]
+    async fn test_action_metadata() {
+        use crate::action::Action;
+
+        let chat = Action::ChatCompletions;
+        assert_eq!(chat.name(), "ChatCompletions");
+        assert_eq!(chat.method(), "POST");
+        assert!(chat.streams());
+        assert!(!Action::GetEmbedding.streams());
+        for action in Action::ALL {
+            assert_eq!(action.to_string().parse::<Action>(), Ok(*action));
+        }
+        assert!("NoSuchAction".parse::<Action>().is_err());
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("GetTokenCount")
+                .respond_json(200, &serde_json::json!({ "Response": { "TokenCount": 3 } })),
+        );
+        let client = retrying_client(transport.clone(), crate::RetryPolicy::new(0));
+        client
+            .call_raw(
+                Action::GetTokenCount,
+                &serde_json::json!({}),
+                &RequestOptions::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(transport.requests()[0].method, "POST");
+    }
+
+    #[tokio::test]
+    async fn test_request_serialized_once_across_retries_without_body_serializer() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// A request counting how often it is serialized.
+        struct Counting(AtomicUsize);
+
+        impl serde::Serialize for Counting {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                chat_request().serialize(serializer)
+            }
+        }
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::ConnectionReset)
+                .respond_status(503, "busy")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = retrying_client(
+            transport.clone(),
+            crate::RetryPolicy::new(2).initial_backoff(Duration::ZERO),
+        );
+        let req = Counting(AtomicUsize::new(0));
+        let _: serde_json::Value = client
+            .call("ChatCompletions", &req, &RequestOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(req.0.load(Ordering::SeqCst), 1);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|r| r.body.as_ptr() == requests[0].body.as_ptr()));
+    }
+
+    #[tokio::test]
+    async fn test_each_retry_attempt_is_signed_with_a_fresh_timestamp() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+
+        /// A clock advancing five minutes every time it is read, as if each
+        /// attempt waited that long in backoff and queueing.
+        struct Stepping(AtomicI64);
+
+        impl crate::clock::Clock for Stepping {
+            fn unix_timestamp(&self) -> i64 {
+                self.0.fetch_add(300, Ordering::SeqCst)
+            }
+        }
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::ConnectionReset)
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .retry_policy(crate::RetryPolicy::new(1).initial_backoff(Duration::ZERO))
+            .clock(Arc::new(Stepping(AtomicI64::new(1_700_000_000))))
+            .debug(false)
+            .build();
+        client.chat_completions(&chat_request()).await.unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        let timestamp = |i: usize| -> i64 {
+            requests[i].headers["X-TC-Timestamp"]
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap()
+        };
+        assert!(timestamp(1) > timestamp(0));
+        assert_ne!(
+            requests[0].headers["Authorization"],
+            requests[1].headers["Authorization"]
+        );
+        assert_eq!(requests[0].body.as_ptr(), requests[1].body.as_ptr());
+    }
+
+    #[tokio::test]
+    async fn test_retry_if_retries_a_normally_final_error() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_error(200, "FailedOperation.EngineBusy", "try again")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let busy = |e: &SdkError| matches!(e, SdkError::Service { code, .. } if code == "FailedOperation.EngineBusy");
+        assert!(!SdkError::Service {
+            code: "FailedOperation.EngineBusy".to_string(),
+            message: String::new(),
+            request_id: None,
+        }
+        .is_retryable());
+
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .retry_policy(crate::RetryPolicy::new(2).initial_backoff(Duration::ZERO))
+            .retry_if(move |e| busy(e) || e.is_retryable())
+            .debug(false)
+            .build();
+        let (resp, meta) = client
+            .chat_completions_with_meta(&chat_request())
+            .await
+            .unwrap();
+        assert_eq!(meta.attempts, 2);
+        assert_eq!(
+            resp.messages().next().unwrap().content.as_deref(),
+            Some("ok")
+        );
+
+        // Without the predicate the error is final.
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_error(200, "FailedOperation.EngineBusy", "try again")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = retrying_client(
+            transport.clone(),
+            crate::RetryPolicy::new(2).initial_backoff(Duration::ZERO),
+        );
+        assert!(client.chat_completions(&chat_request()).await.is_err());
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_large_response_parsed_from_bytes_and_log_truncated() {
+        let embedding: Vec<f64> = (0..1024).map(|i| i as f64 / 1024.0).collect();
+        let resp = (0..64)
+            .fold(EmbeddingResponseFixture::new(), |f, _| {
+                f.embedding(embedding.clone())
+            })
+            .build();
+        let body_len = serde_json::to_vec(&resp).unwrap().len();
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("GetEmbedding")
+                .respond_json(200, &resp),
+        );
+        let logs = Arc::new(CaptureSink::default());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .log_sink(logs.clone())
+            .debug(true)
+            .build();
+
+        let parsed = client
+            .get_embedding(&crate::models::GetEmbeddingRequest {
+                input: Some("hello".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(parsed, resp);
+
+        let lines = logs.lines(LogLevel::Debug);
+        let logged = lines
+            .iter()
+            .find(|l| l.starts_with("[hunyuan-sdk][response] status=200"))
+            .unwrap();
+        assert!(body_len > 500_000);
+        assert!(logged.len() < 4200, "{} bytes logged", logged.len());
+        assert!(logged.ends_with(&format!("... ({} bytes)", body_len)));
+    }
+
+    #[test]
+    fn test_unexpected_response_snippet_decodes_only_the_start() {
+        let body = format!("  <html>{}</html>", "错误".repeat(100_000));
+        let err = crate::client::unexpected_response(502, None, body.as_bytes()).unwrap();
+        match err {
+            SdkError::UnexpectedResponse { snippet, .. } => {
+                assert!(snippet.starts_with("<html>错误"));
+                assert_eq!(snippet.chars().count(), 203);
+                assert!(snippet.ends_with("..."));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(crate::client::unexpected_response(200, None, b" {\"Response\":{}}").is_none());
+    }
+
+    #[test]
+    fn test_response_note_round_trip() {
+        use crate::models::ChatCompletionsResponse;
+
+        let json = serde_json::json!({"Response": {
+            "RequestId": "r-1",
+            "Id": "c-1",
+            "Choices": [],
+            "Usage": null,
+            "Note": "以上内容为AI生成，不代表开发者立场",
+        }});
+        let resp: ChatCompletionsResponse = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(resp.note(), Some("以上内容为AI生成，不代表开发者立场"));
+        assert!(resp.response.extra.is_empty());
+        assert_eq!(serde_json::to_value(&resp).unwrap(), json);
+
+        let mut json = json;
+        json["Response"].as_object_mut().unwrap().remove("Note");
+        let resp: ChatCompletionsResponse = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(resp.note(), None);
+        assert_eq!(serde_json::to_value(&resp).unwrap(), json);
+    }
+
+    #[test]
+    fn test_chat_choice_delta_and_message_round_trip() {
+        use crate::models::{ChatCompletionsChunk, ChatCompletionsResponse};
+
+        let frame = serde_json::json!({
+            "Id": "c-1",
+            "Created": 1700000000,
+            "Choices": [{
+                "Index": 0,
+                "Delta": {
+                    "Role": "assistant",
+                    "Content": "Hel",
+                    "ReasoningContent": "thinking",
+                    "ToolCalls": [{
+                        "Id": "call-1",
+                        "Type": "function",
+                        "Function": {"Name": "get_weather", "Arguments": "{\"ci"},
+                        "Index": 0,
+                    }],
+                    "Audio": "future field",
+                },
+                "FinishReason": null,
+            }],
+            "Usage": null,
+        });
+        let chunk: ChatCompletionsChunk = serde_json::from_value(frame.clone()).unwrap();
+        let delta = chunk.choices.as_ref().unwrap()[0].delta.as_ref().unwrap();
+        assert_eq!(delta.role.as_deref(), Some("assistant"));
+        assert_eq!(delta.content.as_deref(), Some("Hel"));
+        assert_eq!(delta.reasoning_content.as_deref(), Some("thinking"));
+        assert_eq!(
+            delta.tool_calls.as_ref().unwrap()[0].function.arguments,
+            "{\"ci"
+        );
+        assert_eq!(delta.extra["Audio"], "future field");
+        assert_eq!(serde_json::to_value(&chunk).unwrap(), frame);
+
+        // Later frames usually carry only the new text.
+        let frame = serde_json::json!({
+            "Id": "c-1",
+            "Created": 1700000000,
+            "Choices": [{"Index": 0, "Delta": {"Content": "lo"}, "FinishReason": "stop"}],
+            "Usage": null,
+        });
+        let chunk: ChatCompletionsChunk = serde_json::from_value(frame.clone()).unwrap();
+        let delta = chunk.choices.as_ref().unwrap()[0].delta.as_ref().unwrap();
+        assert_eq!(
+            (delta.role.as_deref(), delta.tool_calls.as_ref()),
+            (None, None)
+        );
+        assert_eq!(serde_json::to_value(&chunk).unwrap(), frame);
+
+        let full = serde_json::json!({"Response": {
+            "RequestId": "r-1",
+            "Id": "c-1",
+            "Created": 1700000000,
+            "Choices": [{
+                "Index": 0,
+                "Message": {
+                    "Role": "assistant",
+                    "Content": "Hello",
+                    "ToolCalls": [{
+                        "Id": "call-1",
+                        "Type": "function",
+                        "Function": {"Name": "get_weather", "Arguments": "{\"city\":\"Shenzhen\"}"},
+                    }],
+                },
+                "FinishReason": "tool_calls",
+            }],
+            "Usage": {"PromptTokens": 1, "CompletionTokens": 2, "TotalTokens": 3},
+        }});
+        let resp: ChatCompletionsResponse = serde_json::from_value(full.clone()).unwrap();
+        let message = resp.response.choices.as_ref().unwrap()[0]
+            .message
+            .as_ref()
+            .unwrap();
+        assert_eq!(message.content.as_deref(), Some("Hello"));
+        assert_eq!(
+            message.tool_calls.as_ref().unwrap()[0].function.name,
+            "get_weather"
+        );
+        assert_eq!(serde_json::to_value(&resp).unwrap(), full);
+    }
+
+    #[test]
+    fn test_response_messages_in_index_order() {
+        use crate::models::{ChatChoice, ChatChoiceMessage, ChatCompletionsResponseInner};
+
+        let choice = |index: u32, content: Option<&str>| ChatChoice {
+            index: Some(index),
+            message: content.map(|c| ChatChoiceMessage {
+                role: Some("assistant".to_string()),
+                content: Some(c.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let resp = crate::models::ChatCompletionsResponse {
+            response: ChatCompletionsResponseInner {
+                choices: Some(vec![
+                    choice(2, Some("third")),
+                    choice(1, None),
+                    choice(0, Some("first")),
+                    choice(3, Some("fourth")),
+                ]),
+                ..Default::default()
+            },
+        };
+
+        let contents: Vec<_> = resp
+            .messages()
+            .map(|m| m.content.as_deref().unwrap())
+            .collect();
+        assert_eq!(contents, ["first", "third", "fourth"]);
+        assert_eq!(
+            crate::models::ChatCompletionsResponse {
+                response: Default::default()
+            }
+            .messages()
+            .count(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trace_context_headers_sent_unsigned() {
+        use crate::trace::{TraceContext, TraceContextProvider};
+
+        struct FixedTrace;
+
+        impl TraceContextProvider for FixedTrace {
+            fn trace_context(&self) -> Option<TraceContext> {
+                Some(TraceContext::new(
+                    0x4bf92f3577b34da6a3ce929d0e0e4736,
+                    0x00f067aa0ba902b7,
+                    true,
+                ))
+            }
+        }
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .trace_context_provider(Arc::new(FixedTrace))
+            .debug(false)
+            .build();
+        let upstream =
+            TraceContext::parse("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-00")
+                .unwrap()
+                .with_tracestate("congo=t61rcWkgMzE")
+                .unwrap();
+        assert!(matches!(
+            upstream.clone().with_tracestate("congo=a\r\nX-Injected: 1"),
+            Err(SdkError::InvalidRequest(_))
+        ));
+
+        client.chat_completions(&chat_request()).await.unwrap();
+        client
+            .chat_completions_with_options(
+                &chat_request(),
+                &RequestOptions::new().trace_context(upstream),
+            )
+            .await
+            .unwrap();
+
+        let requests = transport.requests();
+        let traceparent = requests[0].header("traceparent").unwrap();
+        assert_eq!(
+            traceparent,
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+        let parts: Vec<&str> = traceparent.split('-').collect();
+        assert_eq!(
+            parts.iter().map(|p| p.len()).collect::<Vec<_>>(),
+            [2, 32, 16, 2]
+        );
+        assert!(parts.iter().all(|p| p
+            .bytes()
+            .all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase())));
+        assert_eq!(requests[0].header("tracestate"), None);
+        assert_eq!(
+            requests[1].header("traceparent"),
+            Some("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-00")
+        );
+        assert_eq!(requests[1].header("tracestate"), Some("congo=t61rcWkgMzE"));
+        for req in &requests {
+            assert!(req
+                .header("Authorization")
+                .unwrap()
+                .contains("SignedHeaders=content-type;host,"));
+        }
+
+        let ctx =
+            TraceContext::parse("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01").unwrap();
+        assert_eq!(ctx.trace_id(), "0af7651916cd43dd8448eb211c80319c");
+        assert_eq!(ctx.span_id(), "b7ad6b7169203331");
+        for invalid in [
+            "",
+            "01-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+            "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-0000000000000000-01",
+            "00-0AF7651916CD43DD8448EB211C80319C-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331",
+        ] {
+            assert!(
+                matches!(
+                    TraceContext::parse(invalid),
+                    Err(SdkError::InvalidRequest(_))
+                ),
+                "{:?}",
+                invalid
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_raw_response_into_bytes() {
+        let body = bytes::Bytes::from_static(b"{\"Response\":{}}");
+        let single = RawResponse::from_bytes(200, Default::default(), body.clone());
+        let read = single.into_bytes().await.unwrap();
+        assert_eq!(read, body);
+        assert_eq!(read.as_ptr(), body.as_ptr());
+
+        let chunks = ["{\"Resp", "onse\"", ":{}}"].map(|c| Ok(bytes::Bytes::from(c)));
+        let split = RawResponse {
+            status: 200,
+            headers: Default::default(),
+            body: Box::pin(futures_util::stream::iter(chunks)),
+        };
+        assert_eq!(split.bytes().await.unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_response_is_aborted() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Serves an endless HTML body in 64 KiB chunks, counting the chunks
+        /// read.
+        struct Gateway(Arc<AtomicUsize>);
+
+        impl Transport for Gateway {
+            fn execute(&self, _: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>> {
+                let read = self.0.clone();
+                let chunk = bytes::Bytes::from(vec![b'x'; 64 << 10]);
+                let body = futures_util::stream::repeat_with(move || {
+                    read.fetch_add(1, Ordering::SeqCst);
+                    Ok(chunk.clone())
+                });
+                let mut headers = reqwest::header::HeaderMap::new();
+                headers.insert("content-type", "text/html".parse().unwrap());
+                headers.insert("X-TC-RequestId", "req-big".parse().unwrap());
+                Box::pin(async move {
+                    Ok(RawResponse {
+                        status: 502,
+                        headers,
+                        body: Box::pin(body),
+                    })
+                })
+            }
+        }
+
+        let read = Arc::new(AtomicUsize::new(0));
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(Arc::new(Gateway(read.clone())))
+            .max_response_bytes(1 << 20)
+            .debug(false)
+            .build();
+        let err = client.chat_completions(&chat_request()).await.unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                SdkError::ResponseTooLarge {
+                    limit: 0x10_0000,
+                    received: 0x11_0000,
+                    status: Some(502),
+                    request_id: Some(id),
+                } if id == "req-big"
+            ),
+            "{:?}",
+            err
+        );
+        assert!(!err.is_retryable());
+        assert_eq!(read.load(Ordering::SeqCst), 17);
+
+        // A per-call limit replaces the client's.
+        let options = RequestOptions::new().max_response_bytes(4 << 20);
+        let err = client
+            .chat_completions_with_options(&chat_request(), &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SdkError::ResponseTooLarge {
+                limit: 0x40_0000,
+                ..
+            }
+        ));
+        assert_eq!(read.load(Ordering::SeqCst), 17 + 65);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_drains_in_flight_calls() {
+        use futures_util::FutureExt;
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("slow"))
+                .with_latency(Duration::from_secs(5))
+                .respond_sse(ChunkSequence::from_text("streamed", 2).events()),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+
+        let slow = tokio::spawn({
+            let client = client.clone();
+            async move { client.chat_completions(&chat_request()).await }
+        });
+        while client.in_flight() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let mut stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        assert_eq!(client.in_flight(), 2);
+
+        let mut shutdown = Box::pin(client.clone().shutdown());
+        assert!((&mut shutdown).now_or_never().is_none());
+        // New calls fail fast, on every clone.
+        let err = client
+            .clone()
+            .chat_completions(&chat_request())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::ClientClosed), "{err:?}");
+        assert!(matches!(
+            client.chat_completions_stream(&chat_request()).await,
+            Err(SdkError::ClientClosed)
+        ));
+        assert_eq!(transport.requests().len(), 2);
+
+        // A stream counts until it ends.
+        while stream.next().await.is_some() {}
+        assert_eq!(client.in_flight(), 1);
+        assert!((&mut shutdown).now_or_never().is_none());
+
+        assert!(slow.await.unwrap().is_ok());
+        assert_eq!(client.in_flight(), 0);
+        shutdown.await;
+        // Resolves at once when nothing is in flight.
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_cassette_records_then_replays() {
+        use crate::test_utils::cassette::CassetteTransport;
+
+        let path =
+            std::env::temp_dir().join(format!("hunyuan-cassette-{}.json", std::process::id()));
+        let client_with = |transport: Arc<dyn crate::transport::Transport>, timestamp| {
+            ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport)
+                .clock(Arc::new(crate::clock::FixedClock(timestamp)))
+                .debug(false)
+                .build()
+        };
+        let reply = |resp: crate::models::ChatCompletionsResponse| {
+            resp.response.choices.unwrap()[0]
+                .message
+                .as_ref()
+                .unwrap()
+                .content
+                .clone()
+        };
+
+        let inner = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("recorded")),
+        );
+        let recorder = Arc::new(CassetteTransport::record(&path, inner.clone()));
+        let client = client_with(recorder.clone(), 1_700_000_000);
+        let resp = client.chat_completions(&chat_request()).await.unwrap();
+        assert_eq!(reply(resp).as_deref(), Some("recorded"));
+        assert!(recorder.is_recording());
+        assert_eq!(inner.requests().len(), 1);
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains("Authorization") && !saved.contains("TC3-HMAC"));
+
+        // Signed at a different time, the request still matches.
+        let player = Arc::new(CassetteTransport::auto(&path, inner.clone()).unwrap());
+        assert!(!player.is_recording());
+        let client = client_with(player.clone(), 1_800_000_000);
+        let resp = client.chat_completions(&chat_request()).await.unwrap();
+        assert_eq!(reply(resp).as_deref(), Some("recorded"));
+        assert_eq!(inner.requests().len(), 1);
+
+        // Each recording answers once, and other requests are not answered.
+        let err = client.chat_completions(&chat_request()).await.unwrap_err();
+        assert!(matches!(err, SdkError::Transport(_)), "{err:?}");
+        let player = CassetteTransport::replay(&path).unwrap();
+        assert_eq!(player.interactions().len(), 1);
+        let client = client_with(Arc::new(player), 1_800_000_000);
+        let mut other = chat_request();
+        other.messages[0].content = "something else".into();
+        let err = client.chat_completions(&other).await.unwrap_err();
+        assert!(matches!(err, SdkError::Transport(_)), "{err:?}");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_priority_jumps_the_concurrency_queue() {
+        use crate::Priority;
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok"))
+                .with_latency(Duration::from_secs(1)),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .max_concurrent_requests(1)
+            .debug(false)
+            .build();
+        let call = |text: &str, priority| {
+            let client = client.clone();
+            let mut req = chat_request();
+            req.messages[0].content = text.to_string();
+            tokio::spawn(async move {
+                let options = RequestOptions::new().priority(priority);
+                client.chat_completions_with_options(&req, &options).await
+            })
+        };
+        async fn settle() {
+            for _ in 0..5 {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        // One low-priority call holds the only slot, two more wait for it.
+        let mut calls = vec![call("low-0", Priority::Low)];
+        settle().await;
+        calls.push(call("low-1", Priority::Low));
+        calls.push(call("low-2", Priority::Low));
+        settle().await;
+        assert_eq!(transport.requests().len(), 1);
+        calls.push(call("high", Priority::High));
+        for call in calls {
+            assert!(call.await.unwrap().is_ok());
+        }
+
+        let order: Vec<_> = transport
+            .requests()
+            .iter()
+            .map(|r| {
+                let body: serde_json::Value = serde_json::from_slice(&r.body).unwrap();
+                body["Messages"][0]["Content"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(order, ["low-0", "high", "low-1", "low-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_limiter_does_not_starve_low_priority() {
+        use crate::limiter::{Limiter, MAX_PASSED_OVER};
+        use crate::Priority;
+
+        async fn settle() {
+            for _ in 0..5 {
+                tokio::task::yield_now().await;
+            }
+        }
+        let limiter = Arc::new(Limiter::new(1));
+        let acquire = |priority| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire(priority).await })
+        };
+
+        let mut held = limiter.acquire(Priority::Normal).await;
+        let low = acquire(Priority::Low);
+        settle().await;
+        for _ in 0..MAX_PASSED_OVER {
+            let high = acquire(Priority::High);
+            settle().await;
+            drop(held);
+            held = high.await.unwrap();
+            assert!(!low.is_finished());
+        }
+        // Passed over often enough: served before the next high call.
+        let high = acquire(Priority::High);
+        settle().await;
+        drop(held);
+        let held = low.await.unwrap();
+        settle().await;
+        assert!(!high.is_finished());
+        drop(held);
+        drop(high.await.unwrap());
+
+        // A waiter that gave up does not keep the slot.
+        let held = limiter.acquire(Priority::Normal).await;
+        let gave_up = acquire(Priority::High);
+        settle().await;
+        gave_up.abort();
+        settle().await;
+        drop(held);
+        drop(limiter.acquire(Priority::Low).await);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_alternatives() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(
+                    200,
+                    &serde_json::json!({"Response": {
+                        "RequestId": "req-alt",
+                        "Choices": [
+                            {"Index": 1, "Message": {"Role": "assistant", "Content": "second"}},
+                            {"Index": 0, "Message": {"Role": "assistant", "Content": "first"}},
+                        ],
+                    }}),
+                )
+                .respond_json(
+                    200,
+                    &serde_json::json!({"Response": {"RequestId": "req-empty", "Choices": []}}),
+                ),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+
+        let req = chat_request().with_n(2);
+        let texts = client.chat_completions_alternatives(&req).await.unwrap();
+        assert_eq!(texts, ["first", "second"]);
+        let body: serde_json::Value =
+            serde_json::from_slice(&transport.requests()[0].body).unwrap();
+        assert_eq!(body["N"], 2);
+
+        let err = client
+            .chat_completions_alternatives(&req)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, SdkError::EmptyResponse { request_id } if request_id.as_deref() == Some("req-empty")),
+            "{err:?}"
+        );
+        assert!(!err.is_retryable());
+        assert_eq!(err.to_repr().kind, "empty_response");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idempotency_key_prevents_resubmission() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("SubmitHunyuanImageJob")
+                .respond_error(200, "InvalidParameter", "bad prompt")
+                .respond_json(
+                    200,
+                    &serde_json::json!({"Response": {"JobId": "job-1", "RequestId": "req-1"}}),
+                )
+                .with_latency(Duration::from_secs(1)),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let submit = || {
+            let client = client.clone();
+            async move {
+                let options = RequestOptions::new().idempotency_key("submit-42");
+                let body = serde_json::json!({"Prompt": "a cat"});
+                client
+                    .call_raw("SubmitHunyuanImageJob", &body, &options)
+                    .await
+            }
+        };
+
+        // A failed call is forgotten and can be sent again.
+        assert!(matches!(submit().await, Err(SdkError::Service { .. })));
+        let headers = &transport.requests()[0].headers;
+        assert_eq!(headers["Idempotency-Key"], "submit-42");
+
+        let first = tokio::spawn(submit());
+        while transport.requests().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        let err = submit().await.unwrap_err();
+        assert!(
+            matches!(&err, SdkError::DuplicateRequest { key } if key == "submit-42"),
+            "{err:?}"
+        );
+        let original = first.await.unwrap().unwrap();
+        assert_eq!(original["Response"]["JobId"], "job-1");
+
+        // Answered from the first call once it succeeded.
+        assert_eq!(submit().await.unwrap(), original);
+        assert_eq!(transport.requests().len(), 2);
+        // The key is bound to the action and body it was first used with.
+        let other_body = serde_json::json!({"Prompt": "a dog"});
+        let options = RequestOptions::new().idempotency_key("submit-42");
+        let err = client
+            .call_raw("SubmitHunyuanImageJob", &other_body, &options)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, SdkError::IdempotencyKeyMismatch { key } if key == "submit-42"),
+            "{err:?}"
+        );
+        assert!(!err.is_retryable());
+        assert_eq!(err.to_repr().kind, "idempotency_key_mismatch");
+        let body = serde_json::json!({"Prompt": "a cat"});
+        let err = client
+            .call_raw("QueryHunyuanImageJob", &body, &options)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SdkError::IdempotencyKeyMismatch { .. }),
+            "{err:?}"
+        );
+        assert_eq!(transport.requests().len(), 2);
+        // Without the key, the call is sent.
+        let body = serde_json::json!({"Prompt": "a cat"});
+        client
+            .call_raw("SubmitHunyuanImageJob", &body, &RequestOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(transport.requests().len(), 3);
+        // A key that cannot be sent as a header is rejected, not used locally.
+        let options = RequestOptions::new().idempotency_key("submit\n42");
+        let err = client
+            .call_raw("SubmitHunyuanImageJob", &body, &options)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, SdkError::InvalidRequest(m) if m.contains("idempotency key")),
+            "{err:?}"
+        );
+        assert_eq!(transport.requests().len(), 3);
+
+        // Forgotten after the TTL.
+        tokio::time::advance(crate::idempotency::KEY_TTL).await;
+        submit().await.unwrap();
+        assert_eq!(transport.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_classification() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::RateLimited {
+                    retry_after_secs: 7,
+                })
+                .respond_error(200, "RequestLimitExceeded", "throttled"),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .build();
+
+        // HTTP 429: Retry-After is parsed.
+        let err = client.chat_completions(&chat_request()).await.unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                SdkError::RateLimited { code, retry_after, .. }
+                    if code == "RequestLimitExceeded"
+                        && *retry_after == Some(Duration::from_secs(7))
+            ),
+            "{:?}",
+            err
+        );
+        assert!(err.is_retryable());
+        assert_eq!(err.to_repr().kind, "rate_limited");
+
+        // Throttling code on a 200 response: same variant, no delay.
+        let err = client.chat_completions(&chat_request()).await.unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                SdkError::RateLimited { code, message, retry_after: None, .. }
+                    if code == "RequestLimitExceeded" && message == "throttled"
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quota_exhausted_is_not_retried() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_error(
+                    200,
+                    "FailedOperation.FreeResourcePackExhausted",
+                    "free resource pack exhausted",
+                ),
+        );
+        let policy = crate::RetryPolicy::new(3).initial_backoff(Duration::from_millis(1));
+        let err = retrying_client(transport.clone(), policy)
+            .chat_completions(&chat_request())
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(
+                &err,
+                SdkError::QuotaExhausted { code, message, .. }
+                    if code == "FailedOperation.FreeResourcePackExhausted"
+                        && message == "free resource pack exhausted"
+            ),
+            "{:?}",
+            err
+        );
+        assert!(!err.is_retryable());
+        assert_eq!(transport.requests().len(), 1);
+        let repr = err.to_repr();
+        assert_eq!(repr.kind, "quota_exhausted");
+        assert_eq!(
+            repr.code.as_deref(),
+            Some("FailedOperation.FreeResourcePackExhausted")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_limits() {
+        // Retry-After is honored but capped by max_backoff.
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::RateLimited {
+                    retry_after_secs: 60,
+                })
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let policy = crate::RetryPolicy::new(1)
+            .initial_backoff(Duration::from_millis(1))
+            .max_backoff(Duration::from_millis(50));
+        let started = std::time::Instant::now();
+        retrying_client(transport.clone(), policy.clone())
+            .chat_completions(&chat_request())
+            .await
+            .unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        transport.assert_called("ChatCompletions", 2);
+
+        // Malformed JSON is not retryable.
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::MalformedJson)
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let err = retrying_client(transport.clone(), policy.clone())
+            .chat_completions(&chat_request())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::Serde(_)));
+        transport.assert_called("ChatCompletions", 1);
+
+        // Retries stop once the policy is exhausted.
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::ConnectionReset),
+        );
+        let err = retrying_client(transport.clone(), policy.max_backoff(Duration::ZERO))
+            .chat_completions(&chat_request())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::Transport(_)));
+        transport.assert_called("ChatCompletions", 2);
+    }
+
+    #[test]
+    fn test_retry_policy_backoff() {
+        let policy = crate::RetryPolicy::default();
+        assert_eq!(policy.max_retries, 2);
+        assert_eq!(policy.initial_backoff, Duration::from_millis(200));
+        assert_eq!(policy.max_backoff, Duration::from_secs(5));
+        assert_eq!(crate::RetryPolicy::new(7).max_retries, 7);
+        assert_eq!(crate::RetryPolicy::new(7).max_backoff, policy.max_backoff);
+
+        // Doubles per retry until max_backoff caps it.
+        let policy = crate::RetryPolicy::new(10)
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(700));
+        let delays: Vec<_> = (0..5).map(|retry| policy.backoff(retry, None)).collect();
+        assert_eq!(delays, [100, 200, 400, 700, 700].map(Duration::from_millis));
+        assert_eq!(policy.backoff(u32::MAX, None), Duration::from_millis(700));
+
+        // Retry-After replaces the computed delay, still capped.
+        assert_eq!(
+            policy.backoff(3, Some(Duration::from_millis(50))),
+            Duration::from_millis(50)
+        );
+        assert_eq!(
+            policy.backoff(0, Some(Duration::from_secs(60))),
+            Duration::from_millis(700)
+        );
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(crate::retry::retry_after(&headers), None);
+        headers.insert(reqwest::header::RETRY_AFTER, " 3 ".parse().unwrap());
+        assert_eq!(
+            crate::retry::retry_after(&headers),
+            Some(Duration::from_secs(3))
+        );
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(crate::retry::retry_after(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn test_transport_errors_are_retried_and_counted() {
+        assert!(SdkError::Transport("reset".to_string()).is_retryable());
+        assert!(!SdkError::InvalidRequest("bad".to_string()).is_retryable());
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let (_, meta) = retrying_client(transport, crate::RetryPolicy::new(2))
+            .chat_completions_with_meta(&chat_request())
+            .await
+            .unwrap();
+        assert_eq!(meta.attempts, 1);
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::ConnectionReset)
+                .respond_fault(Fault::ConnectionReset)
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let policy = crate::RetryPolicy::new(2).max_backoff(Duration::ZERO);
+        let (_, meta) = retrying_client(transport.clone(), policy)
+            .chat_completions_with_meta(&chat_request())
+            .await
+            .unwrap();
+        assert_eq!(meta.attempts, 3);
+        transport.assert_called("ChatCompletions", 3);
+    }
+
+    #[tokio::test]
+    async fn test_stream_open_retried_and_cut_mid_stream() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::RateLimited {
+                    retry_after_secs: 0,
+                })
+                .respond_sse(ChunkSequence::from_text("abcdef", 2).events())
+                .cut_sse_after(2),
+        );
+        let client = retrying_client(transport.clone(), crate::RetryPolicy::new(2));
+
+        let mut stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        let mut text = String::new();
+        let mut error = None;
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(chunk) => text.extend(
+                    chunk
+                        .choices
+                        .unwrap()
+                        .into_iter()
+                        .filter_map(|c| c.delta?.content),
+                ),
+                Err(e) => error = Some(e),
+            }
+        }
+        assert_eq!(text, "abcd");
+        assert!(matches!(error, Some(SdkError::Transport(_))));
+        // The interrupted stream itself is not retried.
+        transport.assert_called("ChatCompletions", 2);
+    }
+
+    #[tokio::test]
+    async fn test_decode_base64_png_image() {
+        // 1x1 transparent PNG.
+        const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("TextToImageLite")
+                .match_body_json(&serde_json::json!({"RspImgType": "base64"}))
+                .respond_json(
+                    200,
+                    &serde_json::json!({"Response": {"RequestId": "r", "ResultImage": PNG}}),
+                ),
+        );
+        let client = retrying_client(transport, crate::RetryPolicy::new(0));
+        let req = crate::models::TextToImageLiteRequest {
+            prompt: "a dot".to_string(),
+            rsp_img_type: Some("base64".to_string()),
+            ..Default::default()
+        };
+
+        let bytes = client
+            .text_to_image_lite(&req)
+            .await
+            .unwrap()
+            .response
+            .decode_image()
+            .unwrap();
+        assert_eq!(bytes.len(), 68);
+        assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(crate::image::sniff_content_type(&bytes), Some("image/png"));
+        let data_uri = format!("data:image/png;base64,{}", PNG);
+        assert_eq!(crate::image::decode_base64_image(&data_uri).unwrap(), bytes);
+
+        for bad in ["not base64!", "", "https://example.com/a.png"] {
+            let err = crate::image::decode_base64_image(bad).unwrap_err();
+            assert!(matches!(err, SdkError::Decode(_)), "{:?}", err);
+            assert!(!err.is_retryable());
+        }
+    }
+
+    #[test]
+    fn test_image_job_parameters_use_the_api_wire_strings() {
+        use crate::models::{ImageClarity, ImageResolution, ImageStyle};
+
+        let styles = [
+            (ImageStyle::Riman, "riman"),
+            (ImageStyle::Shuimo, "shuimo"),
+            (ImageStyle::Monai, "monai"),
+            (ImageStyle::Bianping, "bianping"),
+            (ImageStyle::Xiangsu, "xiangsu"),
+            (ImageStyle::Ertonghuiben, "ertonghuiben"),
+            (ImageStyle::Xuanran3d, "3dxuanran"),
+            (ImageStyle::Manhua, "manhua"),
+            (ImageStyle::Heibaimanhua, "heibaimanhua"),
+            (ImageStyle::Dianyingjuzhao, "dianyingjuzhao"),
+            (ImageStyle::Xieshi, "xieshi"),
+            (ImageStyle::Youhua, "youhua"),
+            (ImageStyle::Custom("guofeng".to_string()), "guofeng"),
+        ];
+        for (style, wire) in styles {
+            assert_eq!(serde_json::to_value(&style).unwrap(), wire);
+            assert_eq!(
+                serde_json::from_value::<ImageStyle>(wire.into()).unwrap(),
+                style
+            );
+            assert_eq!(ImageStyle::from(wire), style);
+        }
+
+        let resolutions = [
+            (ImageResolution::Res768x768, "768:768"),
+            (ImageResolution::Res768x1024, "768:1024"),
+            (ImageResolution::Res1024x768, "1024:768"),
+            (ImageResolution::Res1024x1024, "1024:1024"),
+            (ImageResolution::Res720x1280, "720:1280"),
+            (ImageResolution::Res1280x720, "1280:720"),
+            (ImageResolution::Res768x1280, "768:1280"),
+            (ImageResolution::Res1280x768, "1280:768"),
+            (ImageResolution::Custom("512:512".to_string()), "512:512"),
+        ];
+        for (resolution, wire) in resolutions {
+            assert_eq!(serde_json::to_value(&resolution).unwrap(), wire);
+            assert_eq!(
+                serde_json::from_value::<ImageResolution>(wire.into()).unwrap(),
+                resolution
+            );
+            assert!(resolution.dimensions().is_some());
+        }
+        assert_eq!(ImageResolution::Res1280x720.dimensions(), Some((1280, 720)));
+
+        for (clarity, wire) in [(ImageClarity::X2, "x2"), (ImageClarity::X4, "x4")] {
+            assert_eq!(serde_json::to_value(clarity).unwrap(), wire);
+            assert_eq!(
+                serde_json::from_value::<ImageClarity>(wire.into()).unwrap(),
+                clarity
+            );
+        }
+        assert!(serde_json::from_value::<ImageClarity>("x3".into()).is_err());
+    }
+
+    #[test]
+    fn test_image_job_builder_reports_every_violation() {
+        use crate::models::{
+            ImageClarity, ImageResolution, ImageStyle, LogoParam, LogoRect,
+            SubmitHunyuanImageJobRequest,
+        };
+
+        let logo = LogoParam {
+            logo_url: Some("https://example.com/logo.png".to_string()),
+            logo_rect: Some(LogoRect {
+                x: 10,
+                y: 10,
+                width: 64,
+                height: 32,
+            }),
+            ..Default::default()
+        };
+        let req = SubmitHunyuanImageJobRequest::builder()
+            .prompt("a cat")
+            .negative_prompt("dogs")
+            .style(ImageStyle::Xuanran3d)
+            .resolution(ImageResolution::Res768x1024)
+            .num(4)
+            .clarity(ImageClarity::X2)
+            .seed(7)
+            .revise(false)
+            .logo(logo.clone())
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "Prompt": "a cat",
+                "NegativePrompt": "dogs",
+                "Style": "3dxuanran",
+                "Resolution": "768:1024",
+                "Num": 4,
+                "Clarity": "x2",
+                "Seed": 7,
+                "Revise": 0,
+                "LogoAdd": 1,
+                "LogoParam": {
+                    "LogoUrl": "https://example.com/logo.png",
+                    "LogoRect": {"X": 10, "Y": 10, "Width": 64, "Height": 32},
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<SubmitHunyuanImageJobRequest>(json).unwrap(),
+            req
+        );
+        let minimal = SubmitHunyuanImageJobRequest::builder()
+            .prompt("a cat")
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&minimal).unwrap(),
+            serde_json::json!({"Prompt": "a cat"})
+        );
+
+        let violations = |builder: crate::models::SubmitHunyuanImageJobRequestBuilder| {
+            builder.build().unwrap_err().violations
+        };
+        let valid = || SubmitHunyuanImageJobRequest::builder().prompt("a cat");
+        assert_eq!(
+            violations(SubmitHunyuanImageJobRequest::builder().prompt("  ")),
+            ["Prompt must not be empty"]
+        );
+        assert_eq!(
+            violations(valid().style(ImageStyle::Custom(String::new()))),
+            ["Style must not be empty"]
+        );
+        for bad in ["1024", "1024:0", "a:b", "-1:768"] {
+            assert_eq!(
+                violations(valid().resolution(ImageResolution::from(bad))),
+                [format!(
+                    "Resolution {:?} is not width:height with both positive",
+                    bad
+                )]
+            );
+        }
+        for num in [0, 5] {
+            assert_eq!(
+                violations(valid().num(num)),
+                [format!("Num {} is outside 1..=4", num)]
+            );
+        }
+        assert!(valid().num(1).build().is_ok());
+        assert_eq!(violations(valid().seed(0)), ["Seed must be at least 1"]);
+        let mut unlogged = valid().logo(logo.clone()).build().unwrap();
+        unlogged.logo_add = None;
+        assert_eq!(
+            unlogged.validate().unwrap_err().violations,
+            ["LogoParam is set but LogoAdd is not 1"]
+        );
+        for source in [
+            LogoParam::default(),
+            LogoParam {
+                logo_image: Some("aGk=".to_string()),
+                ..logo.clone()
+            },
+        ] {
+            assert_eq!(
+                violations(valid().logo(source)),
+                ["LogoParam needs exactly one of LogoUrl and LogoImage"]
+            );
+        }
+        let flat = LogoParam {
+            logo_rect: Some(LogoRect {
+                height: 0,
+                ..logo.logo_rect.unwrap()
+            }),
+            ..logo.clone()
+        };
+        assert_eq!(
+            violations(valid().logo(flat)),
+            ["LogoRect size 64x0 is not positive"]
+        );
+
+        // All problems are reported together.
+        let err = SubmitHunyuanImageJobRequest::builder()
+            .num(9)
+            .seed(0)
+            .logo(LogoParam::default())
+            .build()
+            .unwrap_err();
+        assert_eq!(err.violations.len(), 4);
+        assert_eq!(
+            err.to_string(),
+            "invalid SubmitHunyuanImageJob request: Prompt must not be empty; \
+             Num 9 is outside 1..=4; Seed must be at least 1; \
+             LogoParam needs exactly one of LogoUrl and LogoImage"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_model_fallback_chain_records_serving_model() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .match_body_json(&serde_json::json!({"Model": "hunyuan-pro"}))
+                .respond_error(200, "RequestLimitExceeded", "throttled")
+                .respond_error(200, "RequestLimitExceeded", "throttled")
+                .expect_action("ChatCompletions")
+                .match_body_json(&serde_json::json!({"Model": "hunyuan-standard"}))
+                .respond_json(200, &fixtures::chat_completions_response("fallback"))
+                .expect_action("ChatCompletions")
+                .match_body_json(&serde_json::json!({"Model": "hunyuan-lite"}))
+                .respond_error(200, "InvalidParameter", "bad"),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .retry_policy(crate::RetryPolicy::new(1).initial_backoff(Duration::ZERO))
+            .model_fallback_chain(vec![
+                "hunyuan-pro".to_string(),
+                "hunyuan-standard".to_string(),
+            ])
+            .debug(false)
+            .build();
+
+        let req = ChatCompletionsRequest {
+            model: None,
+            ..chat_request()
+        };
+        let (resp, meta) = client.chat_completions_with_fallback(&req).await.unwrap();
+        let choice = &resp.response.choices.unwrap()[0];
+        assert_eq!(
+            choice.message.as_ref().unwrap().content.as_deref(),
+            Some("fallback")
+        );
+        assert_eq!(meta.model.as_deref(), Some("hunyuan-standard"));
+        assert_eq!(meta.attempts, 3);
+
+        // Non-retryable errors are returned without trying other models.
+        let err = client
+            .chat_completions_with_fallback(&chat_request())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::Service { code, .. } if code == "InvalidParameter"));
+        transport.assert_called("ChatCompletions", 4);
+    }
+
+    #[tokio::test]
+    async fn test_model_fallback_follows_retry_if_and_counts_real_attempts() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .match_body_json(&serde_json::json!({"Model": "hunyuan-pro"}))
+                .respond_error(200, "FailedOperation.EngineBusy", "busy")
+                .respond_error(200, "FailedOperation.EngineBusy", "busy")
+                .respond_error(200, "RequestLimitExceeded", "throttled")
+                .expect_action("ChatCompletions")
+                .match_body_json(&serde_json::json!({"Model": "hunyuan-standard"}))
+                .respond_json(200, &fixtures::chat_completions_response("fallback")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .retry_policy(crate::RetryPolicy::new(1).initial_backoff(Duration::ZERO))
+            .retry_if(|err| {
+                matches!(err, SdkError::Service { code, .. } if code == "FailedOperation.EngineBusy")
+            })
+            .model_fallback_chain(vec![
+                "hunyuan-pro".to_string(),
+                "hunyuan-standard".to_string(),
+            ])
+            .debug(false)
+            .build();
+        let req = ChatCompletionsRequest {
+            model: None,
+            ..chat_request()
+        };
+
+        // Normally final, but retried per `retry_if`, so the chain moves on.
+        let (_, meta) = client.chat_completions_with_fallback(&req).await.unwrap();
+        assert_eq!(meta.model.as_deref(), Some("hunyuan-standard"));
+        assert_eq!(meta.attempts, 3);
+
+        // Normally retryable, but not per `retry_if`: one attempt, no fallback.
+        let err = client
+            .chat_completions_with_fallback(&req)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::RateLimited { .. }), "{err:?}");
+        transport.assert_called("ChatCompletions", 4);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_default_model() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .match_body_json(&serde_json::json!({"Model": "hunyuan-pro"}))
+                .respond_json(200, &fixtures::chat_completions_response("slow"))
+                .with_latency(Duration::from_millis(200))
+                .respond_json(200, &fixtures::chat_completions_response("fast")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .default_model("hunyuan-pro")
+            .timeout(Duration::from_millis(50))
+            .retry_policy(crate::RetryPolicy::new(1).initial_backoff(Duration::ZERO))
+            .debug(false)
+            .build();
+
+        let req = ChatCompletionsRequest {
+            model: None,
+            ..chat_request()
+        };
+        // The first attempt times out and is retried.
+        let (resp, meta) = client.chat_completions_with_meta(&req).await.unwrap();
+        let choice = &resp.response.choices.unwrap()[0];
+        assert_eq!(
+            choice.message.as_ref().unwrap().content.as_deref(),
+            Some("fast")
+        );
+        assert_eq!(meta.attempts, 2);
+        assert_eq!(meta.model.as_deref(), Some("hunyuan-pro"));
+        transport.assert_called("ChatCompletions", 2);
+    }
+
+    /// Hands out whatever credential the test last stored.
+    struct RefreshingCredential(Mutex<Credential>);
+
+    impl crate::CredentialProvider for RefreshingCredential {
+        fn credential(&self) -> Credential {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refreshed_credential_without_token_drops_token_header() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let provider = Arc::new(RefreshingCredential(Mutex::new(Credential {
+            token: Some("session-1".to_string()),
+            ..test_credential()
+        })));
+        let client = Client::builder()
+            .credential_provider(provider.clone())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+
+        assert!(client.sends_token());
+        client.chat_completions(&chat_request()).await.unwrap();
+        *provider.0.lock().unwrap() = test_credential();
+        assert!(!client.sends_token());
+        client.chat_completions(&chat_request()).await.unwrap();
+
+        let tokens: Vec<Option<String>> = transport
+            .requests()
+            .iter()
+            .map(|r| {
+                r.headers
+                    .get("X-TC-Token")
+                    .map(|v| v.to_str().unwrap().to_string())
+            })
+            .collect();
+        assert_eq!(tokens, vec![Some("session-1".to_string()), None]);
+
+        let fixed = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .build();
+        assert!(!fixed.sends_token());
+    }
+
+    #[tokio::test]
+    async fn test_stream_reencoded_as_sse_frames() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_sse(ChunkSequence::from_text("hello\nworld", 4).events())
+                .respond_sse(ChunkSequence::from_text("abcdef", 2).events())
+                .cut_sse_after(1),
+        );
+        let client = retrying_client(transport, crate::RetryPolicy::new(0));
+
+        let stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        let frames: Vec<String> = stream
+            .into_sse()
+            .map(|f| String::from_utf8(f.unwrap().to_vec()).unwrap())
+            .collect()
+            .await;
+        let (last, chunks) = frames.split_last().unwrap();
+        assert_eq!(last, "event: message\ndata: [DONE]\n\n");
+        let mut text = String::new();
+        for frame in chunks {
+            let data = frame
+                .strip_prefix("event: message\ndata: ")
+                .and_then(|f| f.strip_suffix("\n\n"))
+                .unwrap();
+            assert!(!data.contains('\n'), "frame data spans lines: {:?}", frame);
+            let chunk: crate::models::ChatCompletionsChunk = serde_json::from_str(data).unwrap();
+            text.extend(
+                chunk
+                    .choices
+                    .unwrap()
+                    .into_iter()
+                    .filter_map(|c| c.delta?.content),
+            );
+        }
+        assert_eq!(text, "hello\nworld");
+
+        let stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        let frames: Vec<_> = stream.into_sse().map(Result::unwrap).collect().await;
+        assert_eq!(frames.len(), 2);
+        let error = String::from_utf8(frames[1].to_vec()).unwrap();
+        let data = error.strip_prefix("event: error\ndata: ").unwrap();
+        let value: serde_json::Value = serde_json::from_str(data.trim_end()).unwrap();
+        assert!(value["Message"]
+            .as_str()
+            .unwrap()
+            .starts_with("transport error"));
+    }
+
+    #[tokio::test]
+    async fn test_accumulator_reassembles_streamed_tool_call_arguments() {
+        let events = [
+            r#"{"Id":"t-1","Choices":[{"Index":0,"Delta":{"Role":"assistant","Content":"","ToolCalls":[{"Index":0,"Id":"call_1","Type":"function","Function":{"Name":"get_weather","Arguments":"{\"city\":"}}]}}]}"#,
+            r#"{"Id":"t-1","Choices":[{"Index":0,"Delta":{"ToolCalls":[{"Index":0,"Function":{"Arguments":"\"深圳\","}}]}}]}"#,
+            r#"{"Id":"t-1","Choices":[{"Index":0,"Delta":{"ToolCalls":[{"Index":0,"Function":{"Arguments":"\"unit\":\"c\"}"}}]}}]}"#,
+            r#"{"Id":"t-1","Choices":[{"Index":0,"Delta":{"Content":""},"FinishReason":"tool_calls"}]}"#,
+        ];
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_sse(events),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .debug(false)
+            .build();
+
+        let mut stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        let mut acc = crate::ChatCompletionsAccumulator::new();
+        for _ in 0..3 {
+            acc.push(&stream.next().await.unwrap().unwrap()).unwrap();
+        }
+        // Arguments are still partial until the finish reason arrives.
+        assert!(acc.tool_calls().is_empty());
+        while let Some(chunk) = stream.next().await {
+            acc.push(&chunk.unwrap()).unwrap();
+        }
+
+        let [call] = acc.tool_calls() else {
+            panic!("expected one tool call: {:?}", acc.tool_calls());
+        };
+        assert_eq!(call.id, "call_1");
+        assert_eq!(call.call_type, "function");
+        assert_eq!(call.function.name, "get_weather");
+        let args: serde_json::Value = serde_json::from_str(&call.function.arguments).unwrap();
+        assert_eq!(args, serde_json::json!({"city": "深圳", "unit": "c"}));
+
+        let resp = acc.into_response();
+        let message = resp.messages().next().unwrap();
+        assert_eq!(
+            message.tool_calls.as_ref().unwrap()[0].function.name,
+            "get_weather"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_accumulator_caps_long_stream() {
+        use crate::models::FinishReason;
+
+        let text = "长".repeat(4000); // 12000 bytes
+        let events = ChunkSequence::from_text(&text, 100)
+            .finish_reason(FinishReason::Length)
+            .usage(5, 4000)
+            .events();
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_sse(events),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .debug(false)
+            .build();
+
+        // Soft cap: text past 1000 bytes is discarded, metadata survives.
+        let mut acc = crate::ChatCompletionsAccumulator::new().max_retained_bytes(1000);
+        let mut stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        let mut emitted = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.unwrap();
+            emitted += chunk.choices.as_ref().unwrap()[0]
+                .delta
+                .as_ref()
+                .unwrap()
+                .content
+                .as_ref()
+                .unwrap()
+                .len();
+            acc.push(&chunk).unwrap();
+        }
+        assert_eq!(emitted, 12000);
+        assert!(acc.is_truncated());
+        assert_eq!(acc.content().len(), 999);
+        assert_eq!(acc.total_bytes(), 12000);
+        assert_eq!(acc.finish_reason(), Some("length"));
+        assert_eq!(acc.usage().unwrap().total_tokens, Some(4005));
+
+        // Hard cap: the chunk crossing 1000 bytes fails.
+        let mut acc = crate::ChatCompletionsAccumulator::new().max_total_bytes(1000);
+        let mut stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        let mut pushed = 0;
+        let err = loop {
+            let chunk = stream.next().await.unwrap().unwrap();
+            match acc.push(&chunk) {
+                Ok(()) => pushed += 1,
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(pushed, 3);
+        assert!(matches!(
+            err,
+            SdkError::ResponseTooLarge {
+                limit: 1000,
+                received: 1200,
+                status: None,
+                request_id: None,
+            }
+        ));
+        assert_eq!(acc.total_bytes(), 900);
+    }
+
+    #[test]
+    fn test_sse_decoder_holds_one_event_of_a_long_stream() {
+        use crate::stream::SseDecoder;
+
+        // ~1 MiB of ~1 KiB events with both terminators, sent 100 times in
+        // chunks that split events and terminators alike.
+        let mut block = Vec::new();
+        for i in 0..1000 {
+            let terminator = if i % 2 == 0 { "\n\n" } else { "\r\n\r\n" };
+            block.extend_from_slice(format!("data: {:04}{}", i, "x".repeat(1000)).as_bytes());
+            block.extend_from_slice(terminator.as_bytes());
+        }
+        let cap = 8 * 1024;
+        let mut decoder = SseDecoder::with_max_buffered(cap);
+        let (mut events, mut peak) = (0, 0);
+        for _ in 0..100 {
+            for chunk in block.chunks(4093) {
+                decoder.push(chunk);
+                peak = peak.max(decoder.buffered());
+                while let Some(event) = decoder.next_event().unwrap() {
+                    assert_eq!(event.data[..4], format!("{:04}", events % 1000));
+                    assert_eq!(event.data.len(), 1004);
+                    events += 1;
+                }
+            }
+        }
+        assert_eq!(events, 100_000);
+        assert!(peak < cap, "peak {}", peak);
+        assert_eq!(decoder.buffered(), 0);
+        assert_eq!(decoder.finish(), None);
+    }
+
+    #[test]
+    fn test_sse_decoder_releases_unterminated_event() {
+        use crate::stream::SseDecoder;
+
+        let cap = 64 * 1024;
+        let mut decoder = SseDecoder::with_max_buffered(cap);
+        decoder.push(b"data: ");
+        let mut pushed = 6;
+        let err = loop {
+            decoder.push(&[b'x'; 1000]);
+            pushed += 1000;
+            match decoder.next_event() {
+                Ok(None) => assert!(decoder.buffered() <= cap),
+                Ok(Some(event)) => panic!("unexpected event {:?}", event),
+                Err(err) => break err,
+            }
+        };
+        assert!(
+            matches!(err, SdkError::StreamEventTooLarge { limit, size } if limit == cap && size == pushed),
+            "{:?}",
+            err
+        );
+        assert_eq!(pushed, 66_006);
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_stream_closes_connection() {
+        /// Sends SSE events every 10ms until the client closes the
+        /// connection; resolves to how many were written.
+        async fn serve_endless_stream() -> (String, tokio::task::JoinHandle<usize>) {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let endpoint = format!("http://{}", listener.local_addr().unwrap());
+            let handle = tokio::spawn(async move {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 64 * 1024];
+                let _ = socket.read(&mut buf).await.unwrap();
+                socket
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\n\r\n",
+                    )
+                    .await
+                    .unwrap();
+                let event = format!(
+                    "data: {}\n\n",
+                    ChunkSequence::from_pieces(["hi"]).events()[0]
+                );
+                let chunk = format!("{:x}\r\n{}\r\n", event.len(), event);
+                let mut sent = 0;
+                loop {
+                    tokio::select! {
+                        read = socket.read(&mut buf) => {
+                            if matches!(read, Ok(0) | Err(_)) {
+                                return sent;
+                            }
+                        }
+                        _ = tokio::time::sleep(Duration::from_millis(10)) => {
+                            if socket.write_all(chunk.as_bytes()).await.is_err() {
+                                return sent;
+                            }
+                            sent += 1;
+                        }
+                    }
+                }
+            });
+            (endpoint, handle)
+        }
+
+        for cancel in [true, false] {
+            let (endpoint, server) = serve_endless_stream().await;
+            let client = ClientBuilder::new()
+                .credential(test_credential())
+                .endpoint(endpoint)
+                .debug(false)
+                .build();
+            let mut stream = client
+                .chat_completions_stream(&chat_request())
+                .await
+                .unwrap();
+            stream.next().await.unwrap().unwrap();
+            if cancel {
+                stream.cancel();
+            } else {
+                drop(stream);
+            }
+            // The server sees the connection close instead of a client that
+            // keeps reading, even though the client itself is still alive.
+            let sent = tokio::time::timeout(Duration::from_secs(2), server)
+                .await
+                .expect("connection closed promptly")
+                .unwrap();
+            assert!(sent < 50, "sent {} events", sent);
+            drop(client);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_frames_map_to_service_errors() {
+        let shapes = [
+            (
+                r#"{"Error":{"Code":"InternalError","Message":"boom"},"RequestId":"req-1"}"#,
+                ("InternalError", "boom", Some("req-1")),
+            ),
+            (
+                r#"{"Response":{"Error":{"Code":"LimitExceeded","Message":"quota"},"RequestId":"req-2"}}"#,
+                ("LimitExceeded", "quota", Some("req-2")),
+            ),
+            (
+                r#"{"Id":"chat-1","Created":1715150001,"Choices":[],"ErrorMsg":{"Code":2001,"Msg":"内容审核未通过"}}"#,
+                ("2001", "内容审核未通过", None),
+            ),
+        ];
+        for (frame, (code, message, request_id)) in shapes {
+            let first = ChunkSequence::from_pieces(["partial"]).events()[0].clone();
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_sse([first.as_str(), frame, first.as_str()]),
+            );
+            let client = ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport)
+                .debug(false)
+                .build();
+
+            let mut stream = client
+                .chat_completions_stream(&chat_request())
+                .await
+                .unwrap();
+            assert!(stream.next().await.unwrap().is_ok());
+            let err = stream.next().await.unwrap().unwrap_err();
+            assert!(
+                matches!(
+                    &err,
+                    SdkError::Service { code: c, message: m, request_id: r }
+                        if c == code && m == message && r.as_deref() == request_id
+                ),
+                "{}: {:?}",
+                frame,
+                err
+            );
+            assert!(stream.next().await.is_none(), "{}", frame);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_exposes_sse_event_id() {
+        let events = ChunkSequence::from_pieces(["a", "b", "c"]).events();
+        let body = format!(
+            "id: 41\ndata: {}\n\ndata: {}\n\nid: 42\ndata: {}\n\ndata: [DONE]\n\n",
+            events[0], events[1], events[2]
+        );
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond(MockResponse::with_content_type(
+                    200,
+                    "text/event-stream",
+                    body,
+                )),
+        );
+        let client = retrying_client(transport, crate::RetryPolicy::new(0));
+
+        let mut stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        assert_eq!(stream.last_event_id(), None);
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(stream.last_event_id(), Some("41"));
+        // The id carries over to events without one.
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(stream.last_event_id(), Some("41"));
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(stream.last_event_id(), Some("42"));
+        assert!(stream.next().await.is_none());
+
+        let stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        let frames: Vec<String> = stream
+            .into_sse()
+            .map(|f| String::from_utf8(f.unwrap().to_vec()).unwrap())
+            .collect()
+            .await;
+        assert!(frames[0].starts_with("id: 41\nevent: message\n"));
+        assert!(frames[1].starts_with("event: message\n"));
+        assert!(frames[2].starts_with("id: 42\nevent: message\n"));
+    }
+
+    #[tokio::test]
+    async fn test_unterminated_sse_event_fails_past_buffer_cap() {
+        let body = format!(
+            "data: {}\n\ndata: {}",
+            ChunkSequence::from_pieces(["hi"]).events()[0],
+            "x".repeat(4096)
+        );
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond(MockResponse::with_content_type(
+                    200,
+                    "text/event-stream",
+                    body,
+                )),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .max_sse_buffer_bytes(1024)
+            .debug(false)
+            .build();
+
+        let mut stream = client
+            .chat_completions_stream(&chat_request())
+            .await
+            .unwrap();
+        assert!(stream.next().await.unwrap().is_ok());
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(
+            matches!(
+                err,
+                SdkError::StreamEventTooLarge {
+                    limit: 1024,
+                    size: 4102,
+                }
+            ),
+            "{:?}",
+            err
+        );
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_identical_embeddings_share_one_call() {
+        use crate::models::GetEmbeddingRequest;
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("GetEmbedding")
+                .match_body_json(&serde_json::json!({"Input": "bad"}))
+                .respond_error(200, "InvalidParameter", "bad input")
+                .with_latency(Duration::from_millis(50))
+                .expect_action("GetEmbedding")
+                .respond_json(
+                    200,
+                    &EmbeddingResponseFixture::new()
+                        .embedding(vec![0.5, 0.25])
+                        .build(),
+                )
+                .with_latency(Duration::from_millis(50)),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .coalesce_embeddings(true)
+            .debug(false)
+            .build();
+        let request = |input: &str| GetEmbeddingRequest {
+            input: Some(input.to_string()),
+            ..Default::default()
+        };
+        let wire_calls = |input: &str| {
+            transport
+                .requests_for("GetEmbedding")
+                .iter()
+                .filter(|r| r.body == serde_json::to_vec(&request(input)).unwrap())
+                .count()
+        };
+
+        let same = request("same");
+        let results =
+            futures_util::future::join_all((0..10).map(|_| client.get_embedding(&same))).await;
+        let first = results[0].as_ref().unwrap();
+        assert!(results.iter().all(|r| r.as_ref().unwrap() == first));
+        assert_eq!(wire_calls("same"), 1);
+
+        // Waiters share errors too, and different inputs are not merged.
+        let (bad, other) = (request("bad"), request("other"));
+        let calls = (0..5)
+            .map(|_| client.get_embedding(&bad))
+            .chain([client.get_embedding(&other)]);
+        let results = futures_util::future::join_all(calls).await;
+        for result in &results[..5] {
+            assert!(matches!(
+                result,
+                Err(SdkError::Service { code, .. }) if code == "InvalidParameter"
+            ));
+        }
+        assert!(results[5].is_ok());
+        assert_eq!((wire_calls("bad"), wire_calls("other")), (1, 1));
+
+        // Finished calls are not cached.
+        client.get_embedding(&same).await.unwrap();
+        assert_eq!(wire_calls("same"), 2);
+
+        // A call whose only caller gave up is dropped, not joined later.
+        let abandoned =
+            tokio::time::timeout(Duration::from_millis(10), client.get_embedding(&same)).await;
+        assert!(abandoned.is_err());
+        client.get_embedding(&same).await.unwrap();
+        assert_eq!(wire_calls("same"), 4);
+    }
+
+    #[tokio::test]
+    async fn test_embed_batch_splits_at_input_limit_and_keeps_order() {
+        use crate::models::{GetEmbeddingRequest, MAX_EMBEDDING_INPUTS};
+
+        /// Embeds text `n` as `[n]`, listing `Data` in reverse, and records
+        /// the number of inputs per request.
+        #[derive(Default)]
+        struct Numbered(Mutex<Vec<usize>>);
+
+        impl Transport for Numbered {
+            fn execute(&self, req: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>> {
+                let body: GetEmbeddingRequest = serde_json::from_slice(&req.body).unwrap();
+                let inputs = body.input_list.unwrap();
+                self.0.lock().unwrap().push(inputs.len());
+                let mut resp = inputs
+                    .iter()
+                    .fold(EmbeddingResponseFixture::new(), |f, text| {
+                        f.embedding(vec![text.parse().unwrap()])
+                    })
+                    .build();
+                resp.response.data.as_mut().unwrap().reverse();
+                Box::pin(async move {
+                    let mut headers = reqwest::header::HeaderMap::new();
+                    headers.insert("content-type", "application/json".parse().unwrap());
+                    Ok(RawResponse::from_bytes(
+                        200,
+                        headers,
+                        serde_json::to_vec(&resp).unwrap(),
+                    ))
+                })
+            }
+        }
+
+        let transport = Arc::new(Numbered::default());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let texts = |n: usize| (0..n).map(|i| i.to_string()).collect::<Vec<_>>();
+
+        let vectors = client
+            .embed_batch(texts(MAX_EMBEDDING_INPUTS))
+            .await
+            .unwrap();
+        assert_eq!(*transport.0.lock().unwrap(), [MAX_EMBEDDING_INPUTS]);
+        assert_eq!(vectors.len(), MAX_EMBEDDING_INPUTS);
+
+        transport.0.lock().unwrap().clear();
+        let n = 2 * MAX_EMBEDDING_INPUTS + 1;
+        let vectors = client.embed_batch(texts(n)).await.unwrap();
+        let mut sizes = transport.0.lock().unwrap().clone();
+        sizes.sort();
+        assert_eq!(sizes, [1, MAX_EMBEDDING_INPUTS, MAX_EMBEDDING_INPUTS]);
+        let expected: Vec<Vec<f32>> = (0..n).map(|i| vec![i as f32]).collect();
+        assert_eq!(vectors, expected);
+
+        assert!(client.embed_batch(Vec::new()).await.unwrap().is_empty());
+        assert_eq!(transport.0.lock().unwrap().len(), 3);
+
+        // Base64 vectors are decoded like number arrays.
+        use base64::Engine;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(
+            [0.5f32, -2.0]
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("GetEmbedding")
+                .respond_json(
+                    200,
+                    &serde_json::json!({ "Response": {
+                        "RequestId": "req-1",
+                        "Data": [{ "Embedding": encoded, "Index": 0, "Object": "embedding" }]
+                    }}),
+                ),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .debug(false)
+            .build();
+        let vectors = client.embed_batch(texts(1)).await.unwrap();
+        assert_eq!(vectors, [vec![0.5f32, -2.0]]);
+    }
+
+    #[tokio::test]
+    async fn test_embedding_cache_hits_evicts_and_keys_on_input_type() {
+        use crate::models::{GetEmbeddingRequest, INPUT_TYPE_QUERY};
+
+        /// Embeds every text as `[first byte]` and records the inputs sent.
+        #[derive(Default)]
+        struct EmbeddingEcho(Mutex<Vec<Vec<String>>>);
+
+        impl Transport for EmbeddingEcho {
+            fn execute(&self, req: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>> {
+                let body: GetEmbeddingRequest = serde_json::from_slice(&req.body).unwrap();
+                let inputs = body.input_list.unwrap_or_else(|| vec![body.input.unwrap()]);
+                let resp = inputs
+                    .iter()
+                    .fold(EmbeddingResponseFixture::new(), |f, text| {
+                        f.embedding(vec![text.as_bytes()[0] as f64])
+                    })
+                    .usage(inputs.len() as u32)
+                    .build();
+                self.0.lock().unwrap().push(inputs);
+                Box::pin(async move {
+                    let mut headers = reqwest::header::HeaderMap::new();
+                    headers.insert("content-type", "application/json".parse().unwrap());
+                    Ok(RawResponse::from_bytes(
+                        200,
+                        headers,
+                        serde_json::to_vec(&resp).unwrap(),
+                    ))
+                })
+            }
+        }
+
+        let transport = Arc::new(EmbeddingEcho::default());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .embedding_cache(2)
+            .debug(false)
+            .build();
+        let list = |texts: &[&str]| GetEmbeddingRequest {
+            input_list: Some(texts.iter().map(|t| t.to_string()).collect()),
+            ..Default::default()
+        };
+        let vectors = |resp: crate::models::GetEmbeddingResponse| -> Vec<(u32, f64)> {
+            resp.response
+                .data
+                .unwrap()
+                .into_iter()
+                .map(|d| (d.index.unwrap(), d.embedding.unwrap()[0]))
+                .collect()
+        };
+        let cache = || client.embedding_cache().unwrap().stats();
+
+        let resp = client.get_embedding(&list(&["a", "b"])).await.unwrap();
+        assert_eq!(vectors(resp), [(0, 97.0), (1, 98.0)]);
+        assert_eq!((cache().hits, cache().misses, cache().len), (0, 2, 2));
+
+        // Only the miss is sent; "a" was used more recently, so "b" goes.
+        let resp = client.get_embedding(&list(&["c", "a"])).await.unwrap();
+        assert!(resp.response.request_id.is_some());
+        assert_eq!(resp.response.usage.as_ref().unwrap().total_tokens, Some(1));
+        assert_eq!(vectors(resp), [(0, 99.0), (1, 97.0)]);
+        assert_eq!(transport.0.lock().unwrap()[1], ["c"]);
+        assert_eq!((cache().hits, cache().misses, cache().len), (1, 3, 2));
+
+        let resp = client
+            .get_embedding(&GetEmbeddingRequest {
+                input: Some("a".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(resp.response.request_id.is_none());
+        assert_eq!(vectors(resp), [(0, 97.0)]);
+        client.get_embedding(&list(&["b"])).await.unwrap();
+        assert_eq!(transport.0.lock().unwrap().len(), 3);
+
+        // The same text with another input type is a different entry.
+        client
+            .get_embedding(&GetEmbeddingRequest {
+                input_type: Some(INPUT_TYPE_QUERY.to_string()),
+                ..list(&["b"])
+            })
+            .await
+            .unwrap();
+        assert_eq!(transport.0.lock().unwrap().len(), 4);
+        assert_eq!((cache().hits, cache().misses), (2, 5));
+
+        client.embedding_cache().unwrap().clear();
+        assert_eq!(cache().len, 0);
+        client.get_embedding(&list(&["b"])).await.unwrap();
+        assert_eq!(transport.0.lock().unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_response_token_hint_adds_system_note() {
+        use crate::models::Role;
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .response_token_hint(200)
+            .debug(false)
+            .build();
+        let sent_messages = |i: usize| {
+            let body: serde_json::Value =
+                serde_json::from_slice(&transport.requests()[i].body).unwrap();
+            body["Messages"].clone()
+        };
+
+        client.chat_completions(&chat_request()).await.unwrap();
+        assert_eq!(
+            sent_messages(0),
+            serde_json::json!([
+                {"Role": "system", "Content": "Keep the response under 200 tokens."},
+                {"Role": "user", "Content": "Hello"},
+            ])
+        );
+
+        // An existing system message gets the note appended; options win.
+        let mut req = chat_request();
+        req.messages
+            .insert(0, Message::new(Role::System, "You are terse."));
+        let options = RequestOptions::new().response_token_hint(50);
+        client
+            .chat_completions_with_options(&req, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            sent_messages(1),
+            serde_json::json!([
+                {"Role": "system", "Content": "You are terse.\n\nKeep the response under 50 tokens."},
+                {"Role": "user", "Content": "Hello"},
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_session_sends_growing_history() {
+        use crate::models::Role;
+        use crate::session::{ChatParams, ChatSession, SessionConfig};
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("first"))
+                .respond_sse(ChunkSequence::from_pieces(["sec", "ond"]).events())
+                .respond_sse(ChunkSequence::from_pieces(["dropped"]).events())
+                .respond_json(200, &fixtures::chat_completions_response("third")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let mut session = ChatSession::new(
+            client,
+            SessionConfig {
+                model: Some("hunyuan-lite".to_string()),
+                system_prompt: Some("Be brief.".to_string()),
+                params: ChatParams {
+                    temperature: Some(0.5),
+                    top_p: None,
+                },
+                ..Default::default()
+            },
+        );
+        let sent_messages = |i: usize| {
+            let body: serde_json::Value =
+                serde_json::from_slice(&transport.requests()[i].body).unwrap();
+            assert_eq!(body["Model"], "hunyuan-lite");
+            assert_eq!(body["Temperature"], 0.5);
+            body["Messages"].clone()
+        };
+
+        assert_eq!(session.send("one").await.unwrap(), "first");
+        assert_eq!(
+            sent_messages(0),
+            serde_json::json!([
+                {"Role": "system", "Content": "Be brief."},
+                {"Role": "user", "Content": "one"},
+            ])
+        );
+
+        let mut stream = session.send_stream("two").await.unwrap();
+        while let Some(chunk) = stream.next().await {
+            chunk.unwrap();
+        }
+        drop(stream);
+        assert_eq!(session.history().len(), 5);
+        assert_eq!(session.history()[4].content, "second");
+
+        // A stream dropped before its end leaves the history untouched.
+        let mut stream = session.send_stream("lost").await.unwrap();
+        stream.next().await.unwrap().unwrap();
+        drop(stream);
+        assert_eq!(session.history().len(), 5);
+
+        assert_eq!(session.send("three").await.unwrap(), "third");
+        assert_eq!(
+            sent_messages(3),
+            serde_json::json!([
+                {"Role": "system", "Content": "Be brief."},
+                {"Role": "user", "Content": "one"},
+                {"Role": "assistant", "Content": "first"},
+                {"Role": "user", "Content": "two"},
+                {"Role": "assistant", "Content": "second"},
+                {"Role": "user", "Content": "three"},
+            ])
+        );
+        assert_eq!(session.history().len(), 7);
+
+        session.push(Message::new(Role::User, "injected"));
+        assert_eq!(session.history().len(), 8);
+        session.reset();
+        assert_eq!(session.history().len(), 1);
+        assert_eq!(session.history()[0].role, "system");
+    }
+
+    #[tokio::test]
+    async fn test_chat_session_truncation_drops_oldest_messages() {
+        use crate::models::Role;
+        use crate::session::{ChatSession, SessionConfig, TruncationPolicy};
+
+        let sent_and_dropped = |policy| async move {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_json(200, &fixtures::chat_completions_response("ok")),
+            );
+            let client = ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport.clone())
+                .debug(false)
+                .build();
+            let dropped = Arc::new(Mutex::new(Vec::new()));
+            let log = dropped.clone();
+            let mut session = ChatSession::new(
+                client,
+                SessionConfig {
+                    system_prompt: Some("sys".to_string()),
+                    truncation: Some(policy),
+                    ..Default::default()
+                },
+            )
+            .on_truncate(move |messages| {
+                log.lock()
+                    .unwrap()
+                    .extend(messages.iter().map(|m| m.content.clone()))
+            });
+            // 4 + 25 estimated tokens each.
+            for (i, role) in [Role::User, Role::Assistant, Role::User, Role::Assistant]
+                .into_iter()
+                .enumerate()
+            {
+                session.push(Message::new(role, format!("{i}{}", "x".repeat(99))));
+            }
+            session.send("question").await.unwrap();
+
+            let body: serde_json::Value =
+                serde_json::from_slice(&transport.requests()[0].body).unwrap();
+            let sent: Vec<String> = body["Messages"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|m| m["Content"].as_str().unwrap()[..1].to_string())
+                .collect();
+            let dropped: Vec<String> = dropped
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|c| c[..1].to_string())
+                .collect();
+            (sent, dropped)
+        };
+
+        let (sent, dropped) = sent_and_dropped(TruncationPolicy::MaxMessages(4)).await;
+        assert_eq!(sent, ["s", "2", "3", "q"]);
+        assert_eq!(dropped, ["0", "1"]);
+
+        // sys 5 + question 6 + 29 per old message: two old messages fit in 80.
+        let (sent, dropped) = sent_and_dropped(TruncationPolicy::MaxTokens {
+            budget: 100,
+            reserve_for_reply: 20,
+        })
+        .await;
+        assert_eq!(sent, ["s", "2", "3", "q"]);
+        assert_eq!(dropped, ["0", "1"]);
+
+        // The system prompt and the new message are kept even over budget.
+        let (sent, dropped) = sent_and_dropped(TruncationPolicy::MaxMessages(1)).await;
+        assert_eq!(sent, ["s", "q"]);
+        assert_eq!(dropped, ["0", "1", "2", "3"]);
+    }
+
+    #[tokio::test]
+    async fn test_chat_session_compact_replaces_old_messages_with_summary() {
+        use crate::models::Role;
+        use crate::session::{ChatSession, CompactOptions, SessionConfig, SUMMARY_HEADER};
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("first summary"))
+                .respond_json(200, &fixtures::chat_completions_response("second summary")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let mut session = ChatSession::new(
+            client,
+            SessionConfig {
+                system_prompt: Some("sys".to_string()),
+                ..Default::default()
+            },
+        );
+        for i in 0..5 {
+            let role = if i % 2 == 0 {
+                Role::User
+            } else {
+                Role::Assistant
+            };
+            session.push(Message::new(role, format!("m{i}")));
+        }
+        let options = CompactOptions {
+            keep_recent: 2,
+            ..Default::default()
+        };
+        let contents = |session: &ChatSession| -> Vec<String> {
+            session
+                .history()
+                .iter()
+                .map(|m| m.content.clone())
+                .collect()
+        };
+
+        let replaced = session.compact(&options).await.unwrap();
+        let replaced: Vec<&str> = replaced.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(replaced, ["m0", "m1", "m2"]);
+        let summary = format!("{SUMMARY_HEADER}\nfirst summary");
+        assert_eq!(contents(&session), ["sys", summary.as_str(), "m3", "m4"]);
+        assert_eq!(session.history()[1].role, "system");
+        let body: serde_json::Value =
+            serde_json::from_slice(&transport.requests()[0].body).unwrap();
+        assert_eq!(body["Messages"][0]["Role"], "system");
+        assert_eq!(
+            body["Messages"][1]["Content"],
+            "user: m0\n\nassistant: m1\n\nuser: m2"
+        );
+
+        // Already compact: no call, no change.
+        assert!(session.compact(&options).await.unwrap().is_empty());
+        assert_eq!(transport.requests().len(), 1);
+
+        // The earlier summary is folded into the next one.
+        session.push(Message::new(Role::User, "m5"));
+        let replaced = session.compact(&options).await.unwrap();
+        assert_eq!(replaced.len(), 2);
+        assert_eq!(replaced[0].content, summary);
+        assert_eq!(replaced[1].content, "m3");
+        let summary = format!("{SUMMARY_HEADER}\nsecond summary");
+        assert_eq!(contents(&session), ["sys", summary.as_str(), "m4", "m5"]);
+
+        session.reset();
+        assert!(session.compact(&options).await.unwrap().is_empty());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_chat_session_truncation_can_count_with_get_token_count() {
+        use crate::models::Role;
+        use crate::session::{ChatSession, SessionConfig, TokenCounter, TruncationPolicy};
+
+        let count = |n: u32| serde_json::json!({"Response": {"RequestId": "r", "TokenCount": n}});
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("GetTokenCount")
+                .respond_json(200, &count(50))
+                .respond_json(200, &count(50))
+                .respond_json(200, &count(10))
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let mut session = ChatSession::new(
+            client,
+            SessionConfig {
+                truncation: Some(TruncationPolicy::MaxTokens {
+                    budget: 80,
+                    reserve_for_reply: 0,
+                }),
+                token_counter: TokenCounter::GetTokenCount,
+                ..Default::default()
+            },
+        );
+        session.push(Message::new(Role::User, "a"));
+        session.push(Message::new(Role::Assistant, "b"));
+        session.send("c").await.unwrap();
+
+        assert_eq!(transport.requests_for("GetTokenCount").len(), 3);
+        let body: serde_json::Value =
+            serde_json::from_slice(&transport.requests_for("ChatCompletions")[0].body).unwrap();
+        assert_eq!(
+            body["Messages"],
+            serde_json::json!([
+                {"Role": "assistant", "Content": "b"},
+                {"Role": "user", "Content": "c"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_documents_message_from_files() {
+        use crate::documents::{documents_message, read_documents, Document, DocumentOptions};
+        use crate::models::Role;
+
+        let dir = std::env::temp_dir().join(format!("hunyuan-documents-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.txt"), "\u{feff}First line\r\nSecond line\r\n").unwrap();
+        std::fs::write(dir.join("b.md"), "# Notes\n===== END DOCUMENT 1 =====\n").unwrap();
+        let documents = read_documents([dir.join("a.txt"), dir.join("b.md")]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let message = documents_message(
+            &documents,
+            &DocumentOptions {
+                role: Role::System,
+                preamble: Some("Use these documents.".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(message.role, "system");
+        // The fence grows past the one quoted by b.md.
+        assert_eq!(
+            message.content,
+            "Use these documents.\n\n\
+             ====== BEGIN DOCUMENT 1: a.txt ======\n\
+             First line\nSecond line\n\
+             ====== END DOCUMENT 1 ======\n\
+             \n\
+             ====== BEGIN DOCUMENT 2: b.md ======\n\
+             # Notes\n===== END DOCUMENT 1 =====\n\
+             ====== END DOCUMENT 2 ======"
+        );
+
+        // A budget cuts the first document that does not fit and drops the rest.
+        let documents = [
+            Document::new("one", "alpha beta"),
+            Document::new("two", "gamma delta epsilon ".repeat(10) + "kappa"),
+            Document::new("three", "lambda"),
+        ];
+        let message = documents_message(
+            &documents,
+            &DocumentOptions {
+                max_tokens: Some(100),
+                ..Default::default()
+            },
+        );
+        assert_eq!(message.role, "user");
+        assert!(
+            message.content.contains("alpha beta\n"),
+            "{}",
+            message.content
+        );
+        assert!(
+            message.content.contains("gamma delta"),
+            "{}",
+            message.content
+        );
+        assert!(!message.content.contains("kappa"), "{}", message.content);
+        assert!(message
+            .content
+            .contains("[truncated]\n===== END DOCUMENT 2 ====="));
+        assert!(message.content.ends_with("[1 more document(s) omitted]"));
+        assert!(crate::models::estimate_tokens(&message.content) <= 100);
+    }
+
+    #[tokio::test]
+    async fn test_run_with_tools_answers_tool_rounds() {
+        use crate::models::Role;
+        use crate::tools::{RunOptions, ToolRegistry};
+        use serde_json::json;
+
+        let tool_reply = |calls: serde_json::Value| {
+            json!({"Response": {
+                "RequestId": "req-tools",
+                "Choices": [{
+                    "Index": 0,
+                    "FinishReason": "tool_calls",
+                    "Message": {"Role": "assistant", "Content": "", "ToolCalls": calls},
+                }],
+            }})
+        };
+        let call = |id: &str, name: &str, args: &str| json!({"Id": id, "Type": "function", "Function": {"Name": name, "Arguments": args}});
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(
+                    200,
+                    &tool_reply(json!([
+                        call("c1", "add", r#"{"a": 1, "b": 2}"#),
+                        call("c2", "lookup", "{}"),
+                    ])),
+                )
+                .respond_json(200, &tool_reply(json!([call("c3", "fail", "{}")])))
+                .respond_json(200, &fixtures::chat_completions_response("The sum is 3."))
+                .respond_json(200, &tool_reply(json!([call("c4", "add", "{not json")]))),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let mut registry = ToolRegistry::new();
+        registry
+            .register(
+                "add",
+                "Adds two numbers",
+                json!({"type": "object"}),
+                |args| async move {
+                    Ok::<_, String>(json!(
+                        args["a"].as_i64().unwrap() + args["b"].as_i64().unwrap()
+                    ))
+                },
+            )
+            .register(
+                "fail",
+                "Always fails",
+                json!({"type": "object"}),
+                |_| async { Err::<serde_json::Value, _>("backend down") },
+            );
+
+        let mut messages = vec![Message::new(Role::User, "What is 1 + 2?")];
+        let resp = client
+            .run_with_tools(&mut messages, &registry, RunOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            resp.messages().next().unwrap().content.as_deref(),
+            Some("The sum is 3.")
+        );
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(
+            roles,
+            [
+                "user",
+                "assistant",
+                "tool",
+                "tool",
+                "assistant",
+                "tool",
+                "assistant"
+            ]
+        );
+        assert_eq!(messages[1].tool_calls.as_ref().unwrap().len(), 2);
+        assert_eq!(messages[2].tool_call_id.as_deref(), Some("c1"));
+        assert_eq!(messages[2].content, "3");
+        // Unknown tools and handler errors are reported to the model.
+        assert_eq!(
+            messages[3].content,
+            r#"{"error":"unknown tool \"lookup\""}"#
+        );
+        assert_eq!(messages[5].content, r#"{"error":"backend down"}"#);
+        assert_eq!(messages[6].content, "The sum is 3.");
+
+        let bodies: Vec<serde_json::Value> = transport
+            .requests()
+            .iter()
+            .map(|r| serde_json::from_slice(&r.body).unwrap())
+            .collect();
+        assert_eq!(bodies.len(), 3);
+        assert_eq!(bodies[0]["Tools"][0]["Function"]["Name"], "add");
+        assert_eq!(
+            bodies[0]["Tools"][1]["Function"]["Parameters"],
+            r#"{"type":"object"}"#
+        );
+        let second = bodies[1]["Messages"].as_array().unwrap();
+        assert_eq!(second.len(), 4);
+        assert_eq!(second[1]["ToolCalls"][0]["Id"], "c1");
+        assert_eq!(second[2]["ToolCallId"], "c1");
+
+        // The round limit stops the loop before running the calls.
+        let mut messages = vec![Message::new(Role::User, "Add again")];
+        let err = client
+            .run_with_tools(
+                &mut messages,
+                &registry,
+                RunOptions {
+                    max_rounds: 0,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::Aborted(m) if m.contains("after 0 round")));
+        assert_eq!(messages.len(), 1);
+
+        let result = registry
+            .call(&crate::models::ToolCall {
+                function: crate::models::ToolCallFunction {
+                    name: "add".to_string(),
+                    arguments: "{not json".to_string(),
+                },
+                ..Default::default()
+            })
+            .await;
+        assert!(result.contains("arguments are not valid JSON"), "{result}");
+    }
+
+    #[tokio::test]
+    async fn test_chat_structured_parses_and_repairs_replies() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct City {
+            name: String,
+            population: u64,
+        }
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(
+                    200,
+                    &fixtures::chat_completions_response(r#"{"name":"Shenzhen","population":17}"#),
+                )
+                .respond_json(
+                    200,
+                    &fixtures::chat_completions_response(
+                        "Sure! Here it is:\n```json\n{\"name\": \"Beijing\", \"population\": 21}\n```\nAnything else?",
+                    ),
+                )
+                .respond_json(
+                    200,
+                    &fixtures::chat_completions_response(r#"{"name": "Shanghai", "population": "lots"}"#),
+                )
+                .respond_json(
+                    200,
+                    &fixtures::chat_completions_response(r#"{"name": "Shanghai", "population": 24}"#),
+                )
+                .respond_json(200, &fixtures::chat_completions_response("no idea"))
+                .respond_json(200, &fixtures::chat_completions_response("still no idea"))
+                .respond_json(200, &fixtures::chat_completions_response("nope")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let sent_messages = |i: usize| {
+            let body: serde_json::Value =
+                serde_json::from_slice(&transport.requests()[i].body).unwrap();
+            body["Messages"].as_array().unwrap().clone()
+        };
+
+        let city: City = client
+            .chat_structured(&chat_request(), "Give name and population.")
+            .await
+            .unwrap();
+        assert_eq!(city.name, "Shenzhen");
+        let prompt = sent_messages(0)[0]["Content"].as_str().unwrap().to_string();
+        assert!(
+            prompt.starts_with("Hello\n\nGive name and population.\n\n"),
+            "{prompt}"
+        );
+
+        let city: City = client.chat_structured(&chat_request(), "").await.unwrap();
+        assert_eq!(city.population, 21);
+
+        // The bad reply and the parse error are sent back for one repair.
+        let city: City = client.chat_structured(&chat_request(), "").await.unwrap();
+        assert_eq!(city.population, 24);
+        let repair = sent_messages(3);
+        assert_eq!(repair.len(), 3);
+        assert_eq!(repair[1]["Role"], "assistant");
+        assert_eq!(
+            repair[1]["Content"],
+            r#"{"name": "Shanghai", "population": "lots"}"#
+        );
+        assert!(repair[2]["Content"]
+            .as_str()
+            .unwrap()
+            .contains("invalid type: string \"lots\""));
+
+        let err = client
+            .chat_structured::<City>(&chat_request(), "")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::Decode(m) if m.contains("after 1 repair attempt")));
+        assert_eq!(transport.requests().len(), 6);
+
+        let err = client
+            .chat_structured_with_options::<City>(
+                &chat_request(),
+                "",
+                crate::StructuredOptions { repair_attempts: 0 },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::Decode(m) if m.contains("after 0 repair attempt")));
+        assert_eq!(transport.requests().len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_request_client_and_extra_headers_are_sent_unsigned() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .extra_header("X-TC-Experimental-Feature", "on")
+            .extra_header("X-TC-Action", "Ignored")
+            .debug(false)
+            .build();
+        client.chat_completions(&chat_request()).await.unwrap();
+
+        let req = &transport.requests()[0];
+        assert_eq!(
+            req.header("X-TC-RequestClient"),
+            Some(concat!(
+                "tencentcloud-hunyuan-sdk/",
+                env!("CARGO_PKG_VERSION")
+            ))
+        );
+        assert_eq!(req.header("X-TC-Experimental-Feature"), Some("on"));
+        assert_eq!(req.action(), Some("ChatCompletions"));
+        let auth = req.header("Authorization").unwrap();
+        assert!(auth.contains("SignedHeaders=content-type;host,"));
+
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .request_client("my-app/2.0")
+            .debug(false)
+            .build();
+        client.chat_completions(&chat_request()).await.unwrap();
+        assert_eq!(
+            transport.requests()[1].header("X-TC-RequestClient"),
+            Some("my-app/2.0")
+        );
+    }
+
+    #[test]
+    fn test_header_settings_are_checked_at_build() {
+        let config_error = |builder: ClientBuilder| match builder
+            .credential(test_credential())
+            .transport(Arc::new(MockTransport::new()))
+            .try_build()
+        {
+            Err(SdkError::InvalidConfig(message)) => message,
+            other => panic!("expected InvalidConfig, got {:?}", other.err()),
+        };
+        let message = config_error(ClientBuilder::new().extra_header("Authorization", "x"));
+        assert!(message.contains("must start with X-TC-"), "{message}");
+        let message = config_error(ClientBuilder::new().extra_header("X-TC-Bad Name", "x"));
+        assert!(message.contains("not a valid header name"), "{message}");
+        let message = config_error(ClientBuilder::new().extra_header("X-TC-Feature", "a\nb"));
+        assert!(message.contains("invalid header value"), "{message}");
+        let message = config_error(ClientBuilder::new().request_client("my-app\n2.0"));
+        assert!(message.contains("request client"), "{message}");
+        let message = config_error(ClientBuilder::new().host_override("hunyuan\n.example"));
+        assert!(message.contains("host"), "{message}");
+        let message = config_error(
+            ClientBuilder::new().region(crate::client::Region::Custom("ap-\u{7}x".to_string())),
+        );
+        assert!(message.contains("region"), "{message}");
+    }
+
+    #[tokio::test]
+    async fn test_default_headers_merge_with_per_call_headers() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("ok")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .default_header("X-Env", "staging")
+            .default_header("X-Tenant", "acme")
+            .default_header("X-Tenant", "globex")
+            .debug(false)
+            .build();
+
+        client.chat_completions(&chat_request()).await.unwrap();
+        let options = RequestOptions::new()
+            .header("x-env", "canary")
+            .header("X-Route", "blue");
+        client
+            .chat_completions_with_options(&chat_request(), &options)
+            .await
+            .unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].header("X-Env"), Some("staging"));
+        assert_eq!(requests[0].header("X-Tenant"), Some("globex"));
+        assert_eq!(requests[0].header("X-Route"), None);
+        assert_eq!(requests[1].header("X-Env"), Some("canary"));
+        assert_eq!(requests[1].header("X-Tenant"), Some("globex"));
+        assert_eq!(requests[1].header("X-Route"), Some("blue"));
+        for req in &requests {
+            assert_eq!(req.action(), Some("ChatCompletions"));
+            let auth = req.header("Authorization").unwrap();
+            assert!(auth.contains("SignedHeaders=content-type;host,"));
+        }
+
+        // Headers set directly on the options are checked the same way.
+        let mut options = RequestOptions::new();
+        options
+            .extra_headers
+            .push(("X-TC-Action".to_string(), "Other".to_string()));
+        let err = client
+            .chat_completions_with_options(&chat_request(), &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::InvalidRequest(_)), "{err:?}");
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_custom_headers_reject_protected_and_invalid_headers() {
+        let transport = Arc::new(MockTransport::new());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let config_error = |builder: ClientBuilder| match builder
+            .credential(test_credential())
+            .transport(transport.clone())
+            .try_build()
+        {
+            Err(SdkError::InvalidConfig(message)) => message,
+            other => panic!("expected InvalidConfig, got {:?}", other.err()),
+        };
+        let request_error = |options: RequestOptions| {
+            let client = &client;
+            async move {
+                match client
+                    .chat_completions_with_options(&chat_request(), &options)
+                    .await
+                {
+                    Err(SdkError::InvalidRequest(message)) => message,
+                    other => panic!("expected InvalidRequest, got {:?}", other.err()),
+                }
+            }
+        };
+
+        for name in [
+            "Host",
+            "content-type",
+            "Authorization",
+            "X-TC-Action",
+            "x-tc-token",
+        ] {
+            let message = config_error(ClientBuilder::new().default_header(name, "x"));
+            assert!(
+                message.contains("cannot be overridden"),
+                "{name}: {message}"
+            );
+            let message = request_error(RequestOptions::new().header(name, "x")).await;
+            assert!(
+                message.contains("cannot be overridden"),
+                "{name}: {message}"
+            );
+            let message = config_error(ClientBuilder::new().trace_id_header(name));
+            assert!(
+                message.contains("cannot be overridden"),
+                "{name}: {message}"
+            );
+        }
+        let message = config_error(ClientBuilder::new().default_header("X Env", "x"));
+        assert!(message.contains("not a valid header name"), "{message}");
+        let message = config_error(
+            ClientBuilder::new()
+                .default_header("X-Env", "line\nbreak")
+                .default_header("X Env", "x"),
+        );
+        assert!(message.contains("invalid header value"), "{message}");
+        let message = request_error(RequestOptions::new().header("X Env", "x")).await;
+        assert!(message.contains("not a valid header name"), "{message}");
+        let message = request_error(RequestOptions::new().header("X-Env", "line\nbreak")).await;
+        assert!(message.contains("invalid header value"), "{message}");
+        assert!(transport.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_clock_skew_warning() {
+        let mut dated = MockResponse::json(200, &fixtures::chat_completions_response("ok"));
+        dated.headers.insert(
+            "Date",
+            reqwest::header::HeaderValue::from_static("Tue, 14 Nov 2023 22:13:20 GMT"),
+        );
+        let server_time = 1_700_000_000;
+        let client_at = |timestamp: i64, logs: Arc<CaptureSink>| {
+            ClientBuilder::new()
+                .credential(test_credential())
+                .transport(Arc::new(
+                    MockTransport::new()
+                        .expect_action("ChatCompletions")
+                        .respond(dated.clone()),
+                ))
+                .clock(Arc::new(crate::clock::FixedClock(timestamp)))
+                .clock_skew_warning(Duration::from_secs(60))
+                .log_sink(logs)
+                .debug(false)
+                .build()
+        };
+
+        // Two minutes behind: the first response reveals the skew, the next
+        // request warns.
+        let logs = Arc::new(CaptureSink::default());
+        let client = client_at(server_time - 120, logs.clone());
+        client.chat_completions(&chat_request()).await.unwrap();
+        assert!(logs.lines(LogLevel::Warn).is_empty());
+        client.chat_completions(&chat_request()).await.unwrap();
+        let warnings = logs.lines(LogLevel::Warn);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("clock skew"), "{}", warnings[0]);
+        assert!(warnings[0].contains("skew_secs=-120"), "{}", warnings[0]);
+        assert!(warnings[0].contains("behind"), "{}", warnings[0]);
+
+        // Within the threshold: no warning.
+        let logs = Arc::new(CaptureSink::default());
+        let client = client_at(server_time + 30, logs.clone());
+        client.chat_completions(&chat_request()).await.unwrap();
+        client.chat_completions(&chat_request()).await.unwrap();
+        assert!(logs.lines(LogLevel::Warn).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_raw_returns_body_verbatim() {
+        let body = concat!(
+            "{\"Response\": {\n  \"RequestId\": \"req-raw\",\n",
+            "  \"Choices\": [{\"Index\": 0, \"Message\": {\"Role\": \"assistant\", \"Content\": \"\\u4f60\\u597d\"}}],\n",
+            "  \"Experimental\": {\"Score\": 0.25}\n}}\n"
+        );
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond(MockResponse::with_content_type(
+                    200,
+                    "application/json",
+                    body,
+                )),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport)
+            .debug(false)
+            .build();
+
+        let (resp, raw) = client.chat_completions_raw(&chat_request()).await.unwrap();
+        assert_eq!(raw, body);
+        assert_eq!(
+            resp.messages().next().unwrap().content.as_deref(),
+            Some("你好")
+        );
+    }
+
+    /// Action, labels, attempts and error of a recorded call.
+    type Recorded = (String, HashMap<String, String>, u32, Option<String>);
+
+    #[derive(Default)]
+    struct CaptureMetrics(Mutex<Vec<Recorded>>);
+
+    impl MetricsSink for CaptureMetrics {
+        fn record(&self, call: &CallMetrics<'_>) {
+            self.0.lock().unwrap().push((
+                call.action.to_string(),
+                call.labels.clone(),
+                call.attempts,
+                call.error.map(|e| e.to_string()),
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trace_id_is_sent_logged_and_reported() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::ConnectionReset)
+                .respond_json(200, &fixtures::chat_completions_response("hi")),
+        );
+        let logs = Arc::new(CaptureSink::default());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .retry_policy(crate::RetryPolicy::new(1).initial_backoff(Duration::ZERO))
+            .log_sink(logs.clone())
+            .debug(true)
+            .build();
+        assert_eq!(client.trace_id_header(), "x-tc-traceid");
+        let options = RequestOptions::new().trace_id("req-7f3a");
+
+        let (_, meta): (crate::models::ChatCompletionsResponse, _) = client
+            .call_with_meta("ChatCompletions", &chat_request(), &options)
+            .await
+            .unwrap();
+        assert_eq!(meta.trace_id.as_deref(), Some("req-7f3a"));
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        for req in &requests {
+            assert_eq!(req.headers["X-TC-TraceId"], "req-7f3a");
+        }
+        let lines = logs.lines(LogLevel::Debug);
+        for prefix in [
+            "[hunyuan-sdk][request] ",
+            "[hunyuan-sdk][retry] ",
+            "[hunyuan-sdk][response] ",
+        ] {
+            assert!(
+                lines
+                    .iter()
+                    .any(|l| l.starts_with(prefix) && l.contains(" trace_id=req-7f3a")),
+                "no {} line with the trace id in {:?}",
+                prefix,
+                lines
+            );
+        }
+
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_json(200, &fixtures::chat_completions_response("hi")),
+        );
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .trace_id_header("X-Request-ID")
+            .debug(false)
+            .build();
+        client
+            .chat_completions_with_options(&chat_request(), &options)
+            .await
+            .unwrap();
+        let request = transport.requests().remove(0);
+        assert_eq!(request.headers["X-Request-ID"], "req-7f3a");
+        assert!(request.headers.get("X-TC-TraceId").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_trace_id_fails_the_call_before_sending() {
+        let transport = Arc::new(MockTransport::new());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .debug(false)
+            .build();
+        let options = RequestOptions::new().trace_id("line\nbreak");
+
+        let err = client
+            .chat_completions_with_options(&chat_request(), &options)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, SdkError::InvalidRequest(m) if m.contains("trace id")),
+            "{err:?}"
+        );
+        assert!(transport.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_labels_reach_metrics_sink_but_not_server() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .expect_action("ChatCompletions")
+                .respond_fault(Fault::ConnectionReset)
+                .respond_json(200, &fixtures::chat_completions_response("hi"))
+                .respond_sse(ChunkSequence::from_text("streamed", 4).events())
+                .expect_action("GetTokenCount")
+                .respond_error(400, "InvalidParameter", "bad"),
+        );
+        let metrics = Arc::new(CaptureMetrics::default());
+        let logs = Arc::new(CaptureSink::default());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .transport(transport.clone())
+            .retry_policy(crate::RetryPolicy::new(1).initial_backoff(Duration::ZERO))
+            .metrics_sink(metrics.clone())
+            .log_sink(logs.clone())
+            .debug(true)
+            .build();
+        let options = RequestOptions::new()
+            .label("tenant", "acme")
+            .label("feature", "search");
+
+        client
+            .chat_completions_with_options(&chat_request(), &options)
+            .await
+            .unwrap();
+        let err = client
+            .call_raw("GetTokenCount", &serde_json::json!({}), &options)
+            .await
+            .unwrap_err();
+        client
+            .chat_completions_stream_with_options(&chat_request(), &RequestOptions::new())
+            .await
+            .unwrap();
+
+        let labels: HashMap<String, String> = [("tenant", "acme"), ("feature", "search")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(
+            *metrics.0.lock().unwrap(),
+            vec![
+                ("ChatCompletions".to_string(), labels.clone(), 2, None),
+                (
+                    "GetTokenCount".to_string(),
+                    labels,
+                    1,
+                    Some(err.to_string())
+                ),
+                ("ChatCompletions".to_string(), HashMap::new(), 1, None),
+            ]
+        );
+        for req in transport.requests() {
+            assert!(req.headers.keys().all(|k| !k.as_str().contains("tenant")));
+            assert!(!String::from_utf8_lossy(&req.body).contains("acme"));
+        }
+        assert!(logs.lines(LogLevel::Debug).iter().any(|l| l
+            .starts_with("[hunyuan-sdk][request] action=GetTokenCount")
+            && l.ends_with(" labels=feature=search,tenant=acme")));
+    }
+
+    #[tokio::test]
+    async fn test_html_error_page_is_unexpected_response() {
+        let page = format!(
+            "<!DOCTYPE html><html><body><h1>Proxy Error</h1>{}</body></html>",
+            "x".repeat(500)
+        );
+        for status in [200, 403] {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond(MockResponse::with_content_type(
+                        status,
+                        "text/html; charset=utf-8",
+                        page.clone(),
+                    )),
+            );
+            let client = ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport)
+                .debug(false)
+                .build();
+
+            match client.chat_completions(&chat_request()).await {
+                Err(SdkError::UnexpectedResponse {
+                    status: actual,
+                    content_type,
+                    snippet,
+                }) => {
+                    assert_eq!(actual, status);
+                    assert_eq!(content_type.as_deref(), Some("text/html; charset=utf-8"));
+                    assert!(snippet.starts_with("<!DOCTYPE html><html><body><h1>Proxy Error"));
+                    assert_eq!(snippet.chars().count(), 203);
+                }
+                other => panic!("unexpected result: {:?}", other.map(|_| ())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_warning_above_threshold() {
+        let (endpoint, server) = serve_once(Duration::from_millis(200), 200, chat_ok_body()).await;
+        let sink = Arc::new(CaptureSink::default());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .endpoint(endpoint)
+            .debug(false)
+            .log_sink(sink.clone())
+            .slow_request_threshold(Duration::from_millis(50))
+            .build();
+
+        let (resp, meta) = client
+            .chat_completions_with_meta(&chat_request())
+            .await
+            .unwrap();
+        server.await.unwrap();
+
+        assert_eq!(resp.response.id.as_deref(), Some("id-1"));
+        assert!(meta.slow);
+        assert_eq!(meta.request_id.as_deref(), Some("req-1"));
+        let warnings = sink.lines(LogLevel::Warn);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("slow request action=ChatCompletions"));
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_no_warning_below_threshold() {
+        let (endpoint, server) = serve_once(Duration::ZERO, 200, chat_ok_body()).await;
+        let sink = Arc::new(CaptureSink::default());
+        let client = ClientBuilder::new()
+            .credential(test_credential())
+            .endpoint(endpoint)
+            .debug(false)
+            .log_sink(sink.clone())
+            .slow_request_threshold(Duration::from_secs(5))
+            .build();
+
+        let (_, meta) = client
+            .chat_completions_with_meta(&chat_request())
+            .await
+            .unwrap();
+        server.await.unwrap();
+
+        assert!(!meta.slow);
+        assert!(sink.lines(LogLevel::Warn).is_empty());
+    }
+
+    #[cfg(feature = "blocking")]
+    mod blocking {
+        use super::*;
+        use crate::blocking;
+
+        fn blocking_client(transport: Arc<MockTransport>) -> blocking::Client {
+            ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport)
+                .debug(false)
+                .build_blocking()
+        }
+
+        #[test]
+        fn chat_completions_blocks_until_response() {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_json(200, &fixtures::chat_completions_response("hi"))
+                    .with_latency(Duration::from_millis(20)),
+            );
+            let client = blocking_client(transport.clone());
+
+            let resp = client.chat_completions(&chat_request()).unwrap();
+            let choice = &resp.response.choices.unwrap()[0];
+            assert_eq!(
+                choice.message.as_ref().unwrap().content.as_deref(),
+                Some("hi")
+            );
+            transport.assert_called("ChatCompletions", 1);
+        }
+
+        #[test]
+        fn stream_iterates_chunks() {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_sse(ChunkSequence::from_text("hello world", 3).events()),
+            );
+            let client = blocking_client(transport);
+
+            let text: String = client
+                .chat_completions_stream(&chat_request())
+                .unwrap()
+                .map(Result::unwrap)
+                .flat_map(|chunk| chunk.choices.unwrap())
+                .filter_map(|c| c.delta?.content)
+                .collect();
+            assert_eq!(text, "hello world");
+        }
+
+        #[test]
+        fn embedding_and_service_errors() {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("GetEmbedding")
+                    .respond_json(
+                        200,
+                        &EmbeddingResponseFixture::new()
+                            .embedding(vec![0.5, 0.25])
+                            .build(),
+                    )
+                    .expect_action("ChatCompletions")
+                    .respond_error(400, "InvalidParameter", "bad model"),
+            );
+            let client = blocking_client(transport);
+
+            let req = crate::models::GetEmbeddingRequest {
+                input: Some("hello".to_string()),
+                ..Default::default()
+            };
+            let resp = client.get_embedding(&req).unwrap();
+            assert_eq!(
+                resp.response.data.unwrap()[0].embedding.as_deref(),
+                Some(&[0.5, 0.25][..])
+            );
+            match client.chat_completions(&chat_request()).unwrap_err() {
+                SdkError::Service { code, .. } => assert_eq!(code, "InvalidParameter"),
+                other => panic!("unexpected error: {:?}", other),
+            }
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    mod gzip {
+        use super::*;
+        use sha2::{Digest, Sha256};
+        use std::io::Read;
+
+        #[tokio::test]
+        async fn compressed_body_is_signed_over_the_compressed_bytes() {
+            let timestamp = 1_704_164_645;
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_json(200, &fixtures::chat_completions_response("hi")),
+            );
+            let client = ClientBuilder::new()
+                .credential(test_credential())
+                .transport(transport.clone())
+                .clock(Arc::new(crate::clock::FixedClock(timestamp)))
+                .gzip_requests(1024)
+                .debug(false)
+                .build();
+
+            let mut large = chat_request();
+            large.messages[0].content = "a long prompt ".repeat(200);
+            client.chat_completions(&large).await.unwrap();
+            client.chat_completions(&chat_request()).await.unwrap();
+
+            let requests = transport.requests();
+            let (compressed, small) = (&requests[0], &requests[1]);
+            assert_eq!(compressed.header("Content-Encoding"), Some("gzip"));
+            let mut json = String::new();
+            flate2::read::GzDecoder::new(&compressed.body[..])
+                .read_to_string(&mut json)
+                .unwrap();
+            assert!(json.len() > compressed.body.len());
+            let sent: serde_json::Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(sent["Messages"][0]["Content"], large.messages[0].content);
+            assert_eq!(small.header("Content-Encoding"), None);
+            serde_json::from_slice::<serde_json::Value>(&small.body).unwrap();
+
+            let payload_hash = hex::encode(Sha256::digest(&compressed.body));
+            let canonical = client
+                .canonical_request_for("ChatCompletions", &large, timestamp)
+                .unwrap();
+            assert!(canonical.ends_with(&payload_hash));
+            assert_eq!(
+                compressed.header("Authorization").unwrap(),
+                expected_authorization(timestamp, &canonical)
+            );
+        }
+    }
+
+    #[cfg(feature = "secrecy")]
+    mod secrecy {
+        use super::*;
+        use crate::credential::SecretCredential;
+
+        #[tokio::test]
+        async fn secret_credential_signs_like_plain_credential() {
+            let mut authorizations = Vec::new();
+            for secret in [true, false] {
+                let transport = Arc::new(
+                    MockTransport::new()
+                        .expect_action("ChatCompletions")
+                        .respond_json(200, &fixtures::chat_completions_response("ok")),
+                );
+                let builder = ClientBuilder::new()
+                    .transport(transport.clone())
+                    .clock(Arc::new(crate::clock::FixedClock(1_700_000_000)))
+                    .debug(false);
+                let builder = if secret {
+                    builder.credential_provider(Arc::new(
+                        SecretCredential::new("test_id", "test_key").with_token("tok"),
+                    ))
+                } else {
+                    builder.credential(Credential {
+                        token: Some("tok".to_string()),
+                        ..test_credential()
+                    })
+                };
+                builder
+                    .build()
+                    .chat_completions(&chat_request())
+                    .await
+                    .unwrap();
+                let request = transport.requests().remove(0);
+                assert_eq!(request.headers["X-TC-Token"], "tok");
+                authorizations.push(request.headers["Authorization"].clone());
+            }
+            assert_eq!(authorizations[0], authorizations[1]);
+        }
+
+        #[test]
+        fn secret_credential_never_reveals_secrets() {
+            let credential: SecretCredential = serde_json::from_value(serde_json::json!({
+                "secret_id": "AKIDvisible",
+                "secret_key": "hidden-key",
+                "token": "hidden-token"
+            }))
+            .unwrap();
+            let debug = format!("{:?}", credential);
+            assert!(debug.contains("AKIDvisible"));
+            assert!(!debug.contains("hidden-key"), "{}", debug);
+            assert!(!debug.contains("hidden-token"), "{}", debug);
+
+            let plain = crate::CredentialProvider::credential(&credential);
+            assert_eq!(plain.secret_key, "hidden-key");
+            assert_eq!(plain.token.as_deref(), Some("hidden-token"));
+        }
+    }
+
+    #[cfg(feature = "model-trait")]
+    mod chat_model {
+        use super::*;
+        use crate::chat_model::{ChatMessage, ChatModel, HunyuanChatModel, ModelError};
+
+        fn model(transport: Arc<MockTransport>) -> Box<dyn ChatModel> {
+            let client = retrying_client(transport, crate::RetryPolicy::new(0));
+            Box::new(HunyuanChatModel::new(client, "hunyuan-pro"))
+        }
+
+        #[tokio::test]
+        async fn generate_and_count_through_trait_object() {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .match_body_json(&serde_json::json!({
+                        "Model": "hunyuan-pro",
+                        "Messages": [
+                            {"Role": "system", "Content": "Be brief."},
+                            {"Role": "user", "Content": "Hi"}
+                        ]
+                    }))
+                    .respond_json(200, &fixtures::chat_completions_response("Hello!"))
+                    .expect_action("GetTokenCount")
+                    .match_body_json(&serde_json::json!({"Prompt": "count me"}))
+                    .respond_json(
+                        200,
+                        &serde_json::json!({"Response": {"RequestId": "r", "TokenCount": 3}}),
+                    ),
+            );
+            let model = model(transport);
+
+            let info = model.info();
+            assert_eq!(
+                (info.provider.as_str(), info.model.as_str()),
+                ("hunyuan", "hunyuan-pro")
+            );
+            let reply = model
+                .generate(&[ChatMessage::system("Be brief."), ChatMessage::user("Hi")])
+                .await
+                .unwrap();
+            assert_eq!(reply, "Hello!");
+            assert_eq!(model.count_tokens("count me").await.unwrap(), 3);
+        }
+
+        #[tokio::test]
+        async fn stream_yields_text_pieces() {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_sse(ChunkSequence::from_text("streamed reply", 4).events()),
+            );
+            let model = model(transport);
+
+            let pieces: Vec<String> = model
+                .generate_stream(&[ChatMessage::user("Hi")])
+                .await
+                .unwrap()
+                .map(Result::unwrap)
+                .collect()
+                .await;
+            assert_eq!(pieces.concat(), "streamed reply");
+            assert!(pieces.len() > 1);
+        }
+
+        #[tokio::test]
+        async fn errors_map_to_provider_neutral_variants() {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .expect_action("ChatCompletions")
+                    .respond_error(200, "RequestLimitExceeded", "slow down")
+                    .respond_error(200, "AuthFailure.SignatureFailure", "bad signature")
+                    .respond_error(200, "InvalidParameterValue.Model", "no such model")
+                    .respond_fault(Fault::ConnectionReset)
+                    .respond_error(200, "ResourceInsufficient", "nope"),
+            );
+            let model = model(transport);
+            let messages = [ChatMessage::user("Hi")];
+
+            let mut errors = Vec::new();
+            for _ in 0..5 {
+                errors.push(model.generate(&messages).await.unwrap_err());
+            }
+            assert!(matches!(errors[0], ModelError::RateLimited(_)));
+            assert!(matches!(errors[1], ModelError::Authentication(_)));
+            assert!(matches!(errors[2], ModelError::InvalidRequest(_)));
+            assert!(matches!(errors[3], ModelError::Unavailable(_)));
+            assert!(matches!(errors[4], ModelError::Other(_)));
+        }
+    }
+
+    mod properties {
+        use crate::arbitrary::extra_fields;
+        use crate::models::{
+            ChatChoice, ChatCompletionsChunk, ChatCompletionsRequest, ChatCompletionsResponseInner,
+            GetEmbeddingRequest, GetEmbeddingResponseInner, Message, SearchInfo,
+            TextToImageLiteRequest, TextToImageLiteResponseInner, Usage,
+        };
+        use proptest::prelude::*;
+        use serde::{de::DeserializeOwned, Serialize};
+        use std::fmt::Debug;
+
+        fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(
+            value: &T,
+        ) -> Result<(), TestCaseError> {
+            let json = serde_json::to_string(value).unwrap();
+            let back: T = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(&back, value, "via {}", json);
+            Ok(())
+        }
+
+        fn has_null(value: &serde_json::Value) -> bool {
+            match value {
+                serde_json::Value::Null => true,
+                serde_json::Value::Array(items) => items.iter().any(has_null),
+                serde_json::Value::Object(map) => map.values().any(has_null),
+                _ => false,
+            }
+        }
+
+        /// Found by `models_round_trip`: without serde_json's
+        /// `float_roundtrip` feature this parsed back one ULP off.
+        #[test]
+        fn embedding_value_round_trips_exactly() {
+            let value = 0.490_954_682_008_676_04_f64;
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(serde_json::from_str::<f64>(&json).unwrap(), value);
+        }
+
+        fn embedding_vector() -> impl Strategy<Value = Vec<f64>> {
+            prop::collection::vec(-1e3..1e3f64, 1..64)
+        }
+
+        proptest! {
+            #[test]
+            fn cosine_similarity_is_reflexive_and_symmetric(
+                (a, b) in embedding_vector().prop_flat_map(|a| {
+                    let len = a.len();
+                    (Just(a), prop::collection::vec(-1e3..1e3f64, len))
+                }),
+            ) {
+                use crate::embeddings::math::{cosine_similarity, l2_norm, l2_normalize_in_place};
+
+                if l2_norm(&a) > 1e-6 {
+                    prop_assert!((cosine_similarity(&a, &a).unwrap() - 1.0).abs() < 1e-9);
+                    let mut unit = a.clone();
+                    l2_normalize_in_place(&mut unit);
+                    prop_assert!((l2_norm(&unit) - 1.0).abs() < 1e-9);
+                }
+                let ab = cosine_similarity(&a, &b).unwrap();
+                prop_assert_eq!(ab, cosine_similarity(&b, &a).unwrap());
+                prop_assert!((-1.0..=1.0).contains(&ab));
+            }
+
+            #[test]
+            fn models_round_trip(
+                message in any::<Message>(),
+                choice in any::<ChatChoice>(),
+                usage in any::<Usage>(),
+                search_info in any::<SearchInfo>(),
+                chunk in any::<ChatCompletionsChunk>(),
+                embedding in any::<GetEmbeddingResponseInner>(),
+                image in any::<TextToImageLiteResponseInner>(),
+            ) {
+                round_trip(&message)?;
+                round_trip(&choice)?;
+                round_trip(&usage)?;
+                round_trip(&search_info)?;
+                round_trip(&chunk)?;
+                round_trip(&embedding)?;
+                round_trip(&image)?;
+            }
+
+            #[test]
+            fn requests_round_trip_without_nulls(
+                chat in any::<ChatCompletionsRequest>(),
+                embedding in any::<GetEmbeddingRequest>(),
+                image in any::<TextToImageLiteRequest>(),
+            ) {
+                round_trip(&chat)?;
+                round_trip(&embedding)?;
+                round_trip(&image)?;
+                prop_assert!(!has_null(&serde_json::to_value(&chat).unwrap()));
+                prop_assert!(!has_null(&serde_json::to_value(&embedding).unwrap()));
+                prop_assert!(!has_null(&serde_json::to_value(&image).unwrap()));
+            }
+
+            #[test]
+            fn unknown_fields_are_preserved(
+                mut inner in any::<ChatCompletionsResponseInner>(),
+                mut choice in any::<ChatChoice>(),
+                inner_extra in extra_fields(),
+                choice_extra in extra_fields(),
+            ) {
+                choice.extra = choice_extra.clone();
+                inner.extra = inner_extra.clone();
+                inner.choices = Some(vec![choice]);
+
+                let json = serde_json::to_value(&inner).unwrap();
+                for key in inner_extra.keys() {
+                    prop_assert_eq!(&json[key], &inner_extra[key]);
+                }
+                let back: ChatCompletionsResponseInner = serde_json::from_value(json).unwrap();
+                prop_assert_eq!(&back.extra, &inner_extra);
+                prop_assert_eq!(&back.choices.as_ref().unwrap()[0].extra, &choice_extra);
+                prop_assert_eq!(back, inner);
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Limiting the number of concurrent calls, serving waiting calls by
+//! priority.
+use crate::client::Priority;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Times a waiting call may be passed over by calls of higher priority before
+/// it is served next.
+pub(crate) const MAX_PASSED_OVER: u32 = 8;
+
+/// A counting semaphore whose waiters queue per [`Priority`].
+///
+/// A free permit goes to the oldest waiter of the highest priority, except
+/// that a queue whose head was passed over [`MAX_PASSED_OVER`] times is
+/// served first, so `Low` calls are delayed but never starved.
+pub(crate) struct Limiter {
+    state: Mutex<State>,
+}
+
+struct State {
+    available: usize,
+    /// Indexed by [`slot`], highest priority first.
+    queues: [VecDeque<oneshot::Sender<Permit>>; 3],
+    passed_over: [u32; 3],
+}
+
+fn slot(priority: Priority) -> usize {
+    match priority {
+        Priority::High => 0,
+        Priority::Normal => 1,
+        Priority::Low => 2,
+    }
+}
+
+impl Limiter {
+    /// A limiter letting `permits` calls run at once; `0` is treated as `1`.
+    pub fn new(permits: usize) -> Self {
+        Self {
+            state: Mutex::new(State {
+                available: permits.max(1),
+                queues: Default::default(),
+                passed_over: [0; 3],
+            }),
+        }
+    }
+
+    /// Waits for a permit. Dropping the future gives up the place in line.
+    pub async fn acquire(self: &Arc<Self>, priority: Priority) -> Permit {
+        let rx = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 && state.queues.iter().all(VecDeque::is_empty) {
+                state.available -= 1;
+                return Permit(Some(self.clone()));
+            }
+            let (tx, rx) = oneshot::channel();
+            state.queues[slot(priority)].push_back(tx);
+            rx
+        };
+        // The sender lives in the queue until a permit is sent, and the
+        // queue lives as long as `self`.
+        rx.await.expect("limiter dropped a waiter")
+    }
+
+    /// Hands a returned permit to the next waiter, or makes it available.
+    fn release(self: &Arc<Self>) {
+        loop {
+            let tx = {
+                let mut state = self.state.lock().unwrap();
+                match state.next_waiter() {
+                    Some(tx) => tx,
+                    None => {
+                        state.available += 1;
+                        return;
+                    }
+                }
+            };
+            match tx.send(Permit(Some(self.clone()))) {
+                Ok(()) => return,
+                // The waiter gave up; try the next one.
+                Err(mut permit) => permit.0 = None,
+            }
+        }
+    }
+}
+
+impl State {
+    fn next_waiter(&mut self) -> Option<oneshot::Sender<Permit>> {
+        let starved = (0..3)
+            .rev()
+            .find(|&i| self.passed_over[i] >= MAX_PASSED_OVER && !self.queues[i].is_empty());
+        let chosen = starved.or_else(|| (0..3).find(|&i| !self.queues[i].is_empty()))?;
+        for i in 0..3 {
+            if i == chosen {
+                self.passed_over[i] = 0;
+            } else if i > chosen && !self.queues[i].is_empty() {
+                self.passed_over[i] += 1;
+            }
+        }
+        self.queues[chosen].pop_front()
+    }
+}
+
+/// The right to run one call; returned to the limiter when dropped.
+pub(crate) struct Permit(Option<Arc<Limiter>>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.0.take() {
+            limiter.release();
+        }
+    }
+}
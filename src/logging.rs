@@ -0,0 +1,30 @@
+//! Log output for the SDK.
+//!
+//! Debug lines and warnings are written to a [`LogSink`]. The default sink,
+//! [`StderrLogSink`], prints to stderr; set another one with
+//! `ClientBuilder::log_sink` to capture or forward SDK logs.
+
+/// Severity of a log line emitted by the SDK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    /// Request/response details, only emitted when debug mode is enabled.
+    Debug,
+    /// Conditions operators should notice, emitted regardless of debug mode.
+    Warn,
+}
+
+/// Destination for SDK log lines.
+pub trait LogSink: Send + Sync {
+    /// Records a single log line.
+    fn log(&self, level: LogLevel, message: &str);
+}
+
+/// Default sink writing every line to stderr.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StderrLogSink;
+
+impl LogSink for StderrLogSink {
+    fn log(&self, _level: LogLevel, message: &str) {
+        eprintln!("{}", message);
+    }
+}
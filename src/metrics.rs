@@ -0,0 +1,32 @@
+//! Per-call metrics for the SDK.
+//!
+//! Every call made through the client is reported to a [`MetricsSink`] set
+//! with `ClientBuilder::metrics_sink`, once the call has finished (for
+//! streams: once the stream is open). Each [`CallMetrics`] carries the labels
+//! attached with `RequestOptions::label`, so operators can slice metrics by
+//! tenant, feature or any other application-defined dimension. Labels are
+//! never sent to the server.
+use crate::client::SdkError;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Outcome of one call, including all of its retries.
+#[derive(Debug, Clone)]
+pub struct CallMetrics<'a> {
+    /// API action, e.g. `ChatCompletions`.
+    pub action: &'a str,
+    /// Labels from the call's `RequestOptions`.
+    pub labels: &'a HashMap<String, String>,
+    /// Time from the first attempt until the call finished.
+    pub elapsed: Duration,
+    /// Number of attempts made, including retries.
+    pub attempts: u32,
+    /// The error the call failed with, if it failed.
+    pub error: Option<&'a SdkError>,
+}
+
+/// Destination for per-call metrics.
+pub trait MetricsSink: Send + Sync {
+    /// Records a finished call.
+    fn record(&self, call: &CallMetrics<'_>);
+}
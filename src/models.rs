@@ -1,14 +1,15 @@
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 
 /// Generic Tencent Cloud success response envelope.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TencentCloudResponse<T> {
     #[serde(rename = "Response")]
     pub response: T,
 }
 
 /// Generic Tencent Cloud error response envelope.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TencentCloudErrorResponse {
     #[serde(rename = "RequestId")]
     pub request_id: Option<String>,
@@ -17,7 +18,7 @@ pub struct TencentCloudErrorResponse {
 }
 
 /// Error content as returned by Tencent Cloud.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ErrorContent {
     #[serde(rename = "Code")]
     pub code: String,
@@ -27,43 +28,423 @@ pub struct ErrorContent {
 
 // Minimal ChatCompletions models based on common TencentCloud LLM APIs.
 // Reference: Go SDK hunyuan/v20230901 (actions like ChatCompletions)
+//
+// Request models omit unset optional fields instead of sending `null`.
+// Response models keep fields this SDK does not model in `extra`, so they
+// survive a deserialize/serialize round trip.
+
+/// Chat models known to this SDK, for [`ChatCompletionsRequest::model`].
+///
+/// Hunyuan has no action to list models, so this is a static list as
+/// documented when the SDK was released; the service may accept models not
+/// listed here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Model {
+    Lite,
+    Standard,
+    Standard256K,
+    Pro,
+    Turbo,
+    Role,
+    FunctionCall,
+    Code,
+    Vision,
+}
+
+/// What a [`Model`] supports, as returned by [`Model::info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelInfo {
+    /// Name sent in `Model`, e.g. `hunyuan-lite`.
+    pub id: &'static str,
+    /// Input plus output tokens the model accepts.
+    pub context_window: u32,
+    /// Whether `Tools` are supported.
+    pub function_calling: bool,
+    /// Whether messages may carry images.
+    pub vision: bool,
+}
+
+impl Model {
+    /// Every known model.
+    pub fn all() -> &'static [Model] {
+        &[
+            Model::Lite,
+            Model::Standard,
+            Model::Standard256K,
+            Model::Pro,
+            Model::Turbo,
+            Model::Role,
+            Model::FunctionCall,
+            Model::Code,
+            Model::Vision,
+        ]
+    }
+
+    /// Looks a model up by the name sent in `Model`.
+    pub fn from_id(id: &str) -> Option<Model> {
+        Model::all().iter().copied().find(|m| m.as_str() == id)
+    }
+
+    /// Name sent in `Model`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Model::Lite => "hunyuan-lite",
+            Model::Standard => "hunyuan-standard",
+            Model::Standard256K => "hunyuan-standard-256K",
+            Model::Pro => "hunyuan-pro",
+            Model::Turbo => "hunyuan-turbo",
+            Model::Role => "hunyuan-role",
+            Model::FunctionCall => "hunyuan-functioncall",
+            Model::Code => "hunyuan-code",
+            Model::Vision => "hunyuan-vision",
+        }
+    }
+
+    /// Context window and capabilities of the model.
+    pub fn info(&self) -> ModelInfo {
+        let (context_window, function_calling, vision) = match self {
+            Model::Lite => (256_000, false, false),
+            Model::Standard => (32_000, false, false),
+            Model::Standard256K => (256_000, false, false),
+            Model::Pro => (32_000, true, false),
+            Model::Turbo => (32_000, true, false),
+            Model::Role => (32_000, false, false),
+            Model::FunctionCall => (32_000, true, false),
+            Model::Code => (8_000, false, false),
+            Model::Vision => (8_000, false, true),
+        };
+        ModelInfo {
+            id: self.as_str(),
+            context_window,
+            function_calling,
+            vision,
+        }
+    }
+
+    /// How much of the model's context window `messages` leave for the
+    /// reply, with the conversation counted by [`estimate_messages_tokens`].
+    pub fn context_budget(&self, messages: &[Message]) -> ContextBudget {
+        ContextBudget {
+            context_window: self.info().context_window as usize,
+            prompt_tokens: estimate_messages_tokens(messages),
+        }
+    }
+}
+
+impl From<Model> for String {
+    fn from(model: Model) -> Self {
+        model.as_str().to_string()
+    }
+}
+
+/// Well-known values of [`Message::role`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+impl Role {
+    /// Lowercase role name as sent in `Role`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        }
+    }
+}
+
+impl From<Role> for String {
+    fn from(role: Role) -> Self {
+        role.as_str().to_string()
+    }
+}
 
 /// Chat message used in `ChatCompletionsRequest`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Message {
     #[serde(rename = "Role")]
     pub role: String,
     #[serde(rename = "Content")]
     pub content: String,
+    /// For a `tool` message, the [`ToolCall::id`] it answers.
+    #[serde(
+        rename = "ToolCallId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tool_call_id: Option<String>,
+    /// For an `assistant` message, the tool calls the model made.
+    #[serde(rename = "ToolCalls", default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+}
+
+impl Message {
+    /// Creates a message with `role` and `content`.
+    pub fn new(role: Role, content: impl Into<String>) -> Self {
+        Self {
+            role: role.into(),
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a `tool` message carrying the result of the call `tool_call_id`.
+    pub fn tool_result(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::new(Role::Tool, content)
+        }
+    }
+}
+
+/// Tokens added per message for its role and framing by
+/// [`estimate_messages_tokens`].
+pub const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Approximate number of tokens in `text`, computed locally.
+///
+/// The estimate counts one token per CJK character (including kana and
+/// hangul), one per four characters of each ASCII word (at least one per
+/// word), and one per other symbol or non-ASCII character; whitespace is
+/// free. It is a rough guide for budgeting, not billing; use
+/// [`Client::get_token_count`](crate::Client::get_token_count) for exact
+/// counts.
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut word: usize = 0;
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            word += 1;
+            continue;
+        }
+        tokens += word.div_ceil(4);
+        word = 0;
+        if !c.is_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens + word.div_ceil(4)
+}
+
+/// Approximate number of tokens `messages` take up in a request: the
+/// [`estimate_tokens`] of each content plus [`MESSAGE_OVERHEAD_TOKENS`] per
+/// message.
+pub fn estimate_messages_tokens(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .map(|m| MESSAGE_OVERHEAD_TOKENS + estimate_tokens(&m.content))
+        .sum()
+}
+
+/// Context window use of a conversation, as returned by
+/// [`Model::context_budget`].
+///
+/// The prompt side is a local estimate, so treat the numbers as a guide,
+/// e.g. for showing "tokens remaining", rather than a guarantee that a
+/// request fits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextBudget {
+    /// Input plus output tokens the model accepts.
+    pub context_window: usize,
+    /// Estimated tokens of the conversation.
+    pub prompt_tokens: usize,
+}
+
+impl ContextBudget {
+    /// Tokens left for the reply; zero once the conversation
+    /// [overflows](Self::overflows).
+    pub fn remaining(&self) -> usize {
+        self.context_window.saturating_sub(self.prompt_tokens)
+    }
+
+    /// Whether the conversation alone is larger than the context window.
+    pub fn overflows(&self) -> bool {
+        self.prompt_tokens > self.context_window
+    }
+
+    /// Tokens to drop for the conversation to fit; zero if it does.
+    pub fn overflow(&self) -> usize {
+        self.prompt_tokens.saturating_sub(self.context_window)
+    }
 }
 
 /// Request for the `ChatCompletions` action.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Sampling is controlled with `Temperature` and `TopP` only. The action has
+/// no repetition, frequency or presence penalty parameter, and the service
+/// rejects unknown parameters, so none is exposed here.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ChatCompletionsRequest {
-    #[serde(rename = "Model")]
+    #[serde(rename = "Model", skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     #[serde(rename = "Messages")]
     pub messages: Vec<Message>,
-    #[serde(rename = "Temperature")]
+    #[serde(rename = "Temperature", skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
-    #[serde(rename = "TopP")]
+    #[serde(rename = "TopP", skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
-    #[serde(rename = "Stream")]
+    #[serde(rename = "Stream", skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    /// Functions the model may call; see [`Tool::function`].
+    #[serde(rename = "Tools", default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    /// Number of completions to generate, each returned as its own choice;
+    /// see [`Client::chat_completions_alternatives`](crate::Client::chat_completions_alternatives).
+    /// One when unset.
+    #[serde(rename = "N", default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    /// Fixes the sampling, so sending the same request with the same seed
+    /// gives the same output as far as the service can guarantee it. See
+    /// [`ResponseMeta::seed`](crate::ResponseMeta::seed).
+    #[serde(rename = "Seed", default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
     // Add other fields as needed per upstream API
 }
 
+/// A tool offered to the model in [`ChatCompletionsRequest::tools`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tool {
+    /// Always `function` at present.
+    #[serde(rename = "Type")]
+    pub tool_type: String,
+    #[serde(rename = "Function")]
+    pub function: ToolFunction,
+}
+
+impl Tool {
+    /// A function tool whose arguments are described by the JSON Schema
+    /// `parameters`.
+    pub fn function(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: &serde_json::Value,
+    ) -> Self {
+        Self {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: name.into(),
+                description: Some(description.into()),
+                parameters: parameters.to_string(),
+            },
+        }
+    }
+}
+
+/// Definition of a function in a [`Tool`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolFunction {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(
+        rename = "Description",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub description: Option<String>,
+    /// JSON Schema of the arguments, as a JSON string.
+    #[serde(rename = "Parameters")]
+    pub parameters: String,
+}
+
+/// Chainable setters, e.g. to vary a shared template per call:
+/// `template.clone().with_temperature(0.2)`.
+///
+/// There is no `with_max_tokens`: the action has no such parameter. See
+/// `RequestOptions::response_token_hint` for a prompt-level hint instead.
+impl ChatCompletionsRequest {
+    /// Sets `Model`.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Sets `Temperature`.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Sets `TopP`.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Sets `N`, the number of completions to generate.
+    pub fn with_n(mut self, n: u32) -> Self {
+        self.n = Some(n);
+        self
+    }
+
+    /// Sets `Seed`, for reproducible output.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets `Stream`. The streaming client methods set it themselves.
+    pub fn with_stream(mut self, stream: bool) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+}
+
 /// Message inside a choice in `ChatCompletionsResponse`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ChatChoiceMessage {
     #[serde(rename = "Role")]
     pub role: Option<String>,
     #[serde(rename = "Content")]
     pub content: Option<String>,
+    /// Functions the model asks to call.
+    #[serde(rename = "ToolCalls", default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// Fields not modeled by this SDK, kept as received.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A function call requested by the model.
+///
+/// Fields missing from a streamed fragment deserialize as empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolCall {
+    #[serde(rename = "Id")]
+    pub id: String,
+    /// Always `function` at present.
+    #[serde(rename = "Type")]
+    pub call_type: String,
+    #[serde(rename = "Function")]
+    pub function: ToolCallFunction,
+    /// Position of the call among those of the message; identifies the
+    /// call across streamed fragments.
+    #[serde(rename = "Index", skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
+}
+
+/// Function name and arguments of a [`ToolCall`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolCallFunction {
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// Arguments as a JSON object string, as generated by the model; it is
+    /// not guaranteed to be valid JSON.
+    #[serde(rename = "Arguments")]
+    pub arguments: String,
 }
 
 /// Single choice in `ChatCompletionsResponse`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ChatChoice {
     #[serde(rename = "Index")]
     pub index: Option<u32>,
@@ -71,10 +452,13 @@ pub struct ChatChoice {
     pub message: Option<ChatChoiceMessage>,
     #[serde(rename = "FinishReason")]
     pub finish_reason: Option<String>,
+    /// Fields not modeled by this SDK, kept as received.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Token usage statistics returned by the service.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Usage {
     #[serde(rename = "PromptTokens")]
     pub prompt_tokens: Option<u32>,
@@ -82,10 +466,13 @@ pub struct Usage {
     pub completion_tokens: Option<u32>,
     #[serde(rename = "TotalTokens")]
     pub total_tokens: Option<u32>,
+    /// Fields not modeled by this SDK, kept as received.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Inner payload for `ChatCompletionsResponse`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ChatCompletionsResponseInner {
     #[serde(rename = "RequestId")]
     pub request_id: Option<String>,
@@ -95,7 +482,909 @@ pub struct ChatCompletionsResponseInner {
     pub choices: Option<Vec<ChatChoice>>,
     #[serde(rename = "Usage")]
     pub usage: Option<Usage>,
+    #[serde(
+        rename = "SearchInfo",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub search_info: Option<SearchInfo>,
+    /// Usage guidance or disclaimer from the service, e.g. that the content
+    /// is AI-generated.
+    #[serde(rename = "Note", default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Fields not modeled by this SDK, kept as received.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ChatCompletionsResponseInner {
+    /// Messages of all choices in `Index` order, skipping choices without a
+    /// message. A missing `Index` counts as 0.
+    pub fn messages(&self) -> impl Iterator<Item = &ChatChoiceMessage> {
+        let mut choices: Vec<&ChatChoice> = self.choices.iter().flatten().collect();
+        choices.sort_by_key(|c| c.index.unwrap_or(0));
+        choices.into_iter().filter_map(|c| c.message.as_ref())
+    }
+
+    /// The `Note` the service attached, if any.
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
 }
 
 /// Type alias for the full `ChatCompletions` response envelope.
 pub type ChatCompletionsResponse = TencentCloudResponse<ChatCompletionsResponseInner>;
+
+impl ChatCompletionsResponse {
+    /// Messages of all choices; see [`ChatCompletionsResponseInner::messages`].
+    pub fn messages(&self) -> impl Iterator<Item = &ChatChoiceMessage> {
+        self.response.messages()
+    }
+
+    /// The `Note` the service attached, if any; see
+    /// [`ChatCompletionsResponseInner::note`].
+    pub fn note(&self) -> Option<&str> {
+        self.response.note()
+    }
+}
+
+/// The part of a message carried by one streamed chunk.
+///
+/// Unlike a [`ChatChoiceMessage`], every field is incremental: `content` and
+/// `reasoning_content` are appended to those of the earlier chunks, `role`
+/// is usually only sent in the first chunk, and tool calls arrive in
+/// fragments. [`ChatCompletionsAccumulator`](crate::ChatCompletionsAccumulator)
+/// puts them together.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatChoiceDelta {
+    #[serde(rename = "Role", default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(rename = "Content", default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Fragments of tool calls; see
+    /// [`ChatCompletionsAccumulator::tool_calls`](crate::ChatCompletionsAccumulator::tool_calls).
+    #[serde(rename = "ToolCalls", default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// Reasoning of models that think before answering, e.g. `hunyuan-t1`.
+    #[serde(
+        rename = "ReasoningContent",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reasoning_content: Option<String>,
+    /// Fields not modeled by this SDK, kept as received.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Single choice in a streamed `ChatCompletionsChunk`. `delta` carries the
+/// incremental part of the message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatChunkChoice {
+    #[serde(rename = "Index")]
+    pub index: Option<u32>,
+    #[serde(rename = "Delta")]
+    pub delta: Option<ChatChoiceDelta>,
+    #[serde(rename = "FinishReason")]
+    pub finish_reason: Option<String>,
+    /// Fields not modeled by this SDK, kept as received.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One SSE data frame of a streamed `ChatCompletions` response.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatCompletionsChunk {
+    #[serde(rename = "Id")]
+    pub id: Option<String>,
+    #[serde(rename = "Created")]
+    pub created: Option<i64>,
+    #[serde(rename = "Choices")]
+    pub choices: Option<Vec<ChatChunkChoice>>,
+    #[serde(rename = "Usage")]
+    pub usage: Option<Usage>,
+    #[serde(
+        rename = "SearchInfo",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub search_info: Option<SearchInfo>,
+    /// See [`ChatCompletionsResponseInner::note`].
+    #[serde(rename = "Note", default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Fields not modeled by this SDK, kept as received.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Search references and citation markers of an enhanced (search-backed)
+/// response. In a stream, each chunk carries the references and citations
+/// that became known with it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchInfo {
+    #[serde(
+        rename = "SearchResults",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub search_results: Option<Vec<SearchResult>>,
+    #[serde(rename = "Citations", default, skip_serializing_if = "Option::is_none")]
+    pub citations: Option<Vec<Citation>>,
+}
+
+/// A reference a response cites, identified by `index` (the `n` of `[n]`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    #[serde(rename = "Index")]
+    pub index: Option<u32>,
+    #[serde(rename = "Title")]
+    pub title: Option<String>,
+    #[serde(rename = "Url")]
+    pub url: Option<String>,
+}
+
+/// A citation marker: reference `index` belongs at character `offset` of the
+/// full assistant message, counted in Unicode scalar values from the start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Citation {
+    #[serde(rename = "Index")]
+    pub index: u32,
+    #[serde(rename = "Offset")]
+    pub offset: u32,
+}
+
+/// Reason a choice stopped generating, as carried in `FinishReason`. More
+/// reasons may be added in minor releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FinishReason {
+    /// The model finished normally.
+    Stop,
+    /// Generation hit the length limit.
+    Length,
+    /// Output was cut by content moderation.
+    Sensitive,
+    /// The model requested tool calls.
+    ToolCalls,
+}
+
+impl FinishReason {
+    /// Wire value of the reason.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FinishReason::Stop => "stop",
+            FinishReason::Length => "length",
+            FinishReason::Sensitive => "sensitive",
+            FinishReason::ToolCalls => "tool_calls",
+        }
+    }
+}
+
+/// `InputType` for embedding search queries.
+pub const INPUT_TYPE_QUERY: &str = "query";
+/// `InputType` for embedding documents to be searched.
+pub const INPUT_TYPE_DOCUMENT: &str = "document";
+/// `EncodingFormat` for embeddings returned as arrays of numbers, the default.
+pub const ENCODING_FORMAT_FLOAT: &str = "float";
+/// `EncodingFormat` for embeddings returned as base64 of little-endian `f32`s,
+/// about a quarter the size of the number arrays on the wire.
+pub const ENCODING_FORMAT_BASE64: &str = "base64";
+/// Most texts one `GetEmbedding` request accepts in `InputList`.
+pub const MAX_EMBEDDING_INPUTS: usize = 200;
+
+/// Request for the `GetEmbedding` action. Set either `input` or `input_list`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GetEmbeddingRequest {
+    #[serde(rename = "Input", skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    #[serde(rename = "InputList", skip_serializing_if = "Option::is_none")]
+    pub input_list: Option<Vec<String>>,
+    /// Whether the input is a search query ([`INPUT_TYPE_QUERY`]) or a
+    /// document ([`INPUT_TYPE_DOCUMENT`]), for models that embed them
+    /// differently. Omitted when unset.
+    #[serde(rename = "InputType", skip_serializing_if = "Option::is_none")]
+    pub input_type: Option<String>,
+    /// How the vectors are encoded in the response: [`ENCODING_FORMAT_FLOAT`]
+    /// or [`ENCODING_FORMAT_BASE64`]. Read either with
+    /// [`EmbeddingData::vector`]. Omitted when unset.
+    #[serde(rename = "EncodingFormat", skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<String>,
+}
+
+impl GetEmbeddingRequest {
+    /// Checks that `input_type`, if set, is `query` or `document`, and that
+    /// `encoding_format`, if set, is `float` or `base64`.
+    pub fn validate(&self) -> crate::SdkResult<()> {
+        match self.input_type.as_deref() {
+            None | Some(INPUT_TYPE_QUERY) | Some(INPUT_TYPE_DOCUMENT) => {}
+            Some(other) => {
+                return Err(crate::SdkError::InvalidRequest(format!(
+                    "invalid InputType {:?}: expected {:?} or {:?}",
+                    other, INPUT_TYPE_QUERY, INPUT_TYPE_DOCUMENT
+                )))
+            }
+        }
+        match self.encoding_format.as_deref() {
+            None | Some(ENCODING_FORMAT_FLOAT) | Some(ENCODING_FORMAT_BASE64) => Ok(()),
+            Some(other) => Err(crate::SdkError::InvalidRequest(format!(
+                "invalid EncodingFormat {:?}: expected {:?} or {:?}",
+                other, ENCODING_FORMAT_FLOAT, ENCODING_FORMAT_BASE64
+            ))),
+        }
+    }
+
+    /// Asks for the vectors as base64 rather than arrays of numbers.
+    pub fn with_base64_encoding(mut self) -> Self {
+        self.encoding_format = Some(ENCODING_FORMAT_BASE64.to_string());
+        self
+    }
+}
+
+/// One embedding vector in `GetEmbeddingResponse`.
+///
+/// `Embedding` is received either as an array of numbers, kept in
+/// `embedding`, or as a base64 string, kept undecoded in `embedding_base64`;
+/// [`vector`](Self::vector) reads whichever is set. When serialized, a set
+/// `embedding` wins over `embedding_base64`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "EmbeddingDataRepr", into = "EmbeddingDataRepr")]
+pub struct EmbeddingData {
+    pub embedding: Option<Vec<f64>>,
+    /// Base64 of the vector as little-endian `f32`s, sent instead of
+    /// `embedding` for requests with [`ENCODING_FORMAT_BASE64`].
+    pub embedding_base64: Option<String>,
+    pub index: Option<u32>,
+    pub object: Option<String>,
+    /// Fields not modeled by this SDK, kept as received.
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl EmbeddingData {
+    /// The vector, from the number array or decoded from base64. Fails with
+    /// [`SdkError::Decode`](crate::SdkError::Decode) on malformed base64 or a
+    /// length that is not a whole number of `f32`s. Numbers are narrowed to
+    /// `f32`; a missing vector is empty.
+    pub fn vector(&self) -> crate::SdkResult<Vec<f32>> {
+        if let Some(floats) = &self.embedding {
+            return Ok(floats.iter().map(|&x| x as f32).collect());
+        }
+        let Some(encoded) = &self.embedding_base64 else {
+            return Ok(Vec::new());
+        };
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| crate::SdkError::Decode(format!("invalid base64 embedding: {}", e)))?;
+        if bytes.len() % 4 != 0 {
+            return Err(crate::SdkError::Decode(format!(
+                "base64 embedding is {} bytes, not a whole number of f32s",
+                bytes.len()
+            )));
+        }
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+}
+
+/// `Embedding` as sent: numbers or a base64 string.
+#[derive(Debug, Clone, PartialEq)]
+enum EmbeddingValue {
+    Floats(Vec<f64>),
+    Base64(String),
+}
+
+/// Newtype name that [`strict`](crate::strict) reads as a declared field
+/// even though `EmbeddingValue` looks at the JSON type.
+pub(crate) const EMBEDDING_VALUE: &str = "$hunyuan::EmbeddingValue";
+
+impl Serialize for EmbeddingValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            EmbeddingValue::Floats(floats) => floats.serialize(serializer),
+            EmbeddingValue::Base64(encoded) => encoded.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for EmbeddingValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ValueVisitor {
+            type Value = EmbeddingValue;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("an array of numbers or a base64 string")
+            }
+
+            fn visit_newtype_struct<D: serde::Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<EmbeddingValue, D::Error> {
+                deserializer.deserialize_any(self)
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<EmbeddingValue, E> {
+                Ok(EmbeddingValue::Base64(v.to_string()))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<EmbeddingValue, A::Error> {
+                let mut floats = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(x) = seq.next_element()? {
+                    floats.push(x);
+                }
+                Ok(EmbeddingValue::Floats(floats))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(EMBEDDING_VALUE, ValueVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct EmbeddingDataRepr {
+    #[serde(rename = "Embedding")]
+    embedding: Option<EmbeddingValue>,
+    #[serde(rename = "Index")]
+    index: Option<u32>,
+    #[serde(rename = "Object")]
+    object: Option<String>,
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<EmbeddingDataRepr> for EmbeddingData {
+    fn from(repr: EmbeddingDataRepr) -> Self {
+        let (embedding, embedding_base64) = match repr.embedding {
+            Some(EmbeddingValue::Floats(floats)) => (Some(floats), None),
+            Some(EmbeddingValue::Base64(encoded)) => (None, Some(encoded)),
+            None => (None, None),
+        };
+        EmbeddingData {
+            embedding,
+            embedding_base64,
+            index: repr.index,
+            object: repr.object,
+            extra: repr.extra,
+        }
+    }
+}
+
+impl From<EmbeddingData> for EmbeddingDataRepr {
+    fn from(data: EmbeddingData) -> Self {
+        let embedding = match (data.embedding, data.embedding_base64) {
+            (Some(floats), _) => Some(EmbeddingValue::Floats(floats)),
+            (None, Some(encoded)) => Some(EmbeddingValue::Base64(encoded)),
+            (None, None) => None,
+        };
+        EmbeddingDataRepr {
+            embedding,
+            index: data.index,
+            object: data.object,
+            extra: data.extra,
+        }
+    }
+}
+
+/// Token usage of a `GetEmbedding` call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingUsage {
+    #[serde(rename = "PromptTokens")]
+    pub prompt_tokens: Option<u32>,
+    #[serde(rename = "TotalTokens")]
+    pub total_tokens: Option<u32>,
+}
+
+/// Inner payload for `GetEmbeddingResponse`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GetEmbeddingResponseInner {
+    #[serde(rename = "RequestId")]
+    pub request_id: Option<String>,
+    #[serde(rename = "Data")]
+    pub data: Option<Vec<EmbeddingData>>,
+    #[serde(rename = "Usage")]
+    pub usage: Option<EmbeddingUsage>,
+    /// Fields not modeled by this SDK, kept as received.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Type alias for the full `GetEmbedding` response envelope.
+pub type GetEmbeddingResponse = TencentCloudResponse<GetEmbeddingResponseInner>;
+
+/// Request for the `TextToImageLite` action.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextToImageLiteRequest {
+    #[serde(rename = "Prompt")]
+    pub prompt: String,
+    #[serde(rename = "NegativePrompt", skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    #[serde(rename = "Style", skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    /// Output size such as `768:768`.
+    #[serde(rename = "Resolution", skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>,
+    /// `base64` to receive the image inline, `url` for a download link.
+    #[serde(rename = "RspImgType", skip_serializing_if = "Option::is_none")]
+    pub rsp_img_type: Option<String>,
+}
+
+/// Inner payload for `TextToImageLiteResponse`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextToImageLiteResponseInner {
+    #[serde(rename = "RequestId")]
+    pub request_id: Option<String>,
+    /// Base64 image data or a URL, depending on `RspImgType`.
+    #[serde(rename = "ResultImage")]
+    pub result_image: Option<String>,
+    /// Fields not modeled by this SDK, kept as received.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TextToImageLiteResponseInner {
+    /// Decodes a base64 `ResultImage` into image bytes; see
+    /// [`decode_base64_image`](crate::image::decode_base64_image).
+    pub fn decode_image(&self) -> crate::SdkResult<Vec<u8>> {
+        let data = self
+            .result_image
+            .as_deref()
+            .ok_or_else(|| crate::SdkError::Decode("response has no ResultImage".to_string()))?;
+        crate::image::decode_base64_image(data)
+    }
+}
+
+/// Type alias for the full `TextToImageLite` response envelope.
+pub type TextToImageLiteResponse = TencentCloudResponse<TextToImageLiteResponseInner>;
+
+/// `Style` of a `SubmitHunyuanImageJob` request. Styles not listed here can
+/// be sent as [`ImageStyle::Custom`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ImageStyle {
+    /// Japanese anime (`riman`).
+    Riman,
+    /// Ink wash painting (`shuimo`).
+    Shuimo,
+    /// In the manner of Monet (`monai`).
+    Monai,
+    /// Flat illustration (`bianping`).
+    Bianping,
+    /// Pixel art (`xiangsu`).
+    Xiangsu,
+    /// Children's picture book (`ertonghuiben`).
+    Ertonghuiben,
+    /// 3D rendering (`3dxuanran`).
+    Xuanran3d,
+    /// Comic (`manhua`).
+    Manhua,
+    /// Black-and-white comic (`heibaimanhua`).
+    Heibaimanhua,
+    /// Film still (`dianyingjuzhao`).
+    Dianyingjuzhao,
+    /// Realistic (`xieshi`).
+    Xieshi,
+    /// Oil painting (`youhua`).
+    Youhua,
+    /// Any other style value, sent as is.
+    Custom(String),
+}
+
+impl ImageStyle {
+    const KNOWN: &'static [ImageStyle] = &[
+        ImageStyle::Riman,
+        ImageStyle::Shuimo,
+        ImageStyle::Monai,
+        ImageStyle::Bianping,
+        ImageStyle::Xiangsu,
+        ImageStyle::Ertonghuiben,
+        ImageStyle::Xuanran3d,
+        ImageStyle::Manhua,
+        ImageStyle::Heibaimanhua,
+        ImageStyle::Dianyingjuzhao,
+        ImageStyle::Xieshi,
+        ImageStyle::Youhua,
+    ];
+
+    /// Wire value of the style.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ImageStyle::Riman => "riman",
+            ImageStyle::Shuimo => "shuimo",
+            ImageStyle::Monai => "monai",
+            ImageStyle::Bianping => "bianping",
+            ImageStyle::Xiangsu => "xiangsu",
+            ImageStyle::Ertonghuiben => "ertonghuiben",
+            ImageStyle::Xuanran3d => "3dxuanran",
+            ImageStyle::Manhua => "manhua",
+            ImageStyle::Heibaimanhua => "heibaimanhua",
+            ImageStyle::Dianyingjuzhao => "dianyingjuzhao",
+            ImageStyle::Xieshi => "xieshi",
+            ImageStyle::Youhua => "youhua",
+            ImageStyle::Custom(style) => style,
+        }
+    }
+}
+
+impl From<&str> for ImageStyle {
+    fn from(style: &str) -> Self {
+        ImageStyle::KNOWN
+            .iter()
+            .find(|known| known.as_str() == style)
+            .cloned()
+            .unwrap_or_else(|| ImageStyle::Custom(style.to_string()))
+    }
+}
+
+/// `Resolution` of a `SubmitHunyuanImageJob` request, as `width:height`.
+/// Other sizes can be sent as [`ImageResolution::Custom`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ImageResolution {
+    Res768x768,
+    Res768x1024,
+    Res1024x768,
+    /// The default when unset.
+    Res1024x1024,
+    Res720x1280,
+    Res1280x720,
+    Res768x1280,
+    Res1280x768,
+    /// Any other `width:height`, sent as is.
+    Custom(String),
+}
+
+impl ImageResolution {
+    const KNOWN: &'static [ImageResolution] = &[
+        ImageResolution::Res768x768,
+        ImageResolution::Res768x1024,
+        ImageResolution::Res1024x768,
+        ImageResolution::Res1024x1024,
+        ImageResolution::Res720x1280,
+        ImageResolution::Res1280x720,
+        ImageResolution::Res768x1280,
+        ImageResolution::Res1280x768,
+    ];
+
+    /// Wire value of the resolution.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ImageResolution::Res768x768 => "768:768",
+            ImageResolution::Res768x1024 => "768:1024",
+            ImageResolution::Res1024x768 => "1024:768",
+            ImageResolution::Res1024x1024 => "1024:1024",
+            ImageResolution::Res720x1280 => "720:1280",
+            ImageResolution::Res1280x720 => "1280:720",
+            ImageResolution::Res768x1280 => "768:1280",
+            ImageResolution::Res1280x768 => "1280:768",
+            ImageResolution::Custom(resolution) => resolution,
+        }
+    }
+
+    /// Width and height, if the value has the form `width:height` with both
+    /// positive.
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        let (width, height) = self.as_str().split_once(':')?;
+        let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+        (width > 0 && height > 0).then_some((width, height))
+    }
+}
+
+impl From<&str> for ImageResolution {
+    fn from(resolution: &str) -> Self {
+        ImageResolution::KNOWN
+            .iter()
+            .find(|known| known.as_str() == resolution)
+            .cloned()
+            .unwrap_or_else(|| ImageResolution::Custom(resolution.to_string()))
+    }
+}
+
+/// `Clarity` of a `SubmitHunyuanImageJob` request: how much the image is
+/// upscaled after generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ImageClarity {
+    /// Twice the resolution (`x2`).
+    X2,
+    /// Four times the resolution (`x4`).
+    X4,
+}
+
+impl ImageClarity {
+    /// Wire value of the clarity.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageClarity::X2 => "x2",
+            ImageClarity::X4 => "x4",
+        }
+    }
+}
+
+impl TryFrom<&str> for ImageClarity {
+    type Error = String;
+
+    fn try_from(clarity: &str) -> Result<Self, Self::Error> {
+        match clarity {
+            "x2" => Ok(ImageClarity::X2),
+            "x4" => Ok(ImageClarity::X4),
+            other => Err(format!(
+                "unknown Clarity {:?}: expected \"x2\" or \"x4\"",
+                other
+            )),
+        }
+    }
+}
+
+/// Serializes the image parameter enums as their wire strings.
+macro_rules! wire_string {
+    ($($ty:ident)*) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(self.as_str())
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let value = String::deserialize(deserializer)?;
+                    $ty::try_from(value.as_str()).map_err(serde::de::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+wire_string! { ImageStyle ImageResolution ImageClarity }
+
+/// `0`/`1` flags, as the image API takes them.
+mod int_flag {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(flag: &Option<bool>, serializer: S) -> Result<S::Ok, S::Error> {
+        match flag {
+            Some(flag) => serializer.serialize_u8(u8::from(*flag)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<bool>, D::Error> {
+        match Option::<u8>::deserialize(deserializer)? {
+            None => Ok(None),
+            Some(0) => Ok(Some(false)),
+            Some(1) => Ok(Some(true)),
+            Some(other) => Err(serde::de::Error::custom(format!(
+                "expected 0 or 1, got {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Fewest and most images one `SubmitHunyuanImageJob` request asks for in
+/// `Num`.
+pub const IMAGE_JOB_NUM_RANGE: std::ops::RangeInclusive<u32> = 1..=4;
+
+/// Where the logo added to generated images comes from, and where it goes.
+/// Set exactly one of `logo_url` and `logo_image`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogoParam {
+    #[serde(rename = "LogoUrl", skip_serializing_if = "Option::is_none")]
+    pub logo_url: Option<String>,
+    /// Base64 image data.
+    #[serde(rename = "LogoImage", skip_serializing_if = "Option::is_none")]
+    pub logo_image: Option<String>,
+    #[serde(rename = "LogoRect", skip_serializing_if = "Option::is_none")]
+    pub logo_rect: Option<LogoRect>,
+}
+
+/// Position and size of the logo, in pixels from the top left corner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogoRect {
+    #[serde(rename = "X")]
+    pub x: i64,
+    #[serde(rename = "Y")]
+    pub y: i64,
+    #[serde(rename = "Width")]
+    pub width: i64,
+    #[serde(rename = "Height")]
+    pub height: i64,
+}
+
+/// Request for the `SubmitHunyuanImageJob` action. Build it with
+/// [`builder`](Self::builder) to have it checked before it is sent.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubmitHunyuanImageJobRequest {
+    #[serde(rename = "Prompt")]
+    pub prompt: String,
+    #[serde(rename = "NegativePrompt", skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    #[serde(rename = "Style", skip_serializing_if = "Option::is_none")]
+    pub style: Option<ImageStyle>,
+    #[serde(rename = "Resolution", skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<ImageResolution>,
+    /// Number of images, within [`IMAGE_JOB_NUM_RANGE`].
+    #[serde(rename = "Num", skip_serializing_if = "Option::is_none")]
+    pub num: Option<u32>,
+    #[serde(rename = "Clarity", skip_serializing_if = "Option::is_none")]
+    pub clarity: Option<ImageClarity>,
+    /// Fixes the seed for reproducible images; at least 1.
+    #[serde(rename = "Seed", skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+    /// Whether the prompt is rewritten before generating; sent as `0`/`1`.
+    #[serde(
+        rename = "Revise",
+        with = "int_flag",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub revise: Option<bool>,
+    /// Whether a logo is added; sent as `0`/`1`.
+    #[serde(
+        rename = "LogoAdd",
+        with = "int_flag",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub logo_add: Option<bool>,
+    /// The logo to add, with `logo_add` set.
+    #[serde(rename = "LogoParam", skip_serializing_if = "Option::is_none")]
+    pub logo_param: Option<LogoParam>,
+}
+
+impl SubmitHunyuanImageJobRequest {
+    /// Starts a request that [`build`](SubmitHunyuanImageJobRequestBuilder::build)
+    /// checks as a whole.
+    pub fn builder() -> SubmitHunyuanImageJobRequestBuilder {
+        SubmitHunyuanImageJobRequestBuilder::default()
+    }
+
+    /// Checks the values the service would reject, reporting every problem
+    /// found rather than the first.
+    pub fn validate(&self) -> Result<(), InvalidImageJobRequest> {
+        let mut violations = Vec::new();
+        if self.prompt.trim().is_empty() {
+            violations.push("Prompt must not be empty".to_string());
+        }
+        if let Some(ImageStyle::Custom(style)) = &self.style {
+            if style.trim().is_empty() {
+                violations.push("Style must not be empty".to_string());
+            }
+        }
+        if let Some(resolution) = &self.resolution {
+            if resolution.dimensions().is_none() {
+                violations.push(format!(
+                    "Resolution {:?} is not width:height with both positive",
+                    resolution.as_str()
+                ));
+            }
+        }
+        if let Some(num) = self.num {
+            if !IMAGE_JOB_NUM_RANGE.contains(&num) {
+                violations.push(format!(
+                    "Num {} is outside {}..={}",
+                    num,
+                    IMAGE_JOB_NUM_RANGE.start(),
+                    IMAGE_JOB_NUM_RANGE.end()
+                ));
+            }
+        }
+        if self.seed == Some(0) {
+            violations.push("Seed must be at least 1".to_string());
+        }
+        if let Some(logo) = &self.logo_param {
+            if self.logo_add != Some(true) {
+                violations.push("LogoParam is set but LogoAdd is not 1".to_string());
+            }
+            if logo.logo_url.is_some() == logo.logo_image.is_some() {
+                violations.push("LogoParam needs exactly one of LogoUrl and LogoImage".to_string());
+            }
+            if let Some(rect) = logo.logo_rect {
+                if rect.width <= 0 || rect.height <= 0 {
+                    violations.push(format!(
+                        "LogoRect size {}x{} is not positive",
+                        rect.width, rect.height
+                    ));
+                }
+            }
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidImageJobRequest { violations })
+        }
+    }
+}
+
+/// Builder for [`SubmitHunyuanImageJobRequest`].
+#[derive(Debug, Clone, Default)]
+pub struct SubmitHunyuanImageJobRequestBuilder {
+    request: SubmitHunyuanImageJobRequest,
+}
+
+impl SubmitHunyuanImageJobRequestBuilder {
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.request.prompt = prompt.into();
+        self
+    }
+
+    pub fn negative_prompt(mut self, negative_prompt: impl Into<String>) -> Self {
+        self.request.negative_prompt = Some(negative_prompt.into());
+        self
+    }
+
+    pub fn style(mut self, style: ImageStyle) -> Self {
+        self.request.style = Some(style);
+        self
+    }
+
+    pub fn resolution(mut self, resolution: ImageResolution) -> Self {
+        self.request.resolution = Some(resolution);
+        self
+    }
+
+    pub fn num(mut self, num: u32) -> Self {
+        self.request.num = Some(num);
+        self
+    }
+
+    pub fn clarity(mut self, clarity: ImageClarity) -> Self {
+        self.request.clarity = Some(clarity);
+        self
+    }
+
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.request.seed = Some(seed);
+        self
+    }
+
+    pub fn revise(mut self, revise: bool) -> Self {
+        self.request.revise = Some(revise);
+        self
+    }
+
+    /// Adds `logo` to the images, setting `LogoAdd` too.
+    pub fn logo(mut self, logo: LogoParam) -> Self {
+        self.request.logo_add = Some(true);
+        self.request.logo_param = Some(logo);
+        self
+    }
+
+    /// Sets `LogoAdd` alone, e.g. to `false` to ask for no logo.
+    pub fn logo_add(mut self, logo_add: bool) -> Self {
+        self.request.logo_add = Some(logo_add);
+        self
+    }
+
+    /// The request, or every problem [`validate`](SubmitHunyuanImageJobRequest::validate)
+    /// finds with it.
+    pub fn build(self) -> Result<SubmitHunyuanImageJobRequest, InvalidImageJobRequest> {
+        self.request.validate()?;
+        Ok(self.request)
+    }
+}
+
+/// Problems found in a [`SubmitHunyuanImageJobRequest`], all at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidImageJobRequest {
+    pub violations: Vec<String>,
+}
+
+impl std::fmt::Display for InvalidImageJobRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid SubmitHunyuanImageJob request: {}",
+            self.violations.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for InvalidImageJobRequest {}
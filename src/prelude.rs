@@ -0,0 +1,40 @@
+//! Types most applications need, for a single glob import.
+//!
+//! ```no_run
+//! use tencentcloud_hunyuan_sdk::prelude::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> SdkResult<()> {
+//!     let client = Client::builder()
+//!         .credential(Credential {
+//!             secret_id: std::env::var("TENCENTCLOUD_SECRET_ID").unwrap_or_default(),
+//!             secret_key: std::env::var("TENCENTCLOUD_SECRET_KEY").unwrap_or_default(),
+//!             token: None,
+//!         })
+//!         .region(Region::ApGuangzhou)
+//!         .build();
+//!
+//!     let req = ChatCompletionsRequest {
+//!         model: Some("hunyuan-lite".to_string()),
+//!         messages: vec![Message::new(Role::User, "Hello, Hunyuan!")],
+//!         temperature: Some(0.7),
+//!         top_p: Some(0.95),
+//!         stream: Some(false),
+//!         tools: None,
+//!         n: None,
+//!         seed: None,
+//!     };
+//!     let resp = client.chat_completions(&req).await?;
+//!     println!("{:?}", resp);
+//!     Ok(())
+//! }
+//! ```
+pub use crate::api::HunyuanApi;
+pub use crate::client::{
+    Client, ClientBuilder, Credential, Region, RequestOptions, ResponseMeta, SdkError, SdkResult,
+};
+pub use crate::models::{
+    ChatCompletionsChunk, ChatCompletionsRequest, ChatCompletionsResponse, GetEmbeddingRequest,
+    GetEmbeddingResponse, Message, Role,
+};
+pub use crate::stream::{ChatCompletionsAccumulator, ChatCompletionsStream};
@@ -0,0 +1,185 @@
+//! Prompt skeletons with named placeholders.
+//!
+//! Placeholders are written `{name}`; `{{` and `}}` stand for literal braces.
+//! Values are inserted as they are, so braces inside user data never break
+//! the template:
+//!
+//! ```
+//! use tencentcloud_hunyuan_sdk::models::Role;
+//! use tencentcloud_hunyuan_sdk::prompt::PromptTemplate;
+//!
+//! let template = PromptTemplate::new("Summarize for {audience}: {text}")
+//!     .system("You write for {audience}. Reply in JSON like {{\"summary\": ...}}.");
+//! let vars = [("audience", "children"), ("text", "fn main() {}")];
+//! assert_eq!(
+//!     template.render(&vars).unwrap(),
+//!     "Summarize for children: fn main() {}"
+//! );
+//! let messages = template.to_messages(Role::User, &vars).unwrap();
+//! assert_eq!(
+//!     messages[0].content,
+//!     "You write for children. Reply in JSON like {\"summary\": ...}."
+//! );
+//! ```
+use crate::models::{Message, Role};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Why a [`PromptTemplate`] could not be rendered.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum TemplateError {
+    #[error("no value for placeholder {{{0}}}")]
+    MissingVariable(String),
+    #[error("variable {0:?} is not used by the template")]
+    ExtraVariable(String),
+    #[error("variable {0:?} is given more than once")]
+    DuplicateVariable(String),
+    /// A `{` at this byte offset without a matching `}`, or with a name that
+    /// is not a valid placeholder name. Write `{{` for a literal brace.
+    #[error("unclosed or invalid placeholder at byte {0}; write {{{{ for a literal brace")]
+    InvalidPlaceholder(usize),
+    /// A `}` at this byte offset outside a placeholder. Write `}}` for a
+    /// literal brace.
+    #[error("unmatched }} at byte {0}; write }}}} for a literal brace")]
+    UnmatchedBrace(usize),
+}
+
+/// A prompt with `{name}` placeholders; see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    template: String,
+    system: Option<String>,
+}
+
+enum Piece<'a> {
+    Text(&'a str),
+    Var(&'a str),
+}
+
+impl PromptTemplate {
+    /// Creates a template. Syntax errors are reported by
+    /// [`render`](Self::render).
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            system: None,
+        }
+    }
+
+    /// Adds a system template, rendered with the same variables as the first
+    /// message of [`to_messages`](Self::to_messages).
+    pub fn system(mut self, template: impl Into<String>) -> Self {
+        self.system = Some(template.into());
+        self
+    }
+
+    /// Placeholder names in order of first appearance.
+    pub fn variables(&self) -> Result<Vec<&str>, TemplateError> {
+        let mut names = Vec::new();
+        for piece in parse(&self.template)? {
+            if let Piece::Var(name) = piece {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Fills in the placeholders. Every placeholder needs a value and every
+    /// value must be used; a placeholder may appear more than once.
+    pub fn render(&self, vars: &[(&str, &str)]) -> Result<String, TemplateError> {
+        check_vars(vars, &[&self.template])?;
+        fill(&self.template, vars)
+    }
+
+    /// Renders the template as a message with `role`, preceded by the system
+    /// template as a system message if one is set. Every value must be used
+    /// by one of the two.
+    pub fn to_messages(
+        &self,
+        role: Role,
+        vars: &[(&str, &str)],
+    ) -> Result<Vec<Message>, TemplateError> {
+        let mut templates = vec![self.template.as_str()];
+        templates.extend(self.system.as_deref());
+        check_vars(vars, &templates)?;
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = &self.system {
+            messages.push(Message::new(Role::System, fill(system, vars)?));
+        }
+        messages.push(Message::new(role, fill(&self.template, vars)?));
+        Ok(messages)
+    }
+}
+
+/// Rejects duplicate values and values none of `templates` uses.
+fn check_vars(vars: &[(&str, &str)], templates: &[&str]) -> Result<(), TemplateError> {
+    let mut used = HashSet::new();
+    for template in templates {
+        for piece in parse(template)? {
+            if let Piece::Var(name) = piece {
+                used.insert(name);
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    for (name, _) in vars {
+        if !seen.insert(*name) {
+            return Err(TemplateError::DuplicateVariable(name.to_string()));
+        }
+        if !used.contains(name) {
+            return Err(TemplateError::ExtraVariable(name.to_string()));
+        }
+    }
+    Ok(())
+}
+
+fn fill(template: &str, vars: &[(&str, &str)]) -> Result<String, TemplateError> {
+    let mut out = String::with_capacity(template.len());
+    for piece in parse(template)? {
+        match piece {
+            Piece::Text(text) => out.push_str(text),
+            Piece::Var(name) => {
+                let (_, value) = vars
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .ok_or_else(|| TemplateError::MissingVariable(name.to_string()))?;
+                out.push_str(value);
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn parse(template: &str) -> Result<Vec<Piece<'_>>, TemplateError> {
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while let Some(at) = rest.find(['{', '}']) {
+        let offset = template.len() - rest.len() + at;
+        pieces.push(Piece::Text(&rest[..at]));
+        let brace = &rest[at..at + 1];
+        if rest[at + 1..].starts_with(brace) {
+            pieces.push(Piece::Text(brace));
+            rest = &rest[at + 2..];
+            continue;
+        }
+        if brace == "}" {
+            return Err(TemplateError::UnmatchedBrace(offset));
+        }
+        let name = rest[at + 1..]
+            .find('}')
+            .map(|end| &rest[at + 1..at + 1 + end])
+            .filter(|name| is_name(name))
+            .ok_or(TemplateError::InvalidPlaceholder(offset))?;
+        pieces.push(Piece::Var(name));
+        rest = &rest[at + name.len() + 2..];
+    }
+    pieces.push(Piece::Text(rest));
+    Ok(pieces)
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
@@ -0,0 +1,96 @@
+//! Retries of failed calls.
+//!
+//! Retries are off by default. Set a [`RetryPolicy`] with
+//! `ClientBuilder::retry_policy` to retry errors for which
+//! [`SdkError::is_retryable`] holds, with exponential backoff. A `Retry-After`
+//! header on the failed response takes precedence over the computed backoff.
+use crate::client::SdkError;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How often and how quickly failed calls are retried.
+///
+/// Serializable for [`ClientConfig`](crate::config::ClientConfig), with
+/// human-readable durations such as `"200ms"`; missing fields take their
+/// defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` disables retries.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for every further retry.
+    #[serde(with = "humantime_serde")]
+    pub initial_backoff: Duration,
+    /// Upper bound for any delay, including one requested by `Retry-After`.
+    #[serde(with = "humantime_serde")]
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy with `max_retries` retries and default backoff.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+
+    /// Set the delay before the first retry.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the upper bound for retry delays.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Delay before retry number `retry` (0-based), honoring `retry_after`
+    /// when the service sent one.
+    pub fn backoff(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let delay = retry_after.unwrap_or_else(|| {
+            self.initial_backoff
+                .saturating_mul(2u32.saturating_pow(retry))
+        });
+        delay.min(self.max_backoff)
+    }
+}
+
+/// A failed attempt, with the delay the service asked for, if any.
+pub(crate) struct Failure {
+    pub error: SdkError,
+    pub retry_after: Option<Duration>,
+}
+
+impl From<SdkError> for Failure {
+    fn from(error: SdkError) -> Self {
+        Self {
+            error,
+            retry_after: None,
+        }
+    }
+}
+
+/// Parses a `Retry-After` header given in seconds.
+pub(crate) fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
@@ -0,0 +1,54 @@
+//! Request body serialization.
+//!
+//! By default request bodies are written as compact JSON in struct field
+//! order. A [`BodySerializer`] set with `ClientBuilder::body_serializer`
+//! controls key order and whitespace instead, e.g. [`SortedKeysJson`] for
+//! deterministic bodies. Whatever the serializer returns is exactly what gets
+//! signed and sent.
+use crate::client::SdkResult;
+use serde_json::{Map, Value};
+
+/// Turns a request body into the bytes that are signed and sent.
+pub trait BodySerializer: Send + Sync {
+    /// Serializes `body`. The result must be valid UTF-8 JSON.
+    fn serialize(&self, body: &Value) -> SdkResult<String>;
+}
+
+/// Compact JSON, keys in the order held by the [`Value`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactJson;
+
+impl BodySerializer for CompactJson {
+    fn serialize(&self, body: &Value) -> SdkResult<String> {
+        Ok(serde_json::to_string(body)?)
+    }
+}
+
+/// Compact JSON with object keys sorted lexicographically at every level.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SortedKeysJson;
+
+impl BodySerializer for SortedKeysJson {
+    fn serialize(&self, body: &Value) -> SdkResult<String> {
+        Ok(serde_json::to_string(&sort_keys(body))?)
+    }
+}
+
+/// Rebuilds `value` with sorted object keys, independent of whether
+/// `serde_json` preserves insertion order.
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.clone(), sort_keys(v)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sort_keys).collect()),
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,440 @@
+//! Multi-turn chat that keeps the history.
+//!
+//! A [`ChatSession`] appends each user message and the assistant's reply to
+//! its history and sends the whole history with every turn:
+//!
+//! ```no_run
+//! use tencentcloud_hunyuan_sdk::session::{ChatSession, SessionConfig};
+//! # async fn run(client: tencentcloud_hunyuan_sdk::Client) -> tencentcloud_hunyuan_sdk::SdkResult<()> {
+//!
+//! let mut session = ChatSession::new(
+//!     client,
+//!     SessionConfig {
+//!         model: Some("hunyuan-lite".into()),
+//!         system_prompt: Some("Answer in one sentence.".into()),
+//!         ..Default::default()
+//!     },
+//! );
+//! let answer = session.send("What is Rust?").await?;
+//! let follow_up = session.send("Who started it?").await?;
+//! assert_eq!(session.history().len(), 5);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A turn only enters the history once it completes: if the call fails, or
+//! a [`SessionStream`] is dropped before its end, the user message is taken
+//! back out so the turn can be retried.
+//!
+//! Set [`SessionConfig::truncation`] to keep long sessions within the
+//! model's context window; see [`TruncationPolicy`]. To keep the gist of old
+//! turns instead of dropping them, call [`ChatSession::compact`].
+use crate::client::{Client, SdkError, SdkResult};
+use crate::conversation::Conversation;
+use crate::models::{
+    estimate_tokens, ChatCompletionsChunk, ChatCompletionsRequest, Message, Role,
+    MESSAGE_OVERHEAD_TOKENS,
+};
+use crate::stream::{ChatCompletionsAccumulator, ChatCompletionsStream};
+use futures_core::Stream;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Sampling parameters sent with every turn of a [`ChatSession`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChatParams {
+    /// `Temperature`; the service default when unset.
+    pub temperature: Option<f32>,
+    /// `TopP`; the service default when unset.
+    pub top_p: Option<f32>,
+}
+
+/// How a [`ChatSession`] trims its history before each turn.
+///
+/// The oldest messages are dropped first. System messages and the user
+/// message being sent are always kept, so a turn is still sent if those
+/// alone exceed the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TruncationPolicy {
+    /// Send at most this many messages, system messages included.
+    MaxMessages(usize),
+    /// Keep the tokens of the history, as counted by
+    /// [`SessionConfig::token_counter`], within `budget` minus
+    /// `reserve_for_reply`.
+    MaxTokens {
+        budget: usize,
+        reserve_for_reply: usize,
+    },
+}
+
+/// How [`TruncationPolicy::MaxTokens`] counts tokens. Either way each
+/// message also counts [`MESSAGE_OVERHEAD_TOKENS`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TokenCounter {
+    /// [`estimate_tokens`], computed locally.
+    #[default]
+    Estimate,
+    /// Exact counts from the `GetTokenCount` action: one call per message in
+    /// the history on every turn.
+    GetTokenCount,
+}
+
+/// Settings of a [`ChatSession`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionConfig {
+    /// Model for every turn; the client's default model when unset.
+    pub model: Option<String>,
+    /// System message starting the history, kept across
+    /// [`ChatSession::reset`].
+    pub system_prompt: Option<String>,
+    /// Sampling parameters.
+    pub params: ChatParams,
+    /// Trimming of the history before each turn; unbounded when unset.
+    pub truncation: Option<TruncationPolicy>,
+    /// Token counting for [`TruncationPolicy::MaxTokens`].
+    pub token_counter: TokenCounter,
+}
+
+/// Settings of [`ChatSession::compact`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactOptions {
+    /// Number of most recent messages kept as they are.
+    pub keep_recent: usize,
+    /// System prompt of the summarization call.
+    pub summary_prompt: String,
+    /// Length asked of the summary. ChatCompletions has no output limit, so
+    /// this is a request in the prompt rather than a hard cap.
+    pub max_summary_tokens: usize,
+}
+
+impl Default for CompactOptions {
+    /// Keeps the last 6 messages and asks for up to 500 tokens of summary.
+    fn default() -> Self {
+        Self {
+            keep_recent: 6,
+            summary_prompt: "Summarize the conversation below so that it can be continued \
+                             without it. Keep names, facts, decisions and open questions."
+                .to_string(),
+            max_summary_tokens: 500,
+        }
+    }
+}
+
+/// Start of the system message holding a [`ChatSession::compact`] summary.
+pub const SUMMARY_HEADER: &str = "Summary of the earlier conversation:";
+
+type TruncateCallback = Arc<dyn Fn(&[Message]) + Send + Sync>;
+
+/// A conversation with the model; see the [module docs](self).
+#[derive(Clone)]
+pub struct ChatSession {
+    client: Client,
+    config: SessionConfig,
+    conversation: Conversation,
+    on_truncate: Option<TruncateCallback>,
+    /// Whether the message after the system prompt is a summary.
+    summarized: bool,
+}
+
+impl fmt::Debug for ChatSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChatSession")
+            .field("config", &self.config)
+            .field("history", &self.history())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChatSession {
+    /// Starts a session whose history holds only the system prompt, if any.
+    pub fn new(client: Client, config: SessionConfig) -> Self {
+        let mut session = Self {
+            client,
+            config,
+            conversation: Conversation::new(),
+            on_truncate: None,
+            summarized: false,
+        };
+        session.reset();
+        session
+    }
+
+    /// Returns the messages sent with the next turn, oldest first.
+    pub fn history(&self) -> &[Message] {
+        self.conversation.messages()
+    }
+
+    /// Returns the history as a [`Conversation`], e.g. to store it.
+    pub fn conversation(&self) -> &Conversation {
+        &self.conversation
+    }
+
+    /// Clears the history back to the system prompt.
+    pub fn reset(&mut self) {
+        self.conversation = Conversation::new();
+        self.summarized = false;
+        if let Some(prompt) = &self.config.system_prompt {
+            self.conversation
+                .push(Message::new(Role::System, prompt.clone()));
+        }
+    }
+
+    /// Appends `message` to the history without sending anything, e.g. a
+    /// tool result or an earlier exchange.
+    pub fn push(&mut self, message: Message) {
+        self.conversation.push(message);
+    }
+
+    /// Calls `callback` with the messages dropped from the history whenever
+    /// the [`TruncationPolicy`] trims it, oldest first.
+    pub fn on_truncate(mut self, callback: impl Fn(&[Message]) + Send + Sync + 'static) -> Self {
+        self.on_truncate = Some(Arc::new(callback));
+        self
+    }
+
+    /// Replaces all but the `keep_recent` latest messages with a summary and
+    /// returns the messages it replaced, oldest first.
+    ///
+    /// The summary is written by the model, in one call with the session's
+    /// model, and kept as a system message after the system prompt starting
+    /// with [`SUMMARY_HEADER`]. A later compaction summarizes the previous
+    /// summary along with the newer messages, so the history never holds
+    /// more than one. Nothing is sent and nothing changes if there are at
+    /// most `keep_recent` messages besides the system prompt and summary, or
+    /// if the call fails.
+    pub async fn compact(&mut self, options: &CompactOptions) -> SdkResult<Vec<Message>> {
+        let start = usize::from(self.config.system_prompt.is_some());
+        let summary = usize::from(self.summarized);
+        let history = self.history();
+        if history.len() <= start + summary + options.keep_recent {
+            return Ok(Vec::new());
+        }
+        let end = history.len() - options.keep_recent;
+        let old = &history[start..end];
+        let transcript = old
+            .iter()
+            .map(|m| format!("{}: {}", m.role, m.content))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let request = ChatCompletionsRequest {
+            model: self.config.model.clone(),
+            messages: vec![
+                Message::new(
+                    Role::System,
+                    format!(
+                        "{}\nUse at most {} tokens.",
+                        options.summary_prompt, options.max_summary_tokens
+                    ),
+                ),
+                Message::new(Role::User, transcript),
+            ],
+            ..Default::default()
+        };
+        let resp = self.client.chat_completions(&request).await?;
+        let text = resp
+            .messages()
+            .next()
+            .and_then(|m| m.content.clone())
+            .ok_or_else(|| {
+                SdkError::Decode("ChatCompletions response has no message".to_string())
+            })?;
+
+        let mut messages = std::mem::take(&mut self.conversation).into_messages();
+        let replaced: Vec<Message> = messages
+            .splice(
+                start..end,
+                [Message::new(
+                    Role::System,
+                    format!("{}\n{}", SUMMARY_HEADER, text.trim()),
+                )],
+            )
+            .collect();
+        self.conversation = Conversation::from_messages(messages);
+        self.summarized = true;
+        Ok(replaced)
+    }
+
+    /// Sends `text` as the next user message and returns the assistant's
+    /// reply, which is added to the history.
+    ///
+    /// Fails with [`SdkError::Decode`] if the response carries no message.
+    pub async fn send(&mut self, text: impl Into<String>) -> SdkResult<String> {
+        let request = self.begin_turn(text.into()).await?;
+        let result = self
+            .client
+            .chat_completions(&request)
+            .await
+            .and_then(|resp| {
+                let message = resp.messages().next().ok_or_else(|| {
+                    SdkError::Decode("ChatCompletions response has no message".to_string())
+                })?;
+                Ok(message.content.clone().unwrap_or_default())
+            });
+        match &result {
+            Ok(reply) => self.finish_turn(reply.clone()),
+            Err(_) => self.abandon_turn(),
+        }
+        result
+    }
+
+    /// Like [`ChatSession::send`], streaming the reply. The reply is added to
+    /// the history when the stream ends.
+    pub async fn send_stream(&mut self, text: impl Into<String>) -> SdkResult<SessionStream<'_>> {
+        let request = self.begin_turn(text.into()).await?;
+        match self.client.chat_completions_stream(&request).await {
+            Ok(stream) => Ok(SessionStream {
+                session: self,
+                stream,
+                accumulator: ChatCompletionsAccumulator::new(),
+                done: false,
+            }),
+            Err(error) => {
+                self.abandon_turn();
+                Err(error)
+            }
+        }
+    }
+
+    /// Appends the user message, trims the history and returns the request
+    /// for the turn. On failure the user message is taken back out.
+    async fn begin_turn(&mut self, text: String) -> SdkResult<ChatCompletionsRequest> {
+        self.conversation.push(Message::new(Role::User, text));
+        if let Err(error) = self.truncate().await {
+            self.abandon_turn();
+            return Err(error);
+        }
+        Ok(ChatCompletionsRequest {
+            model: self.config.model.clone(),
+            messages: self.conversation.messages().to_vec(),
+            temperature: self.config.params.temperature,
+            top_p: self.config.params.top_p,
+            stream: None,
+            tools: None,
+            n: None,
+            seed: None,
+        })
+    }
+
+    /// Drops the oldest droppable messages until the history fits the
+    /// policy. Dropped messages stay dropped even if the turn then fails.
+    async fn truncate(&mut self) -> SdkResult<()> {
+        let Some(policy) = self.config.truncation else {
+            return Ok(());
+        };
+        let (limit, mut sizes) = match policy {
+            TruncationPolicy::MaxMessages(max) => (max, vec![1; self.history().len()]),
+            TruncationPolicy::MaxTokens {
+                budget,
+                reserve_for_reply,
+            } => (
+                budget.saturating_sub(reserve_for_reply),
+                self.count_tokens().await?,
+            ),
+        };
+        let mut messages = std::mem::take(&mut self.conversation).into_messages();
+        let mut dropped = Vec::new();
+        while sizes.iter().sum::<usize>() > limit {
+            let last = messages.len().saturating_sub(1);
+            let Some(oldest) = messages[..last]
+                .iter()
+                .position(|m| m.role != Role::System.as_str())
+            else {
+                break;
+            };
+            sizes.remove(oldest);
+            dropped.push(messages.remove(oldest));
+        }
+        self.conversation = Conversation::from_messages(messages);
+        if let (false, Some(callback)) = (dropped.is_empty(), &self.on_truncate) {
+            callback(&dropped);
+        }
+        Ok(())
+    }
+
+    /// Token count of each message in the history.
+    async fn count_tokens(&self) -> SdkResult<Vec<usize>> {
+        let mut counts = Vec::with_capacity(self.history().len());
+        for message in self.history() {
+            let tokens = match self.config.token_counter {
+                TokenCounter::Estimate => estimate_tokens(&message.content),
+                TokenCounter::GetTokenCount => {
+                    self.client.get_token_count(&message.content).await? as usize
+                }
+            };
+            counts.push(MESSAGE_OVERHEAD_TOKENS + tokens);
+        }
+        Ok(counts)
+    }
+
+    fn finish_turn(&mut self, reply: String) {
+        self.conversation.push(Message::new(Role::Assistant, reply));
+    }
+
+    /// Takes the user message of a failed turn back out of the history.
+    fn abandon_turn(&mut self) {
+        let mut messages = std::mem::take(&mut self.conversation).into_messages();
+        messages.pop();
+        self.conversation = Conversation::from_messages(messages);
+    }
+}
+
+/// Streamed reply of a [`ChatSession`] turn, returned by
+/// [`ChatSession::send_stream`].
+///
+/// Yields the chunks of the underlying [`ChatCompletionsStream`]. Once it
+/// ends, the reply is added to the session's history; if it fails or is
+/// dropped early, the turn is taken back out instead.
+pub struct SessionStream<'a> {
+    session: &'a mut ChatSession,
+    stream: ChatCompletionsStream,
+    accumulator: ChatCompletionsAccumulator,
+    done: bool,
+}
+
+impl Stream for SessionStream<'_> {
+    type Item = SdkResult<ChatCompletionsChunk>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let item = match Pin::new(&mut this.stream).poll_next(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(item) => item,
+        };
+        match item {
+            Some(Ok(chunk)) => match this.accumulator.push(&chunk) {
+                Ok(()) => Poll::Ready(Some(Ok(chunk))),
+                Err(error) => {
+                    this.done = true;
+                    this.session.abandon_turn();
+                    Poll::Ready(Some(Err(error)))
+                }
+            },
+            Some(Err(error)) => {
+                this.done = true;
+                this.session.abandon_turn();
+                Poll::Ready(Some(Err(error)))
+            }
+            None => {
+                this.done = true;
+                let reply = std::mem::take(&mut this.accumulator).content().to_string();
+                this.session.finish_turn(reply);
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+impl Drop for SessionStream<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.session.abandon_turn();
+        }
+    }
+}
@@ -0,0 +1,710 @@
+//! Server-sent events (SSE) decoding for streamed `ChatCompletions`.
+use crate::client::{SdkError, SdkResult};
+use crate::limiter::Permit;
+use crate::models::{
+    ChatChoice, ChatChoiceMessage, ChatCompletionsChunk, ChatCompletionsResponse,
+    ChatCompletionsResponseInner, Citation, SearchInfo, SearchResult, TencentCloudResponse,
+    ToolCall, Usage,
+};
+use crate::shutdown::InFlight;
+use crate::transport::BodyStream;
+use bytes::Bytes;
+use futures_core::Stream;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A single decoded SSE event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct SseEvent {
+    pub event: Option<String>,
+    pub id: Option<String>,
+    pub data: String,
+}
+
+/// Default for [`ClientBuilder::max_sse_buffer_bytes`](crate::ClientBuilder::max_sse_buffer_bytes).
+pub(crate) const DEFAULT_MAX_SSE_BUFFER_BYTES: usize = 16 << 20;
+
+/// Incremental SSE decoder. Bytes are pushed as they arrive and complete
+/// events are read from the front of a rolling buffer, so it only ever holds
+/// the event being received plus the rest of the last pushed chunk.
+#[derive(Debug)]
+pub(crate) struct SseDecoder {
+    buf: Vec<u8>,
+    /// Start of the bytes not yet returned as events. Consumed bytes are
+    /// dropped in one go on the next `push` rather than after every event.
+    start: usize,
+    /// Offset before which no event terminator starts, so a long event is
+    /// not rescanned on every push.
+    scanned: usize,
+    max_buffered: usize,
+}
+
+impl Default for SseDecoder {
+    fn default() -> Self {
+        Self::with_max_buffered(DEFAULT_MAX_SSE_BUFFER_BYTES)
+    }
+}
+
+impl SseDecoder {
+    /// A decoder that fails once an unfinished event exceeds `max_buffered`
+    /// bytes.
+    pub fn with_max_buffered(max_buffered: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            scanned: 0,
+            max_buffered,
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.scanned -= self.start;
+            self.start = 0;
+        }
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Bytes held for events that have not been returned yet.
+    pub fn buffered(&self) -> usize {
+        self.buf.len() - self.start
+    }
+
+    /// Returns the next complete event, if the buffer holds one.
+    ///
+    /// Fails with [`SdkError::StreamEventTooLarge`], releasing the buffer, when
+    /// it holds no complete event but more than the configured maximum, i.e.
+    /// the server keeps sending without ending the event.
+    pub fn next_event(&mut self) -> SdkResult<Option<SseEvent>> {
+        loop {
+            let Some((end, sep)) = find_event_end(&self.buf[self.scanned..]) else {
+                // A terminator may start in the last bytes and end in the next push.
+                self.scanned = self.buf.len().saturating_sub(3).max(self.start);
+                let size = self.buffered();
+                if size > self.max_buffered {
+                    self.buf = Vec::new();
+                    self.start = 0;
+                    self.scanned = 0;
+                    return Err(SdkError::StreamEventTooLarge {
+                        limit: self.max_buffered,
+                        size,
+                    });
+                }
+                return Ok(None);
+            };
+            let end = self.scanned + end;
+            let event = parse_event(&String::from_utf8_lossy(&self.buf[self.start..end]));
+            self.start = end + sep;
+            self.scanned = self.start;
+            if let Some(event) = event {
+                return Ok(Some(event));
+            }
+        }
+    }
+
+    /// Flushes a trailing event that was not followed by a blank line.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        let raw = std::mem::take(&mut self.buf);
+        let event = parse_event(&String::from_utf8_lossy(&raw[self.start..]));
+        self.start = 0;
+        self.scanned = 0;
+        event
+    }
+}
+
+/// Finds the end of the first event and the length of its terminator.
+fn find_event_end(buf: &[u8]) -> Option<(usize, usize)> {
+    (0..buf.len()).find_map(|i| {
+        if buf[i..].starts_with(b"\r\n\r\n") {
+            Some((i, 4))
+        } else if buf[i..].starts_with(b"\n\n") {
+            Some((i, 2))
+        } else {
+            None
+        }
+    })
+}
+
+fn parse_event(raw: &str) -> Option<SseEvent> {
+    let mut event = SseEvent::default();
+    let mut data: Vec<&str> = Vec::new();
+    for line in raw.lines() {
+        if line.is_empty() || line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => data.push(value),
+            "event" => event.event = Some(value.to_string()),
+            "id" => event.id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    if data.is_empty() && event.event.is_none() && event.id.is_none() {
+        return None;
+    }
+    event.data = data.join("\n");
+    Some(event)
+}
+
+/// Stream of [`ChatCompletionsChunk`]s returned by
+/// [`Client::chat_completions_stream`](crate::Client::chat_completions_stream).
+///
+/// Ends after the final chunk or a `[DONE]` marker.
+///
+/// Dropping the stream before it ends closes its connection instead of
+/// reading the rest of the response in the background; see
+/// [`cancel`](Self::cancel).
+pub struct ChatCompletionsStream {
+    body: BodyStream,
+    decoder: SseDecoder,
+    body_done: bool,
+    finished: bool,
+    on_first_chunk: Option<Box<dyn FnOnce() + Send>>,
+    parse: fn(&str) -> SdkResult<ChatCompletionsChunk>,
+    last_event_id: Option<String>,
+    /// Keeps the client's call count up until the stream ends.
+    in_flight: Option<(InFlight, Option<Permit>)>,
+}
+
+impl ChatCompletionsStream {
+    pub(crate) fn new(body: BodyStream, on_first_chunk: Box<dyn FnOnce() + Send>) -> Self {
+        Self::with_parser(body, on_first_chunk, parse_chunk)
+    }
+
+    /// Like [`ChatCompletionsStream::new`], decoding each event's data with
+    /// `parse`, e.g. for chunks in another API's format.
+    pub(crate) fn with_parser(
+        body: BodyStream,
+        on_first_chunk: Box<dyn FnOnce() + Send>,
+        parse: fn(&str) -> SdkResult<ChatCompletionsChunk>,
+    ) -> Self {
+        Self {
+            body,
+            decoder: SseDecoder::default(),
+            body_done: false,
+            finished: false,
+            on_first_chunk: Some(on_first_chunk),
+            parse,
+            last_event_id: None,
+            in_flight: None,
+        }
+    }
+
+    /// Counts the stream as a call in flight, and keeps its slot under the
+    /// concurrency limit, until it ends or is dropped.
+    pub(crate) fn hold(mut self, in_flight: InFlight, permit: Option<Permit>) -> Self {
+        self.in_flight = Some((in_flight, permit));
+        self
+    }
+
+    /// Fail once an event that has not ended exceeds `bytes`.
+    pub(crate) fn max_buffered(mut self, bytes: usize) -> Self {
+        self.decoder = SseDecoder::with_max_buffered(bytes);
+        self
+    }
+
+    /// Stops reading and closes the connection, so the server notices the
+    /// client is gone and can stop generating.
+    ///
+    /// Hunyuan has no endpoint to abort a generation, so closing the
+    /// connection is the only signal available: over HTTP/1.1 it is closed
+    /// rather than drained and returned to the pool, and over HTTP/2 the
+    /// request is reset with `CANCEL`. Dropping the stream does the same; this
+    /// method spells out the intent at the call site.
+    pub fn cancel(self) {
+        drop(self);
+    }
+
+    /// The `id:` of the latest event that carried one, as in the SSE
+    /// `Last-Event-ID`; an empty `id:` clears it.
+    ///
+    /// The SDK does not reconnect a dropped stream by itself. Where the
+    /// service supports resuming, send this back with
+    /// [`RequestOptions::header`](crate::RequestOptions::header)`("Last-Event-ID", id)`
+    /// on the new call.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// Re-encodes the stream as SSE frames for forwarding to a browser, e.g.
+    /// as the body of an axum or warp response with
+    /// `Content-Type: text/event-stream`.
+    ///
+    /// Every chunk becomes `event: message\ndata: {...}\n\n` with the chunk
+    /// serialized as received, followed by `event: message\ndata: [DONE]\n\n`
+    /// once the stream ends. If the stream fails, a final
+    /// `event: error\ndata: {"Message":"..."}\n\n` frame replaces `[DONE]`.
+    /// A chunk whose event changed the [event id](Self::last_event_id) is
+    /// framed with an `id:` line, so a browser reconnecting through
+    /// `EventSource` reports where it left off.
+    pub fn into_sse(self) -> SseFrames {
+        SseFrames {
+            inner: self,
+            done: false,
+            sent_id: None,
+        }
+    }
+
+    fn decode(&mut self, event: SseEvent) -> Option<SdkResult<ChatCompletionsChunk>> {
+        if let Some(id) = event.id {
+            self.last_event_id = Some(id).filter(|id| !id.is_empty());
+        }
+        let data = event.data.trim();
+        if data == "[DONE]" {
+            self.finished = true;
+            return None;
+        }
+        if data.is_empty() {
+            return None;
+        }
+        if let Some(callback) = self.on_first_chunk.take() {
+            callback();
+        }
+        let item = (self.parse)(data);
+        if matches!(
+            item,
+            Err(SdkError::Service { .. } | SdkError::RateLimited { .. })
+        ) {
+            // The service gives up on the response after reporting an error.
+            self.finished = true;
+        }
+        Some(item)
+    }
+}
+
+impl Stream for ChatCompletionsStream {
+    type Item = SdkResult<ChatCompletionsChunk>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let poll = this.poll_item(cx);
+        if this.finished {
+            this.in_flight = None;
+        }
+        poll
+    }
+}
+
+impl ChatCompletionsStream {
+    fn poll_item(&mut self, cx: &mut Context<'_>) -> Poll<Option<SdkResult<ChatCompletionsChunk>>> {
+        loop {
+            if self.finished {
+                return Poll::Ready(None);
+            }
+            let event = match self.decoder.next_event() {
+                Ok(Some(event)) => Some(event),
+                Ok(None) if self.body_done => match self.decoder.finish() {
+                    Some(event) => Some(event),
+                    None => {
+                        self.finished = true;
+                        return Poll::Ready(None);
+                    }
+                },
+                Ok(None) => None,
+                Err(e) => {
+                    self.finished = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            };
+            if let Some(event) = event {
+                if let Some(item) = self.decode(event) {
+                    return Poll::Ready(Some(item));
+                }
+                continue;
+            }
+            match self.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(bytes))) => self.decoder.push(&bytes),
+                Poll::Ready(Some(Err(e))) => {
+                    self.finished = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Ready(None) => self.body_done = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Parses one event of a Hunyuan stream, mapping error frames to
+/// [`SdkError::Service`], or [`SdkError::RateLimited`] for throttling and
+/// [`SdkError::QuotaExhausted`] for used-up quota.
+fn parse_chunk(data: &str) -> SdkResult<ChatCompletionsChunk> {
+    let chunk: ChatCompletionsChunk = serde_json::from_str(data)?;
+    match stream_error(&chunk.extra) {
+        Some(error) => Err(error.classify(None)),
+        None => Ok(chunk),
+    }
+}
+
+/// Reads an error reported in place of a chunk. Every field of a chunk is
+/// optional, so without this check an error frame would pass for an empty
+/// chunk. Accepted shapes:
+///
+/// - the API error envelope, `{"Error":{"Code","Message"},"RequestId"}`,
+///   with or without an outer `Response` object;
+/// - Hunyuan's in-stream `{"ErrorMsg":{"Code","Msg"}}`, sent when generation
+///   fails part-way, where `Code` is a number.
+fn stream_error(fields: &serde_json::Map<String, serde_json::Value>) -> Option<SdkError> {
+    use serde_json::Value;
+
+    fn text(value: Option<&Value>) -> Option<String> {
+        match value? {
+            Value::String(s) => Some(s.clone()),
+            Value::Null => None,
+            other => Some(other.to_string()),
+        }
+    }
+
+    let envelope = fields
+        .get("Response")
+        .and_then(Value::as_object)
+        .unwrap_or(fields);
+    let request_id = text(envelope.get("RequestId")).or_else(|| text(fields.get("RequestId")));
+    if let Some(error) = envelope.get("Error").and_then(Value::as_object) {
+        return Some(SdkError::Service {
+            code: text(error.get("Code")).unwrap_or_default(),
+            message: text(error.get("Message")).unwrap_or_default(),
+            request_id,
+        });
+    }
+    let error = fields.get("ErrorMsg").and_then(Value::as_object)?;
+    Some(SdkError::Service {
+        code: text(error.get("Code")).unwrap_or_default(),
+        message: text(error.get("Msg")).unwrap_or_default(),
+        request_id,
+    })
+}
+
+/// SSE frames re-encoded from a [`ChatCompletionsStream`]; see
+/// [`ChatCompletionsStream::into_sse`].
+pub struct SseFrames {
+    inner: ChatCompletionsStream,
+    done: bool,
+    /// The event id last written to a frame.
+    sent_id: Option<String>,
+}
+
+fn sse_frame(event: &str, data: &str) -> Bytes {
+    Bytes::from(format!("event: {}\ndata: {}\n\n", event, data))
+}
+
+fn sse_frame_with_id(id: &str, data: &str) -> Bytes {
+    Bytes::from(format!("id: {}\nevent: message\ndata: {}\n\n", id, data))
+}
+
+impl Stream for SseFrames {
+    type Item = Result<Bytes, Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let frame = match Pin::new(&mut this.inner).poll_next(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Some(Ok(chunk))) => match serde_json::to_string(&chunk) {
+                Ok(data) => {
+                    let id = this.inner.last_event_id.as_deref();
+                    if id == this.sent_id.as_deref() {
+                        sse_frame("message", &data)
+                    } else {
+                        this.sent_id = id.map(str::to_string);
+                        sse_frame_with_id(id.unwrap_or(""), &data)
+                    }
+                }
+                Err(e) => {
+                    this.done = true;
+                    sse_frame("error", &error_data(&e.into()))
+                }
+            },
+            Poll::Ready(Some(Err(e))) => {
+                this.done = true;
+                sse_frame("error", &error_data(&e))
+            }
+            Poll::Ready(None) => {
+                this.done = true;
+                sse_frame("message", "[DONE]")
+            }
+        };
+        Poll::Ready(Some(Ok(frame)))
+    }
+}
+
+fn error_data(error: &SdkError) -> String {
+    serde_json::json!({ "Message": error.to_string() }).to_string()
+}
+
+/// Folds streamed [`ChatCompletionsChunk`]s of the first choice into a
+/// complete message.
+///
+/// Besides the text it keeps the search references and citation markers
+/// announced along the way, so the final offset-to-reference mapping is
+/// available once the stream ends.
+///
+/// Memory can be bounded for very long generations: past
+/// [`max_retained_bytes`](Self::max_retained_bytes) further text is discarded
+/// (callers emit it from the chunks themselves) while usage and finish reason
+/// are still recorded; past [`max_total_bytes`](Self::max_total_bytes)
+/// `push` fails with [`SdkError::ResponseTooLarge`].
+///
+/// ```
+/// use tencentcloud_hunyuan_sdk::stream::ChatCompletionsAccumulator;
+/// # use tencentcloud_hunyuan_sdk::models::ChatCompletionsChunk;
+/// # fn chunks() -> Vec<ChatCompletionsChunk> { Vec::new() }
+///
+/// let mut acc = ChatCompletionsAccumulator::new();
+/// for chunk in chunks() {
+///     acc.push(&chunk).unwrap();
+/// }
+/// for (offset, refs) in acc.citation_map() {
+///     println!("at {}: {:?}", offset, refs.iter().map(|r| &r.url).collect::<Vec<_>>());
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChatCompletionsAccumulator {
+    id: Option<String>,
+    role: Option<String>,
+    content: String,
+    tool_calls: Vec<ToolCall>,
+    finish_reason: Option<String>,
+    usage: Option<Usage>,
+    note: Option<String>,
+    references: BTreeMap<u32, SearchResult>,
+    citations: Vec<Citation>,
+    total_bytes: usize,
+    truncated: bool,
+    max_retained_bytes: Option<usize>,
+    max_total_bytes: Option<usize>,
+}
+
+impl ChatCompletionsAccumulator {
+    /// Creates an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `bytes` of content; text beyond it is discarded and
+    /// [`is_truncated`](Self::is_truncated) becomes true.
+    pub fn max_retained_bytes(mut self, bytes: usize) -> Self {
+        self.max_retained_bytes = Some(bytes);
+        self
+    }
+
+    /// Fail with [`SdkError::ResponseTooLarge`] once more than `bytes` of
+    /// content have been received, retained or not.
+    pub fn max_total_bytes(mut self, bytes: usize) -> Self {
+        self.max_total_bytes = Some(bytes);
+        self
+    }
+
+    /// Adds one chunk. Choices other than index 0 are ignored.
+    ///
+    /// Fails once the content received exceeds `max_total_bytes`; the
+    /// chunk's content is not added in that case.
+    pub fn push(&mut self, chunk: &ChatCompletionsChunk) -> SdkResult<()> {
+        if self.id.is_none() {
+            self.id.clone_from(&chunk.id);
+        }
+        for choice in chunk.choices.iter().flatten() {
+            if choice.index.unwrap_or(0) != 0 {
+                continue;
+            }
+            if let Some(delta) = &choice.delta {
+                if delta.role.is_some() {
+                    self.role.clone_from(&delta.role);
+                }
+                if let Some(content) = &delta.content {
+                    self.push_content(content)?;
+                }
+                for fragment in delta.tool_calls.iter().flatten() {
+                    self.push_tool_call(fragment);
+                }
+            }
+            if choice.finish_reason.is_some() {
+                self.finish_reason.clone_from(&choice.finish_reason);
+            }
+        }
+        if chunk.usage.is_some() {
+            self.usage.clone_from(&chunk.usage);
+        }
+        if chunk.note.is_some() {
+            self.note.clone_from(&chunk.note);
+        }
+        if let Some(info) = &chunk.search_info {
+            for result in info.search_results.iter().flatten() {
+                if let Some(index) = result.index {
+                    self.references.insert(index, result.clone());
+                }
+            }
+            for citation in info.citations.iter().flatten() {
+                if !self.citations.contains(citation) {
+                    self.citations.push(*citation);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges a streamed tool-call fragment into the call with the same
+    /// `Index` (or `Id`, or the latest call when neither is set): id, type and
+    /// name are taken from the first fragment carrying them, arguments are
+    /// concatenated.
+    fn push_tool_call(&mut self, fragment: &ToolCall) {
+        let slot = match fragment.index {
+            Some(index) => self.tool_calls.iter().position(|c| c.index == Some(index)),
+            None if fragment.id.is_empty() => self.tool_calls.len().checked_sub(1),
+            None => self.tool_calls.iter().position(|c| c.id == fragment.id),
+        };
+        let Some(call) = slot.map(|i| &mut self.tool_calls[i]) else {
+            self.tool_calls.push(fragment.clone());
+            return;
+        };
+        if call.id.is_empty() {
+            call.id.clone_from(&fragment.id);
+        }
+        if call.call_type.is_empty() {
+            call.call_type.clone_from(&fragment.call_type);
+        }
+        if call.function.name.is_empty() {
+            call.function.name.clone_from(&fragment.function.name);
+        }
+        call.function
+            .arguments
+            .push_str(&fragment.function.arguments);
+    }
+
+    fn push_content(&mut self, content: &str) -> SdkResult<()> {
+        let total = self.total_bytes + content.len();
+        if let Some(limit) = self.max_total_bytes {
+            if total > limit {
+                return Err(SdkError::ResponseTooLarge {
+                    limit,
+                    received: total,
+                    status: None,
+                    request_id: None,
+                });
+            }
+        }
+        self.total_bytes = total;
+        let room = self
+            .max_retained_bytes
+            .map_or(usize::MAX, |max| max.saturating_sub(self.content.len()));
+        if content.len() <= room {
+            self.content.push_str(content);
+        } else {
+            let mut end = room;
+            while !content.is_char_boundary(end) {
+                end -= 1;
+            }
+            self.content.push_str(&content[..end]);
+            self.truncated = true;
+        }
+        Ok(())
+    }
+
+    /// Text received so far, up to `max_retained_bytes`.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Whether content was discarded because of `max_retained_bytes`.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Bytes of content received, including discarded ones.
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Tool calls of the reply, with their arguments reassembled from the
+    /// streamed fragments.
+    ///
+    /// Empty until a chunk carries a finish reason: before that, arguments
+    /// may still be incomplete.
+    pub fn tool_calls(&self) -> &[ToolCall] {
+        if self.finish_reason.is_some() {
+            &self.tool_calls
+        } else {
+            &[]
+        }
+    }
+
+    /// Role announced by the stream, if any.
+    pub fn role(&self) -> Option<&str> {
+        self.role.as_deref()
+    }
+
+    /// Finish reason of the last chunk that carried one.
+    pub fn finish_reason(&self) -> Option<&str> {
+        self.finish_reason.as_deref()
+    }
+
+    /// Usage of the last chunk that carried it.
+    pub fn usage(&self) -> Option<&Usage> {
+        self.usage.as_ref()
+    }
+
+    /// `Note` of the last chunk that carried one.
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    /// Search references by their index.
+    pub fn references(&self) -> &BTreeMap<u32, SearchResult> {
+        &self.references
+    }
+
+    /// Citation markers in arrival order, without duplicates.
+    pub fn citations(&self) -> &[Citation] {
+        &self.citations
+    }
+
+    /// Character offset in [`content`](Self::content) to the references cited
+    /// there, in citation order. Citations of unknown references are skipped.
+    pub fn citation_map(&self) -> BTreeMap<u32, Vec<&SearchResult>> {
+        let mut map: BTreeMap<u32, Vec<&SearchResult>> = BTreeMap::new();
+        for citation in &self.citations {
+            if let Some(reference) = self.references.get(&citation.index) {
+                map.entry(citation.offset).or_default().push(reference);
+            }
+        }
+        map
+    }
+
+    /// Converts the accumulated stream into a unary-style response.
+    pub fn into_response(self) -> ChatCompletionsResponse {
+        let tool_calls = (!self.tool_calls().is_empty()).then_some(self.tool_calls);
+        let search_info =
+            (!self.references.is_empty() || !self.citations.is_empty()).then(|| SearchInfo {
+                search_results: Some(self.references.into_values().collect()),
+                citations: Some(self.citations),
+            });
+        TencentCloudResponse {
+            response: ChatCompletionsResponseInner {
+                request_id: None,
+                id: self.id,
+                choices: Some(vec![ChatChoice {
+                    index: Some(0),
+                    message: Some(ChatChoiceMessage {
+                        role: self.role,
+                        content: Some(self.content),
+                        tool_calls,
+                        ..Default::default()
+                    }),
+                    finish_reason: self.finish_reason,
+                    ..Default::default()
+                }]),
+                usage: self.usage,
+                search_info,
+                note: self.note,
+                ..Default::default()
+            },
+        }
+    }
+}
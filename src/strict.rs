@@ -0,0 +1,215 @@
+//! Parsing responses while noting the fields the models do not know, for
+//! [`DeserializationMode::Strict`](crate::DeserializationMode::Strict).
+//!
+//! The body is parsed into a [`Value`] first and then deserialized through
+//! [`Tracked`], which walks it alongside the model. A field the model does
+//! not declare is either skipped by serde, which asks for
+//! `deserialize_ignored_any`, or kept in a flattened `extra` map, which asks
+//! for `deserialize_any`; both are recorded with their JSON path. A declared
+//! field typed as [`Value`] is reported too, since it is read the same way;
+//! no response model has one.
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Error, Value};
+use std::cell::RefCell;
+
+/// Parses `body` as `T` and returns the paths of the fields `T` does not
+/// declare, e.g. `Response.Choices[0].Audio`, in document order.
+pub(crate) fn from_slice<T: DeserializeOwned>(body: &[u8]) -> serde_json::Result<(T, Vec<String>)> {
+    let value: Value = serde_json::from_slice(body)?;
+    let unknown = RefCell::new(Vec::new());
+    let parsed = T::deserialize(Tracked {
+        value: &value,
+        path: String::new(),
+        unknown: &unknown,
+    })?;
+    Ok((parsed, unknown.into_inner()))
+}
+
+struct Tracked<'a> {
+    value: &'a Value,
+    path: String,
+    unknown: &'a RefCell<Vec<String>>,
+}
+
+impl<'a> Tracked<'a> {
+    fn child(&self, value: &'a Value, path: String) -> Self {
+        Tracked {
+            value,
+            path,
+            unknown: self.unknown,
+        }
+    }
+
+    fn record(&self) {
+        // The root is read as a whole only when `T` is untyped, e.g. `Value`.
+        if !self.path.is_empty() {
+            self.unknown.borrow_mut().push(self.path.clone());
+        }
+    }
+}
+
+macro_rules! forward_to_value {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.value.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'a> Deserializer<'a> for Tracked<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.record();
+        self.value.deserialize_any(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.record();
+        self.value.deserialize_ignored_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'a>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        // A declared field that looks at the JSON type to tell its forms
+        // apart, and so reads it as a whole.
+        if name == crate::models::EMBEDDING_VALUE {
+            return visitor.visit_newtype_struct(self.value);
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Array(items) => visitor.visit_seq(TrackedSeq {
+                items: items.iter().enumerate(),
+                parent: self,
+            }),
+            _ => self.value.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'a>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Object(fields) => visitor.visit_map(TrackedMap {
+                fields: fields.iter(),
+                value: None,
+                parent: self,
+            }),
+            _ => self.value.deserialize_map(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'a>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'a>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.value.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'a>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_value! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32
+        deserialize_f64 deserialize_char deserialize_str deserialize_string deserialize_bytes
+        deserialize_byte_buf deserialize_unit deserialize_identifier
+    }
+}
+
+struct TrackedMap<'a> {
+    fields: serde_json::map::Iter<'a>,
+    value: Option<(&'a String, &'a Value)>,
+    parent: Tracked<'a>,
+}
+
+impl<'a> MapAccess<'a> for TrackedMap<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'a>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some((key, value)) = self.fields.next() else {
+            return Ok(None);
+        };
+        self.value = Some((key, value));
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'a>>(&mut self, seed: S) -> Result<S::Value, Error> {
+        let (key, value) = self
+            .value
+            .take()
+            .expect("next_value_seed after next_key_seed");
+        let path = match self.parent.path.as_str() {
+            "" => key.clone(),
+            parent => format!("{}.{}", parent, key),
+        };
+        seed.deserialize(self.parent.child(value, path))
+    }
+}
+
+struct TrackedSeq<'a> {
+    items: std::iter::Enumerate<std::slice::Iter<'a, Value>>,
+    parent: Tracked<'a>,
+}
+
+impl<'a> SeqAccess<'a> for TrackedSeq<'a> {
+    type Error = Error;
+
+    fn next_element_seed<S: DeserializeSeed<'a>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Error> {
+        let Some((i, item)) = self.items.next() else {
+            return Ok(None);
+        };
+        let path = format!("{}[{}]", self.parent.path, i);
+        seed.deserialize(self.parent.child(item, path)).map(Some)
+    }
+}
@@ -0,0 +1,889 @@
+//! Helpers for testing code that uses this SDK without a network or server.
+//!
+//! Enabled with the `test-utils` feature. [`MockTransport`] plugs into
+//! `ClientBuilder::transport`, answers requests from canned responses and
+//! records everything it receives:
+//!
+//! ```
+//! use std::sync::Arc;
+//! use tencentcloud_hunyuan_sdk::test_utils::{fixtures, MockTransport};
+//! use tencentcloud_hunyuan_sdk::{ClientBuilder, Credential};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mock = Arc::new(
+//!     MockTransport::new()
+//!         .expect_action("ChatCompletions")
+//!         .respond_status(500, "upstream unavailable")
+//!         .respond_json(200, &fixtures::chat_completions_response("Hi!")),
+//! );
+//! let client = ClientBuilder::new()
+//!     .credential(Credential { secret_id: "id".into(), secret_key: "key".into(), token: None })
+//!     .transport(mock.clone())
+//!     .build();
+//!
+//! let req = fixtures::chat_request("Hello");
+//! assert!(client.chat_completions(&req).await.is_err());
+//! let resp = client.chat_completions(&req).await.unwrap();
+//! assert_eq!(resp.response.choices.unwrap()[0].message.as_ref().unwrap().content.as_deref(), Some("Hi!"));
+//! mock.assert_called("ChatCompletions", 2);
+//! # }
+//! ```
+pub mod cassette;
+
+use crate::api::HunyuanApi;
+use crate::client::{SdkError, SdkResult};
+use crate::models::{
+    ChatCompletionsRequest, ChatCompletionsResponse, GetEmbeddingRequest, GetEmbeddingResponse,
+};
+use crate::stream::ChatCompletionsStream;
+use crate::transport::{BoxFuture, PreparedRequest, RawResponse, Transport};
+use bytes::Bytes;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// A canned response served by [`MockTransport`].
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Response headers.
+    pub headers: HeaderMap,
+    /// Response body.
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// A JSON response with the given status.
+    pub fn json(status: u16, body: &impl Serialize) -> Self {
+        Self::with_content_type(
+            status,
+            "application/json",
+            serde_json::to_vec(body).expect("serializable mock body"),
+        )
+    }
+
+    /// A response with the given status, content type and raw body.
+    pub fn with_content_type(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            HeaderValue::from_str(content_type).expect("valid content type"),
+        );
+        Self {
+            status,
+            headers,
+            body: body.into(),
+        }
+    }
+}
+
+/// Failure modes that replace a response, queued with
+/// [`MockTransport::respond_fault`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fault {
+    /// The connection drops before any response:
+    /// the call sees [`SdkError::Transport`].
+    ConnectionReset,
+    /// HTTP 200 with a `application/json` body that is not valid JSON.
+    MalformedJson,
+    /// HTTP 429 with `Retry-After: retry_after_secs` and a
+    /// `RequestLimitExceeded` error envelope.
+    RateLimited {
+        /// Value of the `Retry-After` header, in seconds.
+        retry_after_secs: u64,
+    },
+}
+
+/// One queued reply: a response (or none, for a connection reset) plus the
+/// faults applied while serving it.
+#[derive(Debug, Clone)]
+struct Reply {
+    response: Option<MockResponse>,
+    latency: Option<Duration>,
+    truncate_after: Option<usize>,
+    cut_sse_after: Option<usize>,
+}
+
+impl From<MockResponse> for Reply {
+    fn from(response: MockResponse) -> Self {
+        Self {
+            response: Some(response),
+            latency: None,
+            truncate_after: None,
+            cut_sse_after: None,
+        }
+    }
+}
+
+impl Reply {
+    /// Turns the reply into a transport result, applying its faults.
+    async fn serve(self) -> SdkResult<RawResponse> {
+        if let Some(latency) = self.latency {
+            tokio::time::sleep(latency).await;
+        }
+        let response = self.response.ok_or_else(|| {
+            SdkError::Transport("connection reset by peer (injected)".to_string())
+        })?;
+        let cut = self
+            .truncate_after
+            .or_else(|| {
+                self.cut_sse_after
+                    .map(|k| sse_prefix_len(&response.body, k))
+            })
+            .filter(|&n| n < response.body.len());
+        let Some(n) = cut else {
+            return Ok(RawResponse::from_bytes(
+                response.status,
+                response.headers,
+                response.body,
+            ));
+        };
+        let chunks: Vec<SdkResult<Bytes>> = vec![
+            Ok(Bytes::copy_from_slice(&response.body[..n])),
+            Err(SdkError::Transport(format!(
+                "connection closed after {} body bytes (injected)",
+                n
+            ))),
+        ];
+        Ok(RawResponse {
+            status: response.status,
+            headers: response.headers,
+            body: Box::pin(futures_util::stream::iter(chunks)),
+        })
+    }
+}
+
+/// Length of the first `events` SSE events of `body`, including their
+/// terminating blank lines.
+fn sse_prefix_len(body: &[u8], events: usize) -> usize {
+    let mut end = 0;
+    for _ in 0..events {
+        match body[end..].windows(2).position(|w| w == b"\n\n") {
+            Some(pos) => end += pos + 2,
+            None => return body.len(),
+        }
+    }
+    end
+}
+
+/// One expected request and the replies to serve for it.
+#[derive(Debug)]
+struct Expectation {
+    action: String,
+    headers: Vec<(String, String)>,
+    body: Option<serde_json::Value>,
+    responses: VecDeque<Reply>,
+}
+
+impl Expectation {
+    fn matches(&self, req: &PreparedRequest) -> bool {
+        if req.action() != Some(self.action.as_str()) {
+            return false;
+        }
+        if !self
+            .headers
+            .iter()
+            .all(|(name, value)| req.header(name) == Some(value.as_str()))
+        {
+            return false;
+        }
+        match &self.body {
+            Some(expected) => serde_json::from_slice::<serde_json::Value>(&req.body)
+                .map(|actual| json_contains(&actual, expected))
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
+    /// Serves replies in order; the last one repeats for further calls.
+    fn next_response(&mut self) -> Option<Reply> {
+        if self.responses.len() > 1 {
+            self.responses.pop_front()
+        } else {
+            self.responses.front().cloned()
+        }
+    }
+}
+
+/// Whether `actual` contains everything in `expected`: objects may carry
+/// extra keys, arrays must match element-wise, other values must be equal.
+fn json_contains(actual: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    use serde_json::Value;
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(k, v)| actual.get(k).is_some_and(|a| json_contains(a, v))),
+        (Value::Array(actual), Value::Array(expected)) => {
+            actual.len() == expected.len()
+                && actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(a, e)| json_contains(a, e))
+        }
+        _ => actual == expected,
+    }
+}
+
+/// [`Transport`] serving canned responses per action.
+///
+/// Expectations are declared fluently: `expect_action` starts one, and the
+/// following `match_*`/`respond_*` calls apply to it. Requests are matched
+/// against expectations in declaration order. Responses of an expectation are
+/// served in sequence, with the last one repeating. Requests matching no
+/// expectation get a 404 with a Tencent Cloud style error body.
+///
+/// Faults are injected per reply: [`respond_fault`](Self::respond_fault)
+/// queues a failure in place of a response, and `with_latency`,
+/// `truncate_body` and `cut_sse_after` alter the reply queued last. Follow a
+/// fault with a regular response to test recovery:
+///
+/// ```
+/// use std::time::Duration;
+/// use tencentcloud_hunyuan_sdk::test_utils::{fixtures, Fault, MockTransport};
+///
+/// let mock = MockTransport::new()
+///     .expect_action("ChatCompletions")
+///     .respond_fault(Fault::ConnectionReset)
+///     .respond_fault(Fault::RateLimited { retry_after_secs: 1 })
+///     .respond_json(200, &fixtures::chat_completions_response("ok"))
+///     .with_latency(Duration::from_millis(20));
+/// ```
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    expectations: Mutex<Vec<Expectation>>,
+    requests: Mutex<Vec<PreparedRequest>>,
+}
+
+impl MockTransport {
+    /// Creates a mock without expectations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new expectation for requests with `X-TC-Action: action`.
+    pub fn expect_action(self, action: impl Into<String>) -> Self {
+        self.expectations.lock().unwrap().push(Expectation {
+            action: action.into(),
+            headers: Vec::new(),
+            body: None,
+            responses: VecDeque::new(),
+        });
+        self
+    }
+
+    fn update_last(self, f: impl FnOnce(&mut Expectation)) -> Self {
+        {
+            let mut expectations = self.expectations.lock().unwrap();
+            let last = expectations
+                .last_mut()
+                .expect("call expect_action before configuring an expectation");
+            f(last);
+        }
+        self
+    }
+
+    /// Only match requests carrying header `name` with exactly `value`.
+    pub fn match_header(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let (name, value) = (name.into(), value.into());
+        self.update_last(|e| e.headers.push((name, value)))
+    }
+
+    /// Only match requests whose JSON body contains `body`. Objects in the
+    /// request may carry extra keys; arrays must have the same length.
+    pub fn match_body_json(self, body: &impl Serialize) -> Self {
+        let body = serde_json::to_value(body).expect("serializable body matcher");
+        self.update_last(|e| e.body = Some(body))
+    }
+
+    /// Queues a response for the current expectation.
+    pub fn respond(self, response: MockResponse) -> Self {
+        self.update_last(|e| e.responses.push_back(response.into()))
+    }
+
+    /// Queues a failure in place of a response for the current expectation.
+    pub fn respond_fault(self, fault: Fault) -> Self {
+        match fault {
+            Fault::ConnectionReset => self.update_last(|e| {
+                e.responses.push_back(Reply {
+                    response: None,
+                    latency: None,
+                    truncate_after: None,
+                    cut_sse_after: None,
+                })
+            }),
+            Fault::MalformedJson => self.respond(MockResponse::with_content_type(
+                200,
+                "application/json",
+                r#"{"Response":{"RequestId":"mock-request-id","#,
+            )),
+            Fault::RateLimited { retry_after_secs } => {
+                let mut response = MockResponse::json(
+                    429,
+                    &fixtures::error_response("RequestLimitExceeded", "rate limited (injected)"),
+                );
+                response.headers.insert(
+                    reqwest::header::RETRY_AFTER,
+                    HeaderValue::from(retry_after_secs),
+                );
+                self.respond(response)
+            }
+        }
+    }
+
+    fn update_last_reply(self, f: impl FnOnce(&mut Reply)) -> Self {
+        self.update_last(|e| {
+            f(e.responses
+                .back_mut()
+                .expect("queue a response before applying a fault to it"))
+        })
+    }
+
+    /// Delays the reply queued last by `latency`.
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.update_last_reply(|r| r.latency = Some(latency))
+    }
+
+    /// Cuts the body of the reply queued last after `bytes` bytes; reading
+    /// further fails with [`SdkError::Transport`].
+    pub fn truncate_body(self, bytes: usize) -> Self {
+        self.update_last_reply(|r| r.truncate_after = Some(bytes))
+    }
+
+    /// Cuts the SSE body of the reply queued last after `events` events;
+    /// the stream then fails with [`SdkError::Transport`].
+    pub fn cut_sse_after(self, events: usize) -> Self {
+        self.update_last_reply(|r| r.cut_sse_after = Some(events))
+    }
+
+    /// Queues a JSON response for the current expectation.
+    pub fn respond_json(self, status: u16, body: &impl Serialize) -> Self {
+        self.respond(MockResponse::json(status, body))
+    }
+
+    /// Queues a plain-text response for the current expectation.
+    pub fn respond_status(self, status: u16, body: impl Into<Vec<u8>>) -> Self {
+        self.respond(MockResponse::with_content_type(status, "text/plain", body))
+    }
+
+    /// Queues a Tencent Cloud error envelope for the current expectation.
+    pub fn respond_error(self, status: u16, code: &str, message: &str) -> Self {
+        self.respond_json(status, &fixtures::error_response(code, message))
+    }
+
+    /// Queues an SSE response for the current expectation. Each item of
+    /// `events` is sent as one `data:` event.
+    pub fn respond_sse<I, T>(self, events: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let body: String = events
+            .into_iter()
+            .map(|e| format!("data: {}\n\n", e.as_ref()))
+            .collect();
+        self.respond(MockResponse::with_content_type(
+            200,
+            "text/event-stream",
+            body,
+        ))
+    }
+
+    /// Returns every request received so far, in order.
+    pub fn requests(&self) -> Vec<PreparedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the requests received for `action`, in order.
+    pub fn requests_for(&self, action: &str) -> Vec<PreparedRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|r| r.action() == Some(action))
+            .collect()
+    }
+
+    /// Panics unless exactly `times` requests were received for `action`.
+    pub fn assert_called(&self, action: &str, times: usize) {
+        let actual = self.requests_for(action).len();
+        assert_eq!(
+            actual, times,
+            "expected {} request(s) for {}, got {}",
+            times, action, actual
+        );
+    }
+
+    fn respond_to(&self, req: &PreparedRequest) -> Reply {
+        let mut expectations = self.expectations.lock().unwrap();
+        expectations
+            .iter_mut()
+            .find(|e| e.matches(req))
+            .and_then(Expectation::next_response)
+            .unwrap_or_else(|| {
+                MockResponse::json(
+                    404,
+                    &fixtures::error_response(
+                        "MockTransport.NoMatchingExpectation",
+                        &format!("no expectation matched action {:?}", req.action()),
+                    ),
+                )
+                .into()
+            })
+    }
+}
+
+impl Transport for MockTransport {
+    fn execute(&self, req: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>> {
+        let reply = self.respond_to(&req);
+        self.requests.lock().unwrap().push(req);
+        Box::pin(reply.serve())
+    }
+}
+
+/// Embedding returned by [`FakeHunyuan`] for every input.
+const FAKE_EMBEDDING: [f64; 4] = [1.0, 0.0, 0.0, 0.0];
+
+/// In-memory [`HunyuanApi`] that answers every chat request with the same
+/// assistant message and records the chat requests it receives. Embedding
+/// requests get one fixed unit vector per input.
+///
+/// Use it where application code takes `Arc<dyn HunyuanApi>`; no client,
+/// signing or transport is involved.
+#[derive(Debug)]
+pub struct FakeHunyuan {
+    reply: String,
+    requests: Mutex<Vec<ChatCompletionsRequest>>,
+}
+
+impl FakeHunyuan {
+    /// Creates a fake whose assistant message is always `reply`.
+    pub fn new(reply: impl Into<String>) -> Self {
+        Self {
+            reply: reply.into(),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns every request received so far, in order.
+    pub fn requests(&self) -> Vec<ChatCompletionsRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl HunyuanApi for FakeHunyuan {
+    fn chat_completions<'a>(
+        &'a self,
+        req: &'a ChatCompletionsRequest,
+    ) -> BoxFuture<'a, SdkResult<ChatCompletionsResponse>> {
+        self.requests.lock().unwrap().push(req.clone());
+        let resp = fixtures::chat_completions_response(&self.reply);
+        Box::pin(async move { Ok(resp) })
+    }
+
+    fn chat_completions_stream<'a>(
+        &'a self,
+        req: &'a ChatCompletionsRequest,
+    ) -> BoxFuture<'a, SdkResult<ChatCompletionsStream>> {
+        self.requests.lock().unwrap().push(req.clone());
+        let body = fixtures::ChunkSequence::from_pieces([self.reply.as_str()]).sse_body();
+        let body = RawResponse::from_bytes(200, HeaderMap::new(), body).body;
+        Box::pin(async move { Ok(ChatCompletionsStream::new(body, Box::new(|| {}))) })
+    }
+
+    fn get_embedding<'a>(
+        &'a self,
+        req: &'a GetEmbeddingRequest,
+    ) -> BoxFuture<'a, SdkResult<GetEmbeddingResponse>> {
+        let inputs = req
+            .input_list
+            .as_ref()
+            .map_or(usize::from(req.input.is_some()), Vec::len);
+        let resp = (0..inputs)
+            .fold(fixtures::EmbeddingResponseFixture::new(), |f, _| {
+                f.embedding(FAKE_EMBEDDING.to_vec())
+            })
+            .build();
+        Box::pin(async move { Ok(resp) })
+    }
+}
+
+/// Ready-made requests and responses for tests.
+///
+/// The builders fill in every layer of the Tencent Cloud envelope so tests
+/// only spell out what they care about:
+///
+/// ```
+/// use tencentcloud_hunyuan_sdk::models::FinishReason;
+/// use tencentcloud_hunyuan_sdk::test_utils::fixtures::{
+///     ChatResponseFixture, ChunkSequence, EmbeddingResponseFixture, ErrorResponseFixture,
+/// };
+///
+/// let resp = ChatResponseFixture::new()
+///     .content("hi")
+///     .finish_reason(FinishReason::Stop)
+///     .usage(10, 5)
+///     .build();
+/// assert_eq!(resp.response.usage.unwrap().total_tokens, Some(15));
+///
+/// // "hello world" in 4-character chunks, for `MockTransport::respond_sse`.
+/// let events = ChunkSequence::from_text("hello world", 4).events();
+/// assert_eq!(events.len(), 3);
+///
+/// let err = ErrorResponseFixture::new("RequestLimitExceeded").message("slow down").build();
+/// assert_eq!(err["Response"]["Error"]["Code"], "RequestLimitExceeded");
+///
+/// let emb = EmbeddingResponseFixture::new().embedding(vec![0.1, 0.2]).build();
+/// assert_eq!(emb.response.data.unwrap()[0].embedding.as_deref(), Some(&[0.1, 0.2][..]));
+/// ```
+pub mod fixtures {
+    use crate::models::{
+        ChatChoice, ChatChoiceDelta, ChatChoiceMessage, ChatChunkChoice, ChatCompletionsChunk,
+        ChatCompletionsRequest, ChatCompletionsResponse, ChatCompletionsResponseInner,
+        EmbeddingData, EmbeddingUsage, FinishReason, GetEmbeddingResponse,
+        GetEmbeddingResponseInner, Message, TencentCloudResponse, Usage,
+    };
+
+    const REQUEST_ID: &str = "mock-request-id";
+    const ID: &str = "mock-id";
+
+    /// A single-message user request for `hunyuan-lite`.
+    pub fn chat_request(content: &str) -> ChatCompletionsRequest {
+        ChatCompletionsRequest {
+            model: Some("hunyuan-lite".to_string()),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: content.to_string(),
+                ..Default::default()
+            }],
+            temperature: None,
+            top_p: None,
+            stream: None,
+            tools: None,
+            n: None,
+            seed: None,
+        }
+    }
+
+    /// A successful one-choice response whose assistant message is `content`.
+    pub fn chat_completions_response(content: &str) -> ChatCompletionsResponse {
+        ChatResponseFixture::new()
+            .content(content)
+            .usage(1, 1)
+            .build()
+    }
+
+    /// A Tencent Cloud error envelope.
+    pub fn error_response(code: &str, message: &str) -> serde_json::Value {
+        ErrorResponseFixture::new(code).message(message).build()
+    }
+
+    /// Builder for a one-choice [`ChatCompletionsResponse`].
+    ///
+    /// Defaults: request id `mock-request-id`, id `mock-id`, role
+    /// `assistant`, empty content, finish reason `stop`, no usage.
+    #[derive(Debug, Clone)]
+    pub struct ChatResponseFixture {
+        request_id: String,
+        id: String,
+        content: String,
+        finish_reason: Option<FinishReason>,
+        usage: Option<(u32, u32)>,
+    }
+
+    impl Default for ChatResponseFixture {
+        fn default() -> Self {
+            Self {
+                request_id: REQUEST_ID.to_string(),
+                id: ID.to_string(),
+                content: String::new(),
+                finish_reason: Some(FinishReason::Stop),
+                usage: None,
+            }
+        }
+    }
+
+    impl ChatResponseFixture {
+        /// Starts from the defaults.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Sets `RequestId`.
+        pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+            self.request_id = request_id.into();
+            self
+        }
+
+        /// Sets `Id`.
+        pub fn id(mut self, id: impl Into<String>) -> Self {
+            self.id = id.into();
+            self
+        }
+
+        /// Sets the assistant message.
+        pub fn content(mut self, content: impl Into<String>) -> Self {
+            self.content = content.into();
+            self
+        }
+
+        /// Sets `FinishReason`.
+        pub fn finish_reason(mut self, reason: FinishReason) -> Self {
+            self.finish_reason = Some(reason);
+            self
+        }
+
+        /// Sets `Usage`; the total is the sum of both counts.
+        pub fn usage(mut self, prompt_tokens: u32, completion_tokens: u32) -> Self {
+            self.usage = Some((prompt_tokens, completion_tokens));
+            self
+        }
+
+        /// Builds the full response envelope.
+        pub fn build(self) -> ChatCompletionsResponse {
+            TencentCloudResponse {
+                response: ChatCompletionsResponseInner {
+                    request_id: Some(self.request_id),
+                    id: Some(self.id),
+                    choices: Some(vec![ChatChoice {
+                        index: Some(0),
+                        message: Some(ChatChoiceMessage {
+                            role: Some("assistant".to_string()),
+                            content: Some(self.content),
+                            ..Default::default()
+                        }),
+                        finish_reason: self.finish_reason.map(|r| r.as_str().to_string()),
+                        ..Default::default()
+                    }]),
+                    usage: self.usage.map(usage),
+                    ..Default::default()
+                },
+            }
+        }
+    }
+
+    fn usage((prompt, completion): (u32, u32)) -> Usage {
+        Usage {
+            prompt_tokens: Some(prompt),
+            completion_tokens: Some(completion),
+            total_tokens: Some(prompt + completion),
+            ..Default::default()
+        }
+    }
+
+    /// Builder for the chunks of a streamed response that spells out a text.
+    ///
+    /// The first chunk carries the `assistant` role; the last one carries the
+    /// finish reason (default `stop`) and, if set, usage.
+    #[derive(Debug, Clone)]
+    pub struct ChunkSequence {
+        id: String,
+        pieces: Vec<String>,
+        finish_reason: Option<FinishReason>,
+        usage: Option<(u32, u32)>,
+    }
+
+    impl ChunkSequence {
+        /// Splits `text` into pieces of at most `chunk_size` characters.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `chunk_size` is zero.
+        pub fn from_text(text: &str, chunk_size: usize) -> Self {
+            assert!(chunk_size > 0, "chunk_size must be positive");
+            let chars: Vec<char> = text.chars().collect();
+            Self::from_pieces(
+                chars
+                    .chunks(chunk_size)
+                    .map(|c| c.iter().collect::<String>()),
+            )
+        }
+
+        /// Uses each item of `pieces` as the delta of one chunk.
+        pub fn from_pieces<I, T>(pieces: I) -> Self
+        where
+            I: IntoIterator<Item = T>,
+            T: Into<String>,
+        {
+            Self {
+                id: ID.to_string(),
+                pieces: pieces.into_iter().map(Into::into).collect(),
+                finish_reason: Some(FinishReason::Stop),
+                usage: None,
+            }
+        }
+
+        /// Sets the `FinishReason` of the last chunk.
+        pub fn finish_reason(mut self, reason: FinishReason) -> Self {
+            self.finish_reason = Some(reason);
+            self
+        }
+
+        /// Sets `Usage` on the last chunk.
+        pub fn usage(mut self, prompt_tokens: u32, completion_tokens: u32) -> Self {
+            self.usage = Some((prompt_tokens, completion_tokens));
+            self
+        }
+
+        /// Builds the chunks.
+        pub fn chunks(&self) -> Vec<ChatCompletionsChunk> {
+            let last = self.pieces.len().saturating_sub(1);
+            self.pieces
+                .iter()
+                .enumerate()
+                .map(|(i, piece)| ChatCompletionsChunk {
+                    id: Some(self.id.clone()),
+                    created: None,
+                    choices: Some(vec![ChatChunkChoice {
+                        index: Some(0),
+                        delta: Some(ChatChoiceDelta {
+                            role: (i == 0).then(|| "assistant".to_string()),
+                            content: Some(piece.clone()),
+                            ..Default::default()
+                        }),
+                        finish_reason: (i == last)
+                            .then_some(self.finish_reason)
+                            .flatten()
+                            .map(|r| r.as_str().to_string()),
+                        ..Default::default()
+                    }]),
+                    usage: (i == last).then_some(self.usage).flatten().map(usage),
+                    ..Default::default()
+                })
+                .collect()
+        }
+
+        /// Builds the chunks as JSON event payloads, ready for
+        /// [`MockTransport::respond_sse`](super::MockTransport::respond_sse).
+        pub fn events(&self) -> Vec<String> {
+            self.chunks()
+                .iter()
+                .map(|c| serde_json::to_string(c).expect("serializable chunk"))
+                .collect()
+        }
+
+        /// Builds a complete SSE body, terminated by `data: [DONE]`.
+        pub fn sse_body(&self) -> String {
+            let mut body: String = self
+                .events()
+                .iter()
+                .map(|e| format!("data: {}\n\n", e))
+                .collect();
+            body.push_str("data: [DONE]\n\n");
+            body
+        }
+    }
+
+    /// Builder for a Tencent Cloud error envelope.
+    #[derive(Debug, Clone)]
+    pub struct ErrorResponseFixture {
+        code: String,
+        message: String,
+        request_id: String,
+    }
+
+    impl ErrorResponseFixture {
+        /// Starts an error with `code`, an empty message and request id
+        /// `mock-request-id`.
+        pub fn new(code: impl Into<String>) -> Self {
+            Self {
+                code: code.into(),
+                message: String::new(),
+                request_id: REQUEST_ID.to_string(),
+            }
+        }
+
+        /// Sets `Message`.
+        pub fn message(mut self, message: impl Into<String>) -> Self {
+            self.message = message.into();
+            self
+        }
+
+        /// Sets `RequestId`.
+        pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+            self.request_id = request_id.into();
+            self
+        }
+
+        /// Builds the envelope.
+        pub fn build(self) -> serde_json::Value {
+            serde_json::json!({
+                "Response": {
+                    "Error": { "Code": self.code, "Message": self.message },
+                    "RequestId": self.request_id
+                }
+            })
+        }
+    }
+
+    /// Builder for a [`GetEmbeddingResponse`].
+    #[derive(Debug, Clone)]
+    pub struct EmbeddingResponseFixture {
+        request_id: String,
+        embeddings: Vec<Vec<f64>>,
+        prompt_tokens: Option<u32>,
+    }
+
+    impl Default for EmbeddingResponseFixture {
+        fn default() -> Self {
+            Self {
+                request_id: REQUEST_ID.to_string(),
+                embeddings: Vec::new(),
+                prompt_tokens: None,
+            }
+        }
+    }
+
+    impl EmbeddingResponseFixture {
+        /// Starts with no embeddings and no usage.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Sets `RequestId`.
+        pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+            self.request_id = request_id.into();
+            self
+        }
+
+        /// Appends one embedding; indices follow insertion order.
+        pub fn embedding(mut self, embedding: Vec<f64>) -> Self {
+            self.embeddings.push(embedding);
+            self
+        }
+
+        /// Sets `Usage` with `prompt_tokens` as both prompt and total count.
+        pub fn usage(mut self, prompt_tokens: u32) -> Self {
+            self.prompt_tokens = Some(prompt_tokens);
+            self
+        }
+
+        /// Builds the full response envelope.
+        pub fn build(self) -> GetEmbeddingResponse {
+            TencentCloudResponse {
+                response: GetEmbeddingResponseInner {
+                    request_id: Some(self.request_id),
+                    data: Some(
+                        self.embeddings
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, embedding)| EmbeddingData {
+                                embedding: Some(embedding),
+                                index: Some(i as u32),
+                                object: Some("embedding".to_string()),
+                                ..Default::default()
+                            })
+                            .collect(),
+                    ),
+                    usage: self.prompt_tokens.map(|tokens| EmbeddingUsage {
+                        prompt_tokens: Some(tokens),
+                        total_tokens: Some(tokens),
+                    }),
+                    ..Default::default()
+                },
+            }
+        }
+    }
+}
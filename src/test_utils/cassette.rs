@@ -0,0 +1,224 @@
+//! Recording real interactions once and replaying them in later runs.
+//!
+//! [`CassetteTransport`] sits in front of a real transport. While recording,
+//! it stores every request and response in a JSON cassette file; while
+//! replaying, it answers from that file without any network access or
+//! credentials:
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use tencentcloud_hunyuan_sdk::test_utils::cassette::CassetteTransport;
+//! use tencentcloud_hunyuan_sdk::transport::ReqwestTransport;
+//! use tencentcloud_hunyuan_sdk::{ClientBuilder, Credential};
+//!
+//! // Records on the first run, replays once the file exists.
+//! let transport = CassetteTransport::auto(
+//!     "tests/cassettes/chat.json",
+//!     Arc::new(ReqwestTransport::new(reqwest::Client::new())),
+//! )?;
+//! let client = ClientBuilder::new()
+//!     .credential(Credential {
+//!         secret_id: std::env::var("TENCENTCLOUD_SECRET_ID").unwrap_or_default(),
+//!         secret_key: std::env::var("TENCENTCLOUD_SECRET_KEY").unwrap_or_default(),
+//!         token: None,
+//!     })
+//!     .transport(Arc::new(transport))
+//!     .build();
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Requests are matched on method, URL, action and body. Headers are left
+//! out, so timestamps, signatures and tokens differ freely between runs and
+//! are never written to the cassette. Each recorded interaction is replayed
+//! once, in order, so a request sent twice needs two recordings. Bodies are
+//! stored as text; streamed responses are recorded whole and replayed as a
+//! single chunk.
+use crate::client::{SdkError, SdkResult};
+use crate::transport::{BoxFuture, PreparedRequest, RawResponse, Transport};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// The part of a request that identifies it in a cassette.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    /// `X-TC-Action` header, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    fn of(req: &PreparedRequest) -> Self {
+        Self {
+            method: req.method.clone(),
+            url: req.url.clone(),
+            action: req.action().map(str::to_string),
+            body: String::from_utf8_lossy(&req.body).into_owned(),
+        }
+    }
+}
+
+/// A recorded response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+/// A request and the response it got.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+enum Mode {
+    Record(Arc<dyn Transport>),
+    Replay { used: Vec<bool> },
+}
+
+/// A [`Transport`] recording to or replaying from a cassette file; see the
+/// [module docs](self).
+pub struct CassetteTransport {
+    path: PathBuf,
+    state: Mutex<(Mode, Cassette)>,
+}
+
+impl CassetteTransport {
+    /// Sends requests through `inner` and records them to `path`, replacing
+    /// any earlier recording. The file is rewritten after every response.
+    pub fn record(path: impl Into<PathBuf>, inner: Arc<dyn Transport>) -> Self {
+        Self {
+            path: path.into(),
+            state: Mutex::new((Mode::Record(inner), Cassette::default())),
+        }
+    }
+
+    /// Answers requests from the cassette at `path`. Requests without an
+    /// unused matching recording fail with [`SdkError::Transport`].
+    pub fn replay(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let cassette: Cassette = serde_json::from_slice(&std::fs::read(&path)?)?;
+        let used = vec![false; cassette.interactions.len()];
+        Ok(Self {
+            path,
+            state: Mutex::new((Mode::Replay { used }, cassette)),
+        })
+    }
+
+    /// Replays `path` if it exists and records to it through `inner`
+    /// otherwise.
+    pub fn auto(path: impl Into<PathBuf>, inner: Arc<dyn Transport>) -> io::Result<Self> {
+        let path = path.into();
+        if path.exists() {
+            Self::replay(path)
+        } else {
+            Ok(Self::record(path, inner))
+        }
+    }
+
+    /// Whether requests are sent and recorded rather than replayed.
+    pub fn is_recording(&self) -> bool {
+        matches!(self.state.lock().unwrap().0, Mode::Record(_))
+    }
+
+    /// Path of the cassette file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The interactions recorded or loaded so far.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state.lock().unwrap().1.interactions.clone()
+    }
+
+    fn replay_one(&self, request: &RecordedRequest) -> SdkResult<RawResponse> {
+        let mut state = self.state.lock().unwrap();
+        let (Mode::Replay { used }, cassette) = &mut *state else {
+            unreachable!("replay_one is only called while replaying");
+        };
+        let found = cassette
+            .interactions
+            .iter()
+            .zip(used.iter_mut())
+            .find(|(i, used)| !**used && i.request == *request);
+        let Some((interaction, used)) = found else {
+            return Err(SdkError::Transport(format!(
+                "no unused recording in {} for {} {} action={:?} body={}",
+                self.path.display(),
+                request.method,
+                request.url,
+                request.action,
+                request.body
+            )));
+        };
+        *used = true;
+        let response = &interaction.response;
+        let mut headers = HeaderMap::new();
+        if let Some(content_type) = &response.content_type {
+            let value = HeaderValue::from_str(content_type)
+                .map_err(|e| SdkError::Transport(format!("recorded content type: {}", e)))?;
+            headers.insert(CONTENT_TYPE, value);
+        }
+        Ok(RawResponse::from_bytes(
+            response.status,
+            headers,
+            response.body.clone().into_bytes(),
+        ))
+    }
+
+    fn save(&self, interaction: Interaction) -> SdkResult<()> {
+        let mut state = self.state.lock().unwrap();
+        state.1.interactions.push(interaction);
+        let json = serde_json::to_vec_pretty(&state.1)?;
+        std::fs::write(&self.path, json).map_err(|e| {
+            SdkError::Transport(format!(
+                "cannot write cassette {}: {}",
+                self.path.display(),
+                e
+            ))
+        })
+    }
+}
+
+impl Transport for CassetteTransport {
+    fn execute(&self, req: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>> {
+        Box::pin(async move {
+            let request = RecordedRequest::of(&req);
+            let inner = match &self.state.lock().unwrap().0 {
+                Mode::Record(inner) => Some(inner.clone()),
+                Mode::Replay { .. } => None,
+            };
+            let Some(inner) = inner else {
+                return self.replay_one(&request);
+            };
+            let resp = inner.execute(req).await?;
+            let (status, headers) = (resp.status, resp.headers.clone());
+            let body = resp.into_bytes().await?;
+            self.save(Interaction {
+                request,
+                response: RecordedResponse {
+                    status,
+                    content_type: headers
+                        .get(CONTENT_TYPE)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string),
+                    body: String::from_utf8_lossy(&body).into_owned(),
+                },
+            })?;
+            Ok(RawResponse::from_bytes(status, headers, body))
+        })
+    }
+}
@@ -0,0 +1,156 @@
+//! Local functions the model can call.
+//!
+//! Register each function with its schema in a [`ToolRegistry`], then let
+//! [`Client::run_with_tools`](crate::Client::run_with_tools) drive the
+//! conversation: it offers the tools to the model, runs the calls the model
+//! makes, sends back their results and repeats until the model answers.
+//!
+//! ```no_run
+//! use serde_json::json;
+//! use tencentcloud_hunyuan_sdk::models::{Message, Role};
+//! use tencentcloud_hunyuan_sdk::tools::{RunOptions, ToolRegistry};
+//! # async fn run(client: tencentcloud_hunyuan_sdk::Client) -> tencentcloud_hunyuan_sdk::SdkResult<()> {
+//!
+//! let mut tools = ToolRegistry::new();
+//! tools.register(
+//!     "get_weather",
+//!     "Current weather of a city",
+//!     json!({
+//!         "type": "object",
+//!         "properties": {"city": {"type": "string"}},
+//!         "required": ["city"],
+//!     }),
+//!     |args| async move {
+//!         let city = args["city"].as_str().ok_or("city must be a string")?;
+//!         Ok::<_, &str>(json!({"city": city, "sky": "sunny"}))
+//!     },
+//! );
+//! let mut messages = vec![Message::new(Role::User, "How is the weather in Shenzhen?")];
+//! let options = RunOptions {
+//!     model: Some("hunyuan-functioncall".into()),
+//!     ..Default::default()
+//! };
+//! let resp = client.run_with_tools(&mut messages, &tools, options).await?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::models::{Tool, ToolCall};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+type Handler = Arc<dyn Fn(Value) -> BoxFuture<'static, Result<Value, String>> + Send + Sync>;
+
+/// Functions offered to the model, with the handlers that run them.
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<(Tool, Handler)>,
+}
+
+impl fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.tools.iter().map(|(tool, _)| &tool.function.name))
+            .finish()
+    }
+}
+
+impl ToolRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the function `name`, replacing one of the same name.
+    ///
+    /// `parameters` is the JSON Schema of the arguments. `handler` receives
+    /// the arguments the model generated; its result is sent back to the
+    /// model as JSON, or as is if it is a string. An error is sent back as
+    /// `{"error": "<message>"}` so the model can react to it.
+    pub fn register<F, Fut, E>(
+        &mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: Value,
+        handler: F,
+    ) -> &mut Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value, E>> + Send + 'static,
+        E: fmt::Display,
+    {
+        let tool = Tool::function(name, description, &parameters);
+        let handler: Handler = Arc::new(move |args| {
+            handler(args)
+                .map(|result| result.map_err(|e| e.to_string()))
+                .boxed()
+        });
+        self.tools
+            .retain(|(t, _)| t.function.name != tool.function.name);
+        self.tools.push((tool, handler));
+        self
+    }
+
+    /// Definitions of the registered functions, in registration order, for
+    /// [`ChatCompletionsRequest::tools`](crate::models::ChatCompletionsRequest::tools).
+    pub fn tools(&self) -> Vec<Tool> {
+        self.tools.iter().map(|(tool, _)| tool.clone()).collect()
+    }
+
+    /// Whether no function is registered.
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Runs `call` and returns the content of its tool message. Unknown
+    /// functions, arguments that are not JSON and handler errors are
+    /// reported as `{"error": "<message>"}`.
+    pub async fn call(&self, call: &ToolCall) -> String {
+        let name = &call.function.name;
+        let Some((_, handler)) = self.tools.iter().find(|(t, _)| &t.function.name == name) else {
+            return error_content(format!("unknown tool {:?}", name));
+        };
+        let arguments = call.function.arguments.trim();
+        let args = if arguments.is_empty() {
+            Ok(Value::Object(Default::default()))
+        } else {
+            serde_json::from_str(arguments)
+        };
+        let result = match args {
+            Ok(args) => handler(args).await,
+            Err(e) => Err(format!("arguments are not valid JSON: {}", e)),
+        };
+        match result {
+            Ok(Value::String(text)) => text,
+            Ok(value) => value.to_string(),
+            Err(message) => error_content(message),
+        }
+    }
+}
+
+fn error_content(message: String) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Settings of [`Client::run_with_tools`](crate::Client::run_with_tools).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Model for every call; the client's default model when unset. Tool
+    /// calling needs a model that supports it, e.g. `hunyuan-functioncall`.
+    pub model: Option<String>,
+    /// Maximum number of replies with tool calls to answer before giving up.
+    pub max_rounds: usize,
+}
+
+impl Default for RunOptions {
+    /// The client's default model, up to 8 rounds.
+    fn default() -> Self {
+        Self {
+            model: None,
+            max_rounds: 8,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! W3C Trace Context propagation.
+//!
+//! A call carries `traceparent` (and `tracestate`, if any) when a
+//! [`TraceContext`] is set with `RequestOptions::trace_context`, or else when
+//! the [`TraceContextProvider`] set with `ClientBuilder::trace_context_provider`
+//! returns one. The headers are sent but not signed.
+//!
+//! To join OpenTelemetry traces, implement the provider by reading the current
+//! span context:
+//!
+//! ```ignore
+//! use opentelemetry::trace::TraceContextExt;
+//! use tencentcloud_hunyuan_sdk::trace::{TraceContext, TraceContextProvider};
+//!
+//! struct OtelContext;
+//!
+//! impl TraceContextProvider for OtelContext {
+//!     fn trace_context(&self) -> Option<TraceContext> {
+//!         let context = opentelemetry::Context::current();
+//!         let span = context.span().span_context().clone();
+//!         span.is_valid().then(|| {
+//!             TraceContext::new(
+//!                 u128::from_be_bytes(span.trace_id().to_bytes()),
+//!                 u64::from_be_bytes(span.span_id().to_bytes()),
+//!                 span.is_sampled(),
+//!             )
+//!         })
+//!     }
+//! }
+//! ```
+use crate::client::{SdkError, SdkResult};
+
+/// Trace context of a call, sent as the W3C `traceparent` and `tracestate`
+/// headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    traceparent: String,
+    tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// Creates a version `00` context for the span `span_id` of the trace
+    /// `trace_id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `trace_id` or `span_id` is zero, which W3C Trace Context
+    /// reserves as invalid.
+    pub fn new(trace_id: u128, span_id: u64, sampled: bool) -> Self {
+        assert!(trace_id != 0, "trace id must not be zero");
+        assert!(span_id != 0, "span id must not be zero");
+        Self {
+            traceparent: format!(
+                "00-{:032x}-{:016x}-{:02x}",
+                trace_id,
+                span_id,
+                u8::from(sampled)
+            ),
+            tracestate: None,
+        }
+    }
+
+    /// Parses a `traceparent` header value, e.g. one received from upstream.
+    /// Fails with [`SdkError::InvalidRequest`] unless it is a version `00`
+    /// value with non-zero lowercase hex ids.
+    pub fn parse(traceparent: &str) -> SdkResult<Self> {
+        let invalid = || SdkError::InvalidRequest(format!("invalid traceparent {:?}", traceparent));
+        let parts: Vec<&str> = traceparent.split('-').collect();
+        let [version, trace_id, span_id, flags] = parts[..] else {
+            return Err(invalid());
+        };
+        let is_hex = |s: &str, len: usize| {
+            s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        };
+        let is_zero = |s: &str| s.bytes().all(|b| b == b'0');
+        if version != "00"
+            || !is_hex(trace_id, 32)
+            || !is_hex(span_id, 16)
+            || !is_hex(flags, 2)
+            || is_zero(trace_id)
+            || is_zero(span_id)
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            traceparent: traceparent.to_string(),
+            tracestate: None,
+        })
+    }
+
+    /// Attaches vendor-specific `tracestate`, e.g. `vendor=value` or one
+    /// received from upstream. Fails with [`SdkError::InvalidRequest`] if
+    /// `tracestate` is not a valid header value.
+    pub fn with_tracestate(mut self, tracestate: impl Into<String>) -> SdkResult<Self> {
+        let tracestate = tracestate.into();
+        if reqwest::header::HeaderValue::from_str(&tracestate).is_err() {
+            return Err(SdkError::InvalidRequest(format!(
+                "invalid tracestate {:?}",
+                tracestate
+            )));
+        }
+        self.tracestate = Some(tracestate);
+        Ok(self)
+    }
+
+    /// The `traceparent` header value.
+    pub fn traceparent(&self) -> &str {
+        &self.traceparent
+    }
+
+    /// The `tracestate` header value, if any.
+    pub fn tracestate(&self) -> Option<&str> {
+        self.tracestate.as_deref()
+    }
+
+    /// The trace id as 32 hex digits.
+    pub fn trace_id(&self) -> &str {
+        &self.traceparent[3..35]
+    }
+
+    /// The parent span id as 16 hex digits.
+    pub fn span_id(&self) -> &str {
+        &self.traceparent[36..52]
+    }
+}
+
+/// Source of the trace context for calls without one in their
+/// `RequestOptions`.
+pub trait TraceContextProvider: Send + Sync {
+    /// Trace context of the current call, or `None` to send no trace headers.
+    /// Called once per request attempt.
+    fn trace_context(&self) -> Option<TraceContext>;
+}
@@ -0,0 +1,168 @@
+//! HTTP transport used by [`Client`](crate::Client).
+//!
+//! Every call, unary or streaming, is turned into a fully signed
+//! [`PreparedRequest`] and handed to a [`Transport`]. The default
+//! [`ReqwestTransport`] sends it with `reqwest`; tests and custom HTTP stacks
+//! can plug in their own implementation via `ClientBuilder::transport`.
+use crate::client::{SdkError, SdkResult};
+use bytes::{Bytes, BytesMut};
+use futures_core::Stream;
+use futures_util::{StreamExt, TryStreamExt};
+use reqwest::header::HeaderMap;
+use reqwest::Client as HttpClient;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Boxed, `Send` future as returned by object-safe async traits in this crate.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Response body delivered as a stream of byte chunks.
+pub type BodyStream = Pin<Box<dyn Stream<Item = SdkResult<Bytes>> + Send>>;
+
+/// A signed request ready to be sent.
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    /// HTTP method, e.g. `POST`.
+    pub method: String,
+    /// Absolute request URL.
+    pub url: String,
+    /// All request headers, including `Authorization`.
+    pub headers: HeaderMap,
+    /// Exact body bytes that were signed. Retries of a call share the same
+    /// buffer, so cloning it is cheap.
+    pub body: Bytes,
+}
+
+impl PreparedRequest {
+    /// Returns the value of header `name`, if present and valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// Returns the API action from the `X-TC-Action` header.
+    pub fn action(&self) -> Option<&str> {
+        self.header("X-TC-Action")
+    }
+}
+
+/// Response returned by a [`Transport`].
+pub struct RawResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Response headers.
+    pub headers: HeaderMap,
+    /// Response body. Unary calls read it to the end; streaming calls decode
+    /// it incrementally.
+    pub body: BodyStream,
+}
+
+impl RawResponse {
+    /// Creates a response with a body that is already fully available.
+    pub fn from_bytes(status: u16, headers: HeaderMap, body: impl Into<Bytes>) -> Self {
+        let body: Bytes = body.into();
+        Self {
+            status,
+            headers,
+            body: Box::pin(futures_util::stream::once(async move { Ok(body) })),
+        }
+    }
+
+    /// Reads the whole body into memory.
+    pub async fn bytes(self) -> SdkResult<Vec<u8>> {
+        self.into_bytes().await.map(Vec::from)
+    }
+
+    /// Like [`RawResponse::bytes`], but returns a body delivered as a single
+    /// chunk as is, without copying.
+    pub async fn into_bytes(self) -> SdkResult<Bytes> {
+        self.into_bytes_limited(usize::MAX).await
+    }
+
+    /// Like [`RawResponse::into_bytes`], but stops reading and fails with
+    /// [`SdkError::ResponseTooLarge`] as soon as the body exceeds `limit`
+    /// bytes, so an oversized body is never held in full.
+    pub async fn into_bytes_limited(self, limit: usize) -> SdkResult<Bytes> {
+        let status = self.status;
+        let request_id = self
+            .headers
+            .get("X-TC-RequestId")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let too_large = |received| SdkError::ResponseTooLarge {
+            limit,
+            received,
+            status: Some(status),
+            request_id: request_id.clone(),
+        };
+        let mut body = self.body;
+        let first = match body.next().await {
+            Some(chunk) => chunk?,
+            None => return Ok(Bytes::new()),
+        };
+        if first.len() > limit {
+            return Err(too_large(first.len()));
+        }
+        let Some(second) = body.next().await else {
+            return Ok(first);
+        };
+        let mut out = BytesMut::from(&first[..]);
+        let mut next = Some(second);
+        while let Some(chunk) = next {
+            let chunk = chunk?;
+            let size = out.len() + chunk.len();
+            if size > limit {
+                return Err(too_large(size));
+            }
+            out.extend_from_slice(&chunk);
+            next = body.next().await;
+        }
+        Ok(out.freeze())
+    }
+
+    /// Returns the `Content-Type` header, if any.
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+    }
+}
+
+/// Sends prepared requests over the wire.
+pub trait Transport: Send + Sync {
+    /// Sends `req` and returns the response once its headers are available.
+    fn execute(&self, req: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>>;
+}
+
+/// Default [`Transport`] backed by a `reqwest` client.
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    http: HttpClient,
+}
+
+impl ReqwestTransport {
+    /// Wraps an existing `reqwest` client.
+    pub fn new(http: HttpClient) -> Self {
+        Self { http }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn execute(&self, req: PreparedRequest) -> BoxFuture<'_, SdkResult<RawResponse>> {
+        Box::pin(async move {
+            let method =
+                reqwest::Method::from_bytes(req.method.as_bytes()).unwrap_or(reqwest::Method::POST);
+            let resp = self
+                .http
+                .request(method, req.url)
+                .headers(req.headers)
+                .body(req.body)
+                .send()
+                .await?;
+            Ok(RawResponse {
+                status: resp.status().as_u16(),
+                headers: resp.headers().clone(),
+                body: Box::pin(resp.bytes_stream().map_err(SdkError::Http)),
+            })
+        })
+    }
+}
@@ -0,0 +1,129 @@
+//! Loading client settings from TOML configuration files.
+use std::time::Duration;
+use tencentcloud_hunyuan_sdk::config::ClientConfig;
+use tencentcloud_hunyuan_sdk::{ClientBuilder, Credential, Region, RetryPolicy};
+
+const CONFIG: &str = r#"
+region = "ap-shanghai"
+endpoint = "hunyuan.internal.example.com"
+timeout = "30s"
+debug = false
+default_model = "hunyuan-pro"
+model_fallback_chain = ["hunyuan-standard"]
+slow_request_threshold = "2s 500ms"
+warm_up = true
+max_sse_buffer_bytes = 1048576
+coalesce_embeddings = true
+embedding_cache = 10000
+
+[retry]
+max_retries = 3
+initial_backoff = "100ms"
+
+[credential]
+secret_id = "AKIDconfig"
+secret_key = "configsecret"
+"#;
+
+#[test]
+fn toml_config_round_trips_without_credential() {
+    let config: ClientConfig = toml::from_str(CONFIG).unwrap();
+    assert_eq!(config.region, Some(Region::Custom("ap-shanghai".into())));
+    assert_eq!(config.timeout, Some(Duration::from_secs(30)));
+    assert_eq!(config.warm_up, Some(true));
+    assert_eq!(config.max_sse_buffer_bytes, Some(1 << 20));
+    assert_eq!(config.coalesce_embeddings, Some(true));
+    assert_eq!(config.embedding_cache, Some(10_000));
+    assert_eq!(
+        config.slow_request_threshold,
+        Some(Duration::from_millis(2500))
+    );
+    assert_eq!(
+        config.retry,
+        Some(RetryPolicy::new(3).initial_backoff(Duration::from_millis(100)))
+    );
+    assert_eq!(
+        config.credential.as_ref().map(|c| c.secret_id.as_str()),
+        Some("AKIDconfig")
+    );
+
+    let written = toml::to_string(&config).unwrap();
+    assert!(!written.contains("configsecret"), "{}", written);
+    let reread: ClientConfig = toml::from_str(&written).unwrap();
+    assert_eq!(
+        reread,
+        ClientConfig {
+            credential: None,
+            ..config.clone()
+        }
+    );
+
+    let with_credential = ClientConfig {
+        serialize_credential: true,
+        ..config.clone()
+    };
+    let reread: ClientConfig = toml::from_str(&toml::to_string(&with_credential).unwrap()).unwrap();
+    assert_eq!(reread, config);
+}
+
+#[test]
+fn region_accepts_names_variants_and_custom() {
+    #[derive(serde::Deserialize)]
+    struct Regions {
+        regions: Vec<Region>,
+    }
+    let parsed: Regions = toml::from_str(
+        r#"regions = ["ap-beijing", "ApGuangzhou", "ap-shanghai", { Custom = "ap-singapore" }]"#,
+    )
+    .unwrap();
+    assert_eq!(
+        parsed.regions,
+        vec![
+            Region::ApBeijing,
+            Region::ApGuangzhou,
+            Region::Custom("ap-shanghai".into()),
+            Region::Custom("ap-singapore".into()),
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(Region::ApBeijing).unwrap(),
+        serde_json::json!("ap-beijing")
+    );
+}
+
+#[test]
+fn client_built_from_toml_config() {
+    let client = ClientBuilder::from_config(toml::from_str(CONFIG).unwrap()).build();
+    assert_eq!(client.region().as_str(), "ap-shanghai");
+    assert_eq!(client.endpoint(), "hunyuan.internal.example.com");
+    assert_eq!(client.timeout(), Some(Duration::from_secs(30)));
+    assert_eq!(client.default_model(), Some("hunyuan-pro"));
+    assert_eq!(client.model_fallback_chain(), ["hunyuan-standard"]);
+    assert_eq!(client.retry_policy().map(|p| p.max_retries), Some(3));
+    assert_eq!(
+        client.slow_request_threshold(),
+        Some(Duration::from_millis(2500))
+    );
+    assert!(!client.debug());
+    assert_eq!(
+        client.credential(),
+        &Credential {
+            secret_id: "AKIDconfig".into(),
+            secret_key: "configsecret".into(),
+            token: None,
+        }
+    );
+
+    // A zero capacity turns the embedding cache off rather than failing.
+    let config = ClientConfig {
+        embedding_cache: Some(0),
+        ..toml::from_str(CONFIG).unwrap()
+    };
+    let builder = ClientBuilder::from_config(config);
+    assert!(!builder.has_embedding_cache());
+    assert!(builder.build().embedding_cache().is_none());
+
+    // A config without credentials still needs one from code.
+    let builder = ClientBuilder::from_config(ClientConfig::default());
+    assert!(!builder.has_credential());
+}
@@ -0,0 +1,253 @@
+//! Golden-file tests for the exact bytes the client signs and sends.
+//!
+//! For a fixed credential and a fixed clock, each case captures the request a
+//! call hands to the transport and compares its serialized body, headers,
+//! canonical request, string-to-sign and `Authorization` header against the
+//! snapshot in `tests/golden/<case>.txt`. Any drift fails the test.
+//!
+//! When a change to the wire format is intended, regenerate the snapshots and
+//! review the diff:
+//!
+//! ```text
+//! HUNYUAN_UPDATE_GOLDEN=1 cargo test --test golden
+//! ```
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tencentcloud_hunyuan_sdk::clock::FixedClock;
+use tencentcloud_hunyuan_sdk::models::{ChatCompletionsRequest, GetEmbeddingRequest, Message};
+use tencentcloud_hunyuan_sdk::transport::{BoxFuture, PreparedRequest, RawResponse, Transport};
+use tencentcloud_hunyuan_sdk::{Client, ClientBuilder, Credential, RequestOptions, SdkError};
+
+/// 2024-01-02T03:04:05Z
+const TIMESTAMP: i64 = 1_704_164_645;
+
+/// Records every request and answers with a minimal success response.
+#[derive(Default)]
+struct CaptureTransport {
+    requests: Mutex<Vec<PreparedRequest>>,
+}
+
+impl Transport for CaptureTransport {
+    fn execute(&self, req: PreparedRequest) -> BoxFuture<'_, Result<RawResponse, SdkError>> {
+        let stream = req.body.windows(13).any(|w| w == b"\"Stream\":true");
+        self.requests.lock().unwrap().push(req);
+        Box::pin(async move {
+            let mut headers = reqwest::header::HeaderMap::new();
+            let (content_type, body) = if stream {
+                ("text/event-stream", "data: [DONE]\n\n")
+            } else {
+                ("application/json", r#"{"Response":{"RequestId":"golden"}}"#)
+            };
+            headers.insert("Content-Type", content_type.parse().unwrap());
+            Ok(RawResponse::from_bytes(200, headers, body))
+        })
+    }
+}
+
+fn client(transport: Arc<CaptureTransport>, token: Option<&str>) -> Client {
+    ClientBuilder::new()
+        .credential(Credential {
+            secret_id: "AKIDgoldentest".to_string(),
+            secret_key: "goldensecretkey".to_string(),
+            token: token.map(str::to_string),
+        })
+        .transport(transport)
+        .clock(Arc::new(FixedClock(TIMESTAMP)))
+        .request_client("golden-tests")
+        .debug(false)
+        .build()
+}
+
+fn message(role: &str, content: &str) -> Message {
+    Message {
+        role: role.to_string(),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
+
+fn minimal_chat() -> ChatCompletionsRequest {
+    ChatCompletionsRequest {
+        model: Some("hunyuan-lite".to_string()),
+        messages: vec![message("user", "你好")],
+        temperature: None,
+        top_p: None,
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    }
+}
+
+fn full_chat() -> ChatCompletionsRequest {
+    ChatCompletionsRequest {
+        model: Some("hunyuan-pro".to_string()),
+        messages: vec![
+            message("system", "You are a terse assistant."),
+            message("user", "Name a prime number."),
+            message("assistant", "7"),
+            message("user", "Another one, with \"quotes\" and a\nnewline."),
+        ],
+        temperature: Some(0.7),
+        top_p: Some(0.9),
+        stream: Some(false),
+        tools: None,
+        n: None,
+        seed: None,
+    }
+}
+
+/// Renders everything signed and sent for `req`.
+fn render(req: &PreparedRequest) -> String {
+    let header = |name: &str| req.header(name).unwrap_or("");
+    let mut headers: Vec<String> = req
+        .headers
+        .iter()
+        .filter(|(name, _)| *name != "authorization")
+        .map(|(name, value)| format!("{}: {}", name, value.to_str().unwrap()))
+        .collect();
+    headers.sort();
+
+    let auth = header("Authorization");
+    let field = |name: &str| {
+        auth.split(", ")
+            .find_map(|f| f.rsplit(' ').next()?.strip_prefix(&format!("{}=", name)))
+            .unwrap_or("")
+    };
+    let signed_headers = field("SignedHeaders");
+    let scope = field("Credential").split_once('/').map_or("", |(_, s)| s);
+    let canonical_headers: String = signed_headers
+        .split(';')
+        .map(|h| format!("{}:{}\n", h, header(h).trim().to_lowercase()))
+        .collect();
+    let canonical_request = format!(
+        "{}\n/\n\n{}\n{}\n{}",
+        req.method,
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(&req.body))
+    );
+    let string_to_sign = format!(
+        "TC3-HMAC-SHA256\n{}\n{}\n{}",
+        header("X-TC-Timestamp"),
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    format!(
+        "== url\n{}\n== headers\n{}\n== body\n{}\n== canonical_request\n{}\n== string_to_sign\n{}\n== authorization\n{}\n",
+        req.url,
+        headers.join("\n"),
+        String::from_utf8_lossy(&req.body),
+        canonical_request,
+        string_to_sign,
+        auth
+    )
+}
+
+fn assert_golden(case: &str, transport: &CaptureTransport) {
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(
+        requests.len(),
+        1,
+        "case {} sent {} requests",
+        case,
+        requests.len()
+    );
+    let actual = render(&requests[0]);
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.txt", case));
+    if std::env::var("HUNYUAN_UPDATE_GOLDEN").as_deref() == Ok("1") {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}; run with HUNYUAN_UPDATE_GOLDEN=1",
+            path.display()
+        )
+    });
+    assert_eq!(
+        actual,
+        expected,
+        "request for case {} drifted from {}",
+        case,
+        path.display()
+    );
+}
+
+#[tokio::test]
+async fn minimal_chat_request() {
+    let transport = Arc::new(CaptureTransport::default());
+    client(transport.clone(), None)
+        .chat_completions(&minimal_chat())
+        .await
+        .unwrap();
+    assert_golden("minimal_chat", &transport);
+}
+
+#[tokio::test]
+async fn full_chat_request() {
+    let transport = Arc::new(CaptureTransport::default());
+    client(transport.clone(), None)
+        .chat_completions(&full_chat())
+        .await
+        .unwrap();
+    assert_golden("full_chat", &transport);
+}
+
+#[tokio::test]
+async fn streaming_chat_request_with_session_token() {
+    let transport = Arc::new(CaptureTransport::default());
+    client(transport.clone(), Some("golden-session-token"))
+        .chat_completions_stream(&minimal_chat())
+        .await
+        .unwrap();
+    assert_golden("streaming_chat_with_token", &transport);
+}
+
+#[tokio::test]
+async fn embedding_request() {
+    let transport = Arc::new(CaptureTransport::default());
+    let req = GetEmbeddingRequest {
+        input: Some("golden input".to_string()),
+        ..Default::default()
+    };
+    client(transport.clone(), None)
+        .get_embedding(&req)
+        .await
+        .unwrap();
+    assert_golden("embedding", &transport);
+}
+
+#[tokio::test]
+async fn embedding_list_request() {
+    let transport = Arc::new(CaptureTransport::default());
+    let req = GetEmbeddingRequest {
+        input_list: Some(vec!["first".to_string(), "第二".to_string()]),
+        ..Default::default()
+    };
+    client(transport.clone(), None)
+        .get_embedding(&req)
+        .await
+        .unwrap();
+    assert_golden("embedding_list", &transport);
+}
+
+#[tokio::test]
+async fn raw_action_with_version_override() {
+    let transport = Arc::new(CaptureTransport::default());
+    let body = serde_json::json!({ "Prompt": "count these tokens" });
+    client(transport.clone(), None)
+        .call_raw(
+            "GetTokenCount",
+            &body,
+            &RequestOptions::new().version("2025-01-01"),
+        )
+        .await
+        .unwrap();
+    assert_golden("raw_action", &transport);
+}
@@ -0,0 +1,558 @@
+//! Conversions between canonical OpenAI Chat Completions JSON and the
+//! Hunyuan models, and `OpenAiCompatClient` against a local `wiremock`
+//! server standing in for the OpenAI-compatible endpoint.
+use futures_util::StreamExt;
+use serde_json::json;
+use tencentcloud_hunyuan_sdk::compat::openai::{
+    from_openai_request, to_openai_chunk, to_openai_request, to_openai_response,
+    to_openai_sse_event, OpenAiCompatClient, Warning, OPENAI_SSE_DONE,
+};
+use tencentcloud_hunyuan_sdk::models::{
+    ChatCompletionsChunk, ChatCompletionsRequest, ChatCompletionsResponse, GetEmbeddingRequest,
+    Message, Tool, ToolCall, ToolCallFunction,
+};
+use tencentcloud_hunyuan_sdk::{HunyuanApi, SdkError};
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const API_KEY: &str = "sk-compat-test";
+
+fn message(role: &str, content: &str) -> Message {
+    Message {
+        role: role.to_string(),
+        content: content.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn canonical_request_converts_without_warnings() {
+    let converted = from_openai_request(json!({
+        "model": "hunyuan-pro",
+        "messages": [
+            {"role": "system", "content": "You are a helpful assistant."},
+            {"role": "user", "content": "Hello!"}
+        ],
+        "temperature": 0.5,
+        "top_p": 0.75,
+        "stream": true,
+        "n": 1
+    }))
+    .unwrap();
+
+    assert_eq!(
+        converted.value,
+        ChatCompletionsRequest {
+            model: Some("hunyuan-pro".to_string()),
+            messages: vec![
+                message("system", "You are a helpful assistant."),
+                message("user", "Hello!"),
+            ],
+            temperature: Some(0.5),
+            top_p: Some(0.75),
+            stream: Some(true),
+            tools: None,
+            n: Some(1),
+            seed: None,
+        }
+    );
+    assert!(converted.warnings.is_empty());
+}
+
+/// An OpenAI request using `n`, `seed`, tools and a tool round trip, and
+/// the Hunyuan request it corresponds to.
+fn tool_calling_requests() -> (serde_json::Value, ChatCompletionsRequest) {
+    let schema = json!({"type": "object", "properties": {"city": {"type": "string"}}});
+    let openai = json!({
+        "model": "hunyuan-functioncall",
+        "messages": [
+            {"role": "user", "content": "Weather in Paris?"},
+            {"role": "assistant", "content": "", "tool_calls": [{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+            }]},
+            {"role": "tool", "content": "sunny", "tool_call_id": "call_1"}
+        ],
+        "n": 2,
+        "seed": 42,
+        "tools": [{
+            "type": "function",
+            "function": {"name": "get_weather", "description": "Current weather", "parameters": schema}
+        }]
+    });
+    let hunyuan = ChatCompletionsRequest {
+        model: Some("hunyuan-functioncall".to_string()),
+        messages: vec![
+            message("user", "Weather in Paris?"),
+            Message {
+                tool_calls: Some(vec![ToolCall {
+                    id: "call_1".to_string(),
+                    call_type: "function".to_string(),
+                    function: ToolCallFunction {
+                        name: "get_weather".to_string(),
+                        arguments: r#"{"city":"Paris"}"#.to_string(),
+                    },
+                    index: None,
+                }]),
+                ..message("assistant", "")
+            },
+            Message {
+                tool_call_id: Some("call_1".to_string()),
+                ..message("tool", "sunny")
+            },
+        ],
+        n: Some(2),
+        seed: Some(42),
+        tools: Some(vec![Tool::function(
+            "get_weather",
+            "Current weather",
+            &schema,
+        )]),
+        ..Default::default()
+    };
+    (openai, hunyuan)
+}
+
+#[test]
+fn tool_calling_request_converts_both_ways() {
+    let (openai, hunyuan) = tool_calling_requests();
+
+    let converted = from_openai_request(openai.clone()).unwrap();
+    assert_eq!(converted.value, hunyuan);
+    assert!(converted.warnings.is_empty(), "{:?}", converted.warnings);
+    assert_eq!(to_openai_request(&hunyuan), openai);
+
+    for body in [
+        json!({"messages": [], "seed": -1}),
+        json!({"messages": [], "n": 1.5}),
+        json!({"messages": [], "tools": [{"type": "function"}]}),
+        json!({"messages": [{"role": "assistant", "tool_calls": {}}]}),
+        json!({"messages": [{"role": "tool", "tool_call_id": 1}]}),
+    ] {
+        let err = from_openai_request(body.clone()).unwrap_err();
+        assert!(
+            matches!(&err, SdkError::InvalidRequest(e) if e.starts_with("invalid OpenAI request")),
+            "{}: {:?}",
+            body,
+            err
+        );
+    }
+}
+
+#[test]
+fn tool_calls_convert_to_openai_responses_and_chunks() {
+    let resp: ChatCompletionsResponse = serde_json::from_value(json!({
+        "Response": {
+            "RequestId": "req-3",
+            "Choices": [{
+                "Index": 0,
+                "Message": {"Role": "assistant", "Content": "", "ToolCalls": [{
+                    "Id": "call_1",
+                    "Type": "function",
+                    "Function": {"Name": "get_weather", "Arguments": "{}"}
+                }]},
+                "FinishReason": "tool_calls"
+            }]
+        }
+    }))
+    .unwrap();
+    assert_eq!(
+        to_openai_response(&resp)["choices"][0]["message"]["tool_calls"],
+        json!([{"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{}"}}])
+    );
+
+    // A later fragment carries only the index and more arguments.
+    let chunk: ChatCompletionsChunk = serde_json::from_value(json!({
+        "Id": "c1",
+        "Choices": [{"Index": 0, "Delta": {"ToolCalls": [{"Index": 0, "Function": {"Arguments": "{\"ci"}}]}}]
+    }))
+    .unwrap();
+    assert_eq!(
+        to_openai_chunk(&chunk)["choices"][0]["delta"],
+        json!({"tool_calls": [{"index": 0, "function": {"arguments": "{\"ci"}}]})
+    );
+}
+
+#[test]
+fn unsupported_request_fields_become_warnings() {
+    let converted = from_openai_request(json!({
+        "model": "hunyuan-lite",
+        "messages": [
+            {"role": "developer", "content": "Be brief."},
+            {"role": "user", "name": "alice", "content": [
+                {"type": "text", "text": "What is in "},
+                {"type": "image_url", "image_url": {"url": "https://example.com/cat.png"}},
+                {"type": "text", "text": "this image?"}
+            ]}
+        ],
+        "max_tokens": 256,
+        "stop": null,
+        "presence_penalty": 0.2
+    }))
+    .unwrap();
+
+    assert_eq!(
+        converted.value.messages,
+        vec![
+            message("system", "Be brief."),
+            message("user", "What is in this image?"),
+        ]
+    );
+    let fields: Vec<&str> = converted
+        .warnings
+        .iter()
+        .map(|w: &Warning| w.field.as_str())
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "max_tokens",
+            "messages[0].role",
+            "messages[1].content[1]",
+            "messages[1].name",
+            "presence_penalty",
+        ]
+    );
+    assert_eq!(
+        converted.warnings[0].to_string(),
+        "max_tokens: not supported by Hunyuan; dropped"
+    );
+}
+
+#[test]
+fn malformed_requests_are_rejected() {
+    for body in [
+        json!([]),
+        json!({"model": "hunyuan-lite"}),
+        json!({"messages": {"role": "user"}}),
+        json!({"messages": [{"content": "no role"}]}),
+        json!({"messages": [{"role": "user", "content": 42}]}),
+        json!({"messages": [], "temperature": "hot"}),
+    ] {
+        let err = from_openai_request(body.clone()).unwrap_err();
+        assert!(
+            matches!(&err, SdkError::InvalidRequest(e) if e.starts_with("invalid OpenAI request")),
+            "{}: {:?}",
+            body,
+            err
+        );
+    }
+}
+
+#[test]
+fn response_converts_to_chat_completion_object() {
+    let resp: ChatCompletionsResponse = serde_json::from_value(json!({
+        "Response": {
+            "RequestId": "req-1",
+            "Id": "id-1",
+            "Created": 1704164645,
+            "Choices": [{
+                "Index": 0,
+                "Message": {"Role": "assistant", "Content": "Hi there!"},
+                "FinishReason": "stop"
+            }],
+            "Usage": {"PromptTokens": 9, "CompletionTokens": 12, "TotalTokens": 21}
+        }
+    }))
+    .unwrap();
+
+    assert_eq!(
+        to_openai_response(&resp),
+        json!({
+            "id": "id-1",
+            "object": "chat.completion",
+            "created": 1704164645,
+            "model": "hunyuan",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hi there!"},
+                "logprobs": null,
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 9, "completion_tokens": 12, "total_tokens": 21}
+        })
+    );
+}
+
+#[test]
+fn moderated_response_reports_content_filter() {
+    let resp: ChatCompletionsResponse = serde_json::from_value(json!({
+        "Response": {
+            "RequestId": "req-2",
+            "Choices": [{"Message": {"Content": ""}, "FinishReason": "sensitive"}]
+        }
+    }))
+    .unwrap();
+
+    let out = to_openai_response(&resp);
+    assert_eq!(out["id"], "req-2");
+    assert_eq!(out["choices"][0]["message"]["role"], "assistant");
+    assert_eq!(out["choices"][0]["finish_reason"], "content_filter");
+    assert!(out.get("usage").is_none());
+}
+
+#[test]
+fn stream_chunks_convert_to_openai_events() {
+    let chunks: Vec<ChatCompletionsChunk> = [
+        json!({"Id": "c1", "Created": 1, "Choices": [{"Index": 0, "Delta": {"Role": "assistant", "Content": ""}}]}),
+        json!({"Id": "c1", "Created": 1, "Choices": [{"Index": 0, "Delta": {"Content": "Hello"}}]}),
+        json!({"Id": "c1", "Created": 1, "Choices": [{"Index": 0, "Delta": {}, "FinishReason": "stop"}],
+               "Usage": {"PromptTokens": 3, "CompletionTokens": 1, "TotalTokens": 4}}),
+    ]
+    .into_iter()
+    .map(|v| serde_json::from_value(v).unwrap())
+    .collect();
+
+    assert_eq!(
+        to_openai_chunk(&chunks[0]),
+        json!({
+            "id": "c1",
+            "object": "chat.completion.chunk",
+            "created": 1,
+            "model": "hunyuan",
+            "choices": [{"index": 0, "delta": {"role": "assistant", "content": ""}, "logprobs": null, "finish_reason": null}]
+        })
+    );
+    assert_eq!(
+        to_openai_chunk(&chunks[1])["choices"][0]["delta"],
+        json!({"content": "Hello"})
+    );
+    let last = to_openai_chunk(&chunks[2]);
+    assert_eq!(last["choices"][0]["finish_reason"], "stop");
+    assert_eq!(last["usage"]["total_tokens"], 4);
+
+    let mut sse: String = chunks.iter().map(to_openai_sse_event).collect();
+    sse.push_str(OPENAI_SSE_DONE);
+    let events: Vec<&str> = sse.split("\n\n").filter(|e| !e.is_empty()).collect();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[3], "data: [DONE]");
+    for event in &events[..3] {
+        let data = event.strip_prefix("data: ").unwrap();
+        let value: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert_eq!(value["object"], "chat.completion.chunk");
+    }
+}
+
+fn chat_request() -> ChatCompletionsRequest {
+    ChatCompletionsRequest {
+        model: Some("hunyuan-turbo".to_string()),
+        messages: vec![message("user", "Hello!")],
+        temperature: Some(0.7),
+        top_p: None,
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    }
+}
+
+fn compat_client(server: &MockServer) -> OpenAiCompatClient {
+    OpenAiCompatClient::builder(API_KEY)
+        .base_url(format!("{}/v1/", server.uri()))
+        .build()
+}
+
+#[test]
+fn request_converts_to_openai_body() {
+    assert_eq!(
+        to_openai_request(&chat_request()),
+        json!({
+            "model": "hunyuan-turbo",
+            "messages": [{"role": "user", "content": "Hello!"}],
+            "temperature": 0.7
+        })
+    );
+}
+
+#[tokio::test]
+async fn compat_client_sends_bearer_authenticated_openai_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("Authorization", "Bearer sk-compat-test"))
+        .and(header("Content-Type", "application/json"))
+        .and(body_json(json!({
+            "model": "hunyuan-turbo",
+            "messages": [{"role": "user", "content": "Hello!"}],
+            "temperature": 0.7
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1704164645,
+            "model": "hunyuan-turbo",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hi there!"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 4, "total_tokens": 7}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let api: Box<dyn HunyuanApi> = Box::new(compat_client(&server));
+    let resp = api.chat_completions(&chat_request()).await.unwrap();
+
+    let inner = resp.response;
+    assert_eq!(inner.id.as_deref(), Some("chatcmpl-1"));
+    let choice = &inner.choices.unwrap()[0];
+    assert_eq!(
+        choice.message.as_ref().unwrap().content.as_deref(),
+        Some("Hi there!")
+    );
+    assert_eq!(choice.finish_reason.as_deref(), Some("stop"));
+    assert_eq!(inner.usage.unwrap().total_tokens, Some(7));
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get("X-TC-Action").is_none());
+}
+
+#[tokio::test]
+async fn compat_client_sends_and_receives_tool_calls() {
+    let server = MockServer::start().await;
+    let (openai, hunyuan) = tool_calling_requests();
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_json(openai))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "chatcmpl-2",
+            "object": "chat.completion",
+            "created": 1704164645,
+            "model": "hunyuan-functioncall",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_2",
+                    "type": "function",
+                    "function": {"name": "get_weather", "arguments": "{\"city\":\"Lyon\"}"}
+                }]},
+                "finish_reason": "tool_calls"
+            }]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let resp = compat_client(&server)
+        .chat_completions(&hunyuan)
+        .await
+        .unwrap();
+
+    let choice = &resp.response.choices.unwrap()[0];
+    let calls = choice
+        .message
+        .as_ref()
+        .unwrap()
+        .tool_calls
+        .as_ref()
+        .unwrap();
+    assert_eq!(calls[0].id, "call_2");
+    assert_eq!(calls[0].function.name, "get_weather");
+    assert_eq!(calls[0].function.arguments, r#"{"city":"Lyon"}"#);
+    assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+}
+
+#[tokio::test]
+async fn compat_client_streams_openai_chunks() {
+    let server = MockServer::start().await;
+    let sse = [
+        json!({"id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "hunyuan-turbo",
+               "choices": [{"index": 0, "delta": {"role": "assistant", "content": "Hel"}, "finish_reason": null}]}),
+        json!({"id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "hunyuan-turbo",
+               "choices": [{"index": 0, "delta": {"content": "lo"}, "finish_reason": "content_filter"}]}),
+    ]
+    .iter()
+    .map(|v| format!("data: {}\n\n", v))
+    .collect::<String>()
+        + OPENAI_SSE_DONE;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("Authorization", "Bearer sk-compat-test"))
+        .and(body_json(json!({
+            "model": "hunyuan-lite",
+            "messages": [{"role": "user", "content": "Hello!"}],
+            "stream": true
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(sse, "text/event-stream"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OpenAiCompatClient::builder(API_KEY)
+        .base_url(format!("{}/v1", server.uri()))
+        .default_model("hunyuan-lite")
+        .build();
+    let req = ChatCompletionsRequest {
+        model: None,
+        temperature: None,
+        ..chat_request()
+    };
+    let chunks: Vec<ChatCompletionsChunk> = client
+        .chat_completions_stream(&req)
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    let text: String = chunks
+        .iter()
+        .filter_map(|c| c.choices.as_ref()?[0].delta.as_ref()?.content.clone())
+        .collect();
+    assert_eq!(text, "Hello");
+    let last = &chunks[1].choices.as_ref().unwrap()[0];
+    assert_eq!(last.finish_reason.as_deref(), Some("sensitive"));
+}
+
+#[tokio::test]
+async fn compat_client_embeds_and_maps_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .and(header("Authorization", "Bearer sk-compat-test"))
+        .and(body_json(
+            json!({"model": "hunyuan-embedding", "input": ["a", "b"]}),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "data": [
+                {"object": "embedding", "index": 0, "embedding": [0.5, -0.25]},
+                {"object": "embedding", "index": 1, "embedding": [1.0, 0.0]}
+            ],
+            "usage": {"prompt_tokens": 2, "total_tokens": 2}
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "error": {"message": "Incorrect API key provided", "type": "invalid_request_error", "code": "invalid_api_key"}
+        })))
+        .mount(&server)
+        .await;
+
+    let client = compat_client(&server);
+    let req = GetEmbeddingRequest {
+        input_list: Some(vec!["a".to_string(), "b".to_string()]),
+        ..Default::default()
+    };
+    let resp = client.get_embedding(&req).await.unwrap();
+    let data = resp.response.data.unwrap();
+    assert_eq!(data[0].embedding.as_deref(), Some(&[0.5, -0.25][..]));
+    assert_eq!(data[1].index, Some(1));
+    assert_eq!(resp.response.usage.unwrap().total_tokens, Some(2));
+
+    match client.chat_completions(&chat_request()).await.unwrap_err() {
+        SdkError::Service { code, message, .. } => {
+            assert_eq!(code, "invalid_api_key");
+            assert_eq!(message, "Incorrect API key provided");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(matches!(
+        client.chat_completions_stream(&chat_request()).await,
+        Err(SdkError::Service { .. })
+    ));
+}
@@ -0,0 +1,194 @@
+//! `Client::warm_up` against a local TLS server, checking that the next call
+//! reuses the warmed-up connection.
+#![cfg(feature = "rustls-tls")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tencentcloud_hunyuan_sdk::client::CredentialSet;
+use tencentcloud_hunyuan_sdk::models::{ChatCompletionsRequest, Message, Role};
+use tencentcloud_hunyuan_sdk::{Client, ClientBuilder, Credential, LogLevel, LogSink};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::{self, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+
+const CERT: &[u8] = include_bytes!("fixtures/tls/cert.pem");
+const KEY: &[u8] = include_bytes!("fixtures/tls/key.pem");
+const CHAT_RESPONSE: &str = include_str!("fixtures/chat_completions.json");
+
+/// Requests seen by the server as `(connection number, method)`.
+type Seen = Arc<Mutex<Vec<(usize, String)>>>;
+
+/// Serves HTTP/1.1 over TLS with keep-alive, answering `HEAD` with an empty
+/// response and everything else with a chat completion.
+async fn serve_tls() -> (String, Seen) {
+    let config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![CertificateDer::from_pem_slice(CERT).unwrap()],
+                PrivateKeyDer::from_pem_slice(KEY).unwrap(),
+            )
+            .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("https://{}", listener.local_addr().unwrap());
+    let seen: Seen = Arc::default();
+    let log = seen.clone();
+    tokio::spawn(async move {
+        for connection in 0.. {
+            let (socket, _) = listener.accept().await.unwrap();
+            let acceptor = acceptor.clone();
+            let log = log.clone();
+            tokio::spawn(async move {
+                let Ok(mut tls) = acceptor.accept(socket).await else {
+                    return;
+                };
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                loop {
+                    let Some(end) = find_head_end(&buf) else {
+                        match tls.read(&mut chunk).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                        }
+                        continue;
+                    };
+                    let head = String::from_utf8_lossy(&buf[..end]).to_string();
+                    let content_length = head
+                        .lines()
+                        .find_map(|l| {
+                            let (k, v) = l.split_once(':')?;
+                            k.eq_ignore_ascii_case("content-length")
+                                .then(|| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    while buf.len() < end + 4 + content_length {
+                        match tls.read(&mut chunk).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                        }
+                    }
+                    buf.drain(..end + 4 + content_length);
+                    let method = head.split(' ').next().unwrap_or("").to_string();
+                    let body = if method == "HEAD" { "" } else { CHAT_RESPONSE };
+                    log.lock().unwrap().push((connection, method));
+                    let resp = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    if tls.write_all(resp.as_bytes()).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    (endpoint, seen)
+}
+
+fn find_head_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n")
+}
+
+fn chat_request() -> ChatCompletionsRequest {
+    ChatCompletionsRequest {
+        model: Some("hunyuan-lite".to_string()),
+        messages: vec![Message::new(Role::User, "Hello")],
+        temperature: None,
+        top_p: None,
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    }
+}
+
+/// Log sink keeping every line.
+#[derive(Default)]
+struct CaptureLogs(Mutex<Vec<String>>);
+
+impl LogSink for CaptureLogs {
+    fn log(&self, _level: LogLevel, message: &str) {
+        self.0.lock().unwrap().push(message.to_string());
+    }
+}
+
+impl CaptureLogs {
+    fn contains(&self, needle: &str) -> bool {
+        self.0.lock().unwrap().iter().any(|l| l.contains(needle))
+    }
+}
+
+/// Builder for a client trusting the fixture certificate.
+fn builder(endpoint: &str) -> ClientBuilder<CredentialSet> {
+    let http = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(CERT).unwrap())
+        .build()
+        .unwrap();
+    Client::builder()
+        .credential(Credential {
+            secret_id: "AKIDwarmup".to_string(),
+            secret_key: "warmupsecret".to_string(),
+            token: None,
+        })
+        .endpoint(endpoint)
+        .http(http)
+        .debug(false)
+}
+
+fn client(endpoint: &str) -> Client {
+    builder(endpoint).build()
+}
+
+#[tokio::test]
+async fn next_call_reuses_warmed_up_connection() {
+    let (endpoint, seen) = serve_tls().await;
+    let client = client(&endpoint);
+
+    let warm_up = client.warm_up().await.unwrap();
+    assert_eq!(warm_up.status, 200);
+
+    client.chat_completions(&chat_request()).await.unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![(0, "HEAD".to_string()), (0, "POST".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn warm_up_on_build_connects_in_background() {
+    let (endpoint, seen) = serve_tls().await;
+    let logs = Arc::new(CaptureLogs::default());
+    let client = builder(&endpoint)
+        .warm_up(true)
+        .debug(true)
+        .log_sink(logs.clone())
+        .build();
+
+    // The client logs the warm-up once the connection is back in the pool.
+    for _ in 0..100 {
+        if logs.contains("[hunyuan-sdk][warm-up] url=") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(*seen.lock().unwrap(), vec![(0, "HEAD".to_string())]);
+
+    client.chat_completions(&chat_request()).await.unwrap();
+    assert_eq!(seen.lock().unwrap()[1], (0, "POST".to_string()));
+}
+
+#[tokio::test]
+async fn failed_warm_up_leaves_client_usable() {
+    // Nothing listens on the reserved port 9 of 127.0.0.1.
+    let client = client("https://127.0.0.1:9");
+    assert!(client.warm_up().await.is_err());
+    assert_eq!(client.endpoint(), "https://127.0.0.1:9");
+}
@@ -0,0 +1,630 @@
+//! End-to-end tests running the real client (signing, default `reqwest`
+//! transport, error mapping, streaming) against a local `wiremock` server
+//! serving captured Hunyuan responses from `tests/fixtures/`.
+//!
+//! No network access is needed. To refresh the fixtures from the live API,
+//! export `TENCENTCLOUD_SECRET_ID`/`TENCENTCLOUD_SECRET_KEY` and run:
+//!
+//! ```text
+//! HUNYUAN_RECORD_FIXTURES=1 cargo test --test wire record_fixtures
+//! ```
+//!
+//! Recorded bodies have request ids normalized and credential material
+//! scrubbed before they are written.
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tencentcloud_hunyuan_sdk::client::{tc3_sign_with_service, QUOTA_EXHAUSTED_CODES};
+use tencentcloud_hunyuan_sdk::clock::Clock;
+use tencentcloud_hunyuan_sdk::models::{ChatCompletionsRequest, Message};
+use tencentcloud_hunyuan_sdk::serializer::SortedKeysJson;
+use tencentcloud_hunyuan_sdk::transport::{
+    BoxFuture, PreparedRequest, RawResponse, ReqwestTransport, Transport,
+};
+use tencentcloud_hunyuan_sdk::{
+    Client, ClientBuilder, Credential, HttpVersionOrder, RetryPolicy, SdkError,
+};
+use wiremock::matchers::{body_string, header, method, path};
+use wiremock::{Match, Mock, MockServer, Request, ResponseTemplate};
+
+const SECRET_ID: &str = "AKIDwiretest";
+const SECRET_KEY: &str = "wiretestsecretkey";
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(fixtures_dir().join(name)).unwrap()
+}
+
+fn client_for(server: &MockServer, token: Option<&str>) -> Client {
+    ClientBuilder::new()
+        .credential(Credential {
+            secret_id: SECRET_ID.to_string(),
+            secret_key: SECRET_KEY.to_string(),
+            token: token.map(str::to_string),
+        })
+        .endpoint(server.uri())
+        .debug(false)
+        .build()
+}
+
+fn chat_request() -> ChatCompletionsRequest {
+    ChatCompletionsRequest {
+        model: Some("hunyuan-lite".to_string()),
+        messages: vec![Message {
+            role: "user".to_string(),
+            content: "你好".to_string(),
+            ..Default::default()
+        }],
+        temperature: None,
+        top_p: None,
+        stream: None,
+        tools: None,
+        n: None,
+        seed: None,
+    }
+}
+
+fn hmac_sha256(key: &[u8], msg: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(msg.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// TC3-HMAC-SHA256 signature of `string_to_sign`, derived independently of
+/// the SDK.
+fn expected_signature(secret_key: &str, date: &str, service: &str, string_to_sign: &str) -> String {
+    let k_date = hmac_sha256(format!("TC3{}", secret_key).as_bytes(), date);
+    let k_service = hmac_sha256(&k_date, service);
+    let k_signing = hmac_sha256(&k_service, "tc3_request");
+    hex::encode(hmac_sha256(&k_signing, string_to_sign))
+}
+
+/// Independently recomputes the TC3-HMAC-SHA256 signature from the request
+/// as received on the wire.
+struct ValidTc3Signature;
+
+impl Match for ValidTc3Signature {
+    fn matches(&self, req: &Request) -> bool {
+        let get = |name: &str| {
+            req.headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+        };
+        let auth = get("authorization");
+        let Some(rest) = auth.strip_prefix("TC3-HMAC-SHA256 ") else {
+            return false;
+        };
+        let fields: Vec<(&str, &str)> =
+            rest.split(", ").filter_map(|f| f.split_once('=')).collect();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| *v)
+                .unwrap_or("")
+        };
+        let scope = field("Credential")
+            .strip_prefix(&format!("{}/", SECRET_ID))
+            .unwrap_or("");
+        let signed_headers = field("SignedHeaders");
+        let mut scope_parts = scope.split('/');
+        let (date, service) = (
+            scope_parts.next().unwrap_or(""),
+            scope_parts.next().unwrap_or(""),
+        );
+
+        let canonical_headers: String = signed_headers
+            .split(';')
+            .map(|h| format!("{}:{}\n", h, get(h).trim().to_lowercase()))
+            .collect();
+        let canonical_request = format!(
+            "{}\n/\n\n{}\n{}\n{}",
+            req.method,
+            canonical_headers,
+            signed_headers,
+            hex::encode(Sha256::digest(&req.body))
+        );
+        let string_to_sign = format!(
+            "TC3-HMAC-SHA256\n{}\n{}\n{}",
+            get("x-tc-timestamp"),
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let expected = expected_signature(SECRET_KEY, date, service, &string_to_sign);
+        service == "hunyuan" && field("Signature") == expected
+    }
+}
+
+fn chat_mock() -> wiremock::MockBuilder {
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(header("X-TC-Action", "ChatCompletions"))
+        .and(header("X-TC-Version", "2023-09-01"))
+        .and(header("X-TC-Region", "ap-guangzhou"))
+        .and(header("Content-Type", "application/json; charset=utf-8"))
+        .and(ValidTc3Signature)
+}
+
+#[test]
+fn signs_for_other_services() {
+    let credential = Credential {
+        secret_id: SECRET_ID.to_string(),
+        secret_key: SECRET_KEY.to_string(),
+        token: None,
+    };
+    // 2023-11-14T22:13:20Z
+    let timestamp = 1_700_000_000;
+    let canonical_headers =
+        "content-type:application/json; charset=utf-8\nhost:cvm.tencentcloudapi.com\n";
+    let hashed_payload = hex::encode(Sha256::digest(b"{}"));
+    let (signature, scope) = tc3_sign_with_service(
+        &credential,
+        "cvm",
+        "POST",
+        "/",
+        "",
+        canonical_headers,
+        "content-type;host",
+        &hashed_payload,
+        timestamp,
+    )
+    .unwrap();
+    assert_eq!(scope, "2023-11-14/cvm/tc3_request");
+
+    let canonical_request = format!(
+        "POST\n/\n\n{}\ncontent-type;host\n{}",
+        canonical_headers, hashed_payload
+    );
+    let string_to_sign = format!(
+        "TC3-HMAC-SHA256\n{}\n{}\n{}",
+        timestamp,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    assert_eq!(
+        signature,
+        expected_signature(SECRET_KEY, "2023-11-14", "cvm", &string_to_sign)
+    );
+}
+
+#[tokio::test]
+async fn chat_completions_success() {
+    let server = MockServer::start().await;
+    chat_mock()
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(fixture("chat_completions.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let resp = client_for(&server, None)
+        .chat_completions(&chat_request())
+        .await
+        .unwrap();
+
+    let inner = resp.response;
+    assert_eq!(
+        inner.request_id.as_deref(),
+        Some("00000000-0000-0000-0000-000000000001")
+    );
+    let choice = &inner.choices.unwrap()[0];
+    assert_eq!(choice.finish_reason.as_deref(), Some("stop"));
+    assert_eq!(
+        choice.message.as_ref().unwrap().content.as_deref(),
+        Some("你好！有什么我可以帮你的吗？")
+    );
+    assert_eq!(inner.usage.unwrap().total_tokens, Some(13));
+}
+
+#[tokio::test]
+async fn host_override_is_signed_while_connecting_to_ip() {
+    let server = MockServer::start().await;
+    chat_mock()
+        .and(header("Host", "hunyuan.pinned.invalid"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(fixture("chat_completions.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // The endpoint is an IP address; the override name does not resolve, so
+    // the call only succeeds if it still connects to the pinned address.
+    assert!(server.uri().starts_with("http://127.0.0.1:"));
+    let client = ClientBuilder::new()
+        .credential(Credential {
+            secret_id: SECRET_ID.to_string(),
+            secret_key: SECRET_KEY.to_string(),
+            token: None,
+        })
+        .endpoint(server.uri())
+        .host_override("hunyuan.pinned.invalid")
+        .debug(false)
+        .build();
+    client.chat_completions(&chat_request()).await.unwrap();
+}
+
+#[tokio::test]
+async fn http_version_orders_build_usable_clients_or_need_http2() {
+    let orders = [
+        HttpVersionOrder::Http1Only,
+        HttpVersionOrder::Http2ThenHttp1,
+        HttpVersionOrder::Http2Only,
+    ];
+    let supported = |order: HttpVersionOrder| cfg!(feature = "http2") || !order.uses_http2();
+    let server = MockServer::start().await;
+    chat_mock()
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(fixture("chat_completions.json"), "application/json"),
+        )
+        .expect(orders.into_iter().filter(|o| supported(*o)).count() as u64)
+        .mount(&server)
+        .await;
+
+    // HTTP/1.1 only unless asked otherwise, whatever the features.
+    assert_eq!(HttpVersionOrder::default(), HttpVersionOrder::Http1Only);
+    for order in orders {
+        let built = ClientBuilder::new()
+            .credential(Credential {
+                secret_id: SECRET_ID.to_string(),
+                secret_key: SECRET_KEY.to_string(),
+                token: None,
+            })
+            .endpoint(server.uri())
+            .http_version_order(order)
+            .debug(false)
+            .try_build();
+        if !supported(order) {
+            assert!(
+                matches!(&built, Err(SdkError::InvalidConfig(e)) if e.contains("`http2` feature")),
+                "{:?}: {:?}",
+                order,
+                built.err()
+            );
+            continue;
+        }
+        let resp = built.unwrap().chat_completions(&chat_request()).await;
+        assert!(resp.is_ok(), "{:?}: {:?}", order, resp);
+    }
+}
+
+#[tokio::test]
+async fn sorted_keys_body_is_signed_as_sent() {
+    let server = MockServer::start().await;
+    chat_mock()
+        .and(body_string(
+            r#"{"Messages":[{"Content":"你好","Role":"user"}],"Model":"hunyuan-lite"}"#,
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(fixture("chat_completions.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = ClientBuilder::new()
+        .credential(Credential {
+            secret_id: SECRET_ID.to_string(),
+            secret_key: SECRET_KEY.to_string(),
+            token: None,
+        })
+        .endpoint(server.uri())
+        .body_serializer(Arc::new(SortedKeysJson))
+        .debug(false)
+        .build();
+    client.chat_completions(&chat_request()).await.unwrap();
+}
+
+#[tokio::test]
+async fn session_token_is_sent_and_signature_still_valid() {
+    let server = MockServer::start().await;
+    chat_mock()
+        .and(header("X-TC-Token", "session-token"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(fixture("chat_completions.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client_for(&server, Some("session-token"))
+        .chat_completions(&chat_request())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn documented_error_codes_map_to_service_errors() {
+    let errors: Vec<serde_json::Value> = serde_json::from_str(&fixture("errors.json")).unwrap();
+    for envelope in errors {
+        let server = MockServer::start().await;
+        chat_mock()
+            .respond_with(ResponseTemplate::new(200).set_body_json(&envelope))
+            .mount(&server)
+            .await;
+
+        let err = client_for(&server, None)
+            .chat_completions(&chat_request())
+            .await
+            .unwrap_err();
+
+        let expected_code = envelope["Response"]["Error"]["Code"].as_str().unwrap();
+        // Throttling and exhausted quota have their own variants with the
+        // same fields.
+        let throttled = matches!(err, SdkError::RateLimited { .. });
+        assert_eq!(throttled, expected_code == "RequestLimitExceeded");
+        let exhausted = matches!(err, SdkError::QuotaExhausted { .. });
+        assert_eq!(exhausted, QUOTA_EXHAUSTED_CODES.contains(&expected_code));
+        match err {
+            SdkError::Service {
+                code,
+                message,
+                request_id,
+            }
+            | SdkError::QuotaExhausted {
+                code,
+                message,
+                request_id,
+            }
+            | SdkError::RateLimited {
+                code,
+                message,
+                request_id,
+                ..
+            } => {
+                assert_eq!(code, expected_code);
+                assert_eq!(message, envelope["Response"]["Error"]["Message"]);
+                assert_eq!(
+                    request_id.as_deref(),
+                    envelope["Response"]["RequestId"].as_str()
+                );
+            }
+            other => panic!("{}: unexpected error {:?}", expected_code, other),
+        }
+    }
+}
+
+/// Clock moving one second forward every time it is read.
+struct TickingClock(AtomicI64);
+
+impl Clock for TickingClock {
+    fn unix_timestamp(&self) -> i64 {
+        self.0.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+#[tokio::test]
+async fn retried_attempts_are_signed_afresh() {
+    let server = MockServer::start().await;
+    let errors: Vec<serde_json::Value> = serde_json::from_str(&fixture("errors.json")).unwrap();
+    let throttled = errors
+        .into_iter()
+        .find(|e| e["Response"]["Error"]["Code"] == "RequestLimitExceeded")
+        .unwrap();
+    // Mounted first, so each answers once before the next one matches. Every
+    // mock requires a valid signature.
+    chat_mock()
+        .respond_with(ResponseTemplate::new(503).set_body_string("Service Unavailable"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    chat_mock()
+        .respond_with(ResponseTemplate::new(200).set_body_json(&throttled))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    chat_mock()
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(fixture("chat_completions.json"), "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = ClientBuilder::new()
+        .credential(Credential {
+            secret_id: SECRET_ID.to_string(),
+            secret_key: SECRET_KEY.to_string(),
+            token: None,
+        })
+        .endpoint(server.uri())
+        .clock(Arc::new(TickingClock(AtomicI64::new(1_704_164_645))))
+        .retry_policy(RetryPolicy::new(2).initial_backoff(Duration::ZERO))
+        .debug(false)
+        .build();
+    let (resp, meta) = client
+        .chat_completions_with_meta(&chat_request())
+        .await
+        .unwrap();
+
+    assert_eq!(meta.attempts, 3);
+    assert!(resp.response.choices.is_some());
+    let received = server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 3);
+    let header = |i: usize, name: &str| received[i].headers[name].to_str().unwrap().to_string();
+    let timestamps: Vec<i64> = (0..3)
+        .map(|i| header(i, "X-TC-Timestamp").parse().unwrap())
+        .collect();
+    assert!(
+        timestamps.windows(2).all(|pair| pair[0] < pair[1]),
+        "{:?}",
+        timestamps
+    );
+    let authorizations: Vec<String> = (0..3).map(|i| header(i, "Authorization")).collect();
+    assert_ne!(authorizations[0], authorizations[1]);
+    assert_ne!(authorizations[1], authorizations[2]);
+    assert_eq!(received[0].body, received[2].body);
+}
+
+#[tokio::test]
+async fn non_json_http_error_maps_to_status_code() {
+    let server = MockServer::start().await;
+    chat_mock()
+        .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+        .mount(&server)
+        .await;
+
+    let err = client_for(&server, None)
+        .chat_completions(&chat_request())
+        .await
+        .unwrap_err();
+
+    match err {
+        SdkError::Service { code, message, .. } => {
+            assert_eq!(code, "HTTP_502");
+            assert_eq!(message, "Bad Gateway");
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn streaming_success() {
+    let server = MockServer::start().await;
+    chat_mock()
+        .and(wiremock::matchers::body_partial_json(
+            serde_json::json!({ "Stream": true }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(fixture("chat_completions_stream.sse"), "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let stream = client_for(&server, None)
+        .chat_completions_stream(&chat_request())
+        .await
+        .unwrap();
+    let chunks: Vec<_> = stream.map(Result::unwrap).collect().await;
+
+    assert_eq!(chunks.len(), 3);
+    let text: String = chunks
+        .iter()
+        .filter_map(|c| c.choices.as_ref()?[0].delta.as_ref()?.content.clone())
+        .collect();
+    assert_eq!(text, "你好！有什么可以帮你的吗？");
+    let last = chunks.last().unwrap();
+    assert_eq!(
+        last.choices.as_ref().unwrap()[0].finish_reason.as_deref(),
+        Some("stop")
+    );
+    assert_eq!(last.usage.as_ref().unwrap().total_tokens, Some(13));
+}
+
+#[tokio::test]
+async fn streaming_error_envelope() {
+    let errors: Vec<serde_json::Value> = serde_json::from_str(&fixture("errors.json")).unwrap();
+    let server = MockServer::start().await;
+    chat_mock()
+        .respond_with(ResponseTemplate::new(200).set_body_json(&errors[4]))
+        .mount(&server)
+        .await;
+
+    let result = client_for(&server, None)
+        .chat_completions_stream(&chat_request())
+        .await;
+
+    match result {
+        Err(SdkError::RateLimited {
+            code, retry_after, ..
+        }) => {
+            assert_eq!(code, "RequestLimitExceeded");
+            assert_eq!(retry_after, None);
+        }
+        Err(other) => panic!("unexpected error {:?}", other),
+        Ok(_) => panic!("expected an error"),
+    }
+}
+
+/// Wraps the default transport and keeps every response body.
+struct RecordingTransport {
+    inner: ReqwestTransport,
+    bodies: Mutex<Vec<Vec<u8>>>,
+}
+
+impl Transport for RecordingTransport {
+    fn execute(&self, req: PreparedRequest) -> BoxFuture<'_, Result<RawResponse, SdkError>> {
+        Box::pin(async move {
+            let resp = self.inner.execute(req).await?;
+            let (status, headers) = (resp.status, resp.headers.clone());
+            let body = resp.bytes().await?;
+            self.bodies.lock().unwrap().push(body.clone());
+            Ok(RawResponse::from_bytes(status, headers, body))
+        })
+    }
+}
+
+/// Replaces request ids and any credential material in a recorded body.
+fn scrub(body: &[u8], secrets: &[&str]) -> String {
+    let mut text = String::from_utf8_lossy(body).into_owned();
+    for secret in secrets.iter().filter(|s| !s.is_empty()) {
+        text = text.replace(secret, "***");
+    }
+    let mut out = String::new();
+    let mut rest = text.as_str();
+    while let Some(pos) = rest.find("\"RequestId\":\"") {
+        let start = pos + "\"RequestId\":\"".len();
+        out.push_str(&rest[..start]);
+        out.push_str("00000000-0000-0000-0000-000000000001");
+        rest = &rest[start..];
+        rest = &rest[rest.find('"').unwrap_or(0)..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[tokio::test]
+async fn record_fixtures() {
+    if std::env::var("HUNYUAN_RECORD_FIXTURES").as_deref() != Ok("1") {
+        return;
+    }
+    let secret_id = std::env::var("TENCENTCLOUD_SECRET_ID").expect("TENCENTCLOUD_SECRET_ID");
+    let secret_key = std::env::var("TENCENTCLOUD_SECRET_KEY").expect("TENCENTCLOUD_SECRET_KEY");
+    let transport = Arc::new(RecordingTransport {
+        inner: ReqwestTransport::new(reqwest::Client::new()),
+        bodies: Mutex::new(Vec::new()),
+    });
+    let client = ClientBuilder::new()
+        .credential(Credential {
+            secret_id: secret_id.clone(),
+            secret_key: secret_key.clone(),
+            token: None,
+        })
+        .transport(transport.clone())
+        .build();
+
+    client.chat_completions(&chat_request()).await.unwrap();
+    let stream = client
+        .chat_completions_stream(&chat_request())
+        .await
+        .unwrap();
+    let _: Vec<_> = stream.collect().await;
+
+    let bodies = transport.bodies.lock().unwrap();
+    let secrets = [secret_id.as_str(), secret_key.as_str()];
+    for (name, body) in ["chat_completions.json", "chat_completions_stream.sse"]
+        .iter()
+        .zip(bodies.iter())
+    {
+        std::fs::write(fixtures_dir().join(name), scrub(body, &secrets)).unwrap();
+    }
+}